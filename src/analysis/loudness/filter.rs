@@ -0,0 +1,125 @@
+use std::f64::consts::PI;
+
+/// Second-order IIR section in direct form I
+#[derive(Debug, Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[1] * self.y[0]
+            - self.a[2] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// The K-weighting of ITU-R BS.1770: a high shelf modelling the head, then a high pass.
+/// Coefficients are derived for any sample rate rather than tabulated for 48kHz.
+#[derive(Debug, Clone)]
+pub(super) struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    pub fn new(sample_rate: u32) -> Self {
+        let rate = sample_rate as f64;
+
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (PI * f0 / rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad::new(
+            [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+            [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (PI * f0 / rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad::new(
+            [1.0, -2.0, 1.0],
+            [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        Self { shelf, high_pass }
+    }
+
+    pub fn process(&mut self, sample: f64) -> f64 {
+        self.high_pass.process(self.shelf.process(sample))
+    }
+}
+
+/// Taps each polyphase branch of the true-peak interpolator spans
+const TAPS_PER_PHASE: usize = 12;
+
+/// Estimates the peak of the reconstructed waveform between samples by oversampling,
+/// as BS.1770 specifies for true peak
+#[derive(Debug, Clone)]
+pub(super) struct TruePeak {
+    factor: usize,
+    /// Interpolation filter split into one branch per oversampled phase
+    phases: Vec<[f64; TAPS_PER_PHASE]>,
+    /// Most recent input samples of each channel, newest first
+    history: Vec<[f64; TAPS_PER_PHASE]>,
+    peak: f64,
+}
+
+impl TruePeak {
+    pub fn new(channels: usize, sample_rate: u32) -> Self {
+        // Four times up to 96kHz, so the estimate is always taken at 192kHz or more
+        let factor = match sample_rate {
+            0..=95_999 => 4,
+            96_000..=191_999 => 2,
+            _ => 1,
+        };
+
+        // Hann-windowed sinc low pass at the original Nyquist frequency
+        let length = TAPS_PER_PHASE * factor;
+        let center = (length - 1) as f64 / 2.0;
+        let mut phases = vec![[0.0; TAPS_PER_PHASE]; factor];
+        for n in 0..length {
+            let t = (n as f64 - center) / factor as f64;
+            let sinc = if t == 0.0 { 1.0 } else { (PI * t).sin() / (PI * t) };
+            let window = 0.5 - 0.5 * (2.0 * PI * (n as f64 + 0.5) / length as f64).cos();
+            phases[n % factor][n / factor] = sinc * window;
+        }
+
+        Self {
+            factor,
+            phases,
+            history: vec![[0.0; TAPS_PER_PHASE]; channels],
+            peak: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, channel: usize, sample: f64) {
+        self.peak = self.peak.max(sample.abs());
+        if self.factor == 1 {
+            return;
+        }
+        let history = &mut self.history[channel];
+        history.copy_within(0..TAPS_PER_PHASE - 1, 1);
+        history[0] = sample;
+        for phase in &self.phases {
+            let value: f64 = phase.iter().zip(history.iter()).map(|(tap, x)| tap * x).sum();
+            self.peak = self.peak.max(value.abs());
+        }
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+mod filter;
+#[cfg(test)]
+mod tests;
+
+use filter::{KWeighting, TruePeak};
+
+/// Blocks quieter than this never count towards loudness, in LUFS
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Integrated loudness ignores blocks this far below the ungated average, in LU
+const INTEGRATED_RELATIVE_GATE: f64 = -10.0;
+/// Loudness range ignores short-term values this far below their average, in LU
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+
+/// Sub-blocks of 100ms in a 400ms momentary block and a 3s short-term window
+const MOMENTARY_SUB_BLOCKS: usize = 4;
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+
+/// Loudness of a mean square energy, per BS.1770
+fn to_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn to_energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+/// Measurements of a track or an album per EBU R128
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    /// Gated loudness over the whole programme in LUFS, or `None` for silence
+    pub integrated: Option<f64>,
+    /// Spread between soft and loud passages in LU (EBU Tech 3342)
+    pub range: f64,
+    /// Highest inter-sample peak as a linear sample value
+    pub true_peak: f64,
+}
+
+/// Measures the loudness of interleaved samples fed to it a chunk at a time
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    channels: usize,
+    filters: Vec<KWeighting>,
+    /// BS.1770 channel weights; the LFE counts for nothing, surrounds for +1.5dB
+    weights: Vec<f64>,
+    true_peak: TruePeak,
+    sub_block_frames: usize,
+    frames_in_sub_block: usize,
+    /// Per-channel sums of squared, weighted samples in the current sub-block
+    sums: Vec<f64>,
+    /// Energies of the most recent sub-blocks, newest last
+    recent: VecDeque<f64>,
+    /// Energy of every 400ms block, overlapping by 75%
+    blocks: Vec<f64>,
+    /// Energy of every 3s window, one each 100ms
+    short_term: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let weights = (0..channels)
+            .map(|channel| match (channels, channel) {
+                (6.., 3) => 0.0,
+                (6.., 4 | 5) => 1.41,
+                _ => 1.0,
+            })
+            .collect();
+
+        Self {
+            channels,
+            filters: vec![KWeighting::new(sample_rate); channels],
+            weights,
+            true_peak: TruePeak::new(channels, sample_rate),
+            sub_block_frames: (sample_rate as usize / 10).max(1),
+            frames_in_sub_block: 0,
+            sums: vec![0.0; channels],
+            recent: VecDeque::with_capacity(SHORT_TERM_SUB_BLOCKS),
+            blocks: Vec::new(),
+            short_term: Vec::new(),
+        }
+    }
+
+    /// Feed interleaved samples; a trailing partial frame is ignored
+    pub fn process(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                let sample = sample as f64;
+                self.true_peak.process(channel, sample);
+                let weighted = self.filters[channel].process(sample);
+                self.sums[channel] += weighted * weighted;
+            }
+
+            self.frames_in_sub_block += 1;
+            if self.frames_in_sub_block == self.sub_block_frames {
+                self.finish_sub_block();
+            }
+        }
+    }
+
+    fn finish_sub_block(&mut self) {
+        let frames = self.sub_block_frames as f64;
+        let energy = self.sums.iter().zip(&self.weights).map(|(sum, weight)| weight * sum / frames).sum();
+        self.sums.iter_mut().for_each(|sum| *sum = 0.0);
+        self.frames_in_sub_block = 0;
+
+        if self.recent.len() == SHORT_TERM_SUB_BLOCKS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(energy);
+
+        let mean = |count: usize| self.recent.iter().rev().take(count).sum::<f64>() / count as f64;
+        if self.recent.len() >= MOMENTARY_SUB_BLOCKS {
+            self.blocks.push(mean(MOMENTARY_SUB_BLOCKS));
+        }
+        if self.recent.len() == SHORT_TERM_SUB_BLOCKS {
+            self.short_term.push(mean(SHORT_TERM_SUB_BLOCKS));
+        }
+    }
+
+    /// Everything measured so far
+    pub fn loudness(&self) -> Loudness {
+        Self::combined([self])
+    }
+
+    /// Loudness of several meters' programmes played back to back, as for an album.
+    /// Gating runs over all blocks together, so quiet tracks weigh less than loud ones.
+    pub fn combined<'a>(meters: impl IntoIterator<Item = &'a LoudnessMeter>) -> Loudness {
+        let meters: Vec<_> = meters.into_iter().collect();
+        let blocks: Vec<f64> = meters.iter().flat_map(|meter| meter.blocks.iter().copied()).collect();
+        let short_term: Vec<f64> = meters.iter().flat_map(|meter| meter.short_term.iter().copied()).collect();
+
+        Loudness {
+            integrated: integrated(&blocks),
+            range: range(&short_term),
+            true_peak: meters.iter().map(|meter| meter.true_peak.peak()).fold(0.0, f64::max),
+        }
+    }
+}
+
+/// Energies passing the absolute gate, then those within `relative` LU of their average
+fn gate(energies: &[f64], relative: f64) -> Vec<f64> {
+    let absolute_energy = to_energy(ABSOLUTE_GATE);
+    let loud: Vec<f64> = energies.iter().copied().filter(|&energy| energy > absolute_energy).collect();
+    if loud.is_empty() {
+        return loud;
+    }
+    let threshold = to_energy(to_lufs(loud.iter().sum::<f64>() / loud.len() as f64) + relative);
+    loud.into_iter().filter(|&energy| energy > threshold).collect()
+}
+
+fn integrated(blocks: &[f64]) -> Option<f64> {
+    let gated = gate(blocks, INTEGRATED_RELATIVE_GATE);
+    (!gated.is_empty()).then(|| to_lufs(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+/// Distance between the 10th and 95th percentiles of the gated short-term loudness
+fn range(short_term: &[f64]) -> f64 {
+    let mut levels: Vec<f64> = gate(short_term, RANGE_RELATIVE_GATE).into_iter().map(to_lufs).collect();
+    if levels.is_empty() {
+        return 0.0;
+    }
+    levels.sort_by(f64::total_cmp);
+    let percentile = |p: f64| levels[((levels.len() - 1) as f64 * p).round() as usize];
+    percentile(0.95) - percentile(0.10)
+}
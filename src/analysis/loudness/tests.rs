@@ -0,0 +1,75 @@
+use super::*;
+
+/// Interleaved stereo sine at `dbfs` peak level
+fn sine(frequency: f64, dbfs: f64, seconds: f64, rate: u32) -> Vec<f32> {
+    let amplitude = 10f64.powf(dbfs / 20.0);
+    (0..(seconds * rate as f64) as usize)
+        .flat_map(|n| {
+            let sample = (amplitude * (2.0 * std::f64::consts::PI * frequency * n as f64 / rate as f64).sin()) as f32;
+            [sample, sample]
+        })
+        .collect()
+}
+
+#[test]
+fn test_reference_tone_reads_its_level() {
+    // EBU Tech 3341: a 1kHz stereo tone at -23dBFS measures -23 LUFS
+    for rate in [44100, 48000, 96000] {
+        let mut meter = LoudnessMeter::new(2, rate);
+        meter.process(&sine(1000.0, -23.0, 5.0, rate));
+        let loudness = meter.loudness();
+        let integrated = loudness.integrated.unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "{} LUFS at {}Hz", integrated, rate);
+        assert!(loudness.range < 0.1);
+    }
+}
+
+#[test]
+fn test_gating_ignores_silence() {
+    let mut meter = LoudnessMeter::new(2, 48000);
+    meter.process(&vec![0.0; 48000 * 2 * 3]);
+    assert_eq!(meter.loudness().integrated, None);
+    meter.process(&sine(1000.0, -20.0, 6.0, 48000));
+    // Only the few blocks straddling the start of the tone pull it down
+    let integrated = meter.loudness().integrated.unwrap();
+    assert!((integrated + 20.0).abs() < 0.2, "{} LUFS", integrated);
+}
+
+#[test]
+fn test_loudness_range() {
+    // After EBU Tech 3342 case 1, shortened: a stretch at -20 then one at -30 spans 10 LU
+    let mut meter = LoudnessMeter::new(2, 48000);
+    meter.process(&sine(1000.0, -20.0, 8.0, 48000));
+    meter.process(&sine(1000.0, -30.0, 8.0, 48000));
+    let range = meter.loudness().range;
+    assert!((range - 10.0).abs() < 1.0, "{} LU", range);
+}
+
+#[test]
+fn test_true_peak_between_samples() {
+    // A quarter-rate sine sampled 45 degrees off its crests peaks at 0.707 on the samples
+    let samples: Vec<f32> = (0..48000)
+        .map(|n| (std::f64::consts::FRAC_PI_2 * n as f64 + std::f64::consts::FRAC_PI_4).sin() as f32)
+        .collect();
+    let sample_peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!((sample_peak - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+
+    let mut meter = LoudnessMeter::new(1, 48000);
+    meter.process(&samples);
+    let true_peak = meter.loudness().true_peak;
+    assert!((true_peak - 1.0).abs() < 0.05, "true peak {}", true_peak);
+}
+
+#[test]
+fn test_album_gates_tracks_together() {
+    let mut loud = LoudnessMeter::new(2, 48000);
+    loud.process(&sine(1000.0, -20.0, 3.0, 48000));
+    let mut quiet = LoudnessMeter::new(2, 48000);
+    quiet.process(&sine(1000.0, -26.0, 3.0, 48000));
+
+    // Within 10 LU of each other, so both count and the album lands between them
+    let album = LoudnessMeter::combined([&loud, &quiet]);
+    let integrated = album.integrated.unwrap();
+    assert!(integrated > -23.0 && integrated < -20.0, "{} LUFS", integrated);
+    assert_eq!(album.true_peak, loud.loudness().true_peak);
+}
@@ -0,0 +1,249 @@
+use std::f32::consts::{PI, SQRT_2};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Quietest level shown, in dBFS
+pub const FLOOR_DB: f32 = -60.0;
+
+/// Samples at or beyond full scale are clipped when converted for the device
+pub const CLIP_LEVEL: f32 = 1.0;
+
+/// Time constant of the RMS average
+const RMS_TIME: f32 = 0.3;
+
+/// Time constant of a VU needle, which reaches 99% of a steady tone in 300ms
+const VU_TIME: f32 = 0.065;
+
+/// Scales the rectified average a VU meter follows so a sine reads its RMS level
+const VU_SCALE: f32 = PI / (2.0 * SQRT_2);
+
+/// Attack time constant of a PPM; as it only rises on peaks, a 10ms burst reads about 2dB low
+const PPM_ATTACK: f32 = 0.0025;
+
+/// How fast released levels fall, in dB per second: 24dB in 2.8s for a PPM
+const PPM_RELEASE_DB: f32 = 24.0 / 2.8;
+const DIGITAL_RELEASE_DB: f32 = 20.0 / 1.5;
+
+/// How a meter's needle or bar moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ballistics {
+    /// Average level, rising and falling over about 300ms
+    Vu,
+    /// Quasi-peak: a fast attack that misses only the briefest peaks, then a slow fall
+    Ppm,
+    /// Every sample peak at once, then a steady fall
+    #[default]
+    Digital,
+}
+
+impl Ballistics {
+    pub fn next(self) -> Self {
+        match self {
+            Ballistics::Vu => Ballistics::Ppm,
+            Ballistics::Ppm => Ballistics::Digital,
+            Ballistics::Digital => Ballistics::Vu,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Ballistics::Vu => "VU",
+            Ballistics::Ppm => "PPM",
+            Ballistics::Digital => "Peak",
+        }
+    }
+}
+
+/// What a meter shows for one channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelLevel {
+    /// Reading with the meter's ballistics, in dBFS
+    pub level_db: f32,
+    pub rms_db: f32,
+    /// A sample has reached full scale since the clip indicator was last reset
+    pub clipped: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Channel {
+    /// Linear reading and mean square
+    level: f32,
+    mean_square: f32,
+    clipped: bool,
+}
+
+/// Per-channel level and RMS of what is playing, with latched clip indicators
+#[derive(Debug, Clone)]
+pub struct LevelMeter {
+    ballistics: Ballistics,
+    channels: Vec<Channel>,
+}
+
+impl LevelMeter {
+    pub fn new(ballistics: Ballistics) -> Self {
+        Self { ballistics, channels: vec![Channel::default(); 2] }
+    }
+
+    pub fn ballistics(&self) -> Ballistics {
+        self.ballistics
+    }
+
+    /// Change how the readings move, carrying on from where they are
+    pub fn set_ballistics(&mut self, ballistics: Ballistics) {
+        self.ballistics = ballistics;
+    }
+
+    /// Take in interleaved samples as they were played; a change in channel count starts afresh
+    pub fn push(&mut self, samples: &[f32], channels: usize, sample_rate: u32) {
+        let channels = channels.max(1);
+        if channels != self.channels.len() {
+            self.channels = vec![Channel::default(); channels];
+        }
+        if sample_rate == 0 {
+            return;
+        }
+
+        let per_sample = |time: f32| 1.0 - (-1.0 / (time * sample_rate as f32)).exp();
+        let rms = per_sample(RMS_TIME);
+        let (attack, release) = match self.ballistics {
+            Ballistics::Vu => (per_sample(VU_TIME), per_sample(VU_TIME)),
+            Ballistics::Ppm => (per_sample(PPM_ATTACK), 1.0 - fall(PPM_RELEASE_DB, 1.0 / sample_rate as f32)),
+            Ballistics::Digital => (1.0, 1.0 - fall(DIGITAL_RELEASE_DB, 1.0 / sample_rate as f32)),
+        };
+        let vu = self.ballistics == Ballistics::Vu;
+
+        for frame in samples.chunks_exact(channels) {
+            for (channel, &sample) in self.channels.iter_mut().zip(frame) {
+                let magnitude = sample.abs();
+                channel.clipped |= magnitude >= CLIP_LEVEL;
+                channel.mean_square += (sample * sample - channel.mean_square) * rms;
+                if vu {
+                    channel.level += (magnitude * VU_SCALE - channel.level) * attack;
+                } else if magnitude > channel.level {
+                    channel.level += (magnitude - channel.level) * attack;
+                } else {
+                    channel.level -= channel.level * release;
+                }
+            }
+        }
+    }
+
+    /// Let the readings fall as if `elapsed` of silence had played
+    pub fn decay(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f32();
+        let level = match self.ballistics {
+            Ballistics::Vu => (-seconds / VU_TIME).exp(),
+            Ballistics::Ppm => fall(PPM_RELEASE_DB, seconds),
+            Ballistics::Digital => fall(DIGITAL_RELEASE_DB, seconds),
+        };
+        let mean_square = (-seconds / RMS_TIME).exp();
+        for channel in &mut self.channels {
+            channel.level *= level;
+            channel.mean_square *= mean_square;
+        }
+    }
+
+    /// Clear the clip indicators
+    pub fn reset_clip(&mut self) {
+        self.channels.iter_mut().for_each(|channel| channel.clipped = false);
+    }
+
+    pub fn clipped(&self) -> bool {
+        self.channels.iter().any(|channel| channel.clipped)
+    }
+
+    /// Reading of each channel, in the order they are interleaved
+    pub fn levels(&self) -> Vec<ChannelLevel> {
+        self.channels
+            .iter()
+            .map(|channel| ChannelLevel {
+                level_db: to_db(channel.level),
+                rms_db: to_db(channel.mean_square.sqrt()),
+                clipped: channel.clipped,
+            })
+            .collect()
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new(Ballistics::default())
+    }
+}
+
+/// Factor a level falling at `db_per_second` is multiplied by over `seconds`
+fn fall(db_per_second: f32, seconds: f32) -> f32 {
+    10f32.powf(-db_per_second * seconds / 20.0)
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.max(f32::MIN_POSITIVE).log10()).max(FLOOR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Interleaved stereo 1kHz sine at 48kHz, with the right channel at half the amplitude
+    fn sine(amplitude: f32, millis: usize) -> Vec<f32> {
+        (0..48 * millis)
+            .flat_map(|n| {
+                let sample = amplitude * (2.0 * PI * 1000.0 * n as f32 / 48000.0).sin();
+                [sample, sample / 2.0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ballistics_settle_on_a_steady_tone() {
+        for (ballistics, level_db) in [(Ballistics::Vu, -3.0), (Ballistics::Ppm, 0.0), (Ballistics::Digital, 0.0)] {
+            let mut meter = LevelMeter::new(ballistics);
+            meter.push(&sine(1.0, 2000), 2, 48000);
+            let levels = meter.levels();
+            // A VU meter reads a sine's RMS; the peak meters its peak, give or take their fall between peaks
+            assert!((levels[0].level_db - level_db).abs() < 0.5, "{:?} read {}", ballistics, levels[0].level_db);
+            assert!((levels[1].level_db - levels[0].level_db + 6.02).abs() < 0.1);
+            assert!((levels[0].rms_db + 3.01).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_attack_and_decay() {
+        let burst = sine(0.5, 10);
+        let mut readings = [Ballistics::Vu, Ballistics::Ppm, Ballistics::Digital].map(|ballistics| {
+            let mut meter = LevelMeter::new(ballistics);
+            meter.push(&burst, 2, 48000);
+            meter
+        });
+        let levels = readings.each_ref().map(|meter| meter.levels()[0].level_db);
+        // Digital catches the burst's peak at once, a PPM nearly, a VU meter barely moves
+        assert!((levels[2] + 6.02).abs() < 0.2);
+        assert!(levels[1] < levels[2] && levels[1] > levels[2] - 3.0);
+        assert!(levels[0] < levels[1] - 6.0);
+
+        let meter = &mut readings[2];
+        meter.decay(Duration::from_millis(750));
+        assert!((meter.levels()[0].level_db - (levels[2] - 10.0)).abs() < 0.2);
+        meter.decay(Duration::from_secs(10));
+        assert_eq!(meter.levels()[0].level_db, FLOOR_DB);
+        assert_eq!(meter.levels()[0].rms_db, FLOOR_DB);
+    }
+
+    #[test]
+    fn test_clip_latches_until_reset() {
+        let mut meter = LevelMeter::default();
+        meter.push(&sine(0.9, 20), 2, 48000);
+        assert!(!meter.clipped());
+
+        meter.push(&[1.0, 0.0], 2, 48000);
+        meter.push(&sine(0.1, 20), 2, 48000);
+        let levels = meter.levels();
+        assert!(levels[0].clipped && !levels[1].clipped);
+
+        meter.reset_clip();
+        assert!(!meter.clipped());
+        meter.push(&[0.0, -1.5], 2, 48000);
+        assert!(meter.levels()[1].clipped);
+    }
+}
@@ -0,0 +1,11 @@
+//! Analysis of audio: offline for files, and live for the spectrum and levels of what is playing
+
+pub mod loudness;
+pub mod meter;
+pub mod scan;
+pub mod spectrum;
+
+pub use loudness::{Loudness, LoudnessMeter};
+pub use meter::{Ballistics, ChannelLevel, LevelMeter};
+pub use scan::{LoudnessScanner, ScanProgress, ScanReport};
+pub use spectrum::{SpectrumAnalyzer, SpectrumSettings};
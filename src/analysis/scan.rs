@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use crate::audio::formats::{get_decoder, AudioDecoder, DecoderType};
+use crate::audio::replaygain::REPLAYGAIN_REFERENCE;
+use crate::metadata::{Metadata, MetadataManager};
+use super::loudness::{Loudness, LoudnessMeter};
+
+/// Samples decoded per read while measuring
+const CHUNK_SAMPLES: usize = 16384;
+
+/// Decode a whole track and measure it
+pub fn analyze(path: &Path) -> Result<LoudnessMeter, Box<dyn Error>> {
+    let mut reader = get_decoder(path).decode(path)?;
+    let mut meter = LoudnessMeter::new(reader.format.channels, reader.format.sample_rate);
+    let mut buffer = vec![0.0f32; CHUNK_SAMPLES];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(meter);
+        }
+        meter.process(&buffer[..read]);
+    }
+}
+
+/// ReplayGain tags for a track measured as `track`, on an album measured as `album`
+pub fn replaygain_tags(track: &Loudness, album: Option<&Loudness>) -> Vec<(String, String)> {
+    let mut tags = Vec::new();
+    let mut push = |kind: &str, loudness: &Loudness| {
+        if let Some(integrated) = loudness.integrated {
+            let gain = REPLAYGAIN_REFERENCE as f64 - integrated;
+            tags.push((format!("REPLAYGAIN_{}_GAIN", kind), format!("{:+.2} dB", gain)));
+            tags.push((format!("REPLAYGAIN_{}_PEAK", kind), format!("{:.6}", loudness.true_peak)));
+        }
+    };
+    push("TRACK", track);
+    if let Some(album) = album {
+        push("ALBUM", album);
+    }
+    tags
+}
+
+/// Album and album artist a track is grouped under, if it is tagged with an album
+fn album_key(metadata: &Metadata) -> Option<(String, String)> {
+    let album = metadata.album.as_deref()?.trim();
+    if album.is_empty() {
+        return None;
+    }
+    // Vorbis comments and ID3 name the album artist differently
+    let album_artist = ["ALBUMARTIST", "ALBUM ARTIST", "ALBUM_ARTIST", "TPE2"]
+        .iter()
+        .find_map(|name| metadata.extra.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)))
+        .map(|(_, value)| value.as_str())
+        .or(metadata.artist.as_deref())
+        .unwrap_or_default();
+    Some((album.to_lowercase(), album_artist.trim().to_lowercase()))
+}
+
+/// Audio files below `dir`, in path order
+fn audio_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else if !matches!(DecoderType::for_path(&path), DecoderType::None) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Where a scan has got to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanProgress {
+    /// Measuring file `done + 1` of `total`
+    Analysing { done: usize, total: usize },
+    /// Tags written to `tagged` files; `failed` could not be measured or tagged
+    Finished { tagged: usize, failed: usize },
+}
+
+impl fmt::Display for ScanProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanProgress::Analysing { done, total } => write!(f, "Loudness scan: {}/{} files", done, total),
+            ScanProgress::Finished { tagged, failed: 0 } => write!(f, "Loudness scan: tagged {} files", tagged),
+            ScanProgress::Finished { tagged, failed } => {
+                write!(f, "Loudness scan: tagged {} files, {} failed", tagged, failed)
+            }
+        }
+    }
+}
+
+/// Measurements of one scanned track
+#[derive(Debug, Clone)]
+pub struct TrackLoudness {
+    pub path: PathBuf,
+    pub loudness: Loudness,
+    /// Album the track was grouped into, as an index into `ScanReport::albums`
+    pub album: Option<usize>,
+}
+
+/// Measurements of the tracks sharing an album and album artist
+#[derive(Debug, Clone)]
+pub struct AlbumLoudness {
+    pub album: String,
+    pub album_artist: String,
+    pub loudness: Loudness,
+}
+
+/// Outcome of scanning a directory
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    pub tracks: Vec<TrackLoudness>,
+    pub albums: Vec<AlbumLoudness>,
+    /// Files that could not be measured or tagged, and why
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Measures every track below a directory and writes ReplayGain tags back to them
+pub struct LoudnessScanner {
+    metadata: MetadataManager,
+    write_tags: bool,
+}
+
+impl LoudnessScanner {
+    pub fn new() -> Self {
+        Self {
+            metadata: MetadataManager::with_format_parsers(),
+            write_tags: true,
+        }
+    }
+
+    /// Measure without touching any files
+    pub fn dry_run(mut self) -> Self {
+        self.write_tags = false;
+        self
+    }
+
+    /// Scan `dir`, reporting progress as each file is measured
+    pub fn scan(&self, dir: &Path, mut progress: impl FnMut(ScanProgress)) -> ScanReport {
+        let files = audio_files(dir);
+        let mut report = ScanReport::default();
+        let mut meters = Vec::new();
+        let mut groups: BTreeMap<(String, String), Vec<usize>> = BTreeMap::new();
+
+        for (done, path) in files.iter().enumerate() {
+            progress(ScanProgress::Analysing { done, total: files.len() });
+            match analyze(path) {
+                Ok(meter) => {
+                    let metadata = self.metadata.parse_metadata(path).unwrap_or_default();
+                    if let Some(key) = album_key(&metadata) {
+                        groups.entry(key).or_default().push(report.tracks.len());
+                    }
+                    report.tracks.push(TrackLoudness { path: path.clone(), loudness: meter.loudness(), album: None });
+                    meters.push(meter);
+                }
+                Err(e) => report.failed.push((path.clone(), e.to_string())),
+            }
+        }
+
+        for ((album, album_artist), tracks) in groups {
+            let loudness = LoudnessMeter::combined(tracks.iter().map(|&track| &meters[track]));
+            for &track in &tracks {
+                report.tracks[track].album = Some(report.albums.len());
+            }
+            report.albums.push(AlbumLoudness { album, album_artist, loudness });
+        }
+
+        let mut tagged = 0;
+        if self.write_tags {
+            for track in &report.tracks {
+                let album = track.album.map(|album| &report.albums[album].loudness);
+                match self.metadata.write_tags(&track.path, &replaygain_tags(&track.loudness, album)) {
+                    Ok(()) => tagged += 1,
+                    Err(e) => report.failed.push((track.path.clone(), format!("could not write tags: {}", e))),
+                }
+            }
+        }
+
+        progress(ScanProgress::Finished { tagged, failed: report.failed.len() });
+        report
+    }
+
+    /// Scan `dir` on a background thread, which sends progress until it finishes
+    pub fn spawn(self, dir: PathBuf) -> Receiver<ScanProgress> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            self.scan(&dir, |progress| {
+                let _ = sender.send(progress);
+            });
+        });
+        receiver
+    }
+}
+
+impl Default for LoudnessScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_from_loudness() {
+        let track = Loudness { integrated: Some(-12.5), range: 4.0, true_peak: 0.98765432 };
+        let album = Loudness { integrated: Some(-20.0), range: 6.0, true_peak: 1.0 };
+        let tags = replaygain_tags(&track, Some(&album));
+        let value = |key: &str| tags.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(value("REPLAYGAIN_TRACK_GAIN"), Some("-5.50 dB"));
+        assert_eq!(value("REPLAYGAIN_TRACK_PEAK"), Some("0.987654"));
+        assert_eq!(value("REPLAYGAIN_ALBUM_GAIN"), Some("+2.00 dB"));
+
+        // Silence has no loudness to level
+        let silent = Loudness { integrated: None, range: 0.0, true_peak: 0.0 };
+        assert!(replaygain_tags(&silent, None).is_empty());
+    }
+
+    #[test]
+    fn test_scan_tags_albums() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["testaudio-short.flac", "testaudio-short.mp3", "testaudio-short.ogg"] {
+            fs::copy(Path::new("test").join(name), dir.path().join(name)).unwrap();
+        }
+        // Files that do not decode are reported, not fatal
+        fs::write(dir.path().join("notes.txt"), "not audio").unwrap();
+        let mut tagged = MetadataManager::with_format_parsers();
+        tagged.write_tags(&dir.path().join("testaudio-short.flac"), &[("ALBUM".to_string(), "Tones".to_string())]).unwrap();
+        tagged.write_tags(&dir.path().join("testaudio-short.ogg"), &[("ALBUM".to_string(), "tones ".to_string())]).unwrap();
+        tagged = MetadataManager::with_format_parsers();
+
+        let mut seen = Vec::new();
+        let report = LoudnessScanner::new().scan(dir.path(), |progress| seen.push(progress));
+        assert_eq!(seen.first(), Some(&ScanProgress::Analysing { done: 0, total: 3 }));
+        assert_eq!(seen.last(), Some(&ScanProgress::Finished { tagged: 3, failed: 0 }));
+        assert_eq!(report.tracks.len(), 3);
+        assert_eq!(report.albums.len(), 1);
+
+        let flac = tagged.parse_metadata(&dir.path().join("testaudio-short.flac")).unwrap();
+        let ogg = tagged.parse_metadata(&dir.path().join("testaudio-short.ogg")).unwrap();
+        let mp3 = tagged.parse_metadata(&dir.path().join("testaudio-short.mp3")).unwrap();
+        assert!(flac.extra.contains_key("REPLAYGAIN_TRACK_GAIN"));
+        assert_eq!(flac.extra.get("REPLAYGAIN_ALBUM_GAIN"), ogg.extra.get("REPLAYGAIN_ALBUM_GAIN"));
+        assert!(mp3.extra.contains_key("REPLAYGAIN_TRACK_PEAK"));
+        assert!(!mp3.extra.contains_key("REPLAYGAIN_ALBUM_GAIN"));
+    }
+}
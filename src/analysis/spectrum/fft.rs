@@ -0,0 +1,56 @@
+use std::f32::consts::PI;
+
+/// Radix-2 FFT of a fixed power-of-two length, with its twiddles and bit reversal worked out once
+#[derive(Debug, Clone)]
+pub(super) struct Fft {
+    /// `e^(-2πik/n)` for the first half of the circle
+    twiddles: Vec<(f32, f32)>,
+    reversed: Vec<usize>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two(), "FFT size must be a power of two");
+        let bits = size.trailing_zeros();
+        Self {
+            twiddles: (0..size / 2)
+                .map(|k| {
+                    let angle = -2.0 * PI * k as f32 / size as f32;
+                    (angle.cos(), angle.sin())
+                })
+                .collect(),
+            reversed: (0..size)
+                .map(|i| if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) })
+                .collect(),
+        }
+    }
+
+    /// Transform the complex signal in `re` and `im` in place
+    pub fn process(&self, re: &mut [f32], im: &mut [f32]) {
+        let size = self.reversed.len();
+        for (i, &j) in self.reversed.iter().enumerate() {
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= size {
+            let stride = size / len;
+            for start in (0..size).step_by(len) {
+                for k in 0..len / 2 {
+                    let (wr, wi) = self.twiddles[k * stride];
+                    let (a, b) = (start + k, start + k + len / 2);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
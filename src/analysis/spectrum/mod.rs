@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+mod fft;
+#[cfg(test)]
+mod tests;
+
+use fft::Fft;
+
+/// Frames analysed at a time; about 11Hz per bin at 44.1kHz
+pub const FFT_SIZE: usize = 4096;
+
+/// Quietest level shown, in dBFS
+pub const FLOOR_DB: f32 = -72.0;
+
+/// Limits on the number of bars
+pub const MIN_BANDS: usize = 4;
+pub const MAX_BANDS: usize = 64;
+
+/// Range of frequencies the bars cover
+const LOW_HZ: f32 = 20.0;
+const HIGH_HZ: f32 = 20_000.0;
+
+/// Without new samples for this long the output has gone quiet, so the bars fall away
+const IDLE: Duration = Duration::from_millis(150);
+
+/// How the bars are laid out and how they move
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectrumSettings {
+    pub bands: usize,
+    /// How fast bars and released peaks fall, in dB per second
+    pub falloff_db: f32,
+    /// How long a peak marker stays at the highest level reached
+    pub peak_hold: Duration,
+}
+
+impl Default for SpectrumSettings {
+    fn default() -> Self {
+        Self {
+            bands: 24,
+            falloff_db: 30.0,
+            peak_hold: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Levels of log-spaced frequency bands in what is playing, with falloff and peak hold
+#[derive(Debug, Clone)]
+pub struct SpectrumAnalyzer {
+    settings: SpectrumSettings,
+    fft: Fft,
+    /// Hann window, scaled so a full-scale sine reads 0dB
+    window: Vec<f32>,
+    /// The most recent frames mixed down to mono, newest last
+    history: VecDeque<f32>,
+    /// Time since samples last arrived
+    idle: Duration,
+    /// Level of each bar, and of its peak marker and how long that has been held, in dBFS
+    levels: Vec<f32>,
+    peaks: Vec<f32>,
+    held: Vec<Duration>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(settings: SpectrumSettings) -> Self {
+        // The Hann window halves a sine's amplitude, and the spectrum splits it over two bins
+        let window = (0..FFT_SIZE)
+            .map(|i| (1.0 - (2.0 * PI * i as f32 / FFT_SIZE as f32).cos()) * 2.0 / FFT_SIZE as f32)
+            .collect();
+        let mut analyzer = Self {
+            settings,
+            fft: Fft::new(FFT_SIZE),
+            window,
+            history: VecDeque::with_capacity(FFT_SIZE),
+            idle: IDLE,
+            levels: Vec::new(),
+            peaks: Vec::new(),
+            held: Vec::new(),
+            re: vec![0.0; FFT_SIZE],
+            im: vec![0.0; FFT_SIZE],
+        };
+        analyzer.set_bands(analyzer.settings.bands);
+        analyzer
+    }
+
+    pub fn settings(&self) -> &SpectrumSettings {
+        &self.settings
+    }
+
+    /// Change the number of bars, starting them all from silence
+    pub fn set_bands(&mut self, bands: usize) {
+        let bands = bands.clamp(MIN_BANDS, MAX_BANDS);
+        self.settings.bands = bands;
+        self.levels = vec![FLOOR_DB; bands];
+        self.peaks = vec![FLOOR_DB; bands];
+        self.held = vec![Duration::ZERO; bands];
+    }
+
+    /// Add interleaved samples as they were played
+    pub fn push(&mut self, samples: &[f32], channels: usize) {
+        let channels = channels.max(1);
+        if samples.len() < channels {
+            return;
+        }
+        let frames = samples.len() / channels;
+        let skip = frames.saturating_sub(FFT_SIZE);
+        for frame in samples.chunks_exact(channels).skip(skip) {
+            if self.history.len() == FFT_SIZE {
+                self.history.pop_front();
+            }
+            self.history.push_back(frame.iter().sum::<f32>() / channels as f32);
+        }
+        self.idle = Duration::ZERO;
+    }
+
+    /// Measure the latest samples and move the bars and peaks on by `elapsed`
+    pub fn update(&mut self, sample_rate: u32, elapsed: Duration) {
+        let fall = self.settings.falloff_db * elapsed.as_secs_f32();
+        let active = self.idle < IDLE;
+        self.idle = self.idle.saturating_add(elapsed);
+        let targets = match active {
+            true => self.measure(sample_rate),
+            false => vec![FLOOR_DB; self.levels.len()],
+        };
+
+        for (band, target) in targets.into_iter().enumerate() {
+            let level = target.max(self.levels[band] - fall).max(FLOOR_DB);
+            self.levels[band] = level;
+            if level >= self.peaks[band] {
+                self.peaks[band] = level;
+                self.held[band] = Duration::ZERO;
+            } else if self.held[band] >= self.settings.peak_hold {
+                self.peaks[band] = (self.peaks[band] - fall).max(level);
+            } else {
+                self.held[band] += elapsed;
+            }
+        }
+    }
+
+    /// Level of each bar in dBFS, lowest frequency first
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// Level of each bar's peak marker in dBFS
+    pub fn peaks(&self) -> &[f32] {
+        &self.peaks
+    }
+
+    /// Level in each band of the frames in `history`
+    fn measure(&mut self, sample_rate: u32) -> Vec<f32> {
+        // Anything short of a full window is padded with silence at the start
+        let start = FFT_SIZE - self.history.len();
+        self.re.iter_mut().for_each(|sample| *sample = 0.0);
+        self.im.iter_mut().for_each(|sample| *sample = 0.0);
+        for (i, sample) in self.history.iter().enumerate() {
+            self.re[start + i] = sample * self.window[start + i];
+        }
+        self.fft.process(&mut self.re, &mut self.im);
+
+        let magnitude = |bin: usize| (self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin]).sqrt();
+        let bin_hz = sample_rate.max(1) as f32 / FFT_SIZE as f32;
+        let last_bin = FFT_SIZE / 2;
+        let bands = self.levels.len();
+        (0..bands)
+            .map(|band| {
+                let (low, high) = (band_edge(band, bands), band_edge(band + 1, bands));
+                let first = (low / bin_hz).ceil() as usize;
+                let last = ((high / bin_hz).ceil() as usize).min(last_bin + 1);
+                let amplitude = match first < last {
+                    true => (first..last).map(magnitude).fold(0.0, f32::max),
+                    // Narrower than a bin, so take the bin at its centre
+                    false if first <= last_bin => magnitude((band_frequency(band, bands) / bin_hz).round() as usize),
+                    false => 0.0,
+                };
+                (20.0 * amplitude.max(f32::MIN_POSITIVE).log10()).max(FLOOR_DB)
+            })
+            .collect()
+    }
+}
+
+/// Lowest frequency of band `index` of `bands`, spaced evenly in octaves from 20Hz to 20kHz
+pub fn band_edge(index: usize, bands: usize) -> f32 {
+    LOW_HZ * (HIGH_HZ / LOW_HZ).powf(index as f32 / bands.max(1) as f32)
+}
+
+/// Centre frequency of band `index` of `bands`, on a log scale
+pub fn band_frequency(index: usize, bands: usize) -> f32 {
+    (band_edge(index, bands) * band_edge(index + 1, bands)).sqrt()
+}
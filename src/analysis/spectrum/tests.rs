@@ -0,0 +1,81 @@
+use super::*;
+
+/// Interleaved stereo sine at `amplitude`
+fn sine(frequency: f32, amplitude: f32, frames: usize, rate: u32) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|n| {
+            let sample = amplitude * (2.0 * PI * frequency * n as f32 / rate as f32).sin();
+            [sample, sample]
+        })
+        .collect()
+}
+
+/// Band whose range holds `frequency`
+fn band_of(frequency: f32, bands: usize) -> usize {
+    (0..bands).find(|&band| band_edge(band + 1, bands) > frequency).unwrap()
+}
+
+#[test]
+fn test_fft_matches_dft() {
+    let input: Vec<f32> = (0..16).map(|i| ((i * 7) % 5) as f32 - 2.0).collect();
+    let (mut re, mut im) = (input.clone(), vec![0.0; 16]);
+    Fft::new(16).process(&mut re, &mut im);
+    for k in 0..16 {
+        let (mut dr, mut di) = (0.0, 0.0);
+        for (n, x) in input.iter().enumerate() {
+            let angle = -2.0 * PI * (k * n) as f32 / 16.0;
+            dr += x * angle.cos();
+            di += x * angle.sin();
+        }
+        assert!((re[k] - dr).abs() < 1e-4 && (im[k] - di).abs() < 1e-4, "bin {}", k);
+    }
+}
+
+#[test]
+fn test_sine_lights_its_band() {
+    for (frequency, rate) in [(1000.0, 44100), (100.0, 48000), (8000.0, 96000)] {
+        let mut analyzer = SpectrumAnalyzer::new(SpectrumSettings::default());
+        analyzer.push(&sine(frequency, 0.5, FFT_SIZE, rate), 2);
+        analyzer.update(rate, Duration::from_millis(20));
+
+        let band = band_of(frequency, 24);
+        let levels = analyzer.levels();
+        // Half scale is 6dB down, less up to 1.4dB for a tone between two bins
+        assert!((levels[band] + 6.0).abs() < 1.5, "{}Hz reads {}dB", frequency, levels[band]);
+        let others = levels.iter().enumerate().filter(|(index, _)| index.abs_diff(band) > 2);
+        for (index, level) in others {
+            assert!(*level < -50.0, "{}Hz leaks {}dB into band {}", frequency, level, index);
+        }
+        assert_eq!(analyzer.peaks()[band], levels[band]);
+    }
+}
+
+#[test]
+fn test_bars_fall_and_peaks_hold() {
+    let settings = SpectrumSettings { bands: 16, falloff_db: 20.0, peak_hold: Duration::from_millis(500) };
+    let mut analyzer = SpectrumAnalyzer::new(settings);
+    analyzer.push(&sine(1000.0, 1.0, FFT_SIZE, 48000), 2);
+    analyzer.update(48000, Duration::from_millis(100));
+    let band = band_of(1000.0, 16);
+    let top = analyzer.levels()[band];
+    assert!(top > -1.0);
+
+    // Once nothing arrives the bar falls at the falloff rate and the peak stays put
+    let tick = Duration::from_millis(100);
+    for _ in 0..3 {
+        analyzer.update(48000, tick);
+    }
+    assert!((analyzer.levels()[band] - (top - 4.0)).abs() < 0.01, "{}", analyzer.levels()[band]);
+    assert_eq!(analyzer.peaks()[band], top);
+
+    // After the hold the peak falls too, never below the bar
+    for _ in 0..10 {
+        analyzer.update(48000, tick);
+    }
+    assert!(analyzer.peaks()[band] < top - 10.0);
+    assert!(analyzer.peaks()[band] >= analyzer.levels()[band]);
+
+    analyzer.set_bands(200);
+    assert_eq!(analyzer.levels().len(), MAX_BANDS);
+    assert!(analyzer.levels().iter().all(|&level| level == FLOOR_DB));
+}
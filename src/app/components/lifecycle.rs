@@ -24,6 +24,7 @@ impl ComponentLifecycle for ComponentManager {
             Action::PreviousTrack => Event::Key(KeyEvent::Previous),
             Action::VolumeUp => Event::Key(KeyEvent::VolumeUp),
             Action::VolumeDown => Event::Key(KeyEvent::VolumeDown),
+            Action::ToggleMute => Event::Key(KeyEvent::Mute),
             Action::Player(player_action) => match player_action {
                 PlayerAction::Play => Event::Key(KeyEvent::Play),
                 PlayerAction::Pause => Event::Key(KeyEvent::Pause),
@@ -38,7 +39,7 @@ impl ComponentLifecycle for ComponentManager {
                 UIAction::Focus(direction) => Event::Key(KeyEvent::Focus(*direction)),
                 UIAction::UpdateTheme(_) | UIAction::Resize { .. } => Event::System(SystemEvent::TrackLoaded),
             },
-            Action::Playlist(_) | Action::Metadata(_) => Event::System(SystemEvent::TrackLoaded),
+            Action::Playlist(_) | Action::Metadata(_) | Action::Equalizer(_) => Event::System(SystemEvent::TrackLoaded),
             Action::App(app_action) => match *app_action {
                 AppAction::Error(ref msg) => Event::System(SystemEvent::Error(msg.clone())),
                 AppAction::Quit => Event::Key(KeyEvent::Escape),
@@ -48,7 +49,7 @@ impl ComponentLifecycle for ComponentManager {
             Action::Select => Event::Key(KeyEvent::Enter),
             Action::Back => Event::Key(KeyEvent::Escape),
             Action::Refresh => return None,
-            Action::SetVolume(_) => Event::System(SystemEvent::TrackLoaded),
+            Action::SetVolume(_) | Action::SetMuted(_) => Event::System(SystemEvent::TrackLoaded),
         };
         Some(event)
     }
@@ -34,7 +34,7 @@ impl<T: Component + 'static> EventHandler for ComponentWrapper<T> {
                     KeyEvent::Space | // Pause/Play
                     KeyEvent::Play | KeyEvent::Pause | KeyEvent::Stop |
                     KeyEvent::Next | KeyEvent::Previous |
-                    KeyEvent::VolumeUp | KeyEvent::VolumeDown |
+                    KeyEvent::VolumeUp | KeyEvent::VolumeDown | KeyEvent::Mute |
                     KeyEvent::Record | KeyEvent::FastForward |
                     KeyEvent::Rewind => false,
                     
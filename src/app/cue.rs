@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+use crate::audio::cue::{Cue, CueDirection, CueStep};
+use crate::audio::{AudioPlayer, PlaybackState as EngineState, SeekTarget};
+use crate::events::{Action, PlayerAction, PlaylistAction};
+use crate::state::SeekState;
+use super::App;
+
+/// Fast-forward and rewind scanning for the App
+impl App {
+    /// Keep `SeekState` in step with the transport actions
+    pub(crate) fn follow_seek_state(&mut self, action: &Action) {
+        self.state.player.seek_state = match action {
+            Action::Player(PlayerAction::FastForward) => SeekState::FastForward,
+            Action::Player(PlayerAction::Rewind) => SeekState::Rewind,
+            Action::Play | Action::Stop | Action::Player(PlayerAction::Play | PlayerAction::Stop | PlayerAction::StopEject) => {
+                SeekState::Normal
+            }
+            _ => return,
+        };
+    }
+
+    /// Scan through the track while the seek state is fast-forward or rewind; call once per UI tick
+    pub(crate) fn poll_cue(&mut self) {
+        let direction = match self.state.player.seek_state {
+            SeekState::FastForward => CueDirection::Forward,
+            SeekState::Rewind => CueDirection::Backward,
+            SeekState::Normal => {
+                self.cue = None;
+                return;
+            }
+        };
+        // A pause holds the scan; it starts again from the slowest rate
+        if self.player.state() != EngineState::Playing {
+            self.cue = None;
+            return;
+        }
+
+        let now = Instant::now();
+        let cue = match self.cue.as_mut() {
+            Some(cue) if cue.direction() == direction => cue,
+            _ => self.cue.insert(Cue::new(direction, now)),
+        };
+        let Some(step) = cue.step(now, self.player.position(), self.player.duration()) else {
+            return;
+        };
+        match step {
+            CueStep::Jump(target) => self.cue_seek(target),
+            CueStep::PastEnd(overshoot) => {
+                if self.cue_into_track(1) {
+                    self.cue_seek(overshoot);
+                } else {
+                    // What is left of the track plays out as normal
+                    self.end_cue();
+                }
+            }
+            CueStep::PastStart(overshoot) => {
+                if self.cue_into_track(-1) {
+                    let duration = self.player.duration().unwrap_or_default();
+                    self.cue_seek(duration.saturating_sub(overshoot));
+                } else {
+                    self.cue_seek(Duration::ZERO);
+                    self.end_cue();
+                }
+            }
+        }
+    }
+
+    fn cue_seek(&mut self, target: Duration) {
+        if let Err(e) = self.player.seek_to(SeekTarget::Absolute(target)) {
+            let _ = self.logger.log_debug(&format!("Cue could not seek to {:?}: {}", target, e));
+        }
+        self.state.player.position = self.player.position();
+    }
+
+    /// Load the playlist entry `offset` from the current one, if cueing may cross into it
+    fn cue_into_track(&mut self, offset: isize) -> bool {
+        let across = self.preferences.as_ref().is_some_and(|prefs| prefs.config().cue_across_tracks);
+        let playlist = &self.state.playlist;
+        let Some(index) = playlist.selected_index.and_then(|index| index.checked_add_signed(offset)) else {
+            return false;
+        };
+        if !across || index >= playlist.tracks.len() {
+            return false;
+        }
+        self.process_action(Action::Playlist(PlaylistAction::SelectTrack(index)));
+        self.player.current_file() == self.state.playlist.tracks.get(index).map(String::as_str)
+    }
+
+    /// Drop back to normal playback, as if Play had been pressed
+    fn end_cue(&mut self) {
+        self.cue = None;
+        self.process_action(Action::Player(PlayerAction::Play));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::audio::eq::find_preset;
+use crate::events::EqualizerAction;
+use super::App;
+
+/// Equalizer wiring for the App
+impl App {
+    /// Hand EQ changes to the engine and remember presets and the selection in preferences
+    pub(crate) fn apply_equalizer_action(&mut self, action: EqualizerAction) {
+        let (enabled, preset) = match action {
+            EqualizerAction::Apply { enabled, preset } => {
+                if let Err(e) = self.player.set_equalizer(enabled.then(|| preset.clone())) {
+                    let _ = self.logger.log_debug(&format!("Playback engine rejected EQ {}: {}", preset.name, e));
+                }
+                (enabled, preset)
+            }
+            EqualizerAction::SavePreset(preset) => {
+                if let Some(prefs) = self.preferences.as_mut() {
+                    prefs.save_eq_preset(preset.clone());
+                }
+                (self.player.equalizer().is_some(), preset)
+            }
+        };
+
+        let Some(prefs) = self.preferences.as_mut() else {
+            return;
+        };
+        // An edited curve isn't saved until it becomes a preset, so keep the last named one
+        let name = match find_preset(&preset.name, &prefs.config().eq_user_presets) {
+            Some(_) => preset.name,
+            None => prefs.config().eq_preset.clone(),
+        };
+        prefs.update_equalizer(enabled, name);
+        if let Err(e) = prefs.save_if_dirty() {
+            let _ = self.logger.log_debug(&format!("Could not save EQ preferences: {}", e));
+        }
+    }
+}
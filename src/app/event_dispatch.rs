@@ -4,10 +4,8 @@ use crate::events::{
     Event, EventResult, Action, AppAction,
     EventDispatcher, EventHandler, EventError
 };
-use crate::components::{
-    Component, LibraryBrowser, TrackList, TrackDetails,
-    CurrentTrackInfo, PlaybackStatus, Controls, VolumeControl
-};
+use crate::components::Component;
+use super::AppComponents;
 
 /// Wrapper for components that implement the EventHandler trait
 struct ComponentWrapper<T: Component> {
@@ -40,25 +38,19 @@ impl EventManager {
         }
     }
 
-    pub fn register_components(
-        &mut self,
-        library_browser: &Rc<RefCell<LibraryBrowser>>,
-        track_list: &Rc<RefCell<TrackList>>,
-        track_details: &Rc<RefCell<TrackDetails>>,
-        current_track_info: &Rc<RefCell<CurrentTrackInfo>>,
-        playback_status: &Rc<RefCell<PlaybackStatus>>,
-        controls: &Rc<RefCell<Controls>>,
-        volume_control: &Rc<RefCell<VolumeControl>>,
-    ) {
+    pub fn register_components(&mut self, components: &AppComponents) {
         // Register each component with a wrapper
         let components: Vec<Box<dyn EventHandler>> = vec![
-            Box::new(ComponentWrapper { component: Rc::clone(library_browser) }),
-            Box::new(ComponentWrapper { component: Rc::clone(track_list) }),
-            Box::new(ComponentWrapper { component: Rc::clone(track_details) }),
-            Box::new(ComponentWrapper { component: Rc::clone(current_track_info) }),
-            Box::new(ComponentWrapper { component: Rc::clone(playback_status) }),
-            Box::new(ComponentWrapper { component: Rc::clone(controls) }),
-            Box::new(ComponentWrapper { component: Rc::clone(volume_control) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.library_browser) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.track_list) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.track_details) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.current_track_info) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.playback_status) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.equalizer) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.visualizer) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.controls) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.meters) }),
+            Box::new(ComponentWrapper { component: Rc::clone(&components.volume_control) }),
         ];
 
         // Register components with the dispatcher
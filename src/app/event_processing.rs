@@ -19,7 +19,7 @@ impl App {
                     // Process the click event
                     if let Ok(action) = self.event_manager.dispatch_event(&Event::Mouse(mouse_event)) {
                         let _ = self.logger.log_debug(&format!("Generated action from click: {:?}", action));
-                        self.component_manager.update_components(action);
+                        self.process_action(action);
                     }
                 }
             },
@@ -28,7 +28,7 @@ impl App {
                 let _focused_component = self.focus_manager.current_focus();
                 if let Ok(action) = self.event_manager.dispatch_event(&Event::Mouse(mouse_event)) {
                     let _ = self.logger.log_debug(&format!("Generated action from scroll: {:?}", action));
-                    self.component_manager.update_components(action);
+                    self.process_action(action);
                 }
             }
         }
@@ -70,7 +70,7 @@ impl App {
             let _ = self.logger.log_debug("Component should process event");
             if let Ok(action) = self.event_manager.dispatch_event(&Event::Key(key_event)) {
                 let _ = self.logger.log_debug(&format!("Generated action: {:?}", action));
-                self.component_manager.update_components(action);
+                self.process_action(action);
             }
         } else {
             let _ = self.logger.log_debug("Event ignored - component not focused");
@@ -83,7 +83,7 @@ impl App {
         let _ = self.logger.log_debug("Processing global hotkey");
         if let Ok(action) = self.event_manager.dispatch_event(&Event::Key(key_event)) {
             let _ = self.logger.log_debug(&format!("Generated action from hotkey: {:?}", action));
-            self.component_manager.update_components(action);
+            self.process_action(action);
         }
         Ok(())
     }
@@ -116,7 +116,11 @@ impl App {
                     KeyEvent::Space | KeyEvent::Quit | KeyEvent::Escape |
                     KeyEvent::Play | KeyEvent::Pause | KeyEvent::Stop |
                     KeyEvent::Next | KeyEvent::Previous |
-                    KeyEvent::VolumeUp | KeyEvent::VolumeDown => {
+                    KeyEvent::VolumeUp | KeyEvent::VolumeDown | KeyEvent::Mute |
+                    KeyEvent::SeekBackward | KeyEvent::SeekForward |
+                    KeyEvent::SpeedDown | KeyEvent::SpeedUp |
+                    KeyEvent::PreviousChapter | KeyEvent::NextChapter |
+                    KeyEvent::FastForward | KeyEvent::Rewind | KeyEvent::Record => {
                         self.process_hotkey_event(key_event)
                     },
 
@@ -129,7 +133,7 @@ impl App {
 
                         if let Ok(action) = self.event_manager.dispatch_event(&event) {
                             let _ = self.logger.log_debug(&format!("Generated action from other event: {:?}", action));
-                            self.component_manager.update_components(action);
+                            self.process_action(action);
                         }
                         Ok(())
                     },
@@ -141,7 +145,7 @@ impl App {
                 let _ = self.logger.log_debug("Processing system event");
                 if let Ok(action) = self.event_manager.dispatch_event(&event) {
                     let _ = self.logger.log_debug(&format!("Generated action from system event: {:?}", action));
-                    self.component_manager.update_components(action);
+                    self.process_action(action);
                 }
                 Ok(())
             },
@@ -155,7 +159,7 @@ impl App {
 
                 if let Ok(action) = self.event_manager.dispatch_event(&event) {
                     let _ = self.logger.log_debug(&format!("Generated action from other event: {:?}", action));
-                    self.component_manager.update_components(action);
+                    self.process_action(action);
                 }
                 Ok(())
             },
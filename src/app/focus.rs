@@ -1,8 +1,6 @@
-use crate::components::{
-    Component, LibraryBrowser, TrackList, TrackDetails,
-    CurrentTrackInfo, PlaybackStatus, Controls, VolumeControl
-};
+use crate::components::Component;
 use crate::events::{Event, KeyEvent, FocusDirection, EventResult};
+use super::AppComponents;
 
 /// Manages focus state and navigation between components.
 /// 
@@ -33,7 +31,10 @@ impl FocusManager {
                 "track_details".to_string(),
                 "current_track_info".to_string(),
                 "playback_status".to_string(),
+                "equalizer".to_string(),
+                "visualizer".to_string(),
                 "controls".to_string(),
+                "meters".to_string(),
                 "volume_control".to_string(),
             ],
             current_focus: 0,
@@ -52,6 +53,18 @@ impl FocusManager {
         }
     }
 
+    /// Put `name` straight after `after` in the focus order, or take it out of the order
+    /// without one; the current focus stays where it is
+    pub fn place_after(&mut self, name: &str, after: Option<&str>) {
+        let focused = self.current_focus().to_string();
+        self.component_order.retain(|component| component != name);
+        if let Some(index) = after.and_then(|after| self.component_order.iter().position(|component| component == after)) {
+            self.component_order.insert(index + 1, name.to_string());
+        }
+        self.current_focus = 0;
+        self.set_focus(&focused);
+    }
+
     /// Moves focus in the specified direction
     pub fn move_focus(&mut self, direction: FocusDirection) {
         match direction {
@@ -85,7 +98,17 @@ impl FocusManager {
             Event::Key(KeyEvent::Next) |
             Event::Key(KeyEvent::Previous) |
             Event::Key(KeyEvent::VolumeUp) |
-            Event::Key(KeyEvent::VolumeDown) => true,
+            Event::Key(KeyEvent::VolumeDown) |
+            Event::Key(KeyEvent::Mute) |
+            Event::Key(KeyEvent::SeekBackward) |
+            Event::Key(KeyEvent::SeekForward) |
+            Event::Key(KeyEvent::SpeedDown) |
+            Event::Key(KeyEvent::SpeedUp) |
+            Event::Key(KeyEvent::PreviousChapter) |
+            Event::Key(KeyEvent::NextChapter) |
+            Event::Key(KeyEvent::FastForward) |
+            Event::Key(KeyEvent::Rewind) |
+            Event::Key(KeyEvent::Record) => true,
             
             // Frame-Specific Events - Only process if component has focus
             Event::Key(KeyEvent::Enter) |
@@ -111,24 +134,18 @@ impl FocusManager {
     }
 
     /// Updates component focus states based on current focus
-    pub fn update_focus_states(
-        &self,
-        library_browser: &mut LibraryBrowser,
-        track_list: &mut TrackList,
-        track_details: &mut TrackDetails,
-        current_track_info: &mut CurrentTrackInfo,
-        playback_status: &mut PlaybackStatus,
-        controls: &mut Controls,
-        volume_control: &mut VolumeControl,
-    ) {
+    pub fn update_focus_states(&self, components: &AppComponents) {
         let focused = self.current_focus();
-        Component::set_focused(library_browser, focused == "library_browser");
-        Component::set_focused(track_list, focused == "track_list");
-        Component::set_focused(track_details, focused == "track_details");
-        Component::set_focused(current_track_info, focused == "current_track_info");
-        Component::set_focused(playback_status, focused == "playback_status");
-        Component::set_focused(controls, focused == "controls");
-        Component::set_focused(volume_control, focused == "volume_control");
+        components.library_browser.borrow_mut().set_focused(focused == "library_browser");
+        components.track_list.borrow_mut().set_focused(focused == "track_list");
+        components.track_details.borrow_mut().set_focused(focused == "track_details");
+        components.current_track_info.borrow_mut().set_focused(focused == "current_track_info");
+        components.playback_status.borrow_mut().set_focused(focused == "playback_status");
+        components.equalizer.borrow_mut().set_focused(focused == "equalizer");
+        components.visualizer.borrow_mut().set_focused(focused == "visualizer");
+        components.controls.borrow_mut().set_focused(focused == "controls");
+        components.meters.borrow_mut().set_focused(focused == "meters");
+        components.volume_control.borrow_mut().set_focused(focused == "volume_control");
     }
 
     /// Handles focus-related events
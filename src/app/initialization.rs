@@ -1,17 +1,20 @@
 use crate::components::{
     Component, LibraryBrowser, TrackList, TrackDetails,
-    CurrentTrackInfo, PlaybackStatus, Controls, VolumeControl
+    CurrentTrackInfo, PlaybackStatus, EqualizerControl, Visualizer, Controls, Meters, VolumeControl
 };
 use crate::state::AppState;
 use crate::theme::Theme;
 use crate::logger::Logger;
-use anyhow::Result;
+use crate::audio::output::output_spec;
+use crate::audio::player::PlaybackEngine;
+use crate::preferences::PreferencesManager;
+use anyhow::{anyhow, Result};
 use std::rc::Rc;
 use std::cell::RefCell;
-use super::{App, ComponentManager, EventManager, FocusManager, AreaManager, ComponentRegistry};
+use super::{App, AppComponents, ComponentManager, EventManager, FocusManager, AreaManager, ComponentRegistry};
 
-/// Creates a new App instance with default configuration
-pub fn new() -> Result<App> {
+/// Creates a new App instance, configured from `preferences` when there are any
+pub fn new(preferences: Option<PreferencesManager>) -> Result<App> {
     let theme = Theme::load_default()?;
     
     // Initialize components wrapped in Rc<RefCell>
@@ -20,7 +23,10 @@ pub fn new() -> Result<App> {
     let track_details = Rc::new(RefCell::new(TrackDetails::new()));
     let current_track_info = Rc::new(RefCell::new(CurrentTrackInfo::new()));
     let playback_status = Rc::new(RefCell::new(PlaybackStatus::new()));
+    let equalizer = Rc::new(RefCell::new(EqualizerControl::new()));
+    let visualizer = Rc::new(RefCell::new(Visualizer::new()));
     let controls = Rc::new(RefCell::new(Controls::new()));
+    let meters = Rc::new(RefCell::new(Meters::new()));
     let volume_control = Rc::new(RefCell::new(VolumeControl::new()));
 
     // Initialize managers
@@ -28,29 +34,56 @@ pub fn new() -> Result<App> {
     let mut event_manager = EventManager::new();
     let focus_manager = FocusManager::new();
     let area_manager = AreaManager::new();
-    let logger = Logger::new()?;
+    let mut logger = Logger::new()?;
 
-    // Register components with both managers using cloned Rc references
-    ComponentRegistry::register_components(
-        &mut component_manager,
-        &library_browser,
-        &track_list,
-        &track_details,
-        &current_track_info,
-        &playback_status,
-        &controls,
-        &volume_control,
-    );
+    // PLAYTUI_OUTPUT wins over the saved preference so CI can render to a file
+    let configured = preferences.as_ref().map(|prefs| prefs.config().output.clone());
+    let output = output_spec(configured.as_deref());
+    let mut player = PlaybackEngine::with_output(&output)
+        .map_err(|e| anyhow!("Failed to set up audio output '{}': {}", output, e))?;
+    if let Some(prefs) = preferences.as_ref() {
+        // Preferences are validated as they load, so a value the engine still rejects is
+        // logged and left at the engine's default rather than stopping the app from starting
+        let config = prefs.config();
+        player.set_replay_gain(config.replay_gain());
+        let applied = [
+            ("crossfade", player.set_crossfade(config.crossfade())),
+            ("volume", player.set_volume_curve(config.volume_curve())),
+            ("equalizer", player.set_equalizer(config.equalizer())),
+            ("audio processor", player.set_processor_chain(config.processors.clone())),
+            ("playback speed", player.set_speed(config.playback_speed())),
+        ];
+        for (name, result) in applied {
+            if let Err(e) = result {
+                let _ = logger.log_debug(&format!("Ignoring invalid {} preference: {}", name, e));
+            }
+        }
+        equalizer.borrow_mut().restore(config.eq_enabled, &config.eq_preset(), &config.eq_user_presets);
+        visualizer.borrow_mut().configure(config.visualizer_mode, config.spectrum_settings(), config.visualizer_pane);
+        meters.borrow_mut().configure(config.meter_ballistics);
+    }
+    // Start at the saved volume, or wherever the volume control starts
+    let volume = preferences.as_ref().map_or(volume_control.borrow().volume(), |prefs| prefs.config().volume);
+    player.set_volume(volume);
+    volume_control.borrow_mut().show_volume(player.volume());
+    playback_status.borrow_mut().show_speed(player.speed());
+    let output_tap = player.output_tap();
 
-    event_manager.register_components(
-        &library_browser,
-        &track_list,
-        &track_details,
-        &current_track_info,
-        &playback_status,
-        &controls,
-        &volume_control,
-    );
+    // Register components with both managers using cloned Rc references
+    let components = AppComponents {
+        library_browser: Rc::clone(&library_browser),
+        track_list: Rc::clone(&track_list),
+        track_details: Rc::clone(&track_details),
+        current_track_info: Rc::clone(&current_track_info),
+        playback_status: Rc::clone(&playback_status),
+        equalizer: Rc::clone(&equalizer),
+        visualizer: Rc::clone(&visualizer),
+        controls: Rc::clone(&controls),
+        meters: Rc::clone(&meters),
+        volume_control: Rc::clone(&volume_control),
+    };
+    ComponentRegistry::register_components(&mut component_manager, &components);
+    event_manager.register_components(&components);
 
     // Create App instance
     let mut app = App {
@@ -61,15 +94,27 @@ pub fn new() -> Result<App> {
         track_details,
         current_track_info,
         playback_status,
+        equalizer,
+        visualizer,
         controls,
+        meters,
         volume_control,
         component_manager,
         event_manager,
         focus_manager,
         area_manager,
+        player,
+        output_tap,
+        loudness_scan: None,
+        cue: None,
+        preferences,
         logger,
     };
 
+    // The visualizer takes its turn for focus next to the component it shares a pane with
+    let pane = app.visualizer.borrow().pane();
+    app.focus_manager.place_after("visualizer", pane.component());
+
     // Initialize focus states
     app.update_focus_states();
 
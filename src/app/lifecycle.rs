@@ -9,15 +9,7 @@ impl App {
         self.state.ui.focused_component = self.focus_manager.current_focus().to_string();
 
         // Update component focus states
-        self.focus_manager.update_focus_states(
-            &mut self.library_browser.borrow_mut(),
-            &mut self.track_list.borrow_mut(),
-            &mut self.track_details.borrow_mut(),
-            &mut self.current_track_info.borrow_mut(),
-            &mut self.playback_status.borrow_mut(),
-            &mut self.controls.borrow_mut(),
-            &mut self.volume_control.borrow_mut(),
-        );
+        self.focus_manager.update_focus_states(&self.components());
     }
 
     /// Moves focus to the next component
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use std::sync::mpsc::TryRecvError;
+use crate::analysis::{LoudnessScanner, ScanProgress};
+use crate::events::{Action, MetadataAction};
+use super::App;
+
+/// Background loudness scanning for the App
+impl App {
+    /// Start measuring and tagging every track below `dir`, unless a scan is already running
+    pub(crate) fn start_loudness_scan(&mut self, dir: &str) {
+        if self.loudness_scan.is_some() {
+            let _ = self.logger.log_debug("Loudness scan already running, ignoring request");
+            return;
+        }
+        let _ = self.logger.log_debug(&format!("Starting loudness scan of {}", dir));
+        self.loudness_scan = Some(LoudnessScanner::new().spawn(PathBuf::from(dir)));
+    }
+
+    /// Show how far the running scan has got; call once per UI tick
+    pub fn poll_loudness_scan(&mut self) {
+        let Some(receiver) = self.loudness_scan.as_ref() else {
+            return;
+        };
+        let mut latest = None;
+        let finished = loop {
+            match receiver.try_recv() {
+                Ok(progress) => latest = Some(progress),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if finished || matches!(latest, Some(ScanProgress::Finished { .. })) {
+            self.loudness_scan = None;
+        }
+        if let Some(progress) = latest {
+            let _ = self.logger.log_debug(&progress.to_string());
+            self.process_action(Action::Metadata(MetadataAction::LoudnessScan(progress)));
+        }
+    }
+}
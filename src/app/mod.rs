@@ -5,6 +5,12 @@ mod lifecycle;
 mod state;
 mod areas;
 mod focus;
+mod playback;
+mod loudness;
+mod equalizer;
+mod cue;
+mod recording;
+mod monitor;
 
 pub use event_dispatch::EventManager;
 
@@ -14,28 +20,40 @@ use crate::events::{Event, Action, EventResult};
 use crate::logger::Logger;
 use crate::components::{
     Component, LibraryBrowser, TrackList, TrackDetails,
-    CurrentTrackInfo, PlaybackStatus, Controls, VolumeControl
+    CurrentTrackInfo, PlaybackStatus, EqualizerControl, Visualizer, Controls, Meters, VolumeControl
 };
 use crate::theme::Theme;
 use crate::state::AppState;
+use crate::audio::player::PlaybackEngine;
+use crate::audio::tap::TapReader;
+use crate::analysis::ScanProgress;
+use crate::audio::cue::Cue;
+use crate::preferences::PreferencesManager;
 use areas::AreaManager;
 use focus::FocusManager;
 
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::mpsc::Receiver;
+
+/// Shared handles to the UI components, for the managers that each keep their own
+#[derive(Clone)]
+pub struct AppComponents {
+    pub library_browser: Rc<RefCell<LibraryBrowser>>,
+    pub track_list: Rc<RefCell<TrackList>>,
+    pub track_details: Rc<RefCell<TrackDetails>>,
+    pub current_track_info: Rc<RefCell<CurrentTrackInfo>>,
+    pub playback_status: Rc<RefCell<PlaybackStatus>>,
+    pub equalizer: Rc<RefCell<EqualizerControl>>,
+    pub visualizer: Rc<RefCell<Visualizer>>,
+    pub controls: Rc<RefCell<Controls>>,
+    pub meters: Rc<RefCell<Meters>>,
+    pub volume_control: Rc<RefCell<VolumeControl>>,
+}
 
 /// Component registry trait for managing component registration
 pub trait ComponentRegistry {
-    fn register_components(
-        &mut self,
-        library_browser: &Rc<RefCell<LibraryBrowser>>,
-        track_list: &Rc<RefCell<TrackList>>,
-        track_details: &Rc<RefCell<TrackDetails>>,
-        current_track_info: &Rc<RefCell<CurrentTrackInfo>>,
-        playback_status: &Rc<RefCell<PlaybackStatus>>,
-        controls: &Rc<RefCell<Controls>>,
-        volume_control: &Rc<RefCell<VolumeControl>>,
-    );
+    fn register_components(&mut self, components: &AppComponents);
 }
 
 /// Component manager for handling component updates and interactions
@@ -50,7 +68,8 @@ impl ComponentManager {
         }
     }
 
-    pub fn update_components(&mut self, action: Action) {
+    /// Update every component with `action` and the actions it triggers, returning all of them
+    pub fn update_components(&mut self, action: Action) -> Vec<Action> {
         let mut actions_to_process = vec![action];
         let mut processed_actions = Vec::new();
 
@@ -70,28 +89,23 @@ impl ComponentManager {
                 }
             }
         }
+        processed_actions
     }
 }
 
 impl ComponentRegistry for ComponentManager {
-    fn register_components(
-        &mut self,
-        library_browser: &Rc<RefCell<LibraryBrowser>>,
-        track_list: &Rc<RefCell<TrackList>>,
-        track_details: &Rc<RefCell<TrackDetails>>,
-        current_track_info: &Rc<RefCell<CurrentTrackInfo>>,
-        playback_status: &Rc<RefCell<PlaybackStatus>>,
-        controls: &Rc<RefCell<Controls>>,
-        volume_control: &Rc<RefCell<VolumeControl>>,
-    ) {
+    fn register_components(&mut self, components: &AppComponents) {
         self.components.clear();
-        self.components.push(Rc::clone(library_browser) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(track_list) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(track_details) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(current_track_info) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(playback_status) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(controls) as Rc<RefCell<dyn Component>>);
-        self.components.push(Rc::clone(volume_control) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.library_browser) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.track_list) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.track_details) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.current_track_info) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.playback_status) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.equalizer) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.visualizer) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.controls) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.meters) as Rc<RefCell<dyn Component>>);
+        self.components.push(Rc::clone(&components.volume_control) as Rc<RefCell<dyn Component>>);
     }
 }
 
@@ -104,6 +118,15 @@ pub struct App {
     pub component_manager: ComponentManager,
     pub focus_manager: FocusManager,
     pub area_manager: AreaManager,
+    pub player: PlaybackEngine,
+    /// Reads what the engine plays, for the visualizer and meters
+    pub output_tap: TapReader,
+    /// Progress of the loudness scan running in the background, if any
+    pub loudness_scan: Option<Receiver<ScanProgress>>,
+    /// Fast-forward or rewind scan under way, following `state.player.seek_state`
+    pub cue: Option<Cue>,
+    /// Saved settings, when the preferences file could be opened
+    pub preferences: Option<PreferencesManager>,
 
     // UI Components
     pub library_browser: Rc<RefCell<LibraryBrowser>>,
@@ -111,14 +134,38 @@ pub struct App {
     pub track_details: Rc<RefCell<TrackDetails>>,
     pub current_track_info: Rc<RefCell<CurrentTrackInfo>>,
     pub playback_status: Rc<RefCell<PlaybackStatus>>,
+    pub equalizer: Rc<RefCell<EqualizerControl>>,
+    pub visualizer: Rc<RefCell<Visualizer>>,
     pub controls: Rc<RefCell<Controls>>,
+    pub meters: Rc<RefCell<Meters>>,
     pub volume_control: Rc<RefCell<VolumeControl>>,
 }
 
 impl App {
-    /// Creates a new application instance
+    /// Creates a new application instance, with the preferences saved for this user
     pub fn new() -> Result<Self> {
-        initialization::new()
+        Self::with_preferences(PreferencesManager::new().ok())
+    }
+
+    /// Creates a new application instance that reads and saves settings through `preferences`
+    pub fn with_preferences(preferences: Option<PreferencesManager>) -> Result<Self> {
+        initialization::new(preferences)
+    }
+
+    /// Handles to the UI components, shared with the app
+    pub fn components(&self) -> AppComponents {
+        AppComponents {
+            library_browser: Rc::clone(&self.library_browser),
+            track_list: Rc::clone(&self.track_list),
+            track_details: Rc::clone(&self.track_details),
+            current_track_info: Rc::clone(&self.current_track_info),
+            playback_status: Rc::clone(&self.playback_status),
+            equalizer: Rc::clone(&self.equalizer),
+            visualizer: Rc::clone(&self.visualizer),
+            controls: Rc::clone(&self.controls),
+            meters: Rc::clone(&self.meters),
+            volume_control: Rc::clone(&self.volume_control),
+        }
     }
 
     /// Updates component areas in the UI
@@ -130,6 +177,294 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::speed::{PlaybackSpeed, SpeedMode};
+    use crate::audio::{AudioPlayer, PlaybackState, SeekTarget};
+    use crate::events::{EqualizerAction, KeyEvent, PlayerAction, PlaylistAction};
+    use std::time::{Duration, Instant};
 
-    // Add tests here
+    /// App with default preferences that it saves to a temporary directory, kept alive by
+    /// the returned guard, rather than to the user's preferences file
+    fn test_app() -> (App, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let preferences = PreferencesManager::at(dir.path().join("preferences.json"));
+        (App::with_preferences(Some(preferences)).unwrap(), dir)
+    }
+
+    /// Handle engine events until `done` holds, failing with `what` if it takes too long
+    fn poll_until(app: &mut App, what: &str, done: impl Fn(&App) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            app.poll_player_events().unwrap();
+            if done(app) {
+                return;
+            }
+            assert!(Instant::now() < deadline, "{}", what);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_player_actions_drive_engine() {
+        let (mut app, _prefs) = test_app();
+        app.process_action(Action::Player(PlayerAction::LoadTrack("test/testaudio-short.wav".to_string())));
+        assert_eq!(app.player.current_file(), Some("test/testaudio-short.wav"));
+        assert_eq!(app.player.state(), PlaybackState::Playing);
+
+        app.process_action(Action::Player(PlayerAction::Pause));
+        assert_eq!(app.player.state(), PlaybackState::Paused);
+        app.poll_player_events().unwrap();
+        assert!(matches!(app.state.player.playback_state, crate::state::PlaybackState::Paused));
+
+        // Empty paths from the next/previous buttons leave the current track alone
+        app.process_action(Action::Player(PlayerAction::LoadTrack(String::new())));
+        assert_eq!(app.player.current_file(), Some("test/testaudio-short.wav"));
+
+        app.process_action(Action::Player(PlayerAction::Stop));
+        assert_eq!(app.player.state(), PlaybackState::Stopped);
+    }
+
+    #[test]
+    fn test_volume_actions_drive_engine() {
+        let (mut app, _prefs) = test_app();
+        let start = app.player.volume().level();
+        assert_eq!(app.volume_control.borrow().volume(), start);
+
+        app.process_action(Action::VolumeDown);
+        assert_eq!(app.player.volume().level(), start.saturating_sub(5));
+        app.process_action(Action::ToggleMute);
+        assert!(app.player.volume().is_muted());
+        assert!(app.state.player.muted);
+        app.process_action(Action::ToggleMute);
+        assert!(!app.player.volume().is_muted());
+        assert_eq!(app.player.volume().level(), start.saturating_sub(5));
+
+        app.process_action(Action::SetVolume(80));
+        assert_eq!(app.player.volume().level(), 80);
+        assert_eq!(app.state.player.volume, 80);
+    }
+
+    #[test]
+    fn test_seek_actions_drive_engine() {
+        let (mut app, _prefs) = test_app();
+        app.process_action(Action::Player(PlayerAction::LoadTrack("test/testaudio-short.wav".to_string())));
+        app.process_action(Action::Player(PlayerAction::Pause));
+        app.process_action(Action::Player(PlayerAction::Seek(SeekTarget::Percent(50.0))));
+        assert_eq!(app.player.position(), Duration::from_millis(500));
+        assert_eq!(app.state.player.position, Duration::from_millis(500));
+
+        app.process_action(Action::Player(PlayerAction::Seek(SeekTarget::Backward(Duration::from_secs(10)))));
+        assert_eq!(app.player.position(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_chapter_keys_seek_between_chapters() {
+        use id3::TagLike;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.mp3");
+        std::fs::copy("test/testaudio-short.mp3", &path).unwrap();
+        let mut tag = id3::Tag::new();
+        for (id, start) in [("ch0", 0), ("ch1", 250), ("ch2", 600)] {
+            tag.add_frame(id3::frame::Chapter {
+                element_id: id.to_string(),
+                start_time: start,
+                end_time: 0,
+                start_offset: u32::MAX,
+                end_offset: u32::MAX,
+                frames: Vec::new(),
+            });
+        }
+        tag.write_to_path(&path, id3::Version::Id3v24).unwrap();
+
+        let (mut app, _prefs) = test_app();
+        app.process_action(Action::Player(PlayerAction::LoadTrack(path.to_string_lossy().into_owned())));
+        app.process_action(Action::Player(PlayerAction::Pause));
+        let chapters = app.state.metadata.current_metadata.as_ref().map(|metadata| metadata.chapters.len());
+        assert_eq!(chapters, Some(3));
+
+        app.handle_event(Event::Key(KeyEvent::NextChapter)).unwrap();
+        assert_eq!(app.player.position(), Duration::from_millis(250));
+        app.handle_event(Event::Key(KeyEvent::NextChapter)).unwrap();
+        assert_eq!(app.player.position(), Duration::from_millis(600));
+        // Nothing follows the last chapter
+        app.handle_event(Event::Key(KeyEvent::NextChapter)).unwrap();
+        assert_eq!(app.player.position(), Duration::from_millis(600));
+
+        // Just into a chapter, going back moves to the one before
+        app.process_action(Action::Player(PlayerAction::Seek(SeekTarget::Absolute(Duration::from_millis(700)))));
+        app.handle_event(Event::Key(KeyEvent::PreviousChapter)).unwrap();
+        assert_eq!(app.player.position(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_speed_actions_drive_engine() {
+        let (mut app, _prefs) = test_app();
+        app.process_action(Action::Player(PlayerAction::LoadTrack("test/testaudio-short.wav".to_string())));
+        app.process_action(Action::Player(PlayerAction::Pause));
+
+        let varispeed = PlaybackSpeed { rate: 0.8, mode: SpeedMode::Varispeed };
+        app.process_action(Action::Player(PlayerAction::SetSpeed(varispeed)));
+        assert_eq!(app.player.speed(), varispeed);
+
+        // Out of range is refused and the engine keeps its speed
+        app.process_action(Action::Player(PlayerAction::SetSpeed(PlaybackSpeed { rate: 5.0, ..varispeed })));
+        assert_eq!(app.player.speed(), varispeed);
+
+        // The hotkeys step from whatever the engine is playing at
+        app.handle_event(Event::Key(KeyEvent::SpeedUp)).unwrap();
+        assert_eq!(app.player.speed(), PlaybackSpeed { rate: 0.9, ..varispeed });
+
+        // Seeking still works in track time
+        app.process_action(Action::Player(PlayerAction::Seek(SeekTarget::Percent(50.0))));
+        assert_eq!(app.player.position(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_cue_scans_through_track() {
+        let (mut app, _prefs) = test_app();
+        app.process_action(Action::Player(PlayerAction::LoadTrack("test/testaudio-long.mp3".to_string())));
+
+        // Fast-forward covers several times the ground of normal playback
+        app.process_action(Action::Player(PlayerAction::FastForward));
+        assert!(matches!(app.state.player.seek_state, crate::state::SeekState::FastForward));
+        let started = Instant::now();
+        poll_until(&mut app, "fast-forward never got 3s in", |app| app.state.player.position > Duration::from_secs(3));
+        // The position shown follows the scan
+        let scanned = app.state.player.position;
+        assert!(scanned > started.elapsed() * 2, "only reached {:?} in {:?}", scanned, started.elapsed());
+
+        // Play drops back to normal speed from wherever the scan got to
+        app.process_action(Action::Player(PlayerAction::Play));
+        assert!(matches!(app.state.player.seek_state, crate::state::SeekState::Normal));
+        let resumed = app.player.position();
+        poll_until(&mut app, "playback never resumed", |app| app.player.position() > resumed);
+        assert!(app.player.position() < scanned + Duration::from_secs(1));
+
+        // Rewinding stops at the start of the track and plays on from there
+        app.process_action(Action::Player(PlayerAction::Rewind));
+        poll_until(&mut app, "rewind never reached the start", |app| {
+            matches!(app.state.player.seek_state, crate::state::SeekState::Normal)
+        });
+        assert!(app.player.position() < Duration::from_secs(1));
+        assert!(!app.controls.borrow().is_seeking_backward);
+        assert_eq!(app.player.state(), PlaybackState::Playing);
+    }
+
+    #[test]
+    fn test_visualizer_focus_follows_its_pane() {
+        let (mut app, _prefs) = test_app();
+        app.focus_manager.place_after("visualizer", Some("volume_control"));
+        app.focus_manager.set_focus("meters");
+        app.handle_event(Event::Key(KeyEvent::Tab)).unwrap();
+        app.handle_event(Event::Key(KeyEvent::Tab)).unwrap();
+        assert_eq!(app.focus_manager.current_focus(), "visualizer");
+
+        // Hidden, it never takes focus
+        app.focus_manager.place_after("visualizer", None);
+        assert_eq!(app.focus_manager.current_focus(), "library_browser");
+        for _ in 0..10 {
+            app.handle_event(Event::Key(KeyEvent::Tab)).unwrap();
+            assert_ne!(app.focus_manager.current_focus(), "visualizer");
+        }
+    }
+
+    #[test]
+    fn test_meters_show_effective_gain() {
+        let (mut app, _prefs) = test_app();
+        app.player.set_volume(100);
+        app.player.set_equalizer(None).unwrap();
+        assert_eq!(app.effective_gain(), Some(0.0));
+
+        let rock = crate::audio::eq::find_preset("Rock", &[]).unwrap();
+        app.player.set_equalizer(Some(rock.clone())).unwrap();
+        assert_eq!(app.effective_gain(), Some(rock.preamp_db));
+        app.player.set_muted(true);
+        assert_eq!(app.effective_gain(), None);
+    }
+
+    #[test]
+    fn test_equalizer_actions_drive_engine() {
+        let (mut app, _prefs) = test_app();
+        let rock = crate::audio::eq::find_preset("Rock", &[]).unwrap();
+
+        app.process_action(Action::Equalizer(EqualizerAction::Apply { enabled: true, preset: rock.clone() }));
+        assert_eq!(app.player.equalizer(), Some(&rock));
+        app.process_action(Action::Equalizer(EqualizerAction::Apply { enabled: false, preset: rock }));
+        assert!(app.player.equalizer().is_none());
+    }
+
+    #[test]
+    fn test_shuffle_plays_track_gain() {
+        use crate::audio::replaygain::{GainSource, ReplayGain, ReplayGainMode};
+        use id3::TagLike;
+        let dir = tempfile::tempdir().unwrap();
+        let tagged = dir.path().join("tagged.mp3");
+        std::fs::copy("test/testaudio-short.mp3", &tagged).unwrap();
+        let mut tag = id3::Tag::new();
+        for (description, value) in [("REPLAYGAIN_TRACK_GAIN", "-3.00 dB"), ("REPLAYGAIN_ALBUM_GAIN", "-6.00 dB")] {
+            tag.add_frame(id3::frame::ExtendedText { description: description.to_string(), value: value.to_string() });
+        }
+        tag.write_to_path(&tagged, id3::Version::Id3v24).unwrap();
+        let tagged = tagged.to_string_lossy().into_owned();
+
+        let (mut app, _prefs) = test_app();
+        app.player.set_replay_gain(ReplayGain { mode: ReplayGainMode::Auto, ..Default::default() });
+        for path in [tagged.as_str(), "test/testaudio-short.wav", "test/testaudio-short.flac"] {
+            app.process_action(Action::Playlist(PlaylistAction::AddTrack(path.to_string())));
+        }
+        app.process_action(Action::Playlist(PlaylistAction::SelectTrack(0)));
+        assert_eq!(app.player.applied_gain().map(|gain| gain.source), Some(GainSource::Album));
+
+        // Shuffling keeps the playing track on top, and the next one it loads is at track gain
+        let added = app.state.playlist.tracks.clone();
+        app.focus_manager.set_focus("track_list");
+        app.update_focus_states();
+        app.handle_event(Event::Key(KeyEvent::Char('z'))).unwrap();
+        assert!(app.state.playlist.shuffle);
+        assert_eq!(app.state.playlist.tracks[0], tagged);
+        app.process_action(Action::Playlist(PlaylistAction::SelectTrack(0)));
+        assert_eq!(app.player.applied_gain().map(|gain| gain.source), Some(GainSource::Track));
+
+        // Back in order, albums play at album gain again
+        app.handle_event(Event::Key(KeyEvent::Char('z'))).unwrap();
+        assert_eq!(app.state.playlist.tracks, added);
+        assert_eq!(app.state.playlist.selected_index, Some(0));
+        app.process_action(Action::Playlist(PlaylistAction::SelectTrack(0)));
+        assert_eq!(app.player.applied_gain().map(|gain| gain.source), Some(GainSource::Album));
+    }
+
+    #[test]
+    fn test_processor_actions_drive_engine() {
+        use crate::audio::dsp::{ProcessorStage, EQUALIZER};
+        let (mut app, prefs) = test_app();
+
+        app.process_action(Action::Player(PlayerAction::BypassProcessor { index: 0, bypassed: true }));
+        assert_eq!(app.player.processor_chain(), [ProcessorStage { bypassed: true, ..ProcessorStage::new(EQUALIZER) }]);
+
+        // An unknown processor is refused and the chain stays as it was
+        let unknown = vec![ProcessorStage::new("reverb")];
+        app.process_action(Action::Player(PlayerAction::SetProcessorChain(unknown)));
+        assert!(app.player.processor_chain()[0].bypassed);
+        app.process_action(Action::Player(PlayerAction::SetProcessorChain(Vec::new())));
+        assert!(app.player.processor_chain().is_empty());
+        // The last chain the engine took is what the next start loads
+        let saved = PreferencesManager::at(prefs.path().join("preferences.json"));
+        assert!(saved.config().processors.is_empty());
+    }
+
+    #[test]
+    fn test_playlist_advances_with_gapless_playback() {
+        let (mut app, _prefs) = test_app();
+        for path in ["test/testaudio-short.wav", "test/testaudio-sweep-96k24.wav"] {
+            app.process_action(Action::Playlist(PlaylistAction::AddTrack(path.to_string())));
+        }
+        app.process_action(Action::Playlist(PlaylistAction::SelectTrack(0)));
+        assert_eq!(app.state.playlist.selected_index, Some(0));
+        assert_eq!(app.player.current_file(), Some("test/testaudio-short.wav"));
+        assert_eq!(app.player.queued_file(), Some("test/testaudio-sweep-96k24.wav"));
+
+        // The selection follows the engine across the boundary, with nothing left to queue
+        poll_until(&mut app, "playlist never advanced", |app| app.state.playlist.selected_index == Some(1));
+        assert_eq!(app.state.player.current_track.as_deref(), Some("test/testaudio-sweep-96k24.wav"));
+        assert!(app.player.queued_file().is_none());
+    }
 }
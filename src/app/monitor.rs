@@ -0,0 +1,24 @@
+use super::App;
+
+/// Monitoring of what the engine plays for the App
+impl App {
+    /// Hand the samples played since the last tick to the visualizer and meters, along with the
+    /// gain the engine applies; call once per UI tick
+    pub(crate) fn poll_output(&mut self) {
+        let mut samples = Vec::new();
+        self.output_tap.read(&mut samples);
+        let (channels, sample_rate) = self.output_tap.layout();
+        self.visualizer.borrow_mut().show_output(&samples, channels, sample_rate);
+        let mut meters = self.meters.borrow_mut();
+        meters.show_output(&samples, channels, sample_rate);
+        meters.show_gain(self.effective_gain());
+    }
+
+    /// Volume, ReplayGain and EQ preamp together, in dB; `None` while nothing can be heard
+    pub(crate) fn effective_gain(&self) -> Option<f32> {
+        let volume = self.player.volume().db()?;
+        let replay_gain = self.player.applied_gain().map_or(0.0, |gain| gain.db);
+        let preamp = self.player.equalizer().map_or(0.0, |preset| preset.preamp_db);
+        Some(volume + replay_gain + preamp)
+    }
+}
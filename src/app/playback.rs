@@ -0,0 +1,258 @@
+use std::path::Path;
+use crate::audio::player::EngineEvent;
+use crate::audio::{AudioPlayer, PlaybackState as EngineState};
+use std::time::Duration;
+use crate::audio::SeekTarget;
+use crate::events::{Action, Event, EventResult, MetadataAction, PlayerAction, TrackMetadata};
+use crate::metadata::{chapters, MetadataManager};
+use crate::state::{PlaybackState, StateManager};
+use super::App;
+
+/// Playback engine wiring for the App
+impl App {
+    /// Let the components react to an action, then hand any player actions to the engine
+    pub(crate) fn process_action(&mut self, action: Action) {
+        for action in self.component_manager.update_components(action) {
+            if let Action::Playlist(_) = action {
+                self.apply_playlist_action(action);
+                continue;
+            }
+            if let Action::Equalizer(eq_action) = action {
+                self.apply_equalizer_action(eq_action);
+                continue;
+            }
+            if let Action::Metadata(MetadataAction::ScanLoudness(dir)) = &action {
+                self.start_loudness_scan(dir);
+                continue;
+            }
+            self.apply_player_action(&action);
+        }
+    }
+
+    /// Update the playlist, loading whatever track a selection resolves to
+    fn apply_playlist_action(&mut self, action: Action) {
+        if let Some(follow_up) = self.state.update(action) {
+            self.process_action(follow_up);
+        }
+        // ReplayGain's auto mode plays shuffled tracks at their own gain rather than the album's
+        self.player.set_shuffle(self.state.playlist.shuffle);
+        // The entry after the current one may have just been added, removed or reselected
+        self.queue_next_track();
+    }
+
+    /// Have the engine follow the current playlist entry with the next one, without a gap
+    fn queue_next_track(&mut self) {
+        let playlist = &self.state.playlist;
+        let Some(selected) = playlist.selected_index else {
+            return;
+        };
+        // Only when the engine is actually playing the selected entry
+        if self.player.current_file() != playlist.tracks.get(selected).map(String::as_str) {
+            return;
+        }
+        let Some(next) = playlist.tracks.get(selected + 1) else {
+            return;
+        };
+        if self.player.queued_file() != Some(next.as_str()) {
+            // Consecutive tracks of one album are joined gaplessly even when crossfading
+            let result = match self.player.crossfade() {
+                Some(_) if continues_album(&playlist.tracks[selected], next) => self.player.queue_gapless(next),
+                _ => self.player.queue_next(next),
+            };
+            if let Err(e) = result {
+                let _ = self.logger.log_debug(&format!("Could not queue {}: {}", next, e));
+            }
+        }
+    }
+
+    /// Move the playlist selection along once the engine has crossed into the queued entry
+    fn advance_playlist(&mut self) {
+        let playlist = &mut self.state.playlist;
+        playlist.selected_index = playlist.selected_index.map(|index| index + 1);
+        self.state.player.current_track = self.player.current_file().map(str::to_string);
+        self.queue_next_track();
+        self.show_track_metadata();
+        self.show_replay_gain();
+    }
+
+    /// Read the tags and chapters of the track now playing and pass them to the components
+    fn show_track_metadata(&mut self) {
+        let Some(path) = self.player.current_file() else {
+            return;
+        };
+        let metadata = MetadataManager::with_format_parsers()
+            .parse_metadata(Path::new(path))
+            .map(|metadata| TrackMetadata::from(&metadata))
+            .unwrap_or_default();
+        let action = Action::Metadata(MetadataAction::Update(metadata));
+        self.state.update(action.clone());
+        self.process_action(action);
+    }
+
+    /// Where a chapter key takes the current track, if anywhere
+    fn chapter_target(&self, action: &PlayerAction) -> Option<Duration> {
+        let chapters = &self.state.metadata.current_metadata.as_ref()?.chapters;
+        if chapters.is_empty() {
+            return None;
+        }
+        let position = self.player.position();
+        match action {
+            PlayerAction::NextChapter => chapters::next(chapters, position).map(|index| chapters[index].start),
+            // Before the first chapter going back restarts the track
+            _ => Some(chapters::previous(chapters, position).map_or(Duration::ZERO, |index| chapters[index].start)),
+        }
+    }
+
+    /// Tell the components what gain the engine applies to the track now playing
+    fn show_replay_gain(&mut self) {
+        self.process_action(Action::Metadata(MetadataAction::ReplayGain(self.player.applied_gain())));
+    }
+
+    /// Forward a player action to the playback engine
+    fn apply_player_action(&mut self, action: &Action) {
+        self.follow_seek_state(action);
+        let result = match action {
+            Action::Play | Action::Player(PlayerAction::Play) => self.player.play(),
+            // Scanning is heard, so cueing from pause starts playback
+            Action::Player(PlayerAction::FastForward | PlayerAction::Rewind) if self.player.current_file().is_some() => {
+                self.player.play()
+            }
+            Action::Pause | Action::Player(PlayerAction::Pause) => self.player.pause(),
+            Action::Stop | Action::Player(PlayerAction::Stop | PlayerAction::StopEject) => {
+                self.finish_recording();
+                self.player.stop()
+            }
+            Action::Player(PlayerAction::Record) => {
+                self.toggle_recording();
+                return;
+            }
+            // Next/previous buttons send an empty path until the playlist resolves them
+            Action::Player(PlayerAction::LoadTrack(path)) if !path.is_empty() => {
+                let result = self.player.load(path).and_then(|_| self.player.play());
+                if result.is_ok() {
+                    self.state.player.current_track = Some(path.clone());
+                    self.queue_next_track();
+                    self.show_track_metadata();
+                    self.show_replay_gain();
+                }
+                result
+            }
+            Action::Player(chapter @ (PlayerAction::NextChapter | PlayerAction::PreviousChapter)) => {
+                let Some(start) = self.chapter_target(chapter) else {
+                    return;
+                };
+                let result = self.player.seek_to(SeekTarget::Absolute(start));
+                self.state.player.position = self.player.position();
+                result
+            }
+            Action::Player(PlayerAction::Seek(target)) => {
+                let result = self.player.seek_to(*target);
+                self.state.player.position = self.player.position();
+                result
+            }
+            Action::Player(PlayerAction::SetSpeed(speed)) => {
+                let result = self.player.set_speed(*speed);
+                // Show the speed actually playing, even if this one was rejected
+                self.playback_status.borrow_mut().show_speed(self.player.speed());
+                if result.is_ok() {
+                    self.save_speed();
+                }
+                result
+            }
+            Action::Player(PlayerAction::SetProcessorChain(stages)) => {
+                let result = self.player.set_processor_chain(stages.clone());
+                if result.is_ok() {
+                    self.save_processors();
+                }
+                result
+            }
+            Action::Player(PlayerAction::BypassProcessor { index, bypassed }) => {
+                let result = self.player.set_processor_bypassed(*index, *bypassed);
+                if result.is_ok() {
+                    self.save_processors();
+                }
+                result
+            }
+            Action::SetVolume(level) | Action::Player(PlayerAction::SetVolume(level)) => {
+                self.player.set_volume(*level);
+                self.state.player.volume = self.player.volume().level();
+                self.state.player.muted = false;
+                return;
+            }
+            Action::SetMuted(muted) => {
+                self.player.set_muted(*muted);
+                self.state.player.muted = *muted;
+                return;
+            }
+            _ => return,
+        };
+
+        if let Err(e) = result {
+            let _ = self.logger.log_debug(&format!("Playback engine rejected {:?}: {}", action, e));
+        }
+    }
+
+    /// Remember the playback speed for next time
+    fn save_speed(&mut self) {
+        let Some(prefs) = self.preferences.as_mut() else {
+            return;
+        };
+        prefs.update_speed(self.player.speed());
+        if let Err(e) = prefs.save_if_dirty() {
+            let _ = self.logger.log_debug(&format!("Could not save playback speed: {}", e));
+        }
+    }
+
+    /// Remember the processor chain, with which stages are bypassed, for next time
+    fn save_processors(&mut self) {
+        let Some(prefs) = self.preferences.as_mut() else {
+            return;
+        };
+        prefs.update_processors(self.player.processor_chain().to_vec());
+        if let Err(e) = prefs.save_if_dirty() {
+            let _ = self.logger.log_debug(&format!("Could not save audio processors: {}", e));
+        }
+    }
+
+    /// Drain notifications from the engine threads; call once per UI tick
+    pub fn poll_player_events(&mut self) -> EventResult<()> {
+        loop {
+            let queued = self.player.queued_file().is_some();
+            let Some(event) = self.player.try_recv_event() else {
+                break;
+            };
+            // The engine drops its queued track at the boundary it plays into
+            if queued && self.player.queued_file().is_none() {
+                self.advance_playlist();
+            }
+            match event {
+                EngineEvent::StateChanged(state) => {
+                    self.state.player.playback_state = match state {
+                        EngineState::Playing => PlaybackState::Playing,
+                        EngineState::Paused => PlaybackState::Paused,
+                        EngineState::Stopped => PlaybackState::Stopped,
+                    };
+                }
+                EngineEvent::System(system_event) => self.handle_event(Event::System(system_event))?,
+            }
+        }
+        self.poll_cue();
+        self.state.player.position = self.player.position();
+        self.playback_status.borrow_mut().show_progress(self.player.position(), self.player.duration());
+        self.current_track_info.borrow_mut().show_position(self.player.position());
+        if self.state.player.is_recording {
+            self.show_recording();
+        }
+        self.poll_output();
+        Ok(())
+    }
+}
+
+/// Whether `next` follows `current` on the same album, going by their tags
+fn continues_album(current: &str, next: &str) -> bool {
+    let manager = MetadataManager::with_format_parsers();
+    match (manager.parse_metadata(Path::new(current)), manager.parse_metadata(Path::new(next))) {
+        (Ok(current), Ok(next)) => next.continues_album(&current),
+        _ => false,
+    }
+}
@@ -0,0 +1,40 @@
+use super::App;
+
+/// Recording of the playback output for the App
+impl App {
+    /// Start recording what is played, or finish the recording in progress
+    pub(crate) fn toggle_recording(&mut self) {
+        if self.player.recording().is_some() {
+            self.finish_recording();
+            return;
+        }
+        let settings = self.preferences.as_ref()
+            .map(|prefs| prefs.config().record_settings())
+            .unwrap_or_default();
+        let message = match self.player.start_recording(&settings) {
+            Ok(()) => format!("Recording to {}", settings.directory.display()),
+            Err(e) => format!("Could not start recording in {}: {}", settings.directory.display(), e),
+        };
+        let _ = self.logger.log_debug(&message);
+        self.show_recording();
+    }
+
+    /// Stop recording, if recording, and finalize the file
+    pub(crate) fn finish_recording(&mut self) {
+        let message = match self.player.stop_recording() {
+            Ok(Some(status)) => format!("Saved {:?} of recording to {}", status.elapsed, status.path.display()),
+            Ok(None) => return,
+            Err(e) => format!("Could not finish recording: {}", e),
+        };
+        let _ = self.logger.log_debug(&message);
+        self.show_recording();
+    }
+
+    /// Show whether the engine is recording, and how far it has got
+    pub(crate) fn show_recording(&mut self) {
+        let recording = self.player.recording();
+        self.state.player.is_recording = recording.is_some();
+        self.controls.borrow_mut().show_recording(recording.is_some());
+        self.playback_status.borrow_mut().show_recording(recording);
+    }
+}
@@ -0,0 +1,63 @@
+use std::error::Error;
+
+/// Converts float samples to little-endian integer PCM, optionally with TPDF dither
+pub struct Quantizer {
+    bits_per_sample: u16,
+    /// Full scale in output steps
+    scale: f64,
+    dither: bool,
+    /// xorshift32 state feeding the dither
+    seed: u32,
+}
+
+impl Quantizer {
+    /// Quantizer for 16, 24 or 32-bit output
+    pub fn new(bits_per_sample: u16) -> Result<Self, Box<dyn Error>> {
+        if ![16, 24, 32].contains(&bits_per_sample) {
+            return Err(format!("Unsupported output bit depth: {}", bits_per_sample).into());
+        }
+        Ok(Self {
+            bits_per_sample,
+            scale: (1u64 << (bits_per_sample - 1)) as f64,
+            dither: false,
+            seed: 0x9E37_79B9,
+        })
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+
+    /// Dither is only worth its noise when the samples carry more precision than the output
+    pub fn set_dither(&mut self, dither: bool) {
+        self.dither = dither;
+    }
+
+    /// Scale a sample to the output range, rounding to the nearest step
+    pub fn quantize(&mut self, sample: f32) -> i32 {
+        let mut value = sample as f64 * self.scale;
+        if self.dither {
+            // The sum of two uniform values is triangular over +-1 step, which makes
+            // the error independent of the signal instead of harmonic distortion
+            value += self.uniform() + self.uniform() - 1.0;
+        }
+        value.round().clamp(-self.scale, self.scale - 1.0) as i32
+    }
+
+    /// Quantize `samples`, appending them to `out` in little-endian order
+    pub fn write(&mut self, samples: &[f32], out: &mut Vec<u8>) {
+        let width = self.bits_per_sample as usize / 8;
+        for &sample in samples {
+            let value = self.quantize(sample);
+            out.extend_from_slice(&value.to_le_bytes()[..width]);
+        }
+    }
+
+    /// Uniform value in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f64 / (1u32 << 24) as f64
+    }
+}
@@ -0,0 +1,139 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// Speaker positions, in the default WAVE/FLAC channel order for each channel count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Speaker {
+    FrontLeft,
+    FrontRight,
+    Centre,
+    Lfe,
+    BackLeft,
+    BackRight,
+    BackCentre,
+    SideLeft,
+    SideRight,
+}
+
+fn layout(channels: usize) -> Option<&'static [Speaker]> {
+    use Speaker::*;
+    Some(match channels {
+        2 => &[FrontLeft, FrontRight],
+        3 => &[FrontLeft, FrontRight, Centre],
+        4 => &[FrontLeft, FrontRight, BackLeft, BackRight],
+        5 => &[FrontLeft, FrontRight, Centre, BackLeft, BackRight],
+        6 => &[FrontLeft, FrontRight, Centre, Lfe, BackLeft, BackRight],
+        7 => &[FrontLeft, FrontRight, Centre, Lfe, BackCentre, SideLeft, SideRight],
+        8 => &[FrontLeft, FrontRight, Centre, Lfe, BackLeft, BackRight, SideLeft, SideRight],
+        _ => return None,
+    })
+}
+
+/// Where a speaker missing from the output layout is folded in, and at what gain
+fn fold(speaker: Speaker) -> &'static [(Speaker, f32)] {
+    use Speaker::*;
+    match speaker {
+        Centre => &[(FrontLeft, FRAC_1_SQRT_2), (FrontRight, FRAC_1_SQRT_2)],
+        BackLeft | SideLeft => &[(FrontLeft, FRAC_1_SQRT_2)],
+        BackRight | SideRight => &[(FrontRight, FRAC_1_SQRT_2)],
+        BackCentre => &[(FrontLeft, 0.5), (FrontRight, 0.5)],
+        // Bass management is left to the listener's system
+        Lfe | FrontLeft | FrontRight => &[],
+    }
+}
+
+/// Gains mapping every input channel onto every output channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixMatrix {
+    inputs: usize,
+    outputs: usize,
+    /// Row-major, one row of `inputs` gains per output channel
+    gains: Vec<f32>,
+}
+
+impl MixMatrix {
+    /// Standard up- or downmix between two channel counts.
+    ///
+    /// Mono is copied to both front speakers, and anything folded down to mono is the
+    /// average of its stereo downmix. Other layouts keep the speakers they share and fold
+    /// the rest into the front pair, scaled down so no output can exceed full scale.
+    pub fn new(inputs: u16, outputs: u16) -> Self {
+        let (inputs, outputs) = (inputs.max(1) as usize, outputs.max(1) as usize);
+        let mut matrix = Self { inputs, outputs, gains: vec![0.0; inputs * outputs] };
+
+        if inputs == outputs {
+            for channel in 0..inputs {
+                matrix.gains[channel * inputs + channel] = 1.0;
+            }
+        } else if inputs == 1 {
+            for output in 0..outputs.min(2) {
+                matrix.gains[output] = 1.0;
+            }
+        } else if outputs == 1 {
+            let stereo = Self::new(inputs as u16, 2);
+            for input in 0..inputs {
+                matrix.gains[input] = 0.5 * (stereo.gain(0, input) + stereo.gain(1, input));
+            }
+        } else if let (Some(from), Some(to)) = (layout(inputs), layout(outputs)) {
+            for (input, &speaker) in from.iter().enumerate() {
+                match to.iter().position(|&s| s == speaker) {
+                    Some(output) => matrix.gains[output * inputs + input] = 1.0,
+                    None => {
+                        for &(target, gain) in fold(speaker) {
+                            if let Some(output) = to.iter().position(|&s| s == target) {
+                                matrix.gains[output * inputs + input] += gain;
+                            }
+                        }
+                    }
+                }
+            }
+            matrix.normalize();
+        } else {
+            // Unknown layouts: pass through the channels both sides have
+            for channel in 0..inputs.min(outputs) {
+                matrix.gains[channel * inputs + channel] = 1.0;
+            }
+        }
+        matrix
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Gain from `input` into `output`
+    pub fn gain(&self, output: usize, input: usize) -> f32 {
+        self.gains[output * self.inputs + input]
+    }
+
+    /// True when applying the matrix would not change anything
+    pub fn is_identity(&self) -> bool {
+        self.inputs == self.outputs
+            && (0..self.outputs).all(|o| (0..self.inputs).all(|i| self.gain(o, i) == if o == i { 1.0 } else { 0.0 }))
+    }
+
+    /// Mix interleaved input frames, appending the result to `out`
+    pub fn apply(&self, input: &[f32], out: &mut Vec<f32>) {
+        for frame in input.chunks_exact(self.inputs) {
+            for row in self.gains.chunks_exact(self.inputs) {
+                out.push(row.iter().zip(frame).map(|(g, s)| g * s).sum());
+            }
+        }
+    }
+
+    /// Scale every row down by the same amount if any could sum past full scale
+    fn normalize(&mut self) {
+        let loudest = self.gains
+            .chunks_exact(self.inputs)
+            .map(|row| row.iter().map(|g| g.abs()).sum::<f32>())
+            .fold(0.0, f32::max);
+        if loudest > 1.0 {
+            for gain in &mut self.gains {
+                *gain /= loudest;
+            }
+        }
+    }
+}
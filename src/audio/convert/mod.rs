@@ -0,0 +1,141 @@
+use crate::audio::AudioFormat;
+
+mod dither;
+mod mix;
+mod resampler;
+#[cfg(test)]
+mod tests;
+
+pub use dither::Quantizer;
+pub use mix::MixMatrix;
+pub use resampler::{ResampleQuality, Resampler};
+
+/// Layout the engine hands to the output stream; `None` follows the playing track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputFormat {
+    pub channels: Option<u16>,
+    pub sample_rate: Option<u32>,
+    pub bits_per_sample: u16,
+    pub quality: ResampleQuality,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self {
+            channels: None,
+            sample_rate: None,
+            bits_per_sample: 16,
+            quality: ResampleQuality::default(),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// The stream format used for a track in `source` format
+    pub fn resolve(&self, source: &AudioFormat) -> AudioFormat {
+        AudioFormat {
+            channels: self.channels.unwrap_or(source.channels),
+            sample_rate: self.sample_rate.unwrap_or(source.sample_rate),
+            bits_per_sample: self.bits_per_sample,
+            ..Default::default()
+        }
+    }
+}
+
+/// Turns decoded samples into the output layout: channel mixing, then resampling
+pub struct Converter {
+    mix: Option<MixMatrix>,
+    resampler: Option<Resampler>,
+    /// Downmixes run before resampling so fewer channels are filtered, upmixes after
+    mix_first: bool,
+    output_channels: u16,
+    /// Input has more precision than the output, or is altered on the way
+    needs_dither: bool,
+    scratch: Vec<f32>,
+}
+
+impl Converter {
+    pub fn new(input: &AudioFormat, output: &AudioFormat, quality: ResampleQuality) -> Self {
+        let mix = Some(MixMatrix::new(input.channels, output.channels)).filter(|m| !m.is_identity());
+        let mix_first = output.channels < input.channels;
+        let resampler = (input.sample_rate != output.sample_rate && input.sample_rate > 0 && output.sample_rate > 0)
+            .then(|| {
+                let channels = if mix_first { output.channels } else { input.channels };
+                Resampler::new(channels, input.sample_rate, output.sample_rate, quality)
+            });
+        let needs_dither = mix.is_some() || resampler.is_some() || input.bits_per_sample > output.bits_per_sample;
+
+        Self {
+            mix,
+            resampler,
+            mix_first,
+            output_channels: output.channels,
+            needs_dither,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// True when samples come out exactly as they went in
+    pub fn is_passthrough(&self) -> bool {
+        self.mix.is_none() && self.resampler.is_none()
+    }
+
+    /// Whether quantizing the output should be dithered
+    pub fn needs_dither(&self) -> bool {
+        self.needs_dither
+    }
+
+    /// Channels in each converted frame
+    pub fn output_channels(&self) -> u16 {
+        self.output_channels
+    }
+
+    /// Frames produced for `input_frames` source frames once the stream is flushed
+    pub fn output_frames(&self, input_frames: u64) -> u64 {
+        match &self.resampler {
+            Some(resampler) => resampler.output_frames(input_frames),
+            None => input_frames,
+        }
+    }
+
+    /// Convert interleaved `input`, appending the result to `out`
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        match (&self.mix, &mut self.resampler) {
+            (None, None) => out.extend_from_slice(input),
+            (Some(mix), None) => mix.apply(input, out),
+            (None, Some(resampler)) => resampler.process(input, out),
+            (Some(mix), Some(resampler)) => {
+                self.scratch.clear();
+                if self.mix_first {
+                    mix.apply(input, &mut self.scratch);
+                    resampler.process(&self.scratch, out);
+                } else {
+                    resampler.process(input, &mut self.scratch);
+                    mix.apply(&self.scratch, out);
+                }
+            }
+        }
+    }
+
+    /// Emit whatever the resampler still holds at the end of a stream
+    pub fn flush(&mut self, out: &mut Vec<f32>) {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return;
+        };
+        match &self.mix {
+            Some(mix) if !self.mix_first => {
+                self.scratch.clear();
+                resampler.flush(&mut self.scratch);
+                mix.apply(&self.scratch, out);
+            }
+            _ => resampler.flush(out),
+        }
+    }
+
+    /// Drop buffered samples, e.g. after a seek
+    pub fn reset(&mut self) {
+        if let Some(resampler) = self.resampler.as_mut() {
+            resampler.reset();
+        }
+    }
+}
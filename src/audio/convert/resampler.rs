@@ -0,0 +1,197 @@
+use std::f64::consts::PI;
+
+/// Trade-off between CPU time and filter steepness for sample-rate conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleQuality {
+    /// Short filter, audible roll-off above ~15kHz at 44.1kHz
+    Low,
+    Medium,
+    /// Flat to ~90% of the output Nyquist frequency with over 100dB of image rejection
+    #[default]
+    High,
+    Best,
+}
+
+impl ResampleQuality {
+    /// Zero crossings each side of the centre tap, filter phases, Kaiser beta and cutoff
+    /// as a fraction of the lower Nyquist frequency. Each cutoff sits half the window's
+    /// transition width below Nyquist so the stopband starts right at it.
+    fn params(self) -> (usize, usize, f64, f64) {
+        match self {
+            Self::Low => (8, 64, 5.0, 0.80),
+            Self::Medium => (16, 128, 7.0, 0.86),
+            Self::High => (64, 256, 10.0, 0.95),
+            Self::Best => (128, 512, 12.0, 0.97),
+        }
+    }
+}
+
+/// Polyphase windowed-sinc resampler for interleaved samples at any rational ratio
+pub struct Resampler {
+    channels: usize,
+    /// Reduced ratio: `step_int + step_num / den` input frames per output frame
+    input_rate: u64,
+    output_rate: u64,
+    step_int: usize,
+    step_num: u64,
+    den: u64,
+    /// Filter half-length in input frames
+    half: usize,
+    phases: usize,
+    /// `phases + 1` rows of `2 * half` taps, row `p` centred `p / phases` past a frame
+    table: Vec<f32>,
+    coefs: Vec<f32>,
+    /// Input still needed, starting `half - 1` frames before the next output's centre
+    buffer: Vec<f32>,
+    pos: usize,
+    frac: u64,
+    frames_in: u64,
+    frames_out: u64,
+}
+
+impl Resampler {
+    pub fn new(channels: u16, input_rate: u32, output_rate: u32, quality: ResampleQuality) -> Self {
+        let g = gcd(input_rate as u64, output_rate as u64).max(1);
+        let (input_rate, output_rate) = (input_rate as u64 / g, output_rate as u64 / g);
+        let (zero_crossings, phases, beta, cutoff) = quality.params();
+
+        // Cutoff relative to the input Nyquist frequency; narrower when downsampling
+        let fc = cutoff * (output_rate as f64 / input_rate as f64).min(1.0);
+        let half = (zero_crossings as f64 / fc).ceil() as usize;
+        let taps = 2 * half;
+
+        let i0_beta = bessel_i0(beta);
+        let mut table = Vec::with_capacity((phases + 1) * taps);
+        for p in 0..=phases {
+            let offset = p as f64 / phases as f64;
+            let row: Vec<f64> = (0..taps)
+                .map(|j| {
+                    let t = j as f64 - (half - 1) as f64 - offset;
+                    let x = t / half as f64;
+                    let window = if x.abs() < 1.0 { bessel_i0(beta * (1.0 - x * x).sqrt()) / i0_beta } else { 0.0 };
+                    fc * sinc(fc * t) * window
+                })
+                .collect();
+            // Unity gain at DC for every phase
+            let sum: f64 = row.iter().sum();
+            table.extend(row.iter().map(|c| (c / sum) as f32));
+        }
+
+        let mut resampler = Self {
+            channels: channels.max(1) as usize,
+            input_rate,
+            output_rate,
+            step_int: (input_rate / output_rate) as usize,
+            step_num: input_rate % output_rate,
+            den: output_rate,
+            half,
+            phases,
+            table,
+            coefs: vec![0.0; taps],
+            buffer: Vec::new(),
+            pos: 0,
+            frac: 0,
+            frames_in: 0,
+            frames_out: 0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// Output frames produced for the given number of input frames, rounded up
+    pub fn output_frames(&self, input_frames: u64) -> u64 {
+        (input_frames * self.output_rate).div_ceil(self.input_rate)
+    }
+
+    /// Resample `input`, appending whatever output the filter can already produce
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        self.buffer.extend_from_slice(input);
+        self.frames_in += (input.len() / self.channels) as u64;
+        self.drain(out, u64::MAX);
+    }
+
+    /// Produce the tail held back by the filter, ending exactly at the end of the input
+    pub fn flush(&mut self, out: &mut Vec<f32>) {
+        self.buffer.resize(self.buffer.len() + self.half * self.channels, 0.0);
+        self.drain(out, self.output_frames(self.frames_in));
+        self.reset();
+    }
+
+    /// Forget all buffered input, ready to start a new stream
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer.resize((self.half - 1) * self.channels, 0.0);
+        self.pos = 0;
+        self.frac = 0;
+        self.frames_in = 0;
+        self.frames_out = 0;
+    }
+
+    fn drain(&mut self, out: &mut Vec<f32>, limit: u64) {
+        let taps = self.coefs.len();
+        let frames = self.buffer.len() / self.channels;
+
+        while self.pos + taps <= frames && self.frames_out < limit {
+            // Interpolate between the two nearest filter phases
+            let phase = (self.frac * self.phases as u64) as f64 / self.den as f64;
+            let row = phase as usize;
+            let weight = (phase - row as f64) as f32;
+            let (a, b) = self.table[row * taps..(row + 2) * taps].split_at(taps);
+            for ((coef, &a), &b) in self.coefs.iter_mut().zip(a).zip(b) {
+                *coef = a + (b - a) * weight;
+            }
+
+            let window = &self.buffer[self.pos * self.channels..(self.pos + taps) * self.channels];
+            for channel in 0..self.channels {
+                let sum: f32 = self.coefs
+                    .iter()
+                    .zip(window[channel..].iter().step_by(self.channels))
+                    .map(|(c, s)| c * s)
+                    .sum();
+                out.push(sum);
+            }
+            self.frames_out += 1;
+
+            self.pos += self.step_int;
+            self.frac += self.step_num;
+            if self.frac >= self.den {
+                self.frac -= self.den;
+                self.pos += 1;
+            }
+        }
+
+        // Drop input no future output can reach
+        let consumed = self.pos.min(frames);
+        self.buffer.drain(..consumed * self.channels);
+        self.pos -= consumed;
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Zeroth-order modified Bessel function of the first kind, for the Kaiser window
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
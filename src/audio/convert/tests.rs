@@ -0,0 +1,263 @@
+use super::*;
+use std::f64::consts::PI;
+use std::path::Path;
+use crate::audio::formats::{AudioDecoder, AudioReader};
+use crate::audio::formats::wav::WavDecoder;
+
+const SWEEP_SECONDS: f64 = 0.25;
+const SWEEP_FADE: f64 = 0.005;
+
+/// The sweep in test/testaudio-sweep-96k24.wav, mirroring `sweep` in make_fixtures.py
+fn sweep_at(t: f64) -> f64 {
+    exp_sweep(t, 20.0, 20000.0)
+}
+
+/// Exponential sweep from `f0` to `f1` at half scale with raised-cosine fades
+fn exp_sweep(t: f64, f0: f64, f1: f64) -> f64 {
+    let k = (f1 / f0).ln() / SWEEP_SECONDS;
+    let mut value = 0.5 * (2.0 * PI * f0 * ((k * t).exp() - 1.0) / k).sin();
+    let edge = t.min(SWEEP_SECONDS - t);
+    if edge < SWEEP_FADE {
+        value *= 0.5 - 0.5 * (PI * edge.max(0.0) / SWEEP_FADE).cos();
+    }
+    value
+}
+
+fn render(rate: u32, f0: f64, f1: f64) -> Vec<f32> {
+    let frames = (rate as f64 * SWEEP_SECONDS) as usize;
+    (0..frames).map(|n| exp_sweep(n as f64 / rate as f64, f0, f1) as f32).collect()
+}
+
+fn format(channels: u16, sample_rate: u32, bits_per_sample: u16) -> AudioFormat {
+    AudioFormat {
+        channels,
+        sample_rate,
+        bits_per_sample,
+        ..Default::default()
+    }
+}
+
+fn resample(input: &[f32], from: u32, to: u32, quality: ResampleQuality, chunk: usize) -> Vec<f32> {
+    let mut resampler = Resampler::new(1, from, to, quality);
+    let mut out = Vec::new();
+    for part in input.chunks(chunk) {
+        resampler.process(part, &mut out);
+    }
+    resampler.flush(&mut out);
+    out
+}
+
+fn max_error(actual: &[f32], rate: u32, f0: f64, f1: f64) -> f64 {
+    actual
+        .iter()
+        .enumerate()
+        .map(|(n, &s)| (s as f64 - exp_sweep(n as f64 / rate as f64, f0, f1)).abs())
+        .fold(0.0, f64::max)
+}
+
+fn rms(samples: &[f32]) -> f64 {
+    (samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+fn read_all(mut reader: AudioReader) -> Vec<f32> {
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 1024];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            return samples;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+}
+
+#[test]
+fn test_output_format_resolve() {
+    let source = format(6, 96000, 24);
+    let follow = OutputFormat::default().resolve(&source);
+    assert_eq!((follow.channels, follow.sample_rate, follow.bits_per_sample), (6, 96000, 16));
+
+    let fixed = OutputFormat { channels: Some(2), sample_rate: Some(48000), ..Default::default() };
+    let resolved = fixed.resolve(&source);
+    assert_eq!((resolved.channels, resolved.sample_rate), (2, 48000));
+}
+
+#[test]
+fn test_resampled_length_is_exact() {
+    for (from, to) in [(44100, 48000), (48000, 44100), (96000, 48000), (8000, 192000), (44100, 44100 * 3)] {
+        let input = vec![0.0; 12345];
+        let out = resample(&input, from, to, ResampleQuality::Medium, 1000);
+        assert_eq!(out.len() as u64, (12345u64 * to as u64).div_ceil(from as u64), "{} -> {}", from, to);
+    }
+}
+
+#[test]
+fn test_resampler_chunking_does_not_change_output() {
+    let input = render(44100, 20.0, 18000.0);
+    let whole = resample(&input, 44100, 48000, ResampleQuality::High, input.len());
+    let pieces = resample(&input, 44100, 48000, ResampleQuality::High, 333);
+    assert_eq!(whole, pieces);
+}
+
+#[test]
+fn test_upsampled_sweep_matches_reference() {
+    // 20Hz-18kHz stays inside the passband of 44.1kHz for every preset but Low
+    let input = render(44100, 20.0, 18000.0);
+    for (quality, tolerance) in [
+        (ResampleQuality::Medium, 2e-2),
+        (ResampleQuality::High, 2e-4),
+        (ResampleQuality::Best, 1e-4),
+    ] {
+        let out = resample(&input, 44100, 48000, quality, 4096);
+        let error = max_error(&out, 48000, 20.0, 18000.0);
+        assert!(error < tolerance, "{:?}: error {}", quality, error);
+    }
+}
+
+#[test]
+fn test_downsampled_sweep_matches_reference() {
+    let input = render(96000, 20.0, 18000.0);
+    let out = resample(&input, 96000, 44100, ResampleQuality::High, 4096);
+    let error = max_error(&out, 44100, 20.0, 18000.0);
+    assert!(error < 2e-4, "error {}", error);
+}
+
+#[test]
+fn test_downsampling_rejects_aliases() {
+    // Everything in a 26-44kHz sweep lies above the 24kHz Nyquist of a 48kHz output
+    let input = render(96000, 26000.0, 44000.0);
+    assert!(rms(&input) > 0.3);
+    for (quality, limit_db) in [
+        (ResampleQuality::Low, -40.0),
+        (ResampleQuality::Medium, -60.0),
+        (ResampleQuality::High, -90.0),
+        (ResampleQuality::Best, -100.0),
+    ] {
+        let out = resample(&input, 96000, 48000, quality, 4096);
+        let level_db = 20.0 * (rms(&out) / rms(&input)).log10();
+        assert!(level_db < limit_db, "{:?}: aliases at {:.1}dB", quality, level_db);
+    }
+}
+
+#[test]
+fn test_mix_matrices() {
+    let up = MixMatrix::new(1, 2);
+    let mut out = Vec::new();
+    up.apply(&[0.25, -0.5], &mut out);
+    assert_eq!(out, vec![0.25, 0.25, -0.5, -0.5]);
+
+    let down = MixMatrix::new(2, 1);
+    out.clear();
+    down.apply(&[0.25, 0.75, 1.0, -1.0], &mut out);
+    assert_eq!(out, vec![0.5, 0.0]);
+
+    assert!(MixMatrix::new(6, 6).is_identity());
+    assert!(!MixMatrix::new(2, 6).is_identity());
+
+    // Stereo into 5.1 lands on the front pair only
+    let wide = MixMatrix::new(2, 6);
+    assert_eq!((wide.gain(0, 0), wide.gain(1, 1)), (1.0, 1.0));
+    assert!((2..6).all(|o| wide.gain(o, 0) == 0.0 && wide.gain(o, 1) == 0.0));
+}
+
+#[test]
+fn test_surround_downmix_never_clips() {
+    let matrix = MixMatrix::new(6, 2);
+    // L R C LFE Ls Rs
+    assert_eq!(matrix.gain(0, 1), 0.0);
+    assert_eq!(matrix.gain(0, 3), 0.0);
+    assert!((matrix.gain(0, 2) - matrix.gain(1, 2)).abs() < 1e-6);
+    assert!((matrix.gain(0, 4) - matrix.gain(0, 2)).abs() < 1e-6);
+    assert_eq!(matrix.gain(0, 5), 0.0);
+    assert!(matrix.gain(0, 0) > matrix.gain(0, 2));
+
+    for channels in 3..=8 {
+        for outputs in [1, 2] {
+            let matrix = MixMatrix::new(channels, outputs);
+            let mut out = Vec::new();
+            matrix.apply(&vec![1.0; channels as usize], &mut out);
+            assert!(out.iter().all(|&s| s <= 1.0 + 1e-6), "{} -> {}: {:?}", channels, outputs, out);
+            assert!(out.iter().all(|&s| s > 0.5), "{} -> {}: {:?}", channels, outputs, out);
+        }
+    }
+}
+
+#[test]
+fn test_quantizer_rounds_without_dither() {
+    let mut quantizer = Quantizer::new(16).unwrap();
+    assert_eq!(quantizer.quantize(1.0), i16::MAX as i32);
+    assert_eq!(quantizer.quantize(-1.0), i16::MIN as i32);
+    assert_eq!(quantizer.quantize(0.5), 16384);
+    assert_eq!(quantizer.quantize(0.4 / 32768.0), 0);
+
+    let mut bytes = Vec::new();
+    let mut quantizer = Quantizer::new(24).unwrap();
+    quantizer.write(&[-1.0 / 8388608.0, 0.5], &mut bytes);
+    assert_eq!(bytes, vec![0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x40]);
+
+    assert!(Quantizer::new(12).is_err());
+}
+
+#[test]
+fn test_tpdf_dither_preserves_low_level_signal() {
+    // A sine at 0.3 of a 16-bit step vanishes when rounded but survives in dithered noise
+    let step = 1.0 / 32768.0;
+    let sine: Vec<f32> = (0..48000).map(|n| (0.3 * step * (2.0 * PI * n as f64 / 48.0).sin()) as f32).collect();
+
+    let mut plain = Quantizer::new(16).unwrap();
+    assert!(sine.iter().all(|&s| plain.quantize(s) == 0));
+
+    let mut dithered = Quantizer::new(16).unwrap();
+    dithered.set_dither(true);
+    let output: Vec<i32> = sine.iter().map(|&s| dithered.quantize(s)).collect();
+    assert!(output.iter().all(|v| v.abs() <= 2));
+
+    // Correlating with the sine recovers its amplitude, and the noise averages to zero
+    let correlation: f64 = output.iter().zip(&sine).map(|(&q, &s)| q as f64 * s as f64 / step).sum::<f64>()
+        / sine.iter().map(|&s| (s as f64 / step).powi(2)).sum::<f64>();
+    assert!((correlation - 1.0).abs() < 0.1, "gain {}", correlation);
+    let mean = output.iter().map(|&v| v as f64).sum::<f64>() / output.len() as f64;
+    assert!(mean.abs() < 0.02, "mean {}", mean);
+}
+
+#[test]
+fn test_passthrough_converter() {
+    let source = format(2, 44100, 16);
+    let mut converter = Converter::new(&source, &OutputFormat::default().resolve(&source), ResampleQuality::High);
+    assert!(converter.is_passthrough());
+    assert!(!converter.needs_dither());
+
+    let mut out = Vec::new();
+    converter.process(&[0.1, 0.2, 0.3, 0.4], &mut out);
+    converter.flush(&mut out);
+    assert_eq!(out, vec![0.1, 0.2, 0.3, 0.4]);
+
+    let deeper = format(2, 44100, 24);
+    assert!(Converter::new(&deeper, &source, ResampleQuality::High).needs_dither());
+}
+
+#[test]
+fn test_convert_sweep_fixture_to_48k_mono() {
+    // The 96kHz/24-bit stereo fixture downmixed and resampled for a 48kHz mono output
+    let reader = WavDecoder::new().decode(Path::new("test/testaudio-sweep-96k24.wav")).unwrap();
+    assert_eq!((reader.format.channels, reader.format.sample_rate, reader.format.bits_per_sample), (2, 96000, 24));
+    let source = reader.format.clone();
+    let output = format(1, 48000, 16);
+    let mut converter = Converter::new(&source, &output, ResampleQuality::High);
+    assert!(converter.needs_dither());
+
+    let mut out = Vec::new();
+    for chunk in read_all(reader).chunks(4096) {
+        converter.process(chunk, &mut out);
+    }
+    converter.flush(&mut out);
+
+    // Right is the left inverted at half level, so the mono mix is a quarter of the sweep
+    assert_eq!(out.len(), 12000);
+    let error = out
+        .iter()
+        .enumerate()
+        .map(|(n, &s)| (s as f64 - 0.25 * sweep_at(n as f64 / 48000.0)).abs())
+        .fold(0.0, f64::max);
+    assert!(error < 2e-4, "error {}", error);
+}
@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+/// Stretch of track heard between jumps while cueing or reviewing
+pub const SNIPPET: Duration = Duration::from_millis(200);
+
+/// Seconds of track skipped per second held when a cue starts
+const START_RATE: f64 = 4.0;
+
+/// Fastest scan, reached after the rate has doubled a few times
+const MAX_RATE: f64 = 32.0;
+
+/// How long the rate holds before doubling
+const ACCELERATE_AFTER: Duration = Duration::from_secs(2);
+
+/// Which way a cue scans through the track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CueDirection {
+    Forward,
+    Backward,
+}
+
+/// What a cue wants done once a snippet has played
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CueStep {
+    /// Carry on scanning from here
+    Jump(Duration),
+    /// The scan ran off the end of the track, by this much
+    PastEnd(Duration),
+    /// The scan ran back past the start of the track, by this much
+    PastStart(Duration),
+}
+
+/// Skip-ahead playback like a CD player's cue and review: short snippets of the track are
+/// played with jumps between them, covering ground faster the longer the cue is held
+#[derive(Debug, Clone)]
+pub struct Cue {
+    direction: CueDirection,
+    started: Instant,
+    /// When the snippet now playing began, and where in the track
+    snippet: Option<(Instant, Duration)>,
+}
+
+impl Cue {
+    pub fn new(direction: CueDirection, now: Instant) -> Self {
+        Self {
+            direction,
+            started: now,
+            snippet: None,
+        }
+    }
+
+    pub fn direction(&self) -> CueDirection {
+        self.direction
+    }
+
+    /// Seconds of track covered per second at `now`, doubling every `ACCELERATE_AFTER`
+    pub fn rate(&self, now: Instant) -> f64 {
+        let doublings = (now.saturating_duration_since(self.started).as_secs_f64()
+            / ACCELERATE_AFTER.as_secs_f64()).floor();
+        (START_RATE * doublings.exp2()).min(MAX_RATE)
+    }
+
+    /// Where to go from `position` at `now`, once the snippet playing has lasted `SNIPPET`.
+    /// The first call, and the first after leaving the track, starts a snippet where playback is.
+    pub fn step(&mut self, now: Instant, position: Duration, duration: Option<Duration>) -> Option<CueStep> {
+        let Some((since, from)) = self.snippet else {
+            self.snippet = Some((now, position));
+            return None;
+        };
+        let elapsed = now.saturating_duration_since(since);
+        if elapsed < SNIPPET {
+            return None;
+        }
+
+        let distance = elapsed.mul_f64(self.rate(now));
+        let step = match self.direction {
+            // Stop short of the end so the last snippet still has something to play
+            CueDirection::Forward => match duration {
+                Some(duration) if from + distance + SNIPPET > duration => {
+                    CueStep::PastEnd((from + distance).saturating_sub(duration))
+                }
+                _ => CueStep::Jump(from + distance),
+            },
+            CueDirection::Backward if distance > from => CueStep::PastStart(distance - from),
+            CueDirection::Backward => CueStep::Jump(from - distance),
+        };
+        self.snippet = match step {
+            CueStep::Jump(target) => Some((now, target)),
+            _ => None,
+        };
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_accelerates() {
+        let start = Instant::now();
+        let cue = Cue::new(CueDirection::Forward, start);
+        assert_eq!(cue.rate(start), START_RATE);
+        assert_eq!(cue.rate(start + Duration::from_millis(1999)), START_RATE);
+        assert_eq!(cue.rate(start + ACCELERATE_AFTER), 2.0 * START_RATE);
+        assert_eq!(cue.rate(start + Duration::from_secs(60)), MAX_RATE);
+    }
+
+    #[test]
+    fn test_cue_plays_snippets_between_jumps() {
+        let start = Instant::now();
+        let mut cue = Cue::new(CueDirection::Forward, start);
+        let duration = Some(Duration::from_secs(60));
+        assert_eq!(cue.step(start, Duration::from_secs(10), duration), None);
+        // Nothing happens until the snippet has played
+        let half = start + SNIPPET / 2;
+        assert_eq!(cue.step(half, Duration::from_millis(10100), duration), None);
+
+        // Then the jump covers the rate times the time held, from where the snippet began
+        let later = start + SNIPPET;
+        let target = Duration::from_secs(10) + SNIPPET.mul_f64(START_RATE);
+        assert_eq!(cue.step(later, Duration::from_millis(10200), duration), Some(CueStep::Jump(target)));
+        assert_eq!(cue.step(later + SNIPPET, target + SNIPPET, duration), Some(CueStep::Jump(target + SNIPPET.mul_f64(START_RATE))));
+    }
+
+    #[test]
+    fn test_cue_stops_at_track_edges() {
+        let start = Instant::now();
+        let duration = Some(Duration::from_secs(10));
+        let mut forward = Cue::new(CueDirection::Forward, start);
+        forward.step(start, Duration::from_millis(9500), duration);
+        assert_eq!(forward.step(start + SNIPPET, Duration::from_millis(9700), duration), Some(CueStep::PastEnd(Duration::from_millis(300))));
+        // Leaving the track starts a fresh snippet wherever playback lands
+        assert_eq!(forward.step(start + 2 * SNIPPET, Duration::ZERO, duration), None);
+
+        let mut backward = Cue::new(CueDirection::Backward, start);
+        backward.step(start, Duration::from_millis(1000), duration);
+        assert_eq!(backward.step(start + SNIPPET, Duration::from_millis(1200), duration), Some(CueStep::Jump(Duration::from_millis(200))));
+        assert_eq!(backward.step(start + 2 * SNIPPET, Duration::from_millis(400), duration), Some(CueStep::PastStart(Duration::from_millis(600))));
+
+        // Without a known length, forward never runs out
+        let mut stream = Cue::new(CueDirection::Forward, start);
+        stream.step(start, Duration::from_secs(9), None);
+        assert!(matches!(stream.step(start + SNIPPET, Duration::from_secs(9), None), Some(CueStep::Jump(_))));
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use crate::audio::AudioFormat;
+use super::AudioProcessor;
+
+struct Stage {
+    processor: Box<dyn AudioProcessor>,
+    /// Shared with the engine, so bypassing needs no rebuild
+    bypassed: Arc<AtomicBool>,
+    /// Whether the stage was bypassed for the previous block
+    was_bypassed: bool,
+    /// Cleared when `prepare` fails, leaving the stage out until the next layout
+    ready: bool,
+}
+
+/// Processors applied in order to each block the output thread writes
+#[derive(Default)]
+pub struct ProcessorChain {
+    stages: Vec<Stage>,
+    /// Channels and rate the stages were last prepared for
+    layout: Option<(u16, u32)>,
+}
+
+impl ProcessorChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `processor`, skipped whenever `bypassed` is set
+    pub fn push(&mut self, processor: Box<dyn AudioProcessor>, bypassed: Arc<AtomicBool>) {
+        let was_bypassed = bypassed.load(Ordering::Acquire);
+        self.stages.push(Stage { processor, bypassed, was_bypassed, ready: false });
+        self.layout = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Whether the stages are ready for streams of `channels` at `sample_rate`
+    pub fn is_prepared_for(&self, channels: u16, sample_rate: u32) -> bool {
+        self.layout == Some((channels, sample_rate))
+    }
+
+    /// Prepare every stage for `format`; a stage that refuses it is left out until the next one
+    pub fn prepare(&mut self, format: &AudioFormat) {
+        for (index, stage) in self.stages.iter_mut().enumerate() {
+            stage.ready = match stage.processor.prepare(format) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Audio processor {} skipped: {}", index, e);
+                    false
+                }
+            };
+        }
+        self.layout = Some((format.channels, format.sample_rate));
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for stage in self.stages.iter_mut().filter(|stage| stage.ready) {
+            let bypassed = stage.bypassed.load(Ordering::Acquire);
+            // Coming back from bypass, the stage's memory of the signal is stale
+            if stage.was_bypassed && !bypassed {
+                stage.processor.reset();
+            }
+            stage.was_bypassed = bypassed;
+            if !bypassed {
+                stage.processor.process(samples);
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.stages.iter_mut().for_each(|stage| stage.processor.reset());
+    }
+
+    /// Frames of delay added by the stages in use
+    pub fn latency(&self) -> usize {
+        self.stages
+            .iter()
+            .filter(|stage| stage.ready && !stage.bypassed.load(Ordering::Acquire))
+            .map(|stage| stage.processor.latency())
+            .sum()
+    }
+}
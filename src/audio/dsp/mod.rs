@@ -0,0 +1,64 @@
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+use super::AudioFormat;
+
+mod chain;
+mod registry;
+#[cfg(test)]
+mod tests;
+
+pub use chain::ProcessorChain;
+pub use registry::ProcessorRegistry;
+
+/// Name of the built-in equalizer stage
+pub const EQUALIZER: &str = "equalizer";
+
+/// Processors every engine can build without registering anything
+pub const BUILTIN_PROCESSORS: &[&str] = &[EQUALIZER];
+
+/// An effect applied to everything the engine plays, after decoding and format conversion
+pub trait AudioProcessor: Send {
+    /// Get ready for samples in `format`; called before the first block and whenever the
+    /// output layout changes
+    fn prepare(&mut self, format: &AudioFormat) -> Result<(), Box<dyn Error>>;
+
+    /// Process a block of interleaved samples in place
+    fn process(&mut self, samples: &mut [f32]);
+
+    /// Forget the signal so far, as after a seek
+    fn reset(&mut self);
+
+    /// Frames by which the output lags the input
+    fn latency(&self) -> usize {
+        0
+    }
+}
+
+/// Builds the processors for one name in a chain
+pub trait ProcessorFactory {
+    /// Name the processor is referred to by in preferences
+    fn name(&self) -> &str;
+
+    /// A fresh processor, not yet prepared
+    fn create(&self) -> Box<dyn AudioProcessor>;
+}
+
+/// One entry of a processor chain, as configured
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessorStage {
+    pub name: String,
+    /// Passed over without processing
+    #[serde(default)]
+    pub bypassed: bool,
+}
+
+impl ProcessorStage {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), bypassed: false }
+    }
+}
+
+/// The chain an engine starts with
+pub fn default_chain() -> Vec<ProcessorStage> {
+    vec![ProcessorStage::new(EQUALIZER)]
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use super::{AudioProcessor, ProcessorFactory};
+
+/// The processors an engine knows how to build, by name
+pub struct ProcessorRegistry {
+    factories: Vec<Arc<dyn ProcessorFactory + Send + Sync>>,
+}
+
+impl ProcessorRegistry {
+    pub fn new() -> Self {
+        Self {
+            factories: Vec::new(),
+        }
+    }
+
+    /// Register a processor factory; a later factory replaces an earlier one of the same name
+    pub fn register_processor(&mut self, factory: Arc<dyn ProcessorFactory + Send + Sync>) {
+        self.factories.retain(|existing| existing.name() != factory.name());
+        self.factories.push(factory);
+    }
+
+    /// A new processor of the registered `name`
+    pub fn create(&self, name: &str) -> Option<Box<dyn AudioProcessor>> {
+        self.factories
+            .iter()
+            .find(|factory| factory.name() == name)
+            .map(|factory| factory.create())
+    }
+
+    pub fn supports(&self, name: &str) -> bool {
+        self.factories.iter().any(|factory| factory.name() == name)
+    }
+
+    /// Names of every registered processor, in registration order
+    pub fn names(&self) -> Vec<String> {
+        self.factories.iter().map(|factory| factory.name().to_string()).collect()
+    }
+}
+
+impl Default for ProcessorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use super::*;
+
+/// Multiplies every sample by a constant, counting resets
+struct Scale {
+    factor: f32,
+    latency: usize,
+    resets: Arc<AtomicUsize>,
+}
+
+impl AudioProcessor for Scale {
+    fn prepare(&mut self, format: &AudioFormat) -> Result<(), Box<dyn Error>> {
+        if format.channels > 2 {
+            return Err("Stereo only".into());
+        }
+        Ok(())
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        samples.iter_mut().for_each(|sample| *sample *= self.factor);
+    }
+
+    fn reset(&mut self) {
+        self.resets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn latency(&self) -> usize {
+        self.latency
+    }
+}
+
+/// Adds a constant to every sample, so the order of stages shows in the result
+struct Offset(f32);
+
+impl AudioProcessor for Offset {
+    fn prepare(&mut self, _format: &AudioFormat) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        samples.iter_mut().for_each(|sample| *sample += self.0);
+    }
+
+    fn reset(&mut self) {}
+}
+
+struct OffsetFactory(&'static str, f32);
+
+impl ProcessorFactory for OffsetFactory {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn create(&self) -> Box<dyn AudioProcessor> {
+        Box::new(Offset(self.1))
+    }
+}
+
+fn stereo() -> AudioFormat {
+    AudioFormat { channels: 2, sample_rate: 44100, bits_per_sample: 16, ..Default::default() }
+}
+
+fn flag(bypassed: bool) -> Arc<AtomicBool> {
+    Arc::new(AtomicBool::new(bypassed))
+}
+
+#[test]
+fn test_chain_runs_stages_in_order() {
+    let resets = Arc::new(AtomicUsize::new(0));
+    let mut chain = ProcessorChain::new();
+    chain.push(Box::new(Offset(1.0)), flag(false));
+    chain.push(Box::new(Scale { factor: 2.0, latency: 0, resets }), flag(false));
+
+    // Nothing runs before the stages are prepared
+    let mut samples = [0.5f32; 4];
+    chain.process(&mut samples);
+    assert_eq!(samples, [0.5; 4]);
+
+    chain.prepare(&stereo());
+    assert!(chain.is_prepared_for(2, 44100));
+    assert!(!chain.is_prepared_for(2, 48000));
+    chain.process(&mut samples);
+    assert_eq!(samples, [3.0; 4]);
+}
+
+#[test]
+fn test_bypass_and_latency() {
+    let resets = Arc::new(AtomicUsize::new(0));
+    let bypassed = flag(true);
+    let mut chain = ProcessorChain::new();
+    chain.push(Box::new(Scale { factor: 0.5, latency: 64, resets: resets.clone() }), bypassed.clone());
+    chain.push(Box::new(Scale { factor: 1.0, latency: 32, resets: resets.clone() }), flag(false));
+    chain.prepare(&stereo());
+
+    let mut samples = [1.0f32; 2];
+    chain.process(&mut samples);
+    assert_eq!(samples, [1.0; 2]);
+    assert_eq!(chain.latency(), 32);
+
+    // A stage coming back from bypass forgets what it saw before
+    bypassed.store(false, Ordering::Release);
+    chain.process(&mut samples);
+    assert_eq!(samples, [0.5; 2]);
+    assert_eq!(chain.latency(), 96);
+    assert_eq!(resets.load(Ordering::Relaxed), 1);
+
+    chain.reset();
+    assert_eq!(resets.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn test_stage_refusing_layout_is_skipped() {
+    let resets = Arc::new(AtomicUsize::new(0));
+    let mut chain = ProcessorChain::new();
+    chain.push(Box::new(Scale { factor: 0.0, latency: 10, resets }), flag(false));
+
+    chain.prepare(&AudioFormat { channels: 6, ..stereo() });
+    let mut samples = [1.0f32; 6];
+    chain.process(&mut samples);
+    assert_eq!(samples, [1.0; 6]);
+    assert_eq!(chain.latency(), 0);
+
+    chain.prepare(&stereo());
+    chain.process(&mut samples[..2]);
+    assert_eq!(samples[..2], [0.0; 2]);
+}
+
+#[test]
+fn test_registry() {
+    let mut registry = ProcessorRegistry::new();
+    assert!(registry.create("offset").is_none());
+
+    registry.register_processor(Arc::new(OffsetFactory("offset", 1.0)));
+    registry.register_processor(Arc::new(OffsetFactory("other", 2.0)));
+    // Registering a name again replaces the earlier factory
+    registry.register_processor(Arc::new(OffsetFactory("offset", 3.0)));
+    assert_eq!(registry.names(), vec!["other", "offset"]);
+    assert!(registry.supports("other"));
+
+    let mut processor = registry.create("offset").unwrap();
+    let mut samples = [0.0f32; 2];
+    processor.process(&mut samples);
+    assert_eq!(samples, [3.0; 2]);
+}
+
+#[test]
+fn test_stage_serialization() {
+    let stage: ProcessorStage = serde_json::from_str(r#"{"name":"equalizer"}"#).unwrap();
+    assert_eq!(stage, ProcessorStage::new(EQUALIZER));
+    assert_eq!(default_chain(), vec![stage]);
+}
@@ -0,0 +1,104 @@
+use std::f64::consts::PI;
+
+/// Normalised coefficients of a second-order IIR filter, from the RBJ audio EQ cookbook
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Biquad {
+    /// Passes everything through unchanged
+    pub const IDENTITY: Biquad = Biquad { b0: 1.0, b1: 0.0, b2: 0.0, a1: 0.0, a2: 0.0 };
+
+    /// Bell boosting or cutting `gain_db` around `frequency`
+    pub fn peaking(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let (a, cos, alpha) = Self::intermediates(sample_rate, frequency, q, gain_db);
+        Self::normalised(
+            [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+            [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+        )
+    }
+
+    /// Shelf boosting or cutting `gain_db` below `frequency`
+    pub fn low_shelf(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let (a, cos, alpha) = Self::intermediates(sample_rate, frequency, q, gain_db);
+        let root = 2.0 * a.sqrt() * alpha;
+        Self::normalised(
+            [
+                a * ((a + 1.0) - (a - 1.0) * cos + root),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - root),
+            ],
+            [
+                (a + 1.0) + (a - 1.0) * cos + root,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - root,
+            ],
+        )
+    }
+
+    /// Shelf boosting or cutting `gain_db` above `frequency`
+    pub fn high_shelf(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let (a, cos, alpha) = Self::intermediates(sample_rate, frequency, q, gain_db);
+        let root = 2.0 * a.sqrt() * alpha;
+        Self::normalised(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos + root),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - root),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos + root,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - root,
+            ],
+        )
+    }
+
+    /// Amplitude, cosine of the centre frequency and bandwidth term shared by every shape
+    fn intermediates(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> (f64, f64, f64) {
+        let w0 = 2.0 * PI * frequency as f64 / sample_rate as f64;
+        let a = 10f64.powf(gain_db as f64 / 40.0);
+        (a, w0.cos(), w0.sin() / (2.0 * q as f64))
+    }
+
+    fn normalised(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+        }
+    }
+
+    /// Gain in dB the filter applies to a sine at `frequency`
+    pub fn response_db(&self, sample_rate: u32, frequency: f32) -> f64 {
+        let w = 2.0 * PI * frequency as f64 / sample_rate as f64;
+        // Evaluate numerator and denominator at z = e^jw
+        let (c1, s1, c2, s2) = (w.cos(), w.sin(), (2.0 * w).cos(), (2.0 * w).sin());
+        let (nr, ni) = (self.b0 + self.b1 * c1 + self.b2 * c2, -self.b1 * s1 - self.b2 * s2);
+        let (dr, di) = (1.0 + self.a1 * c1 + self.a2 * c2, -self.a1 * s1 - self.a2 * s2);
+        10.0 * ((nr * nr + ni * ni) / (dr * dr + di * di)).log10()
+    }
+}
+
+/// Memory of one channel passing through a biquad, in transposed direct form II
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BiquadState {
+    z1: f64,
+    z2: f64,
+}
+
+impl BiquadState {
+    pub fn process(&mut self, filter: &Biquad, input: f64) -> f64 {
+        let output = filter.b0 * input + self.z1;
+        self.z1 = filter.b1 * input - filter.a1 * output + self.z2;
+        self.z2 = filter.b2 * input - filter.a2 * output;
+        output
+    }
+}
@@ -0,0 +1,175 @@
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+
+mod biquad;
+mod presets;
+mod processor;
+#[cfg(test)]
+mod tests;
+
+pub use biquad::{Biquad, BiquadState};
+pub use presets::{builtin_presets, find_preset, FLAT};
+pub use processor::{EqSettings, EqualizerFactory, EqualizerProcessor};
+
+/// Centre frequencies of the default ten-band graphic EQ, an octave apart
+pub const GRAPHIC_FREQUENCIES: [f32; 10] = [31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0];
+
+/// Q giving the graphic bands a bandwidth of about an octave
+pub const GRAPHIC_Q: f32 = 1.41;
+
+/// Limits on each band's settings
+pub const MAX_BAND_GAIN_DB: f32 = 12.0;
+pub const MIN_FREQUENCY: f32 = 20.0;
+pub const MAX_FREQUENCY: f32 = 20000.0;
+pub const MIN_Q: f32 = 0.1;
+pub const MAX_Q: f32 = 10.0;
+pub const MAX_BANDS: usize = 16;
+
+/// Shape of an EQ band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BandKind {
+    #[default]
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// One filter of the EQ
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    #[serde(default)]
+    pub kind: BandKind,
+    /// Centre or corner frequency in Hz
+    pub frequency: f32,
+    pub q: f32,
+    pub gain_db: f32,
+}
+
+impl EqBand {
+    pub fn peaking(frequency: f32, gain_db: f32) -> Self {
+        Self { kind: BandKind::Peaking, frequency, q: GRAPHIC_Q, gain_db }
+    }
+
+    /// Coefficients at `sample_rate`; bands at or above Nyquist have no effect
+    pub fn coefficients(&self, sample_rate: u32) -> Biquad {
+        if self.gain_db == 0.0 || self.frequency >= sample_rate as f32 * 0.49 {
+            return Biquad::IDENTITY;
+        }
+        match self.kind {
+            BandKind::Peaking => Biquad::peaking(sample_rate, self.frequency, self.q, self.gain_db),
+            BandKind::LowShelf => Biquad::low_shelf(sample_rate, self.frequency, self.q, self.gain_db),
+            BandKind::HighShelf => Biquad::high_shelf(sample_rate, self.frequency, self.q, self.gain_db),
+        }
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if !(MIN_FREQUENCY..=MAX_FREQUENCY).contains(&self.frequency) {
+            return Err(format!("EQ band frequency {}Hz is outside {}-{}Hz", self.frequency, MIN_FREQUENCY, MAX_FREQUENCY).into());
+        }
+        if !(MIN_Q..=MAX_Q).contains(&self.q) {
+            return Err(format!("EQ band Q {} is outside {}-{}", self.q, MIN_Q, MAX_Q).into());
+        }
+        if !(-MAX_BAND_GAIN_DB..=MAX_BAND_GAIN_DB).contains(&self.gain_db) {
+            return Err(format!("EQ band gain {}dB exceeds ±{}dB", self.gain_db, MAX_BAND_GAIN_DB).into());
+        }
+        Ok(())
+    }
+}
+
+/// A named set of bands, with the gain applied ahead of them to keep boosts from clipping
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqPreset {
+    pub name: String,
+    #[serde(default)]
+    pub preamp_db: f32,
+    pub bands: Vec<EqBand>,
+}
+
+impl EqPreset {
+    /// Ten-band graphic EQ with `gains` from 31Hz to 16kHz, attenuated by its largest boost
+    pub fn graphic(name: &str, gains: [f32; 10]) -> Self {
+        let bands = GRAPHIC_FREQUENCIES
+            .iter()
+            .zip(gains)
+            .map(|(&frequency, gain_db)| EqBand::peaking(frequency, gain_db))
+            .collect();
+        let boost = gains.iter().fold(0f32, |max, &gain| max.max(gain));
+        Self { name: name.to_string(), preamp_db: 0.0 - boost, bands }
+    }
+
+    pub fn flat() -> Self {
+        Self::graphic(FLAT, [0.0; 10])
+    }
+
+    /// Check every band and the preamp are within the limits above
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.bands.len() > MAX_BANDS {
+            return Err(format!("EQ presets are limited to {} bands", MAX_BANDS).into());
+        }
+        if !(-MAX_BAND_GAIN_DB..=MAX_BAND_GAIN_DB).contains(&self.preamp_db) {
+            return Err(format!("EQ preamp {}dB exceeds ±{}dB", self.preamp_db, MAX_BAND_GAIN_DB).into());
+        }
+        self.bands.iter().try_for_each(EqBand::validate)
+    }
+}
+
+/// Applies an EQ preset to interleaved samples of a fixed layout
+pub struct Equalizer {
+    channels: usize,
+    sample_rate: u32,
+    preamp: f32,
+    filters: Vec<Biquad>,
+    /// One per band per channel, band-major
+    states: Vec<BiquadState>,
+}
+
+impl Equalizer {
+    pub fn new(preset: &EqPreset, channels: u16, sample_rate: u32) -> Self {
+        let mut equalizer = Self {
+            channels: channels.max(1) as usize,
+            sample_rate,
+            preamp: 1.0,
+            filters: Vec::new(),
+            states: Vec::new(),
+        };
+        equalizer.set_preset(preset);
+        equalizer
+    }
+
+    /// Whether this was built for streams of `channels` at `sample_rate`
+    pub fn matches(&self, channels: u16, sample_rate: u32) -> bool {
+        self.channels == channels.max(1) as usize && self.sample_rate == sample_rate
+    }
+
+    /// Switch to `preset`, keeping the filters' memory when the band count is unchanged so
+    /// adjusting a band doesn't click
+    pub fn set_preset(&mut self, preset: &EqPreset) {
+        self.preamp = 10f32.powf(preset.preamp_db / 20.0);
+        self.filters = preset.bands.iter().map(|band| band.coefficients(self.sample_rate)).collect();
+        self.states.resize(self.filters.len() * self.channels, BiquadState::default());
+    }
+
+    /// Forget the signal so far, as after a seek
+    pub fn reset(&mut self) {
+        self.states.fill(BiquadState::default());
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for frame in samples.chunks_mut(self.channels) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let mut value = (*sample * self.preamp) as f64;
+                for (band, filter) in self.filters.iter().enumerate() {
+                    value = self.states[band * self.channels + channel].process(filter, value);
+                }
+                *sample = value as f32;
+            }
+        }
+    }
+
+    /// Gain in dB applied to a sine at `frequency`, preamp included
+    pub fn response_db(&self, frequency: f32) -> f64 {
+        let bands: f64 = self.filters.iter().map(|filter| filter.response_db(self.sample_rate, frequency)).sum();
+        20.0 * (self.preamp as f64).log10() + bands
+    }
+}
@@ -0,0 +1,30 @@
+use super::EqPreset;
+
+/// Name of the preset that leaves the sound unchanged
+pub const FLAT: &str = "Flat";
+
+/// Gains of the ten graphic bands, from 31Hz to 16kHz
+const BUILTIN: &[(&str, [f32; 10])] = &[
+    (FLAT, [0.0; 10]),
+    ("Rock", [5.0, 4.0, 3.0, 1.0, -1.0, -1.0, 1.0, 3.0, 4.0, 5.0]),
+    ("Pop", [-1.0, 1.0, 3.0, 4.0, 4.0, 2.0, 0.0, -1.0, -1.0, -1.0]),
+    ("Jazz", [3.0, 2.0, 1.0, 2.0, -1.0, -1.0, 0.0, 1.0, 2.0, 3.0]),
+    ("Classical", [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -2.0, -3.0, -3.0, -4.0]),
+    ("Bass Boost", [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+    ("Treble Boost", [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 4.0, 5.0, 6.0]),
+    ("Vocal", [-2.0, -2.0, -1.0, 1.0, 3.0, 4.0, 3.0, 1.0, 0.0, -1.0]),
+    ("Loudness", [5.0, 4.0, 2.0, 0.0, -1.0, -1.0, 0.0, 2.0, 4.0, 5.0]),
+];
+
+/// The presets that ship with playtui, flat first
+pub fn builtin_presets() -> Vec<EqPreset> {
+    BUILTIN.iter().map(|(name, gains)| EqPreset::graphic(name, *gains)).collect()
+}
+
+/// Look a preset up by name among `user` presets, then the built-in ones
+pub fn find_preset(name: &str, user: &[EqPreset]) -> Option<EqPreset> {
+    user.iter()
+        .find(|preset| preset.name == name)
+        .cloned()
+        .or_else(|| builtin_presets().into_iter().find(|preset| preset.name == name))
+}
@@ -0,0 +1,117 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::audio::dsp::{AudioProcessor, ProcessorFactory, EQUALIZER};
+use crate::audio::AudioFormat;
+use super::{EqPreset, Equalizer};
+
+/// EQ shared between the engine and the equalizer stages it builds
+#[derive(Clone, Default)]
+pub struct EqSettings {
+    preset: Arc<Mutex<Option<EqPreset>>>,
+    /// Bumped on every change so each stage notices it once
+    version: Arc<AtomicU64>,
+}
+
+impl EqSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, preset: Option<EqPreset>) {
+        *self.preset.lock().unwrap() = preset;
+        self.version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn get(&self) -> Option<EqPreset> {
+        self.preset.lock().unwrap().clone()
+    }
+
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+/// Chain stage applying the EQ in `EqSettings`, passing samples through while it is off
+pub struct EqualizerProcessor {
+    settings: EqSettings,
+    /// Settings version the filters were built from
+    seen: u64,
+    preset: Option<EqPreset>,
+    layout: Option<(u16, u32)>,
+    equalizer: Option<Equalizer>,
+}
+
+impl EqualizerProcessor {
+    pub fn new(settings: EqSettings) -> Self {
+        let seen = settings.version();
+        let preset = settings.get();
+        Self { settings, seen, preset, layout: None, equalizer: None }
+    }
+
+    /// Pick up a change from the UI thread, keeping the filters' state when only gains moved
+    fn update(&mut self) {
+        let version = self.settings.version();
+        if version == self.seen {
+            return;
+        }
+        self.seen = version;
+        self.preset = self.settings.get();
+        match (self.preset.as_ref(), self.equalizer.as_mut()) {
+            (Some(preset), Some(equalizer)) => equalizer.set_preset(preset),
+            _ => self.rebuild(),
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.equalizer = match (self.preset.as_ref(), self.layout) {
+            (Some(preset), Some((channels, sample_rate))) => Some(Equalizer::new(preset, channels, sample_rate)),
+            _ => None,
+        };
+    }
+}
+
+impl AudioProcessor for EqualizerProcessor {
+    fn prepare(&mut self, format: &AudioFormat) -> Result<(), Box<dyn Error>> {
+        if format.channels == 0 || format.sample_rate == 0 {
+            return Err("Equalizer needs a non-empty layout".into());
+        }
+        self.layout = Some((format.channels, format.sample_rate));
+        self.rebuild();
+        Ok(())
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        self.update();
+        if let Some(equalizer) = self.equalizer.as_mut() {
+            equalizer.process(samples);
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Some(equalizer) = self.equalizer.as_mut() {
+            equalizer.reset();
+        }
+    }
+}
+
+/// Builds equalizer stages that all follow the same settings
+pub struct EqualizerFactory {
+    settings: EqSettings,
+}
+
+impl EqualizerFactory {
+    pub fn new(settings: EqSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl ProcessorFactory for EqualizerFactory {
+    fn name(&self) -> &str {
+        EQUALIZER
+    }
+
+    fn create(&self) -> Box<dyn AudioProcessor> {
+        Box::new(EqualizerProcessor::new(self.settings.clone()))
+    }
+}
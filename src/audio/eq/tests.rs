@@ -0,0 +1,96 @@
+use super::*;
+use std::f64::consts::PI;
+
+const RATE: u32 = 48000;
+
+/// RMS level in dB of a unit sine at `frequency` once it has been through `equalizer`
+fn measured_db(equalizer: &mut Equalizer, frequency: f64) -> f64 {
+    let frames = RATE as usize / 2;
+    let mut samples: Vec<f32> = (0..frames)
+        .map(|n| (2.0 * PI * frequency * n as f64 / RATE as f64).sin() as f32)
+        .collect();
+    equalizer.reset();
+    equalizer.process(&mut samples);
+    // Skip the filters settling in
+    let settled = &samples[frames / 2..];
+    let power = settled.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / settled.len() as f64;
+    10.0 * (power * 2.0).log10()
+}
+
+#[test]
+fn test_peaking_band_shapes_response() {
+    let band = EqBand { kind: BandKind::Peaking, frequency: 1000.0, q: 1.0, gain_db: 6.0 };
+    let filter = band.coefficients(RATE);
+    assert!((filter.response_db(RATE, 1000.0) - 6.0).abs() < 1e-6);
+    assert!(filter.response_db(RATE, 50.0).abs() < 0.1);
+    assert!(filter.response_db(RATE, 18000.0).abs() < 0.1);
+
+    let cut = EqBand { gain_db: -6.0, ..band }.coefficients(RATE);
+    assert!((cut.response_db(RATE, 1000.0) + 6.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_shelves() {
+    let low = EqBand { kind: BandKind::LowShelf, frequency: 200.0, q: 0.707, gain_db: 6.0 }.coefficients(RATE);
+    assert!((low.response_db(RATE, 20.0) - 6.0).abs() < 0.1);
+    assert!(low.response_db(RATE, 5000.0).abs() < 0.1);
+
+    let high = EqBand { kind: BandKind::HighShelf, frequency: 5000.0, q: 0.707, gain_db: -6.0 }.coefficients(RATE);
+    assert!((high.response_db(RATE, 20000.0) + 6.0).abs() < 0.2);
+    assert!(high.response_db(RATE, 100.0).abs() < 0.1);
+}
+
+#[test]
+fn test_equalizer_filters_samples() {
+    let mut preset = EqPreset::flat();
+    preset.bands[5].gain_db = 9.0;
+    preset.preamp_db = -3.0;
+    let mut equalizer = Equalizer::new(&preset, 1, RATE);
+    assert!((measured_db(&mut equalizer, 1000.0) - 6.0).abs() < 0.1);
+    assert!((measured_db(&mut equalizer, 100.0) + 3.0).abs() < 0.2);
+    assert!((equalizer.response_db(1000.0) - 6.0).abs() < 1e-4);
+
+    // Flat passes the signal through untouched
+    equalizer.set_preset(&EqPreset::flat());
+    equalizer.reset();
+    let mut samples = vec![0.5, -0.25, 0.125];
+    equalizer.process(&mut samples);
+    assert_eq!(samples, vec![0.5, -0.25, 0.125]);
+}
+
+#[test]
+fn test_channels_are_filtered_independently() {
+    let mut preset = EqPreset::flat();
+    preset.bands[0].gain_db = 12.0;
+    let mut equalizer = Equalizer::new(&preset, 2, RATE);
+    // An impulse on the left leaves the silent right channel silent
+    let mut samples = vec![0.0; 64];
+    samples[0] = 1.0;
+    equalizer.process(&mut samples);
+    assert!(samples.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    assert!(samples.iter().step_by(2).skip(1).any(|&s| s != 0.0));
+}
+
+#[test]
+fn test_presets() {
+    let builtin = builtin_presets();
+    assert_eq!(builtin[0], EqPreset::flat());
+    for preset in &builtin {
+        preset.validate().unwrap();
+        assert_eq!(preset.bands.len(), GRAPHIC_FREQUENCIES.len());
+        // Boosts are matched by the preamp so the loudest band stays at unity
+        let boost = preset.bands.iter().fold(0f32, |max, band| max.max(band.gain_db));
+        assert_eq!(preset.preamp_db, -boost, "{}", preset.name);
+    }
+
+    let mut mine = EqPreset::graphic("Rock", [1.0; 10]);
+    assert_eq!(find_preset("Rock", std::slice::from_ref(&mine)), Some(mine.clone()));
+    assert_eq!(find_preset("Jazz", &[]).unwrap().name, "Jazz");
+    assert_eq!(find_preset("Missing", &[]), None);
+
+    mine.bands[0].q = 50.0;
+    assert!(mine.validate().is_err());
+    mine.bands[0].q = GRAPHIC_Q;
+    mine.bands[1].frequency = 30000.0;
+    assert!(mine.validate().is_err());
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Length of the ramps that keep play, pause, stop and seek free of clicks
+pub const CLICK_FADE: Duration = Duration::from_millis(10);
+
+/// Longest crossfade the engine accepts
+pub const MAX_CROSSFADE: Duration = Duration::from_secs(12);
+
+/// Shape of a fade between silence and full level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FadeCurve {
+    /// Gain rises in a straight line; the overlap dips about 6dB in the middle
+    Linear,
+    /// Quarter sine and cosine, keeping the summed power constant for uncorrelated tracks
+    #[default]
+    EqualPower,
+    /// Gain rises evenly in decibels, from -60dB
+    Logarithmic,
+}
+
+impl FadeCurve {
+    /// Fade-in gain at `progress` through the fade, from 0 to 1.
+    /// The matching fade-out gain is `gain(1.0 - progress)`.
+    pub fn gain(self, progress: f32) -> f32 {
+        let x = progress.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => x,
+            FadeCurve::EqualPower => (x * std::f32::consts::FRAC_PI_2).sin(),
+            FadeCurve::Logarithmic if x == 0.0 => 0.0,
+            FadeCurve::Logarithmic => 10f32.powf(3.0 * (x - 1.0)),
+        }
+    }
+}
+
+/// Overlap between the end of one track and the start of the next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossfade {
+    pub duration: Duration,
+    pub curve: FadeCurve,
+}
+
+impl Crossfade {
+    /// Whole frames the overlap lasts at `sample_rate`
+    pub fn frames(&self, sample_rate: u32) -> usize {
+        (self.duration.min(MAX_CROSSFADE).as_secs_f64() * sample_rate as f64).round() as usize
+    }
+
+    /// Mix the interleaved tail of the outgoing track with the head of the incoming one, in place
+    /// in `outgoing`. The head may be shorter than the tail, in which case it is padded with silence.
+    pub fn mix(&self, outgoing: &mut [f32], incoming: &[f32], channels: usize) {
+        let frames = outgoing.len() / channels.max(1);
+        for (frame, samples) in outgoing.chunks_mut(channels.max(1)).enumerate() {
+            let progress = (frame as f32 + 0.5) / frames as f32;
+            let (fade_out, fade_in) = (self.curve.gain(1.0 - progress), self.curve.gain(progress));
+            for (channel, sample) in samples.iter_mut().enumerate() {
+                let next = incoming.get(frame * channels + channel).copied().unwrap_or(0.0);
+                *sample = *sample * fade_out + next * fade_in;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curves_run_from_silence_to_full() {
+        for curve in [FadeCurve::Linear, FadeCurve::EqualPower, FadeCurve::Logarithmic] {
+            assert_eq!(curve.gain(0.0), 0.0, "{:?}", curve);
+            assert!((curve.gain(1.0) - 1.0).abs() < 1e-6, "{:?}", curve);
+            assert!(curve.gain(0.25) < curve.gain(0.5) && curve.gain(0.5) < curve.gain(0.75), "{:?}", curve);
+        }
+        assert!((FadeCurve::Linear.gain(0.5) - 0.5).abs() < 1e-6);
+        assert!((FadeCurve::Logarithmic.gain(0.5) - 10f32.powf(-1.5)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_equal_power_keeps_power_constant() {
+        for step in 0..=10 {
+            let x = step as f32 / 10.0;
+            let power = FadeCurve::EqualPower.gain(x).powi(2) + FadeCurve::EqualPower.gain(1.0 - x).powi(2);
+            assert!((power - 1.0).abs() < 1e-5, "power {} at {}", power, x);
+        }
+    }
+
+    #[test]
+    fn test_mix_hands_over_between_tracks() {
+        let fade = Crossfade { duration: Duration::from_millis(1), curve: FadeCurve::Linear };
+        assert_eq!(fade.frames(48000), 48);
+
+        // Stereo, four frames; the incoming head runs out after two
+        let mut outgoing = vec![1.0; 8];
+        fade.mix(&mut outgoing, &[-1.0, -1.0, -1.0, -1.0], 2);
+        assert_eq!(outgoing, vec![0.75, 0.75, 0.25, 0.25, 0.375, 0.375, 0.125, 0.125]);
+    }
+
+    #[test]
+    fn test_curve_names() {
+        assert_eq!(serde_json::to_string(&FadeCurve::EqualPower).unwrap(), "\"equal-power\"");
+        let curve: FadeCurve = serde_json::from_str("\"logarithmic\"").unwrap();
+        assert_eq!(curve, FadeCurve::Logarithmic);
+    }
+}
@@ -0,0 +1,89 @@
+use std::error::Error;
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat, Mp4Codec};
+use super::gapless;
+use super::symphonia_source::SymphoniaSource;
+
+pub struct AacDecoder {}
+
+impl AacDecoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// MP4 tracks this decoder handles; an unidentified `.m4a` is assumed to be AAC
+    pub fn supports_codec(codec: Mp4Codec) -> bool {
+        matches!(codec, Mp4Codec::Aac | Mp4Codec::Unknown)
+    }
+
+    /// Open the track with the encoder priming and padding from `iTunSMPB` trimmed off
+    fn open(source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        let mut reader = SymphoniaSource::open(source)?;
+        if let Some(trim) = gapless::mp4_trim(source) {
+            reader.trim(trim);
+        }
+        Ok(reader)
+    }
+}
+
+impl Default for AacDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDecoder for AacDecoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        match detect_source_format(source) {
+            Some(FileFormat::Mp4(codec)) => Self::supports_codec(codec),
+            _ => false,
+        }
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an AAC file".into());
+        }
+        Ok(Self::open(source)?.format)
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an AAC file".into());
+        }
+
+        Self::open(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_path(filename: &str) -> PathBuf {
+        PathBuf::from(filename)
+    }
+
+    #[test]
+    fn test_can_decode() {
+        let decoder = AacDecoder::new();
+        assert!(decoder.can_decode(test_path("test.m4a").as_path()));
+        assert!(decoder.can_decode(test_path("test.M4A").as_path()));
+        assert!(decoder.can_decode(test_path("test.mp4").as_path()));
+        assert!(!decoder.can_decode(test_path("test.mp3").as_path()));
+        assert!(!decoder.can_decode(test_path("test").as_path()));
+    }
+
+    #[test]
+    fn test_probe_invalid_extension() {
+        let decoder = AacDecoder::new();
+        assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_extension() {
+        let mut decoder = AacDecoder::new();
+        assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
+    }
+}
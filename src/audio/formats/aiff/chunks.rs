@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+use crate::audio::AudioFormat;
+use crate::audio::formats::wav::{ByteTransform, FrameLayout, WavEncoding};
+
+/// Largest COMM chunk we are willing to buffer; AIFC compression names make them 22+ bytes
+const MAX_COMM_CHUNK_SIZE: u64 = 1024;
+
+/// Form type from the 12-byte file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiffKind {
+    Aiff,
+    /// AIFF-C, whose COMM chunk names a compression type
+    Aifc,
+}
+
+/// Byte order of the samples in the SSND chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Big,
+    Little,
+}
+
+/// Parsed contents of a COMM chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommChunk {
+    pub encoding: WavEncoding,
+    pub byte_order: ByteOrder,
+    pub channels: u16,
+    /// Sample frames the file declares, which may be fewer than SSND holds
+    pub sample_frames: u32,
+    pub bits_per_sample: u16,
+    pub sample_rate: u32,
+}
+
+impl CommChunk {
+    /// Bytes occupied by a single sample of a single channel
+    pub fn container_bytes(&self) -> usize {
+        match self.encoding {
+            WavEncoding::ALaw | WavEncoding::MuLaw => 1,
+            WavEncoding::Pcm | WavEncoding::Float => self.bits_per_sample.div_ceil(8) as usize,
+        }
+    }
+
+    /// Bytes occupied by one sample frame
+    pub fn block_align(&self) -> usize {
+        self.container_bytes() * self.channels as usize
+    }
+
+    /// How the SSND chunk's frames are laid out
+    pub(crate) fn frame_layout(&self) -> FrameLayout {
+        FrameLayout {
+            encoding: self.encoding,
+            container_bytes: self.container_bytes(),
+            block_align: self.block_align(),
+        }
+    }
+
+    /// Rewrites samples into the little-endian, unsigned 8-bit layout WAV decoding expects
+    pub(crate) fn to_wav_layout(&self) -> ByteTransform {
+        match self.byte_order {
+            ByteOrder::Big => from_big_endian,
+            ByteOrder::Little => unsign_8bit,
+        }
+    }
+}
+
+fn from_big_endian(layout: FrameLayout, bytes: &mut [u8]) {
+    if layout.container_bytes > 1 {
+        bytes.chunks_exact_mut(layout.container_bytes).for_each(|sample| sample.reverse());
+    }
+    unsign_8bit(layout, bytes);
+}
+
+/// AIFF's 8-bit PCM is signed, where WAV's is not
+fn unsign_8bit(layout: FrameLayout, bytes: &mut [u8]) {
+    if layout.encoding == WavEncoding::Pcm && layout.container_bytes == 1 {
+        bytes.iter_mut().for_each(|sample| *sample ^= 0x80);
+    }
+}
+
+/// Everything needed to stream samples out of an AIFF/AIFC file
+#[derive(Debug, Clone)]
+pub struct AiffInfo {
+    pub format: AudioFormat,
+    pub comm: CommChunk,
+    pub data_offset: u64,
+    pub data_len: u64,
+    /// Number of complete sample frames available
+    pub total_samples: u64,
+}
+
+/// Check the FORM signature and the AIFF/AIFC form type
+pub fn validate_form_header(header: &[u8]) -> Option<AiffKind> {
+    if header.len() < 12 || &header[0..4] != b"FORM" {
+        return None;
+    }
+    match &header[8..12] {
+        b"AIFF" => Some(AiffKind::Aiff),
+        b"AIFC" => Some(AiffKind::Aifc),
+        _ => None,
+    }
+}
+
+/// Convert an 80-bit IEEE 754 extended float, as used for the sample rate, to whole Hz
+pub fn extended_to_rate(bytes: &[u8]) -> Option<u32> {
+    if bytes.len() < 10 || bytes[0] & 0x80 != 0 {
+        return None;
+    }
+    let exponent = u16::from_be_bytes([bytes[0], bytes[1]]) as i32;
+    let mut mantissa = [0u8; 8];
+    mantissa.copy_from_slice(&bytes[2..10]);
+    let mantissa = u64::from_be_bytes(mantissa);
+
+    let rate = mantissa as f64 * 2f64.powi(exponent - 16383 - 63);
+    (rate >= 1.0 && rate <= u32::MAX as f64).then(|| rate.round() as u32)
+}
+
+/// Parse the body of a COMM chunk, including the AIFC compression type
+pub fn parse_comm_chunk(chunk: &[u8], kind: AiffKind) -> Option<CommChunk> {
+    if chunk.len() < 18 {
+        return None;
+    }
+
+    let channels = i16::from_be_bytes([chunk[0], chunk[1]]);
+    let sample_frames = u32::from_be_bytes([chunk[2], chunk[3], chunk[4], chunk[5]]);
+    let mut bits_per_sample = i16::from_be_bytes([chunk[6], chunk[7]]);
+    let sample_rate = extended_to_rate(&chunk[8..18])?;
+
+    let (encoding, byte_order) = match kind {
+        AiffKind::Aiff => (WavEncoding::Pcm, ByteOrder::Big),
+        AiffKind::Aifc => match chunk.get(18..22)? {
+            b"NONE" | b"twos" => (WavEncoding::Pcm, ByteOrder::Big),
+            b"sowt" => (WavEncoding::Pcm, ByteOrder::Little),
+            b"fl32" | b"FL32" => {
+                bits_per_sample = 32;
+                (WavEncoding::Float, ByteOrder::Big)
+            }
+            b"fl64" | b"FL64" => {
+                bits_per_sample = 64;
+                (WavEncoding::Float, ByteOrder::Big)
+            }
+            b"alaw" | b"ALAW" => (WavEncoding::ALaw, ByteOrder::Big),
+            b"ulaw" | b"ULAW" => (WavEncoding::MuLaw, ByteOrder::Big),
+            _ => return None,
+        },
+    };
+
+    if channels <= 0 || (encoding == WavEncoding::Pcm && !(1..=32).contains(&bits_per_sample)) {
+        return None;
+    }
+
+    Some(CommChunk {
+        encoding,
+        byte_order,
+        channels: channels as u16,
+        sample_frames,
+        bits_per_sample: bits_per_sample as u16,
+        sample_rate,
+    })
+}
+
+/// Walk the chunk list until both COMM and SSND have been located
+pub fn find_sound_chunk<R: Read + Seek>(reader: &mut R) -> Result<AiffInfo, Box<dyn Error>> {
+    let mut header = [0u8; 12];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+    let kind = validate_form_header(&header).ok_or("Not an AIFF/AIFC file")?;
+
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut comm = None;
+    let mut sound = None;
+    let mut pos = 12u64;
+
+    while pos + 8 <= file_len {
+        let mut chunk_header = [0u8; 8];
+        reader.seek(SeekFrom::Start(pos))?;
+        reader.read_exact(&mut chunk_header)?;
+
+        let body = pos + 8;
+        let available = file_len - body;
+        let mut size = u32::from_be_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]]) as u64;
+
+        match &chunk_header[0..4] {
+            b"COMM" => {
+                if size > MAX_COMM_CHUNK_SIZE || size > available {
+                    return Err("Invalid COMM chunk".into());
+                }
+                comm = Some(parse_comm_chunk(&read_body(reader, size)?, kind).ok_or("Unsupported AIFF format")?);
+            }
+            b"SSND" => {
+                if size > available {
+                    log::warn!("AIFF sound chunk truncated: {} bytes declared, {} present", size, available);
+                    size = available;
+                }
+                // The sound data is preceded by an offset and block size, both usually zero
+                let prefix = read_body(reader, size.min(8))?;
+                if prefix.len() < 8 {
+                    return Err("Invalid SSND chunk".into());
+                }
+                let offset = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as u64;
+                let data_offset = body + 8 + offset;
+                sound = Some((data_offset, (body + size).saturating_sub(data_offset)));
+                if comm.is_some() {
+                    break;
+                }
+            }
+            _ => {}
+        }
+
+        // Chunks are word aligned, odd sizes carry a pad byte
+        pos = body + size + (size & 1);
+    }
+
+    let comm = comm.ok_or("AIFF file has no COMM chunk")?;
+    let (data_offset, available_len) = sound.ok_or("AIFF file has no SSND chunk")?;
+
+    let block_align = comm.block_align() as u64;
+    let total_samples = (available_len / block_align).min(comm.sample_frames as u64);
+
+    let bits_per_sample = match comm.encoding {
+        // Companded samples expand to 16-bit linear PCM
+        WavEncoding::ALaw | WavEncoding::MuLaw => 16,
+        WavEncoding::Pcm | WavEncoding::Float => comm.bits_per_sample,
+    };
+
+    Ok(AiffInfo {
+        format: AudioFormat {
+            channels: comm.channels,
+            sample_rate: comm.sample_rate,
+            bits_per_sample,
+            duration: Some(Duration::from_secs_f64(total_samples as f64 / comm.sample_rate as f64)),
+            // An extreme rate and frame size can give more than fits
+            bit_rate: u32::try_from(u64::from(comm.sample_rate) * block_align * 8 / 1000).ok(),
+        },
+        data_offset,
+        data_len: total_samples * block_align,
+        total_samples,
+        comm,
+    })
+}
+
+fn read_body<R: Read>(reader: &mut R, size: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut body = vec![0u8; size as usize];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::Path;
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat};
+use super::wav::PcmSource;
+
+mod chunks;
+#[cfg(test)]
+mod tests;
+
+pub use chunks::{extended_to_rate, parse_comm_chunk, validate_form_header, AiffInfo, AiffKind, ByteOrder, CommChunk};
+
+pub struct AiffDecoder {}
+
+impl AiffDecoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Walk the FORM chunks and describe the stream without reading samples
+    pub fn read_info(&self, path: &Path) -> Result<AiffInfo, Box<dyn Error>> {
+        self.read_source_info(&MediaSource::from_path(path))
+    }
+
+    /// Describe a source as `read_info` does a file
+    pub fn read_source_info(&self, source: &MediaSource) -> Result<AiffInfo, Box<dyn Error>> {
+        let mut reader = BufReader::new(source.open()?);
+        chunks::find_sound_chunk(&mut reader)
+    }
+}
+
+impl Default for AiffDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDecoder for AiffDecoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Aiff))
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an AIFF file".into());
+        }
+        Ok(self.read_source_info(source)?.format)
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an AIFF file".into());
+        }
+
+        let mut reader = BufReader::new(source.open()?);
+        let info = chunks::find_sound_chunk(&mut reader)?;
+        reader.seek(SeekFrom::Start(info.data_offset))?;
+
+        let (layout, transform) = (info.comm.frame_layout(), info.comm.to_wav_layout());
+        let source = PcmSource::new(reader, layout, Some(transform), info.data_offset, info.data_len);
+        Ok(AudioReader::with_source(info.format, info.total_samples, Box::new(source)))
+    }
+}
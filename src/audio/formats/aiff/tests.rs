@@ -0,0 +1,259 @@
+use super::*;
+use crate::audio::formats::wav::WavEncoding;
+use std::path::PathBuf;
+
+fn test_path(filename: &str) -> PathBuf {
+    PathBuf::from(filename)
+}
+
+#[test]
+fn test_can_decode() {
+    let decoder = AiffDecoder::new();
+    assert!(decoder.can_decode(test_path("test.aiff").as_path()));
+    assert!(decoder.can_decode(test_path("test.AIF").as_path()));
+    assert!(decoder.can_decode(test_path("test.aifc").as_path()));
+    assert!(!decoder.can_decode(test_path("test.wav").as_path()));
+    assert!(!decoder.can_decode(test_path("test").as_path()));
+}
+
+#[test]
+fn test_probe_invalid_extension() {
+    let decoder = AiffDecoder::new();
+    assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
+}
+
+#[test]
+fn test_decode_invalid_extension() {
+    let mut decoder = AiffDecoder::new();
+    assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
+}
+
+/// 80-bit extended encoding of a whole-number rate
+fn extended(rate: u32) -> [u8; 10] {
+    let exponent = 31 - rate.leading_zeros();
+    let mut bytes = [0u8; 10];
+    bytes[0..2].copy_from_slice(&(16383 + exponent as u16).to_be_bytes());
+    bytes[2..10].copy_from_slice(&((rate as u64) << (63 - exponent)).to_be_bytes());
+    bytes
+}
+
+fn comm_body(channels: u16, frames: u32, bits: u16, compression: Option<&[u8; 4]>) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend(channels.to_be_bytes());
+    body.extend(frames.to_be_bytes());
+    body.extend(bits.to_be_bytes());
+    body.extend(extended(8000));
+    if let Some(compression) = compression {
+        body.extend(compression);
+        body.extend([0, 0]); // empty pascal string, padded to even length
+    }
+    body
+}
+
+fn ssnd_body(data: &[u8]) -> Vec<u8> {
+    let mut body = vec![0u8; 8];
+    body.extend(data);
+    body
+}
+
+fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    chunk_with_size(id, body.len() as u32, body)
+}
+
+fn chunk_with_size(id: &[u8; 4], size: u32, body: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend(size.to_be_bytes());
+    bytes.extend(body);
+    if body.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn form(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+    let body: Vec<u8> = chunks.concat();
+    let mut bytes = b"FORM".to_vec();
+    bytes.extend((body.len() as u32 + 4).to_be_bytes());
+    bytes.extend(kind);
+    bytes.extend(body);
+    bytes
+}
+
+fn write_aiff(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".aiff").tempfile().unwrap();
+    std::io::Write::write_all(&mut file, bytes).unwrap();
+    file
+}
+
+fn decode_all(bytes: &[u8]) -> (AudioReader, Vec<f32>) {
+    let file = write_aiff(bytes);
+    let mut reader = AiffDecoder::new().decode(file.path()).unwrap();
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 64];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+    (reader, samples)
+}
+
+fn decode_pcm(compression: Option<&[u8; 4]>, bits: u16, frames: u32, data: &[u8]) -> Vec<f32> {
+    let kind = if compression.is_some() { b"AIFC" } else { b"AIFF" };
+    let bytes = form(kind, &[chunk(b"COMM", &comm_body(1, frames, bits, compression)), chunk(b"SSND", &ssnd_body(data))]);
+    decode_all(&bytes).1
+}
+
+#[test]
+fn test_probe_fixture() {
+    for name in ["test/testaudio-short.aiff", "test/testaudio-short.aifc"] {
+        let decoder = AiffDecoder::new();
+        let format = decoder.probe_format(Path::new(name)).unwrap();
+        assert_eq!(format.channels, 1, "{}", name);
+        assert_eq!(format.sample_rate, 48000, "{}", name);
+        assert_eq!(format.bits_per_sample, 16, "{}", name);
+        assert_eq!(format.bit_rate, Some(768), "{}", name);
+
+        let info = decoder.read_info(Path::new(name)).unwrap();
+        assert_eq!(info.total_samples, 48000, "{}", name);
+        assert_eq!(info.data_len, 96000, "{}", name);
+    }
+}
+
+#[test]
+fn test_validate_form_header() {
+    assert_eq!(validate_form_header(b"FORM\0\0\0\0AIFF"), Some(AiffKind::Aiff));
+    assert_eq!(validate_form_header(b"FORM\0\0\0\0AIFC"), Some(AiffKind::Aifc));
+    assert_eq!(validate_form_header(b"FORM\0\0\0\08SVX"), None);
+    assert_eq!(validate_form_header(b"RIFF\0\0\0\0AIFF"), None);
+    assert_eq!(validate_form_header(b"FORM"), None);
+}
+
+#[test]
+fn test_extended_to_rate() {
+    for rate in [8000, 22050, 44100, 48000, 96000, 192000] {
+        assert_eq!(extended_to_rate(&extended(rate)), Some(rate));
+    }
+    // Negative and zero rates are meaningless
+    let mut negative = extended(44100);
+    negative[0] |= 0x80;
+    assert_eq!(extended_to_rate(&negative), None);
+    assert_eq!(extended_to_rate(&[0u8; 10]), None);
+}
+
+#[test]
+fn test_parse_comm_chunk() {
+    let comm = parse_comm_chunk(&comm_body(2, 1000, 24, None), AiffKind::Aiff).unwrap();
+    assert_eq!(comm.encoding, WavEncoding::Pcm);
+    assert_eq!(comm.byte_order, ByteOrder::Big);
+    assert_eq!(comm.channels, 2);
+    assert_eq!(comm.sample_frames, 1000);
+    assert_eq!(comm.sample_rate, 8000);
+    assert_eq!(comm.block_align(), 6);
+
+    let parse_aifc = |compression: &[u8; 4]| parse_comm_chunk(&comm_body(1, 1, 16, Some(compression)), AiffKind::Aifc);
+    assert_eq!(parse_aifc(b"sowt").unwrap().byte_order, ByteOrder::Little);
+    assert_eq!(parse_aifc(b"NONE").unwrap().byte_order, ByteOrder::Big);
+    assert_eq!(parse_aifc(b"fl32").unwrap().encoding, WavEncoding::Float);
+    assert_eq!(parse_aifc(b"fl64").unwrap().container_bytes(), 8);
+    assert_eq!(parse_aifc(b"ulaw").unwrap().encoding, WavEncoding::MuLaw);
+    assert_eq!(parse_aifc(b"alaw").unwrap().container_bytes(), 1);
+
+    // Compressed codecs, an AIFC chunk without a compression type and zero channels
+    assert!(parse_aifc(b"ima4").is_none());
+    assert!(parse_comm_chunk(&comm_body(1, 1, 16, None), AiffKind::Aifc).is_none());
+    assert!(parse_comm_chunk(&comm_body(0, 1, 16, None), AiffKind::Aiff).is_none());
+    assert!(parse_comm_chunk(&comm_body(1, 1, 16, None)[..16], AiffKind::Aiff).is_none());
+}
+
+#[test]
+fn test_decode_big_endian_pcm() {
+    // AIFF 8-bit samples are signed, unlike WAV
+    assert_eq!(decode_pcm(None, 8, 3, &[0x80, 0, 0x40]), vec![-1.0, 0.0, 0.5]);
+
+    let data: Vec<u8> = [i16::MIN, 0, 16384].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(decode_pcm(None, 16, 3, &data), vec![-1.0, 0.0, 0.5]);
+
+    let data = [0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00];
+    assert_eq!(decode_pcm(None, 24, 3, &data), vec![-1.0, 0.0, 0.5]);
+
+    let data: Vec<u8> = [i32::MIN, 0, 1 << 30].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(decode_pcm(None, 32, 3, &data), vec![-1.0, 0.0, 0.5]);
+}
+
+#[test]
+fn test_decode_aifc_encodings() {
+    let data: Vec<u8> = [i16::MIN, 16384].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(Some(b"sowt"), 16, 2, &data), vec![-1.0, 0.5]);
+
+    let data: Vec<u8> = [0.25f32, -0.5].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(decode_pcm(Some(b"fl32"), 32, 2, &data), vec![0.25, -0.5]);
+
+    let data: Vec<u8> = [0.75f64, -1.0].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(decode_pcm(Some(b"fl64"), 64, 2, &data), vec![0.75, -1.0]);
+
+    assert_eq!(decode_pcm(Some(b"ulaw"), 8, 1, &[0xFF]), vec![0.0]);
+}
+
+#[test]
+fn test_sample_frames_limit_sound_data() {
+    // SSND may hold more than COMM declares; trailing bytes are not audio
+    let data: Vec<u8> = [100i16, 200, 300].iter().flat_map(|s| s.to_be_bytes()).collect();
+    let bytes = form(b"AIFF", &[
+        chunk(b"COMM", &comm_body(1, 2, 16, None)),
+        chunk(b"SSND", &ssnd_body(&data)),
+        chunk(b"ID3 ", b"ID3\x04\0\0\0\0\0\0"),
+    ]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 2);
+    assert_eq!(samples, vec![100.0 / 32768.0, 200.0 / 32768.0]);
+}
+
+#[test]
+fn test_sound_data_offset() {
+    let mut body = Vec::new();
+    body.extend(4u32.to_be_bytes()); // offset past four alignment bytes
+    body.extend(0u32.to_be_bytes());
+    body.extend([0xAA; 4]);
+    body.extend(1000i16.to_be_bytes());
+
+    let bytes = form(b"AIFF", &[chunk(b"SSND", &body), chunk(b"COMM", &comm_body(1, 1, 16, None))]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 1);
+    assert_eq!(samples, vec![1000.0 / 32768.0]);
+}
+
+#[test]
+fn test_truncated_sound_chunk() {
+    // Header promises 100 stereo frames but the file ends after 10 and a half
+    let bytes = form(b"AIFF", &[
+        chunk(b"COMM", &comm_body(2, 100, 16, None)),
+        chunk_with_size(b"SSND", 408, &ssnd_body(&[0u8; 42])),
+    ]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 10);
+    assert_eq!(samples.len(), 20);
+}
+
+#[test]
+fn test_extreme_sample_rate() {
+    let mut comm = comm_body(64, 2, 32, None);
+    comm[8..18].copy_from_slice(&extended(u32::MAX));
+    let bytes = form(b"AIFF", &[chunk(b"COMM", &comm), chunk(b"SSND", &ssnd_body(&[0u8; 512]))]);
+    let format = AiffDecoder::new().probe_format(write_aiff(&bytes).path()).unwrap();
+    assert_eq!(format.sample_rate, u32::MAX);
+    assert_eq!(format.bit_rate, None);
+}
+
+#[test]
+fn test_missing_chunks() {
+    let no_sound = form(b"AIFF", &[chunk(b"COMM", &comm_body(1, 1, 16, None))]);
+    assert!(AiffDecoder::new().probe_format(write_aiff(&no_sound).path()).is_err());
+
+    let no_comm = form(b"AIFF", &[chunk(b"SSND", &ssnd_body(&[0, 0]))]);
+    assert!(AiffDecoder::new().probe_format(write_aiff(&no_comm).path()).is_err());
+
+    assert!(AiffDecoder::new().probe_format(write_aiff(b"not an aiff file").path()).is_err());
+}
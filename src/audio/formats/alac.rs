@@ -0,0 +1,71 @@
+use std::error::Error;
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat, Mp4Codec};
+use super::symphonia_source::SymphoniaSource;
+
+pub struct AlacDecoder {}
+
+impl AlacDecoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for AlacDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDecoder for AlacDecoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        // `.m4a` alone cannot tell ALAC from AAC, so only the sample entry counts
+        matches!(detect_source_format(source), Some(FileFormat::Mp4(Mp4Codec::Alac)))
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an ALAC file".into());
+        }
+        SymphoniaSource::probe(source)
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an ALAC file".into());
+        }
+
+        SymphoniaSource::open(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_path(filename: &str) -> PathBuf {
+        PathBuf::from(filename)
+    }
+
+    #[test]
+    fn test_can_decode() {
+        let decoder = AlacDecoder::new();
+        assert!(decoder.can_decode(test_path("test/testaudio-short-alac.m4a").as_path()));
+        assert!(!decoder.can_decode(test_path("test/testaudio-silence-aac.m4a").as_path()));
+        assert!(!decoder.can_decode(test_path("test.m4a").as_path()));
+        assert!(!decoder.can_decode(test_path("test").as_path()));
+    }
+
+    #[test]
+    fn test_probe_invalid_extension() {
+        let decoder = AlacDecoder::new();
+        assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_extension() {
+        let mut decoder = AlacDecoder::new();
+        assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
+    }
+}
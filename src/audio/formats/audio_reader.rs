@@ -1,12 +1,38 @@
 use std::error::Error;
+use std::time::Duration;
 use crate::audio::AudioFormat;
+use super::gapless::EncoderTrim;
+
+/// Source of decoded audio, yielding one packet of samples at a time
+pub trait SampleSource: Send {
+    /// Append the next packet's interleaved, normalized samples to `out`.
+    /// Returns `Ok(false)` once the stream is exhausted.
+    fn next_packet(&mut self, out: &mut Vec<f32>) -> Result<bool, Box<dyn Error>>;
+
+    /// Move to a packet starting at or before `frame`, returning the frame it starts at,
+    /// or `None` if the source can only be read forwards
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        let _ = frame;
+        Ok(None)
+    }
+}
 
 /// Represents a reader for decoded audio data
 pub struct AudioReader {
     pub format: AudioFormat,
+    /// Total length in sample frames (samples per channel), 0 if unknown
     pub total_samples: u64,
-    buffer: Vec<f32>, // Normalized float samples
-    position: usize,
+    buffer: Vec<f32>, // Normalized float samples of the current packet
+    buffer_offset: usize,
+    samples_read: u64,
+    /// Encoder delay still to be dropped, in samples
+    skip: u64,
+    /// Samples left before the encoder padding starts, when known
+    remaining: Option<u64>,
+    /// Encoder delay in frames, and the frames of audio after it when known
+    delay: u64,
+    frames: Option<u64>,
+    source: Option<Box<dyn SampleSource>>,
 }
 
 impl AudioReader {
@@ -15,27 +41,167 @@ impl AudioReader {
             format,
             total_samples,
             buffer: Vec::new(),
-            position: 0,
+            buffer_offset: 0,
+            samples_read: 0,
+            skip: 0,
+            remaining: None,
+            delay: 0,
+            frames: None,
+            source: None,
+        }
+    }
+
+    /// Create a reader that pulls packets from the given source on demand
+    pub fn with_source(format: AudioFormat, total_samples: u64, source: Box<dyn SampleSource>) -> Self {
+        Self {
+            source: Some(source),
+            ..Self::new(format, total_samples)
         }
     }
 
-    /// Read the next chunk of samples into the provided buffer
+    /// Drop encoder delay and padding so the reader yields only the original audio
+    pub fn trim(&mut self, trim: EncoderTrim) {
+        let channels = self.format.channels.max(1) as u64;
+        let frames = trim.frames.or_else(|| {
+            (self.total_samples > 0).then(|| self.total_samples.saturating_sub(trim.delay + trim.padding))
+        });
+
+        self.skip = trim.delay * channels;
+        self.remaining = frames.map(|frames| frames * channels);
+        self.delay = trim.delay;
+        self.frames = frames;
+        if let Some(frames) = frames {
+            self.total_samples = frames;
+            if self.format.sample_rate > 0 {
+                self.format.duration = Some(Duration::from_secs_f64(frames as f64 / self.format.sample_rate as f64));
+            }
+        }
+    }
+
+    /// Limit a fresh reader to the frames from `start` up to `end`, or the end of the audio,
+    /// so it plays as a track of its own
+    pub fn restrict(&mut self, start: u64, end: Option<u64>) -> Result<(), Box<dyn Error>> {
+        let channels = self.format.channels.max(1) as u64;
+        let length = self.frames.or_else(|| (self.total_samples > 0).then_some(self.total_samples));
+        let end = match (end, length) {
+            (Some(end), Some(length)) => Some(end.min(length)),
+            (end, length) => end.or(length),
+        };
+        if end.is_some_and(|end| start > end) {
+            return Err(format!("Track starts at frame {}, past the end of the audio", start).into());
+        }
+
+        // Decoding from the top still has to get through everything before the start
+        self.skip += start * channels;
+        self.delay += start;
+        self.frames = end.map(|end| end - start);
+        self.remaining = self.frames.map(|frames| frames * channels);
+        self.total_samples = self.frames.unwrap_or(0);
+        let skipped = Duration::from_secs_f64(start as f64 / self.format.sample_rate.max(1) as f64);
+        self.format.duration = match self.frames {
+            Some(frames) if self.format.sample_rate > 0 => Some(Duration::from_secs_f64(frames as f64 / self.format.sample_rate as f64)),
+            _ => self.format.duration.map(|duration| duration.saturating_sub(skipped)),
+        };
+
+        // Jump straight to the start where the source can seek
+        match start {
+            0 => Ok(()),
+            _ => self.seek(0),
+        }
+    }
+
+    /// Read the next chunk of interleaved samples into the provided buffer.
+    /// Returns the number of samples written; 0 signals the end of the stream.
     pub fn read(&mut self, buffer: &mut [f32]) -> Result<usize, Box<dyn Error>> {
-        // TODO: Implement actual reading from the decoded audio data
-        Ok(0)
+        let mut written = 0;
+
+        while written < buffer.len() && self.remaining != Some(0) {
+            if self.buffer_offset >= self.buffer.len() && !self.fill_buffer()? {
+                break;
+            }
+
+            if self.skip > 0 {
+                let dropped = (self.buffer.len() - self.buffer_offset).min(self.skip as usize);
+                self.buffer_offset += dropped;
+                self.skip -= dropped as u64;
+                continue;
+            }
+
+            let pending = &self.buffer[self.buffer_offset..];
+            let mut count = pending.len().min(buffer.len() - written);
+            if let Some(remaining) = self.remaining.as_mut() {
+                count = count.min(*remaining as usize);
+                *remaining -= count as u64;
+            }
+            buffer[written..written + count].copy_from_slice(&pending[..count]);
+            self.buffer_offset += count;
+            written += count;
+        }
+
+        self.samples_read += written as u64;
+        Ok(written)
     }
 
-    /// Seek to a specific sample position
+    /// Replace the internal buffer with the next decoded packet
+    fn fill_buffer(&mut self) -> Result<bool, Box<dyn Error>> {
+        let source = match self.source.as_mut() {
+            Some(source) => source,
+            None => return Ok(false),
+        };
+
+        self.buffer.clear();
+        self.buffer_offset = 0;
+        // Packets may legitimately decode to zero samples, keep pulling
+        while self.buffer.is_empty() {
+            if !source.next_packet(&mut self.buffer)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Seek to a specific sample frame; the next read starts exactly there
     pub fn seek(&mut self, sample_pos: u64) -> Result<(), Box<dyn Error>> {
-        if sample_pos >= self.total_samples {
+        if self.total_samples > 0 && sample_pos > self.total_samples {
             return Err("Seek position out of bounds".into());
         }
-        // TODO: Implement actual seeking in the decoded audio data
+        let channels = self.format.channels.max(1) as u64;
+        let position = self.position();
+        // Nothing is left to decode at the very end, whatever the source supports
+        if self.total_samples > 0 && sample_pos == self.total_samples {
+            self.buffer.clear();
+            self.buffer_offset = 0;
+            self.skip = 0;
+            self.remaining = Some(0);
+            self.samples_read = sample_pos * channels;
+            return Ok(());
+        }
+
+        let target = sample_pos + self.delay;
+        let landed = match self.source.as_mut() {
+            Some(source) => source.seek(target)?,
+            None => None,
+        };
+        match landed {
+            // The source may land a little early; decode up to the exact frame
+            Some(landed) if landed <= target => {
+                self.buffer.clear();
+                self.buffer_offset = 0;
+                self.skip = (target - landed) * channels;
+            }
+            Some(landed) => return Err(format!("Seek to frame {} landed at {}", target, landed).into()),
+            // A forward-only source can still skip ahead by decoding
+            None if sample_pos >= position => self.skip += (sample_pos - position) * channels,
+            None => return Err("Source cannot seek backwards".into()),
+        }
+
+        self.remaining = self.frames.map(|frames| frames.saturating_sub(sample_pos) * channels);
+        self.samples_read = sample_pos * channels;
         Ok(())
     }
 
-    /// Get current position in samples
+    /// Get current position in sample frames
     pub fn position(&self) -> u64 {
-        self.position as u64
+        self.samples_read / self.format.channels.max(1) as u64
     }
 }
@@ -1,64 +1,87 @@
 use std::path::Path;
 use std::error::Error;
 use crate::audio::AudioFormat;
-use crate::audio::formats::{AudioDecoder, AudioReader};
-use super::{flac, mp3, ogg, wav};
+use crate::audio::formats::{detect_source_format, AudioDecoder, AudioReader, FileFormat, Mp4Codec, OggCodec};
+use crate::media::MediaSource;
+use super::{aac, aiff, alac, flac, mp3, ogg, opus, wav};
 
 /// Enum to handle different decoder types
 #[derive(Default)]
 pub enum DecoderType {
     #[default]
     None,
+    Aac(aac::AacDecoder),
+    Aiff(aiff::AiffDecoder),
+    Alac(alac::AlacDecoder),
     Flac(flac::FlacDecoder),
     Mp3(mp3::Mp3Decoder),
     Ogg(ogg::OggDecoder),
+    Opus(opus::OpusDecoder),
     Wav(wav::WavDecoder),
 }
 
 impl DecoderType {
+    /// Pick a decoder from the file's contents, falling back to its extension
     pub fn for_path(path: &Path) -> Self {
-        match path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_lowercase())
-            .as_deref()
-        {
-            Some("flac") => Self::Flac(flac::FlacDecoder::new()),
-            Some("mp3") => Self::Mp3(mp3::Mp3Decoder::new()),
-            Some("ogg") => Self::Ogg(ogg::OggDecoder::new()),
-            Some("wav") => Self::Wav(wav::WavDecoder::new()),
+        Self::for_source(&MediaSource::from_path(path))
+    }
+
+    /// Pick a decoder from the source's contents, falling back to its name's extension
+    pub fn for_source(source: &MediaSource) -> Self {
+        match detect_source_format(source) {
+            Some(FileFormat::Aiff) => Self::Aiff(aiff::AiffDecoder::new()),
+            Some(FileFormat::Flac) => Self::Flac(flac::FlacDecoder::new()),
+            Some(FileFormat::Mp3) => Self::Mp3(mp3::Mp3Decoder::new()),
+            Some(FileFormat::Mp4(Mp4Codec::Alac)) => Self::Alac(alac::AlacDecoder::new()),
+            Some(FileFormat::Mp4(codec)) if aac::AacDecoder::supports_codec(codec) => Self::Aac(aac::AacDecoder::new()),
+            Some(FileFormat::Ogg(OggCodec::Opus)) => Self::Opus(opus::OpusDecoder::new()),
+            Some(FileFormat::Ogg(codec)) if ogg::OggDecoder::supports_codec(codec) => Self::Ogg(ogg::OggDecoder::new()),
+            Some(FileFormat::Wav) => Self::Wav(wav::WavDecoder::new()),
             _ => Self::None,
         }
     }
 }
 
 impl AudioDecoder for DecoderType {
-    fn can_decode(&self, path: &Path) -> bool {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
         match self {
             Self::None => false,
-            Self::Flac(decoder) => decoder.can_decode(path),
-            Self::Mp3(decoder) => decoder.can_decode(path),
-            Self::Ogg(decoder) => decoder.can_decode(path),
-            Self::Wav(decoder) => decoder.can_decode(path),
+            Self::Aac(decoder) => decoder.can_decode_source(source),
+            Self::Aiff(decoder) => decoder.can_decode_source(source),
+            Self::Alac(decoder) => decoder.can_decode_source(source),
+            Self::Flac(decoder) => decoder.can_decode_source(source),
+            Self::Mp3(decoder) => decoder.can_decode_source(source),
+            Self::Ogg(decoder) => decoder.can_decode_source(source),
+            Self::Opus(decoder) => decoder.can_decode_source(source),
+            Self::Wav(decoder) => decoder.can_decode_source(source),
         }
     }
 
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
         match self {
             Self::None => Err("No decoder available".into()),
-            Self::Flac(decoder) => decoder.probe_format(path),
-            Self::Mp3(decoder) => decoder.probe_format(path),
-            Self::Ogg(decoder) => decoder.probe_format(path),
-            Self::Wav(decoder) => decoder.probe_format(path),
+            Self::Aac(decoder) => decoder.probe_source(source),
+            Self::Aiff(decoder) => decoder.probe_source(source),
+            Self::Alac(decoder) => decoder.probe_source(source),
+            Self::Flac(decoder) => decoder.probe_source(source),
+            Self::Mp3(decoder) => decoder.probe_source(source),
+            Self::Ogg(decoder) => decoder.probe_source(source),
+            Self::Opus(decoder) => decoder.probe_source(source),
+            Self::Wav(decoder) => decoder.probe_source(source),
         }
     }
 
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
         match self {
             Self::None => Err("No decoder available".into()),
-            Self::Flac(decoder) => decoder.decode(path),
-            Self::Mp3(decoder) => decoder.decode(path),
-            Self::Ogg(decoder) => decoder.decode(path),
-            Self::Wav(decoder) => decoder.decode(path),
+            Self::Aac(decoder) => decoder.decode_source(source),
+            Self::Aiff(decoder) => decoder.decode_source(source),
+            Self::Alac(decoder) => decoder.decode_source(source),
+            Self::Flac(decoder) => decoder.decode_source(source),
+            Self::Mp3(decoder) => decoder.decode_source(source),
+            Self::Ogg(decoder) => decoder.decode_source(source),
+            Self::Opus(decoder) => decoder.decode_source(source),
+            Self::Wav(decoder) => decoder.decode_source(source),
         }
     }
 }
@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+use crate::media::MediaSource;
+use super::mp3::parse_frame_header;
+use super::mp4;
+
+/// Bytes read from the start of a file for sniffing
+const SNIFF_LEN: usize = 4096;
+
+/// Sources already reported as mislabelled, so each is warned about once
+static MISLABELLED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Codec carried inside an Ogg container, identified by its first packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OggCodec {
+    Vorbis,
+    Opus,
+    Flac,
+    Speex,
+    Unknown,
+}
+
+/// Codec carried inside an MP4/M4A container, identified by its sample entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4Codec {
+    Aac,
+    Alac,
+    Unknown,
+}
+
+/// Audio file format as identified by content or extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Aiff,
+    Flac,
+    Mp3,
+    Mp4(Mp4Codec),
+    Ogg(OggCodec),
+    Wav,
+}
+
+/// Identify a format from the leading bytes of a file
+pub fn sniff_bytes(bytes: &[u8]) -> Option<FileFormat> {
+    if bytes.starts_with(b"fLaC") {
+        return Some(FileFormat::Flac);
+    }
+    if bytes.starts_with(b"OggS") {
+        return Some(FileFormat::Ogg(sniff_ogg_codec(bytes)));
+    }
+    if bytes.len() >= 12 && matches!(&bytes[0..4], b"RIFF" | b"RF64" | b"BW64") && &bytes[8..12] == b"WAVE" {
+        return Some(FileFormat::Wav);
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"FORM" && matches!(&bytes[8..12], b"AIFF" | b"AIFC") {
+        return Some(FileFormat::Aiff);
+    }
+    if bytes.get(4..8) == Some(b"ftyp") {
+        // The codec lives deep inside `moov`, which `sniff_file` resolves when it can
+        return Some(FileFormat::Mp4(Mp4Codec::Unknown));
+    }
+    if let Some(tag_len) = id3v2_len(bytes) {
+        // FLAC files occasionally carry an ID3v2 prefix; anything else tagged this way is MPEG audio
+        let after_tag = bytes.get(tag_len..).unwrap_or_default();
+        if after_tag.starts_with(b"fLaC") {
+            return Some(FileFormat::Flac);
+        }
+        return Some(FileFormat::Mp3);
+    }
+    if is_frame_sync(bytes) {
+        return Some(FileFormat::Mp3);
+    }
+    None
+}
+
+/// Total length of a leading ID3v2 tag, header included
+fn id3v2_len(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(b"ID3") || bytes.len() < 10 {
+        return None;
+    }
+    let size = bytes[6..10].iter().fold(0usize, |acc, &b| (acc << 7) | (b & 0x7F) as usize);
+    Some(10 + size)
+}
+
+/// Identify the codec from the first packet of the first Ogg page
+fn sniff_ogg_codec(bytes: &[u8]) -> OggCodec {
+    let segments = match bytes.get(26) {
+        Some(&count) => count as usize,
+        None => return OggCodec::Unknown,
+    };
+    let packet = bytes.get(27 + segments..).unwrap_or_default();
+
+    if packet.starts_with(b"\x01vorbis") {
+        OggCodec::Vorbis
+    } else if packet.starts_with(b"OpusHead") {
+        OggCodec::Opus
+    } else if packet.starts_with(b"\x7FFLAC") {
+        OggCodec::Flac
+    } else if packet.starts_with(b"Speex   ") {
+        OggCodec::Speex
+    } else {
+        OggCodec::Unknown
+    }
+}
+
+/// A valid MPEG frame header at the start, confirmed by the next one when available
+fn is_frame_sync(bytes: &[u8]) -> bool {
+    let read_header = |offset: usize| {
+        bytes.get(offset..offset + 4)
+            .and_then(|b| parse_frame_header(u32::from_be_bytes([b[0], b[1], b[2], b[3]])))
+    };
+
+    match read_header(0) {
+        Some(header) => match bytes.get(header.frame_len()..) {
+            Some(rest) if rest.len() >= 4 => read_header(header.frame_len())
+                .map(|next| header.is_compatible(&next))
+                .unwrap_or(false),
+            _ => true,
+        },
+        None => false,
+    }
+}
+
+/// Read the leading bytes of a file and identify its format
+pub fn sniff_file(path: &Path) -> Option<FileFormat> {
+    sniff_source(&MediaSource::from_path(path))
+}
+
+/// Read the leading bytes of a source and identify its format
+pub fn sniff_source(source: &MediaSource) -> Option<FileFormat> {
+    let mut file = source.open().ok()?;
+    let mut bytes = Vec::with_capacity(SNIFF_LEN);
+    file.by_ref().take(SNIFF_LEN as u64).read_to_end(&mut bytes).ok()?;
+
+    // Large ID3v2 tags (embedded artwork) can push the stream start past the sniff window
+    if let Some(tag_len) = id3v2_len(&bytes).filter(|&len| len + 4 > bytes.len()) {
+        let mut after_tag = [0u8; 4];
+        file.seek(SeekFrom::Start(tag_len as u64)).ok()?;
+        let found_flac = file.read_exact(&mut after_tag).is_ok() && &after_tag == b"fLaC";
+        return Some(if found_flac { FileFormat::Flac } else { FileFormat::Mp3 });
+    }
+
+    match sniff_bytes(&bytes)? {
+        FileFormat::Mp4(_) => {
+            let codec = match mp4::audio_sample_entry(&mut file) {
+                Ok(Some(kind)) if &kind == b"mp4a" => Mp4Codec::Aac,
+                Ok(Some(kind)) if &kind == b"alac" => Mp4Codec::Alac,
+                _ => Mp4Codec::Unknown,
+            };
+            Some(FileFormat::Mp4(codec))
+        }
+        format => Some(format),
+    }
+}
+
+/// Map a file extension to the format it conventionally holds
+pub fn format_from_extension(path: &Path) -> Option<FileFormat> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "aif" | "aiff" | "aifc" => Some(FileFormat::Aiff),
+        "flac" => Some(FileFormat::Flac),
+        "mp3" => Some(FileFormat::Mp3),
+        "m4a" | "m4b" | "mp4" => Some(FileFormat::Mp4(Mp4Codec::Unknown)),
+        "ogg" | "oga" => Some(FileFormat::Ogg(OggCodec::Vorbis)),
+        "opus" => Some(FileFormat::Ogg(OggCodec::Opus)),
+        "wav" | "wave" => Some(FileFormat::Wav),
+        _ => None,
+    }
+}
+
+/// Detect a file's format, trusting its contents over its extension.
+/// The extension is only used when the contents are unreadable or unrecognized.
+pub fn detect_format(path: &Path) -> Option<FileFormat> {
+    detect_source_format(&MediaSource::from_path(path))
+}
+
+/// Detect the format of a source as `detect_format` does a file's
+pub fn detect_source_format(source: &MediaSource) -> Option<FileFormat> {
+    let by_extension = format_from_extension(source.file_name());
+    let Some(by_content) = sniff_source(source) else {
+        return by_extension;
+    };
+
+    let agrees = match (by_extension, by_content) {
+        // `.ogg`/`.oga` are generic Ogg extensions; only Opus is considered mislabelled there
+        (Some(FileFormat::Ogg(OggCodec::Vorbis)), FileFormat::Ogg(codec)) => codec != OggCodec::Opus,
+        // `.m4a` says nothing about whether the track is AAC or ALAC
+        (Some(FileFormat::Mp4(Mp4Codec::Unknown)), FileFormat::Mp4(_)) => true,
+        (Some(expected), actual) => expected == actual,
+        (None, _) => true,
+    };
+    if !agrees && first_mismatch(&source.to_string()) {
+        log::warn!(
+            "{} looks like {:?} despite its extension; using its contents",
+            source,
+            by_content
+        );
+    }
+    Some(by_content)
+}
+
+/// Whether `source` has not been reported as mislabelled yet. Probing, playing and tagging a
+/// file all detect it, and only the first of them should say so
+pub(crate) fn first_mismatch(source: &str) -> bool {
+    MISLABELLED.lock().map_or(true, |mut reported| reported.insert(source.to_string()))
+}
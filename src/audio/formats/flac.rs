@@ -1,6 +1,7 @@
 use std::error::Error;
-use std::path::Path;
-use super::{AudioDecoder, AudioFormat, AudioReader};
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat};
+use super::symphonia_source::SymphoniaSource;
 
 pub struct FlacDecoder {
     // TODO: Add fields for FLAC-specific decoding state
@@ -11,7 +12,7 @@ impl FlacDecoder {
         Self {}
     }
 
-    fn read_flac_header(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
+    fn read_flac_header(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
         // TODO: Implement actual FLAC header reading
         // This would typically:
         // 1. Open the file
@@ -24,42 +25,29 @@ impl FlacDecoder {
             channels: 2,
             sample_rate: 44100,
             bits_per_sample: 16,
+            ..Default::default()
         })
     }
 }
 
 impl AudioDecoder for FlacDecoder {
-    fn can_decode(&self, path: &Path) -> bool {
-        // Check if file has .flac extension
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("flac"))
-            .unwrap_or(false)
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Flac))
     }
 
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        if !self.can_decode(path) {
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
             return Err("Not a FLAC file".into());
         }
-        self.read_flac_header(path)
+        self.read_flac_header(source)
     }
 
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
-        if !self.can_decode(path) {
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
             return Err("Not a FLAC file".into());
         }
 
-        // Read format information
-        let format = self.read_flac_header(path)?;
-
-        // TODO: Implement actual FLAC decoding
-        // This would:
-        // 1. Initialize FLAC decoder
-        // 2. Read and validate stream info
-        // 3. Prepare for frame decoding
-        
-        // Create reader with placeholder total samples
-        Ok(AudioReader::new(format, 0))
+        SymphoniaSource::open(source)
     }
 }
 
@@ -0,0 +1,44 @@
+use std::io::BufReader;
+use crate::media::MediaSource;
+use super::mp4;
+
+/// Priming and padding an encoder wrapped around the real audio, in sample frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncoderTrim {
+    /// Frames to drop from the start of the decoded stream
+    pub delay: u64,
+    /// Frames to drop from the end
+    pub padding: u64,
+    /// Length of the original audio, when the tag records it
+    pub frames: Option<u64>,
+}
+
+/// Parse an iTunes `iTunSMPB` value: hex words for a reserved field, the delay,
+/// the padding and the original length, followed by fields we don't need
+pub fn parse_itunsmpb(value: &str) -> Option<EncoderTrim> {
+    let fields = value
+        .split_whitespace()
+        .map(|field| u64::from_str_radix(field, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let trim = EncoderTrim {
+        delay: *fields.get(1)?,
+        padding: *fields.get(2)?,
+        frames: fields.get(3).copied().filter(|&frames| frames > 0),
+    };
+    (trim != EncoderTrim::default()).then_some(trim)
+}
+
+/// Encoder trim from the `iTunSMPB` item in an MP4 file's `ilst`
+pub fn mp4_trim(source: &MediaSource) -> Option<EncoderTrim> {
+    let mut reader = BufReader::new(source.open().ok()?);
+    let value = mp4::freeform_item(&mut reader, "iTunSMPB").ok()??;
+    parse_itunsmpb(&value)
+}
+
+/// Encoder trim from the `iTunSMPB` comment iTunes writes into an MP3's ID3v2 tag
+pub fn id3_trim(source: &MediaSource) -> Option<EncoderTrim> {
+    let tag = id3::Tag::read_from2(source.open().ok()?).ok()?;
+    let comment = tag.comments().find(|comment| comment.description == "iTunSMPB")?;
+    parse_itunsmpb(&comment.text)
+}
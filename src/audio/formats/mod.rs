@@ -1,30 +1,81 @@
 use std::error::Error;
 use std::path::Path;
+use std::time::Duration;
 use crate::audio::AudioFormat;
+use crate::media::MediaSource;
+use crate::metadata::{cuesheet, MetadataManager};
 
 mod audio_reader;
 mod decoder_factory;
+mod symphonia_source;
+pub mod detect;
+pub mod gapless;
 #[cfg(test)]
 mod tests;
 
 // Re-export format-specific modules
+pub mod aac;
+pub mod aiff;
+pub mod alac;
 pub mod flac;
 pub mod mp3;
+pub mod mp4;
 pub mod ogg;
+pub mod opus;
 pub mod wav;
 
 // Re-export key types
-pub use audio_reader::AudioReader;
+pub use audio_reader::{AudioReader, SampleSource};
 pub use decoder_factory::{DecoderType, get_decoder};
+pub use detect::{detect_format, detect_source_format, FileFormat, Mp4Codec, OggCodec};
+pub use gapless::EncoderTrim;
 
-/// Trait for audio format decoders
+/// Decode whatever format the source holds
+pub fn open_source(source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+    DecoderType::for_source(source).decode_source(source)
+}
+
+/// Open `path` for playback, which for a CUE track means the part of the album file that
+/// its sheet gives it
+pub fn open_track(path: &Path) -> Result<AudioReader, Box<dyn Error>> {
+    let (file, number) = match cuesheet::split_track_path(path) {
+        Some(track) => track,
+        None => return open_source(&MediaSource::from_path(path)),
+    };
+    let sheet = MetadataManager::with_format_parsers().cue_sheet(&file)
+        .ok_or_else(|| format!("No CUE sheet for {}", file.display()))?;
+    let (start, end) = sheet.span(number).ok_or_else(|| format!("No track {} in the CUE sheet", number))?;
+
+    let mut reader = open_source(&MediaSource::from_path(&file))?;
+    let rate = reader.format.sample_rate as u128;
+    let frame = |at: Duration| ((at.as_nanos() * rate + 500_000_000) / 1_000_000_000) as u64;
+    reader.restrict(frame(start), end.map(frame))?;
+    Ok(reader)
+}
+
+/// Trait for audio format decoders, which read from any `MediaSource`
 pub trait AudioDecoder {
-    /// Check if the given file is in this format
-    fn can_decode(&self, path: &Path) -> bool;
-    
+    /// Check if the source is in this format, judged by its contents first
+    fn can_decode_source(&self, source: &MediaSource) -> bool;
+
+    /// Get the audio format details without fully loading the source
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>>;
+
+    /// Read audio data from the source
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>>;
+
+    /// Check if the given file is in this format, judged by its contents first
+    fn can_decode(&self, path: &Path) -> bool {
+        self.can_decode_source(&MediaSource::from_path(path))
+    }
+
     /// Get the audio format details without fully loading the file
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>>;
-    
+    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
+        self.probe_source(&MediaSource::from_path(path))
+    }
+
     /// Read audio data from the file
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>>;
+    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
+        self.decode_source(&MediaSource::from_path(path))
+    }
 }
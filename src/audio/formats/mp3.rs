@@ -1,105 +0,0 @@
-use std::error::Error;
-use std::path::Path;
-use super::{AudioDecoder, AudioFormat, AudioReader};
-
-pub struct Mp3Decoder {
-    // TODO: Add fields for MP3-specific decoding state
-}
-
-impl Mp3Decoder {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    fn read_mp3_header(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        // TODO: Implement actual MP3 header reading
-        // This would typically:
-        // 1. Open the file
-        // 2. Find and validate MP3 frame sync
-        // 3. Parse frame header
-        // 4. Extract format information
-        
-        // Placeholder return with typical MP3 format
-        Ok(AudioFormat {
-            channels: 2,
-            sample_rate: 44100,
-            bits_per_sample: 16, // MP3 typically decodes to 16-bit PCM
-        })
-    }
-
-    fn parse_frame_header(&self, header: u32) -> Option<(u32, u16)> {
-        // TODO: Implement MP3 frame header parsing
-        // This would:
-        // 1. Validate sync bits
-        // 2. Extract sample rate from header
-        // 3. Extract channel mode
-        // Returns (sample_rate, channels)
-        None
-    }
-}
-
-impl AudioDecoder for Mp3Decoder {
-    fn can_decode(&self, path: &Path) -> bool {
-        // Check if file has .mp3 extension
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("mp3"))
-            .unwrap_or(false)
-    }
-
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        if !self.can_decode(path) {
-            return Err("Not an MP3 file".into());
-        }
-        self.read_mp3_header(path)
-    }
-
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
-        if !self.can_decode(path) {
-            return Err("Not an MP3 file".into());
-        }
-
-        // Read format information
-        let format = self.read_mp3_header(path)?;
-
-        // TODO: Implement actual MP3 decoding
-        // This would:
-        // 1. Initialize MP3 decoder
-        // 2. Set up frame parsing
-        // 3. Prepare for sample decoding
-        
-        // Create reader with placeholder total samples
-        Ok(AudioReader::new(format, 0))
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
-
-    fn test_path(filename: &str) -> PathBuf {
-        PathBuf::from(filename)
-    }
-
-    #[test]
-    fn test_can_decode() {
-        let decoder = Mp3Decoder::new();
-        assert!(decoder.can_decode(test_path("test.mp3").as_path()));
-        assert!(decoder.can_decode(test_path("test.MP3").as_path()));
-        assert!(!decoder.can_decode(test_path("test.flac").as_path()));
-        assert!(!decoder.can_decode(test_path("test").as_path()));
-    }
-
-    #[test]
-    fn test_probe_invalid_extension() {
-        let decoder = Mp3Decoder::new();
-        assert!(decoder.probe_format(test_path("test.flac").as_path()).is_err());
-    }
-
-    #[test]
-    fn test_decode_invalid_extension() {
-        let mut decoder = Mp3Decoder::new();
-        assert!(decoder.decode(test_path("test.flac").as_path()).is_err());
-    }
-}
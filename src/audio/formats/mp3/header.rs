@@ -0,0 +1,131 @@
+/// MPEG audio version from the frame header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegVersion {
+    Mpeg1,
+    Mpeg2,
+    Mpeg25,
+}
+
+/// MPEG audio layer from the frame header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Layer1,
+    Layer2,
+    Layer3,
+}
+
+/// Decoded 32-bit MPEG audio frame header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: MpegVersion,
+    pub layer: Layer,
+    pub crc_protected: bool,
+    /// Bitrate in kbps
+    pub bit_rate: u32,
+    pub sample_rate: u32,
+    pub padding: bool,
+    pub channels: u16,
+}
+
+const BITRATES_V1_L1: [u32; 15] = [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448];
+const BITRATES_V1_L2: [u32; 15] = [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384];
+const BITRATES_V1_L3: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+const BITRATES_V2_L1: [u32; 15] = [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256];
+const BITRATES_V2_L23: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// Decode a frame header, rejecting reserved and free-format values
+pub fn parse_frame_header(header: u32) -> Option<FrameHeader> {
+    if header & 0xFFE0_0000 != 0xFFE0_0000 {
+        return None;
+    }
+
+    let version = match (header >> 19) & 0b11 {
+        0b00 => MpegVersion::Mpeg25,
+        0b10 => MpegVersion::Mpeg2,
+        0b11 => MpegVersion::Mpeg1,
+        _ => return None,
+    };
+    let layer = match (header >> 17) & 0b11 {
+        0b01 => Layer::Layer3,
+        0b10 => Layer::Layer2,
+        0b11 => Layer::Layer1,
+        _ => return None,
+    };
+
+    let bitrate_index = ((header >> 12) & 0xF) as usize;
+    let table = match (version, layer) {
+        (MpegVersion::Mpeg1, Layer::Layer1) => &BITRATES_V1_L1,
+        (MpegVersion::Mpeg1, Layer::Layer2) => &BITRATES_V1_L2,
+        (MpegVersion::Mpeg1, Layer::Layer3) => &BITRATES_V1_L3,
+        (_, Layer::Layer1) => &BITRATES_V2_L1,
+        _ => &BITRATES_V2_L23,
+    };
+    // Index 0 is free format, which has no computable frame length
+    let bit_rate = *table.get(bitrate_index).filter(|&&rate| rate != 0)?;
+
+    let base_rate = match (header >> 10) & 0b11 {
+        0 => 44100,
+        1 => 48000,
+        2 => 32000,
+        _ => return None,
+    };
+    let sample_rate = match version {
+        MpegVersion::Mpeg1 => base_rate,
+        MpegVersion::Mpeg2 => base_rate / 2,
+        MpegVersion::Mpeg25 => base_rate / 4,
+    };
+
+    // Emphasis value 2 is reserved
+    if header & 0b11 == 0b10 {
+        return None;
+    }
+
+    Some(FrameHeader {
+        version,
+        layer,
+        crc_protected: (header >> 16) & 1 == 0,
+        bit_rate,
+        sample_rate,
+        padding: (header >> 9) & 1 == 1,
+        channels: if (header >> 6) & 0b11 == 0b11 { 1 } else { 2 },
+    })
+}
+
+impl FrameHeader {
+    /// PCM samples per channel decoded from one frame
+    pub fn samples_per_frame(&self) -> u32 {
+        match (self.layer, self.version) {
+            (Layer::Layer1, _) => 384,
+            (Layer::Layer2, _) | (Layer::Layer3, MpegVersion::Mpeg1) => 1152,
+            (Layer::Layer3, _) => 576,
+        }
+    }
+
+    /// Total frame length in bytes, header included
+    pub fn frame_len(&self) -> usize {
+        let padding = self.padding as u32;
+        let len = match self.layer {
+            Layer::Layer1 => (12 * self.bit_rate * 1000 / self.sample_rate + padding) * 4,
+            _ => self.samples_per_frame() / 8 * self.bit_rate * 1000 / self.sample_rate + padding,
+        };
+        len as usize
+    }
+
+    /// Offset from the frame start to the Xing/Info tag, past the side information
+    pub fn xing_offset(&self) -> usize {
+        let side_info = match (self.version, self.channels) {
+            (MpegVersion::Mpeg1, 1) => 17,
+            (MpegVersion::Mpeg1, _) => 32,
+            (_, 1) => 9,
+            _ => 17,
+        };
+        4 + if self.crc_protected { 2 } else { 0 } + side_info
+    }
+
+    /// Whether another header plausibly belongs to the same stream
+    pub fn is_compatible(&self, other: &FrameHeader) -> bool {
+        self.version == other.version
+            && self.layer == other.layer
+            && self.sample_rate == other.sample_rate
+    }
+}
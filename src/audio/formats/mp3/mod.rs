@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat};
+use super::gapless::{self, EncoderTrim};
+use super::symphonia_source::SymphoniaSource;
+
+mod header;
+mod scan;
+mod vbr;
+#[cfg(test)]
+mod tests;
+
+pub use header::{parse_frame_header, FrameHeader, Layer, MpegVersion};
+pub use scan::Mp3Info;
+pub use vbr::{SeekToc, VbrInfo};
+
+pub struct Mp3Decoder {}
+
+impl Mp3Decoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Scan the frame headers and VBR tags for exact timing information
+    pub fn read_info(&self, path: &Path) -> Result<Mp3Info, Box<dyn Error>> {
+        self.read_source_info(&MediaSource::from_path(path))
+    }
+
+    /// Scan a source as `read_info` does a file
+    pub fn read_source_info(&self, source: &MediaSource) -> Result<Mp3Info, Box<dyn Error>> {
+        let mut reader = BufReader::new(source.open()?);
+        scan::read_mp3_info(&mut reader)
+    }
+
+    /// Encoder trim from an iTunes `iTunSMPB` comment, for files without a LAME tag.
+    /// Symphonia already trims the delay and padding a LAME tag records.
+    fn itunes_trim(info: &Mp3Info, source: &MediaSource) -> Option<EncoderTrim> {
+        let has_lame_trim = info.vbr.as_ref().is_some_and(|vbr| vbr.encoder_delay > 0 || vbr.encoder_padding > 0);
+        if has_lame_trim {
+            None
+        } else {
+            gapless::id3_trim(source)
+        }
+    }
+}
+
+impl AudioDecoder for Mp3Decoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Mp3))
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an MP3 file".into());
+        }
+        let info = self.read_source_info(source)?;
+        let duration = match Self::itunes_trim(&info, source) {
+            Some(trim) => {
+                let frames = trim.frames.unwrap_or(info.total_samples.saturating_sub(trim.delay + trim.padding));
+                Duration::from_secs_f64(frames as f64 / info.header.sample_rate as f64)
+            }
+            None => info.duration,
+        };
+        Ok(AudioFormat {
+            channels: info.header.channels,
+            sample_rate: info.header.sample_rate,
+            bits_per_sample: 16, // MP3 typically decodes to 16-bit PCM
+            duration: Some(duration),
+            bit_rate: Some(info.bit_rate),
+        })
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an MP3 file".into());
+        }
+
+        let mut reader = SymphoniaSource::open(source)?;
+        if let Some(trim) = self.read_source_info(source).ok().and_then(|info| Self::itunes_trim(&info, source)) {
+            reader.trim(trim);
+        }
+        Ok(reader)
+    }
+}
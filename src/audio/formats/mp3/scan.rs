@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+use super::header::{parse_frame_header, FrameHeader};
+use super::vbr::{parse_vbr_header, VbrInfo};
+
+/// How far past the leading tags we search for the first frame
+const MAX_SYNC_SEARCH: u64 = 1 << 20;
+
+/// Stream layout and timing of an MP3 file
+#[derive(Debug, Clone)]
+pub struct Mp3Info {
+    /// Header of the first frame in the stream
+    pub header: FrameHeader,
+    /// Offset of the first frame, which may be a Xing/Info/VBRI tag frame
+    pub first_frame_offset: u64,
+    /// Offset of the first frame carrying audio
+    pub audio_offset: u64,
+    /// Offset just past the last frame, before any trailing tags
+    pub stream_end: u64,
+    pub vbr: Option<VbrInfo>,
+    /// Playable samples per channel, with encoder delay and padding removed
+    pub total_samples: u64,
+    pub duration: Duration,
+    /// Average bitrate in kbps
+    pub bit_rate: u32,
+}
+
+/// Locate the first frame past any ID3v2/APE tags and derive exact timing
+pub fn read_mp3_info<R: Read + Seek>(reader: &mut R) -> Result<Mp3Info, Box<dyn Error>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let start = skip_leading_tags(reader, file_len)?;
+    let stream_end = trailing_tags_start(reader, start, file_len)?;
+
+    let window_len = (stream_end - start).min(MAX_SYNC_SEARCH);
+    let mut window = vec![0u8; window_len as usize];
+    reader.seek(SeekFrom::Start(start))?;
+    reader.read_exact(&mut window)?;
+
+    let (index, header) = find_frame_sync(&window, stream_end - start).ok_or("No MPEG audio frame found")?;
+    let first_frame_offset = start + index as u64;
+    let vbr = parse_vbr_header(&header, &window[index..]);
+
+    let sample_rate = header.sample_rate as u64;
+    let samples_per_frame = header.samples_per_frame() as u64;
+    let stream_len = stream_end - first_frame_offset;
+
+    let (audio_offset, total_samples, bit_rate) = match &vbr {
+        Some(info) => {
+            let audio_offset = first_frame_offset + header.frame_len() as u64;
+            let audio_bytes = info.bytes
+                .map(|bytes| (bytes as u64).saturating_sub(header.frame_len() as u64))
+                .unwrap_or(stream_end.saturating_sub(audio_offset));
+            let frames = info.frames
+                .map(u64::from)
+                .unwrap_or_else(|| audio_bytes / header.frame_len().max(1) as u64);
+
+            let decoded = frames * samples_per_frame;
+            let trim = (info.encoder_delay + info.encoder_padding) as u64;
+            let bit_rate = if decoded > 0 {
+                (audio_bytes * 8 * sample_rate + decoded * 500) / (decoded * 1000)
+            } else {
+                header.bit_rate as u64
+            };
+            (audio_offset, decoded.saturating_sub(trim), bit_rate as u32)
+        }
+        None => {
+            // Plain CBR: the header bitrate holds for every frame
+            let total = stream_len * 8 * sample_rate / (header.bit_rate as u64 * 1000);
+            (first_frame_offset, total, header.bit_rate)
+        }
+    };
+
+    Ok(Mp3Info {
+        header,
+        first_frame_offset,
+        audio_offset,
+        stream_end,
+        vbr,
+        total_samples,
+        duration: Duration::from_secs_f64(total_samples as f64 / sample_rate as f64),
+        bit_rate,
+    })
+}
+
+/// Skip any number of ID3v2 and APEv2 tags at the start of the file
+fn skip_leading_tags<R: Read + Seek>(reader: &mut R, file_len: u64) -> Result<u64, Box<dyn Error>> {
+    let mut offset = 0u64;
+    loop {
+        if offset >= file_len {
+            return Ok(file_len);
+        }
+        let mut tag = [0u8; 32];
+        let available = (file_len - offset).min(32) as usize;
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut tag[..available])?;
+
+        if available >= 10 && &tag[0..3] == b"ID3" {
+            let size = tag[6..10].iter().fold(0u64, |acc, &b| (acc << 7) | (b & 0x7F) as u64);
+            let footer = if tag[5] & 0x10 != 0 { 10 } else { 0 };
+            offset += 10 + size + footer;
+        } else if available == 32 && &tag[0..8] == b"APETAGEX" {
+            // The size field covers the items and footer but not this header
+            offset += 32 + u32::from_le_bytes([tag[12], tag[13], tag[14], tag[15]]) as u64;
+        } else {
+            return Ok(offset);
+        }
+    }
+}
+
+/// Offset where trailing ID3v1 and APEv2 tags begin
+fn trailing_tags_start<R: Read + Seek>(reader: &mut R, start: u64, file_len: u64) -> Result<u64, Box<dyn Error>> {
+    let mut end = file_len;
+
+    if end >= start + 128 {
+        let mut id3v1 = [0u8; 3];
+        reader.seek(SeekFrom::Start(end - 128))?;
+        reader.read_exact(&mut id3v1)?;
+        if &id3v1 == b"TAG" {
+            end -= 128;
+        }
+    }
+
+    if end >= start + 32 {
+        let mut footer = [0u8; 32];
+        reader.seek(SeekFrom::Start(end - 32))?;
+        reader.read_exact(&mut footer)?;
+        if &footer[0..8] == b"APETAGEX" {
+            let size = u32::from_le_bytes([footer[12], footer[13], footer[14], footer[15]]) as u64;
+            let has_header = footer[23] & 0x80 != 0;
+            let tag_len = size + if has_header { 32 } else { 0 };
+            end = end.saturating_sub(tag_len).max(start);
+        }
+    }
+
+    Ok(end)
+}
+
+/// Find the first frame header whose successor confirms the sync
+fn find_frame_sync(window: &[u8], stream_len: u64) -> Option<(usize, FrameHeader)> {
+    (0..window.len().saturating_sub(3)).find_map(|i| {
+        let header = parse_frame_header(read_u32(window, i)?)?;
+        let next = i + header.frame_len();
+
+        let confirmed = match read_u32(window, next) {
+            Some(next_header) => parse_frame_header(next_header)
+                .map(|next| header.is_compatible(&next))
+                .unwrap_or(false),
+            // Nothing to compare against: accept a frame that ends the stream exactly
+            None => next as u64 == stream_len || (next >= window.len() && (window.len() as u64) < stream_len),
+        };
+        confirmed.then_some((i, header))
+    })
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
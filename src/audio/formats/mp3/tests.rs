@@ -0,0 +1,259 @@
+use super::*;
+use std::path::PathBuf;
+
+fn test_path(filename: &str) -> PathBuf {
+    PathBuf::from(filename)
+}
+
+#[test]
+fn test_can_decode() {
+    let decoder = Mp3Decoder::new();
+    assert!(decoder.can_decode(test_path("test.mp3").as_path()));
+    assert!(decoder.can_decode(test_path("test.MP3").as_path()));
+    assert!(!decoder.can_decode(test_path("test.flac").as_path()));
+    assert!(!decoder.can_decode(test_path("test").as_path()));
+}
+
+#[test]
+fn test_probe_invalid_extension() {
+    let decoder = Mp3Decoder::new();
+    assert!(decoder.probe_format(test_path("test.flac").as_path()).is_err());
+}
+
+#[test]
+fn test_decode_invalid_extension() {
+    let mut decoder = Mp3Decoder::new();
+    assert!(decoder.decode(test_path("test.flac").as_path()).is_err());
+}
+
+// MPEG1 Layer III, 128kbps, 48kHz, joint stereo: 384-byte frames without padding
+const CBR_HEADER: u32 = 0xFFFB_9444;
+
+fn frame(header: u32) -> Vec<u8> {
+    let len = parse_frame_header(header).unwrap().frame_len();
+    let mut bytes = header.to_be_bytes().to_vec();
+    bytes.resize(len, 0);
+    bytes
+}
+
+fn write_mp3(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".mp3").tempfile().unwrap();
+    std::io::Write::write_all(&mut file, bytes).unwrap();
+    file
+}
+
+fn read_info(bytes: &[u8]) -> Mp3Info {
+    Mp3Decoder::new().read_info(write_mp3(bytes).path()).unwrap()
+}
+
+fn id3v2_tag(body_len: usize) -> Vec<u8> {
+    let mut tag = b"ID3\x04\x00\x00".to_vec();
+    // Syncsafe size, 7 bits per byte
+    tag.extend([(body_len >> 21) as u8 & 0x7F, (body_len >> 14) as u8 & 0x7F, (body_len >> 7) as u8 & 0x7F, body_len as u8 & 0x7F]);
+    tag.resize(10 + body_len, 0);
+    tag
+}
+
+#[test]
+fn test_parse_mpeg1_layer3_header() {
+    let header = parse_frame_header(0xFFFB_54C0).unwrap();
+    assert_eq!(header.version, MpegVersion::Mpeg1);
+    assert_eq!(header.layer, Layer::Layer3);
+    assert!(!header.crc_protected);
+    assert_eq!(header.bit_rate, 64);
+    assert_eq!(header.sample_rate, 48000);
+    assert_eq!(header.channels, 1);
+    assert_eq!(header.samples_per_frame(), 1152);
+    assert_eq!(header.frame_len(), 192);
+    assert_eq!(header.xing_offset(), 21);
+}
+
+#[test]
+fn test_parse_mpeg2_and_25_headers() {
+    let mpeg2 = parse_frame_header(0xFFF3_8000).unwrap();
+    assert_eq!(mpeg2.version, MpegVersion::Mpeg2);
+    assert_eq!(mpeg2.sample_rate, 22050);
+    assert_eq!(mpeg2.bit_rate, 64);
+    assert_eq!(mpeg2.channels, 2);
+    assert_eq!(mpeg2.samples_per_frame(), 576);
+    assert_eq!(mpeg2.frame_len(), 208);
+    assert_eq!(mpeg2.xing_offset(), 21);
+
+    let mpeg25 = parse_frame_header(0xFFE3_8200).unwrap();
+    assert_eq!(mpeg25.version, MpegVersion::Mpeg25);
+    assert_eq!(mpeg25.sample_rate, 11025);
+    assert!(mpeg25.padding);
+    assert_eq!(mpeg25.frame_len(), 418);
+}
+
+#[test]
+fn test_parse_layer1_and_layer2_headers() {
+    let layer2 = parse_frame_header(0xFFFD_9000).unwrap();
+    assert_eq!(layer2.layer, Layer::Layer2);
+    assert_eq!(layer2.bit_rate, 160);
+    assert_eq!(layer2.frame_len(), 522);
+
+    let layer1 = parse_frame_header(0xFFFF_9000).unwrap();
+    assert_eq!(layer1.layer, Layer::Layer1);
+    assert_eq!(layer1.bit_rate, 288);
+    assert_eq!(layer1.samples_per_frame(), 384);
+    assert_eq!(layer1.frame_len(), 312);
+}
+
+#[test]
+fn test_reject_invalid_headers() {
+    assert!(parse_frame_header(0x7FFB_54C0).is_none(), "missing sync");
+    assert!(parse_frame_header(0xFFEB_54C0).is_none(), "reserved version");
+    assert!(parse_frame_header(0xFFF9_54C0).is_none(), "reserved layer");
+    assert!(parse_frame_header(0xFFFB_04C0).is_none(), "free format bitrate");
+    assert!(parse_frame_header(0xFFFB_F4C0).is_none(), "bad bitrate");
+    assert!(parse_frame_header(0xFFFB_5CC0).is_none(), "reserved sample rate");
+    assert!(parse_frame_header(0xFFFB_54C2).is_none(), "reserved emphasis");
+}
+
+#[test]
+fn test_fixture_info() {
+    let info = Mp3Decoder::new().read_info(Path::new("test/testaudio-short.mp3")).unwrap();
+    assert_eq!(info.first_frame_offset, 0x50);
+    assert_eq!(info.audio_offset, 0x50 + 192);
+
+    let vbr = info.vbr.as_ref().unwrap();
+    assert_eq!(vbr.frames, Some(43));
+    assert_eq!(vbr.encoder_delay, 576);
+    assert_eq!(vbr.encoder_padding, 960);
+    assert!(matches!(&vbr.toc, Some(SeekToc::Xing(toc)) if toc.len() == 100));
+
+    assert_eq!(info.total_samples, 48000);
+    assert_eq!(info.duration, std::time::Duration::from_secs(1));
+    assert_eq!(info.bit_rate, 64);
+}
+
+#[test]
+fn test_probe_fixture_reports_exact_timing() {
+    let format = Mp3Decoder::new().probe_format(Path::new("test/testaudio-short.mp3")).unwrap();
+    assert_eq!(format.channels, 1);
+    assert_eq!(format.sample_rate, 48000);
+    assert_eq!(format.duration, Some(std::time::Duration::from_secs(1)));
+    assert_eq!(format.bit_rate, Some(64));
+}
+
+#[test]
+fn test_decoded_length_matches_info() {
+    let path = Path::new("test/testaudio-short.mp3");
+    let info = Mp3Decoder::new().read_info(path).unwrap();
+    let mut reader = Mp3Decoder::new().decode(path).unwrap();
+
+    let mut chunk = [0.0f32; 4096];
+    let mut decoded = 0;
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        decoded += read;
+    }
+    assert_eq!(decoded as u64, info.total_samples);
+}
+
+#[test]
+fn test_cbr_stream_between_tags() {
+    let mut bytes = id3v2_tag(100);
+    for _ in 0..10 {
+        bytes.extend(frame(CBR_HEADER));
+    }
+
+    let mut ape = b"APETAGEX".to_vec();
+    ape.extend(2000u32.to_le_bytes());
+    ape.extend(32u32.to_le_bytes()); // size: footer only
+    ape.extend(0u32.to_le_bytes());
+    ape.extend(0u32.to_le_bytes());
+    ape.resize(32, 0);
+    bytes.extend(ape);
+
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    bytes.extend(id3v1);
+
+    let info = read_info(&bytes);
+    assert!(info.vbr.is_none());
+    assert_eq!(info.first_frame_offset, 110);
+    assert_eq!(info.stream_end, 110 + 10 * 384);
+    assert_eq!(info.total_samples, 10 * 1152);
+    assert_eq!(info.bit_rate, 128);
+}
+
+#[test]
+fn test_skips_false_sync() {
+    let mut bytes = vec![0x00, 0xFF, 0xFB, 0x94, 0x44, 0x12, 0x34];
+    for _ in 0..3 {
+        bytes.extend(frame(CBR_HEADER));
+    }
+    assert_eq!(read_info(&bytes).first_frame_offset, 7);
+}
+
+#[test]
+fn test_xing_with_lame_tag() {
+    let header = parse_frame_header(CBR_HEADER).unwrap();
+    let mut tag_frame = frame(CBR_HEADER);
+    let mut xing = b"Xing".to_vec();
+    xing.extend(0x5u32.to_be_bytes()); // frames + TOC
+    xing.extend(100u32.to_be_bytes());
+    xing.extend((0..100).map(|i| (i * 256 / 100) as u8));
+    xing.extend(b"LAME3.100");
+    xing.resize(xing.len() + 12, 0);
+    // 12-bit delay (576) and padding (1000)
+    xing.extend([0x24, 0x03, 0xE8]);
+    let offset = header.xing_offset();
+    tag_frame[offset..offset + xing.len()].copy_from_slice(&xing);
+
+    let mut bytes = tag_frame;
+    for _ in 0..100 {
+        bytes.extend(frame(CBR_HEADER));
+    }
+
+    let info = read_info(&bytes);
+    let vbr = info.vbr.unwrap();
+    assert_eq!(vbr.frames, Some(100));
+    assert_eq!(vbr.encoder_delay, 576);
+    assert_eq!(vbr.encoder_padding, 1000);
+    assert!(matches!(vbr.toc, Some(SeekToc::Xing(ref toc)) if toc[50] == 128));
+    assert_eq!(info.audio_offset, 384);
+    assert_eq!(info.total_samples, 100 * 1152 - 1576);
+    assert_eq!(info.bit_rate, 128);
+}
+
+#[test]
+fn test_vbri_header() {
+    let mut tag_frame = frame(CBR_HEADER);
+    let mut vbri = b"VBRI".to_vec();
+    vbri.extend(1u16.to_be_bytes()); // version
+    vbri.extend(0u16.to_be_bytes()); // delay
+    vbri.extend(75u16.to_be_bytes()); // quality
+    vbri.extend((5 * 384u32).to_be_bytes()); // stream bytes, tag frame included
+    vbri.extend(4u32.to_be_bytes()); // frames
+    vbri.extend(2u16.to_be_bytes()); // TOC entries
+    vbri.extend(1u16.to_be_bytes()); // scale
+    vbri.extend(2u16.to_be_bytes()); // entry size
+    vbri.extend(2u16.to_be_bytes()); // frames per entry
+    vbri.extend(768u16.to_be_bytes());
+    vbri.extend(768u16.to_be_bytes());
+    tag_frame[36..36 + vbri.len()].copy_from_slice(&vbri);
+
+    let mut bytes = tag_frame;
+    for _ in 0..4 {
+        bytes.extend(frame(CBR_HEADER));
+    }
+
+    let info = read_info(&bytes);
+    let vbr = info.vbr.unwrap();
+    assert_eq!(vbr.frames, Some(4));
+    assert_eq!(vbr.toc, Some(SeekToc::Vbri { entries: vec![768, 768], frames_per_entry: 2 }));
+    assert_eq!(info.total_samples, 4 * 1152);
+    assert_eq!(info.bit_rate, 128);
+}
+
+#[test]
+fn test_no_frames() {
+    let file = write_mp3(&id3v2_tag(20));
+    assert!(Mp3Decoder::new().read_info(file.path()).is_err());
+}
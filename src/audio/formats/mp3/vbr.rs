@@ -0,0 +1,116 @@
+use super::header::FrameHeader;
+
+/// Seek table carried by a VBR header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeekToc {
+    /// Xing: 100 entries mapping percent of duration to 1/256ths of the stream bytes
+    Xing(Vec<u8>),
+    /// VBRI: byte size of each run of `frames_per_entry` frames
+    Vbri { entries: Vec<u32>, frames_per_entry: u32 },
+}
+
+/// Information carried in the first frame of a VBR (or LAME-tagged CBR) stream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VbrInfo {
+    /// Audio frames in the stream, not counting the tag frame itself
+    pub frames: Option<u32>,
+    /// Stream size in bytes as recorded by the encoder
+    pub bytes: Option<u32>,
+    pub toc: Option<SeekToc>,
+    /// Samples of encoder delay at the start, from the LAME tag
+    pub encoder_delay: u32,
+    /// Samples of padding at the end, from the LAME tag
+    pub encoder_padding: u32,
+}
+
+/// Look for a Xing/Info or VBRI tag inside the given first frame
+pub fn parse_vbr_header(header: &FrameHeader, frame: &[u8]) -> Option<VbrInfo> {
+    parse_xing(frame, header.xing_offset()).or_else(|| parse_vbri(frame))
+}
+
+fn parse_xing(frame: &[u8], offset: usize) -> Option<VbrInfo> {
+    let tag = frame.get(offset..offset + 8)?;
+    if &tag[0..4] != b"Xing" && &tag[0..4] != b"Info" {
+        return None;
+    }
+
+    let flags = be_u32(&tag[4..8]);
+    let mut pos = offset + 8;
+    let mut info = VbrInfo::default();
+
+    if flags & 0x1 != 0 {
+        info.frames = Some(be_u32(frame.get(pos..pos + 4)?));
+        pos += 4;
+    }
+    if flags & 0x2 != 0 {
+        info.bytes = Some(be_u32(frame.get(pos..pos + 4)?));
+        pos += 4;
+    }
+    if flags & 0x4 != 0 {
+        info.toc = Some(SeekToc::Xing(frame.get(pos..pos + 100)?.to_vec()));
+        pos += 100;
+    }
+    if flags & 0x8 != 0 {
+        pos += 4;
+    }
+
+    if let Some((delay, padding)) = parse_lame_tag(frame.get(pos..).unwrap_or_default()) {
+        info.encoder_delay = delay;
+        info.encoder_padding = padding;
+    }
+    Some(info)
+}
+
+/// Encoder delay and padding from a LAME extension (also written by FFmpeg's libavcodec)
+fn parse_lame_tag(tag: &[u8]) -> Option<(u32, u32)> {
+    let encoder = tag.get(0..4)?;
+    if encoder != b"LAME" && encoder != b"Lavc" && encoder != b"Lavf" {
+        return None;
+    }
+    let delay_padding = tag.get(21..24)?;
+    let delay = ((delay_padding[0] as u32) << 4) | ((delay_padding[1] as u32) >> 4);
+    let padding = (((delay_padding[1] & 0x0F) as u32) << 8) | delay_padding[2] as u32;
+    Some((delay, padding))
+}
+
+/// Fraunhofer VBRI header, always 32 bytes past the frame header
+fn parse_vbri(frame: &[u8]) -> Option<VbrInfo> {
+    const OFFSET: usize = 36;
+    let tag = frame.get(OFFSET..OFFSET + 26)?;
+    if &tag[0..4] != b"VBRI" {
+        return None;
+    }
+
+    let bytes = be_u32(&tag[10..14]);
+    let frames = be_u32(&tag[14..18]);
+    let entry_count = be_u16(&tag[18..20]) as usize;
+    let scale = be_u16(&tag[20..22]) as u32;
+    let entry_size = be_u16(&tag[22..24]) as usize;
+    let frames_per_entry = be_u16(&tag[24..26]) as u32;
+
+    let table = frame.get(OFFSET + 26..OFFSET + 26 + entry_count * entry_size);
+    let toc = match (table, entry_size) {
+        (Some(table), 1..=4) => Some(SeekToc::Vbri {
+            entries: table.chunks_exact(entry_size)
+                .map(|entry| entry.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32) * scale)
+                .collect(),
+            frames_per_entry,
+        }),
+        _ => None,
+    };
+
+    Some(VbrInfo {
+        frames: Some(frames),
+        bytes: Some(bytes),
+        toc,
+        ..VbrInfo::default()
+    })
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
@@ -0,0 +1,156 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Location of an ISO-BMFF box (atom) within a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxHeader {
+    pub kind: [u8; 4],
+    /// Offset of the first payload byte, past the size/type header
+    pub start: u64,
+    /// Payload length in bytes
+    pub len: u64,
+}
+
+impl BoxHeader {
+    /// Offset one past the last payload byte
+    pub fn end(&self) -> u64 {
+        self.start + self.len
+    }
+}
+
+/// List the boxes laid out back to back between `start` and `end`
+pub fn read_boxes<R: Read + Seek>(reader: &mut R, start: u64, end: u64) -> io::Result<Vec<BoxHeader>> {
+    let mut boxes = Vec::new();
+    let mut pos = start;
+
+    while pos + 8 <= end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let kind = [header[4], header[5], header[6], header[7]];
+
+        let (header_len, total_len) = match size {
+            // A size of 0 means the box runs to the end of its parent
+            0 => (8, end - pos),
+            // A size of 1 means a 64-bit size follows the type
+            1 => {
+                let mut large = [0u8; 8];
+                reader.read_exact(&mut large)?;
+                (16, u64::from_be_bytes(large))
+            }
+            size => (8, size),
+        };
+        if total_len < header_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "MP4 box smaller than its header"));
+        }
+
+        // Truncated files keep whatever part of the last box made it to disk
+        let total_len = total_len.min(end - pos);
+        boxes.push(BoxHeader {
+            kind,
+            start: pos + header_len,
+            len: total_len.saturating_sub(header_len),
+        });
+        pos += total_len;
+    }
+    Ok(boxes)
+}
+
+/// Follow a path of nested box types from the top level of the file
+pub fn find_path<R: Read + Seek>(reader: &mut R, path: &[&[u8; 4]]) -> io::Result<Option<BoxHeader>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut found = None;
+    let (mut start, mut end) = (0, file_len);
+
+    for kind in path {
+        match read_boxes(reader, start, end)?.into_iter().find(|b| &b.kind == *kind) {
+            Some(child) => {
+                start = child.start;
+                end = child.end();
+                found = Some(child);
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(found)
+}
+
+/// Read up to `limit` bytes of a box's payload
+pub fn read_payload<R: Read + Seek>(reader: &mut R, header: &BoxHeader, limit: u64) -> io::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(header.start))?;
+    let mut payload = Vec::new();
+    reader.take(header.len.min(limit)).read_to_end(&mut payload)?;
+    Ok(payload)
+}
+
+/// Locate `ilst` under `moov/udta/meta`, or directly under `moov/meta`
+pub fn find_ilst<R: Read + Seek>(reader: &mut R) -> io::Result<Option<BoxHeader>> {
+    let meta = match find_path(reader, &[b"moov", b"udta", b"meta"])? {
+        Some(meta) => meta,
+        None => match find_path(reader, &[b"moov", b"meta"])? {
+            Some(meta) => meta,
+            None => return Ok(None),
+        },
+    };
+
+    // `meta` is a full box in MP4 but a plain container in QuickTime files
+    let head = read_payload(reader, &meta, 8)?;
+    let children_start = if head.get(4..8) == Some(b"hdlr") { meta.start } else { meta.start + 4 };
+    Ok(read_boxes(reader, children_start, meta.end())?
+        .into_iter()
+        .find(|b| &b.kind == b"ilst"))
+}
+
+/// Text of the freeform `----` item called `name`, such as `iTunSMPB`
+pub fn freeform_item<R: Read + Seek>(reader: &mut R, name: &str) -> io::Result<Option<String>> {
+    let Some(ilst) = find_ilst(reader)? else {
+        return Ok(None);
+    };
+
+    for item in read_boxes(reader, ilst.start, ilst.end())?.iter().filter(|b| &b.kind == b"----") {
+        let children = read_boxes(reader, item.start, item.end())?;
+        let (Some(item_name), Some(data)) = (
+            children.iter().find(|b| &b.kind == b"name"),
+            children.iter().find(|b| &b.kind == b"data"),
+        ) else {
+            continue;
+        };
+
+        // name is a full box; data has a type indicator and locale ahead of the value
+        let item_name = read_payload(reader, item_name, 256)?;
+        if item_name.get(4..).is_some_and(|n| n.eq_ignore_ascii_case(name.as_bytes())) {
+            let value = read_payload(reader, data, 1024)?;
+            return Ok(value.get(8..).map(|v| String::from_utf8_lossy(v).trim_end_matches('\0').to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Type of the first audio sample entry (`mp4a`, `alac`, ...) among the file's tracks
+pub fn audio_sample_entry<R: Read + Seek>(reader: &mut R) -> io::Result<Option<[u8; 4]>> {
+    let Some(moov) = find_path(reader, &[b"moov"])? else {
+        return Ok(None);
+    };
+
+    for trak in read_boxes(reader, moov.start, moov.end())?.iter().filter(|b| &b.kind == b"trak") {
+        let mut parent = *trak;
+        for kind in [b"mdia", b"minf", b"stbl", b"stsd"] {
+            match read_boxes(reader, parent.start, parent.end())?.into_iter().find(|b| &b.kind == kind) {
+                Some(child) => parent = child,
+                None => break,
+            }
+        }
+        if &parent.kind != b"stsd" {
+            continue;
+        }
+
+        // stsd is a full box: version/flags and an entry count precede the entries
+        let entries = read_boxes(reader, parent.start + 8, parent.end())?;
+        if let Some(entry) = entries.first() {
+            if matches!(&entry.kind, b"mp4a" | b"alac") {
+                return Ok(Some(entry.kind));
+            }
+        }
+    }
+    Ok(None)
+}
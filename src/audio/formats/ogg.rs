@@ -1,6 +1,7 @@
 use std::error::Error;
-use std::path::Path;
-use super::{AudioDecoder, AudioFormat, AudioReader};
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat, OggCodec};
+use super::symphonia_source::SymphoniaSource;
 
 pub struct OggDecoder {
     // TODO: Add fields for OGG/Vorbis-specific decoding state
@@ -11,7 +12,12 @@ impl OggDecoder {
         Self {}
     }
 
-    fn read_ogg_header(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
+    /// Codecs the Ogg path can decode; unidentified streams are left to the demuxer
+    pub fn supports_codec(codec: OggCodec) -> bool {
+        matches!(codec, OggCodec::Vorbis | OggCodec::Flac | OggCodec::Unknown)
+    }
+
+    fn read_ogg_header(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
         // TODO: Implement actual OGG/Vorbis header reading
         // This would typically:
         // 1. Open the file
@@ -24,6 +30,7 @@ impl OggDecoder {
             channels: 2,
             sample_rate: 44100,
             bits_per_sample: 16, // Vorbis typically decodes to 16-bit PCM
+            ..Default::default()
         })
     }
 
@@ -48,37 +55,26 @@ impl OggDecoder {
 }
 
 impl AudioDecoder for OggDecoder {
-    fn can_decode(&self, path: &Path) -> bool {
-        // Check if file has .ogg extension
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("ogg"))
-            .unwrap_or(false)
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        match detect_source_format(source) {
+            Some(FileFormat::Ogg(codec)) => Self::supports_codec(codec),
+            _ => false,
+        }
     }
 
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        if !self.can_decode(path) {
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
             return Err("Not an OGG file".into());
         }
-        self.read_ogg_header(path)
+        self.read_ogg_header(source)
     }
 
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
-        if !self.can_decode(path) {
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
             return Err("Not an OGG file".into());
         }
 
-        // Read format information
-        let format = self.read_ogg_header(path)?;
-
-        // TODO: Implement actual OGG/Vorbis decoding
-        // This would:
-        // 1. Initialize OGG stream reader
-        // 2. Initialize Vorbis decoder
-        // 3. Set up packet reading and decoding
-        
-        // Create reader with placeholder total samples
-        Ok(AudioReader::new(format, 0))
+        SymphoniaSource::open(source)
     }
 }
 
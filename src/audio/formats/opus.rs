@@ -0,0 +1,229 @@
+use std::error::Error;
+use crate::media::MediaSource;
+use std::time::Duration;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatReader, SeekMode, SeekTo};
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat, OggCodec, SampleSource};
+use super::symphonia_source::{describe_track, open_format};
+
+/// Opus always decodes at 48kHz, whatever rate the encoder was fed
+const OPUS_RATE: u32 = 48000;
+
+/// Longest possible Opus packet: 120ms at 48kHz
+const MAX_PACKET_FRAMES: usize = 5760;
+
+/// Decoded and dropped ahead of a seek target so the decoder converges: 80ms at 48kHz
+const SEEK_PREROLL: u64 = 3840;
+
+pub struct OpusDecoder {}
+
+impl OpusDecoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Read the identification header; the pre-skip is excluded from the reported length
+    fn read_opus_header(&self, source: &MediaSource) -> Result<(AudioFormat, u64), Box<dyn Error>> {
+        let format = open_format(source)?;
+        let track = format.default_track().ok_or("No audio track found")?;
+        let params = &track.codec_params;
+
+        let pre_skip = params.delay.unwrap_or(0) as u64;
+        let total_samples = params.n_frames.map(|frames| frames.saturating_sub(pre_skip)).unwrap_or(0);
+        let mut audio_format = describe_track(params)?;
+        if total_samples > 0 {
+            audio_format.duration = Some(Duration::from_secs_f64(total_samples as f64 / OPUS_RATE as f64));
+        }
+        Ok((audio_format, total_samples))
+    }
+}
+
+impl Default for OpusDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDecoder for OpusDecoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Ogg(OggCodec::Opus)))
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an Opus file".into());
+        }
+        Ok(self.read_opus_header(source)?.0)
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not an Opus file".into());
+        }
+
+        let (audio_format, total_samples) = self.read_opus_header(source)?;
+        let format = open_format(source)?;
+        let track = format.default_track().ok_or("No audio track found")?;
+        if !(1..=2).contains(&audio_format.channels) {
+            return Err(format!("Opus streams with {} channels are not supported", audio_format.channels).into());
+        }
+
+        let pre_skip = track.codec_params.delay.unwrap_or(0) as u64;
+        let source = OpusSource {
+            track_id: track.id,
+            pre_skip,
+            skip: pre_skip,
+            total: (total_samples > 0).then_some(total_samples),
+            remaining: (total_samples > 0).then_some(total_samples),
+            decoder: Libopus::new(audio_format.channels as usize)?,
+            channels: audio_format.channels as usize,
+            pcm: vec![0.0; MAX_PACKET_FRAMES * audio_format.channels as usize],
+            format,
+        };
+        Ok(AudioReader::with_source(audio_format, total_samples, Box::new(source)))
+    }
+}
+
+/// A libopus decoder state, freed when dropped
+struct Libopus(*mut unsafe_libopus::OpusDecoder);
+
+// The state is only ever reached through the `&mut self` that owns it
+unsafe impl Send for Libopus {}
+
+impl Libopus {
+    fn new(channels: usize) -> Result<Self, Box<dyn Error>> {
+        let mut error = 0;
+        let state = unsafe { unsafe_libopus::opus_decoder_create(OPUS_RATE as i32, channels as i32, &mut error) };
+        if state.is_null() {
+            return Err(format!("Failed to create Opus decoder: {}", unsafe_libopus::opus_strerror(error)).into());
+        }
+        Ok(Self(state))
+    }
+
+    /// Decode one packet into `pcm`, returning the frames it held
+    fn decode_float(&mut self, packet: &[u8], pcm: &mut [f32], channels: usize) -> Result<usize, Box<dyn Error>> {
+        let frames = unsafe {
+            unsafe_libopus::opus_decode_float(
+                self.0,
+                packet.as_ptr(),
+                packet.len() as i32,
+                pcm.as_mut_ptr(),
+                (pcm.len() / channels) as i32,
+                0,
+            )
+        };
+        if frames < 0 {
+            return Err(unsafe_libopus::opus_strerror(frames).into());
+        }
+        Ok(frames as usize)
+    }
+}
+
+impl Drop for Libopus {
+    fn drop(&mut self) {
+        unsafe { unsafe_libopus::opus_decoder_destroy(self.0) }
+    }
+}
+
+/// Decodes Ogg-demuxed Opus packets with libopus, honouring pre-skip and end trimming
+struct OpusSource {
+    format: Box<dyn FormatReader>,
+    decoder: Libopus,
+    track_id: u32,
+    channels: usize,
+    /// Frames the encoder asked to be dropped from the start of the stream
+    pre_skip: u64,
+    /// Frames still to drop from the start of the stream
+    skip: u64,
+    /// Frames from the end of the pre-skip to the final granule position, if known
+    total: Option<u64>,
+    /// Frames left before the final granule position, if known
+    remaining: Option<u64>,
+    pcm: Vec<f32>,
+}
+
+impl SampleSource for OpusSource {
+    fn next_packet(&mut self, out: &mut Vec<f32>) -> Result<bool, Box<dyn Error>> {
+        loop {
+            if self.remaining == Some(0) {
+                return Ok(false);
+            }
+
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(false);
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let frames = match self.decoder.decode_float(packet.buf(), &mut self.pcm, self.channels) {
+                Ok(frames) => frames as u64,
+                // A corrupt packet only costs us that packet, carry on with the next one
+                Err(e) => {
+                    log::warn!("Skipping undecodable Opus packet: {}", e);
+                    continue;
+                }
+            };
+
+            let skipped = self.skip.min(frames);
+            self.skip -= skipped;
+            let mut keep = frames - skipped;
+            if let Some(remaining) = self.remaining.as_mut() {
+                keep = keep.min(*remaining);
+                *remaining -= keep;
+            }
+
+            let start = skipped as usize * self.channels;
+            out.extend_from_slice(&self.pcm[start..start + keep as usize * self.channels]);
+            return Ok(true);
+        }
+    }
+
+    /// Granule positions count the pre-skip, the frames handed out do not
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        let ts = (frame + self.pre_skip).saturating_sub(SEEK_PREROLL);
+        let seeked = self.format.seek(SeekMode::Accurate, SeekTo::TimeStamp { ts, track_id: self.track_id })?;
+        self.decoder = Libopus::new(self.channels)?;
+
+        let landed = seeked.actual_ts;
+        self.skip = self.pre_skip.saturating_sub(landed);
+        let start = landed.saturating_sub(self.pre_skip);
+        self.remaining = self.total.map(|total| total.saturating_sub(start));
+        Ok(Some(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_path(filename: &str) -> PathBuf {
+        PathBuf::from(filename)
+    }
+
+    #[test]
+    fn test_can_decode() {
+        let decoder = OpusDecoder::new();
+        assert!(decoder.can_decode(test_path("test.opus").as_path()));
+        assert!(decoder.can_decode(test_path("test.OPUS").as_path()));
+        assert!(!decoder.can_decode(test_path("test.ogg").as_path()));
+        assert!(!decoder.can_decode(test_path("test").as_path()));
+    }
+
+    #[test]
+    fn test_probe_invalid_extension() {
+        let decoder = OpusDecoder::new();
+        assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_extension() {
+        let mut decoder = OpusDecoder::new();
+        assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::time::Duration;
+use symphonia::core::audio::{SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{CodecParameters, Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use crate::media::MediaSource;
+use super::{AudioFormat, AudioReader};
+use super::audio_reader::SampleSource;
+
+/// Frames decoded and dropped ahead of a seek target, for lossy decoders to converge
+const SEEK_PREROLL: u64 = 4096;
+
+/// Streams decoded packets out of any container/codec symphonia understands
+pub(crate) struct SymphoniaSource {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_buf: Option<SampleBuffer<f32>>,
+    /// Layout of the most recently decoded packet
+    spec: Option<SignalSpec>,
+    /// Samples decoded ahead of time while working out the stream layout
+    pending: Vec<f32>,
+}
+
+impl SymphoniaSource {
+    /// Open `source` and wrap it in a streaming `AudioReader`
+    pub(crate) fn open(source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        let format = open_format(source)?;
+
+        let track = format.default_track().ok_or("No audio track found")?;
+        let mut params = track.codec_params.clone();
+        let decoder = symphonia::default::get_codecs().make(&params, &DecoderOptions::default())?;
+        let track_id = track.id;
+
+        let mut source = Self {
+            format,
+            decoder,
+            track_id,
+            sample_buf: None,
+            spec: None,
+            pending: Vec::new(),
+        };
+
+        // MP4 leaves the channel layout to the codec config, so decode a packet to learn it
+        if params.channels.is_none() {
+            let mut pending = Vec::new();
+            if source.next_packet(&mut pending)? {
+                let spec = source.spec.ok_or("Unknown channel layout")?;
+                params.with_channels(spec.channels).with_sample_rate(spec.rate);
+            }
+            source.pending = pending;
+        }
+
+        let audio_format = describe_track(&params)?;
+        let total_samples = params.n_frames.unwrap_or(0);
+        Ok(AudioReader::with_source(audio_format, total_samples, Box::new(source)))
+    }
+
+    /// Describe the default track of `source`
+    pub(crate) fn probe(source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        Ok(Self::open(source)?.format)
+    }
+}
+
+/// Probe the container of `source`, using its extension as a hint
+pub(crate) fn open_format(source: &MediaSource) -> Result<Box<dyn FormatReader>, Box<dyn Error>> {
+    let mss = MediaSourceStream::new(Box::new(source.open()?), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = source.extension() {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        mss,
+        // Trim encoder delay and padding so gapless sources decode to their exact length
+        &FormatOptions { enable_gapless: true, ..Default::default() },
+        &MetadataOptions::default(),
+    )?;
+    Ok(probed.format)
+}
+
+/// Build an `AudioFormat` from a track's codec parameters
+pub(crate) fn describe_track(params: &CodecParameters) -> Result<AudioFormat, Box<dyn Error>> {
+    Ok(AudioFormat {
+        channels: params.channels.map(|c| c.count() as u16).ok_or("Unknown channel layout")?,
+        sample_rate: params.sample_rate.ok_or("Unknown sample rate")?,
+        // Lossy codecs have no native bit depth; report the usual 16-bit PCM equivalent
+        bits_per_sample: params.bits_per_sample.unwrap_or(16) as u16,
+        duration: params.n_frames.zip(params.sample_rate)
+            .map(|(frames, rate)| Duration::from_secs_f64(frames as f64 / rate as f64)),
+        bit_rate: None,
+    })
+}
+
+impl SymphoniaSource {
+    /// Decode the next packet into `out`, returning its timestamp, or `None` at the end
+    fn decode_next(&mut self, out: &mut Vec<f32>) -> Result<Option<u64>, Box<dyn Error>> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(None);
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    self.spec = Some(*decoded.spec());
+                    let needs_alloc = self.sample_buf.as_ref()
+                        .map(|buf| buf.capacity() < decoded.capacity() * decoded.spec().channels.count())
+                        .unwrap_or(true);
+                    if needs_alloc {
+                        self.sample_buf = Some(SampleBuffer::new(decoded.capacity() as u64, *decoded.spec()));
+                    }
+
+                    if let Some(buf) = self.sample_buf.as_mut() {
+                        buf.copy_interleaved_ref(decoded);
+                        out.extend_from_slice(buf.samples());
+                    }
+                    return Ok(Some(packet.ts()));
+                }
+                // A corrupt packet only costs us that packet, carry on with the next one
+                Err(SymphoniaError::DecodeError(msg)) => {
+                    log::warn!("Skipping undecodable packet: {}", msg);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl SampleSource for SymphoniaSource {
+    fn next_packet(&mut self, out: &mut Vec<f32>) -> Result<bool, Box<dyn Error>> {
+        if !self.pending.is_empty() {
+            out.append(&mut self.pending);
+            return Ok(true);
+        }
+        Ok(self.decode_next(out)?.is_some())
+    }
+
+    /// Lands on the packet holding `frame`: FLAC uses its seek table or a binary search,
+    /// Ogg bisects pages on their granule positions and MP3 walks the frame headers
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        // Start early enough for lossy decoders to settle before the frame wanted
+        let ts = frame.saturating_sub(SEEK_PREROLL);
+        let seeked = self.format.seek(SeekMode::Accurate, SeekTo::TimeStamp { ts, track_id: self.track_id })?;
+        // Decoder state from before the seek would bleed into the first packet
+        self.decoder.reset();
+        self.pending.clear();
+
+        // A freshly reset decoder may need a packet or two before it produces anything
+        let mut pending = Vec::new();
+        while let Some(ts) = self.decode_next(&mut pending)? {
+            if !pending.is_empty() {
+                self.pending = pending;
+                return Ok(Some(ts));
+            }
+        }
+        Ok(Some(seeked.actual_ts))
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::Path;
+use std::time::Duration;
 use super::*;
+use crate::metadata::cuesheet;
 
 #[test]
 fn test_decoder_factory() {
@@ -18,9 +20,517 @@ fn test_audio_reader_creation() {
         channels: 2,
         sample_rate: 44100,
         bits_per_sample: 16,
+        ..Default::default()
     };
     
     let reader = AudioReader::new(format, 1000);
     assert_eq!(reader.total_samples, 1000);
     assert_eq!(reader.position(), 0);
 }
+
+fn decode_fixture(name: &str) -> (AudioReader, Vec<f32>) {
+    let path = Path::new("test").join(name);
+    let mut decoder = get_decoder(&path);
+    let mut reader = decoder.decode(&path).expect("fixture should decode");
+
+    let mut samples = Vec::new();
+    let mut chunk = vec![0.0f32; 4096];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+    (reader, samples)
+}
+
+fn assert_decoded_audio(name: &str) {
+    let (reader, samples) = decode_fixture(name);
+    let channels = reader.format.channels as usize;
+    assert!(channels > 0, "{}: no channels", name);
+    assert_eq!(samples.len() % channels, 0, "{}: partial frame", name);
+
+    let frames = (samples.len() / channels) as u64;
+    assert!(frames > 0, "{}: no samples decoded", name);
+    assert_eq!(reader.position(), frames, "{}: position should track frames read", name);
+    if reader.total_samples > 0 {
+        assert_eq!(frames, reader.total_samples, "{}: sample count mismatch", name);
+    }
+
+    assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)), "{}: samples not normalized", name);
+    assert!(samples.iter().any(|s| s.abs() > 0.01), "{}: decoded only silence", name);
+}
+
+#[test]
+fn test_decode_wav_fixture() {
+    assert_decoded_audio("testaudio-short.wav");
+}
+
+#[test]
+fn test_decode_flac_fixture() {
+    assert_decoded_audio("testaudio-short.flac");
+}
+
+#[test]
+fn test_decode_mp3_fixture() {
+    assert_decoded_audio("testaudio-short.mp3");
+}
+
+#[test]
+fn test_decode_ogg_fixture() {
+    assert_decoded_audio("testaudio-short.ogg");
+}
+
+#[test]
+fn test_decode_aiff_fixture() {
+    assert_decoded_audio("testaudio-short.aiff");
+}
+
+#[test]
+fn test_decode_aifc_fixture() {
+    assert_decoded_audio("testaudio-short.aifc");
+}
+
+#[test]
+fn test_decode_alac_fixture() {
+    assert_decoded_audio("testaudio-short-alac.m4a");
+}
+
+#[test]
+fn test_decode_aac_fixture() {
+    // No AAC encoder is needed to build this fixture: it holds one second of silent frames
+    let (reader, samples) = decode_fixture("testaudio-silence-aac.m4a");
+    assert_eq!(reader.format.channels, 1);
+    assert_eq!(reader.format.sample_rate, 48000);
+    assert_eq!(samples.len() as u64, reader.total_samples);
+    // iTunSMPB trims the 2112 frames of priming and the padding of the last frame
+    assert_eq!(samples.len(), 48000);
+    assert_eq!(reader.format.duration, Some(std::time::Duration::from_secs(1)));
+    assert!(samples.iter().all(|s| s.abs() < 1e-4));
+}
+
+#[test]
+fn test_parse_itunsmpb() {
+    let trim = gapless::parse_itunsmpb(" 00000000 00000840 000001CA 00000000003F1F06 00000000 00000000").unwrap();
+    assert_eq!(trim, EncoderTrim { delay: 2112, padding: 458, frames: Some(4136710) });
+
+    let no_length = gapless::parse_itunsmpb("00000000 00000840 00000000 0000000000000000").unwrap();
+    assert_eq!(no_length.frames, None);
+    assert!(gapless::parse_itunsmpb("00000000 00000000 00000000 0000000000000000").is_none());
+    assert!(gapless::parse_itunsmpb("00000000 0000084").is_none());
+    assert!(gapless::parse_itunsmpb("not hex").is_none());
+}
+
+#[test]
+fn test_mp4_trim_from_fixture() {
+    let trim = gapless::mp4_trim(&MediaSource::from_path(Path::new("test/testaudio-silence-aac.m4a"))).unwrap();
+    assert_eq!(trim.delay, 2112);
+    assert_eq!(trim.frames, Some(48000));
+    assert!(gapless::mp4_trim(&MediaSource::from_path(Path::new("test/testaudio-short-alac.m4a"))).is_none());
+}
+
+/// Stereo frames numbered 0, 1, 2, ... in packets of `packet` frames
+struct CountingSource {
+    next: u32,
+    frames: u32,
+    packet: u32,
+}
+
+impl SampleSource for CountingSource {
+    fn next_packet(&mut self, out: &mut Vec<f32>) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.next >= self.frames {
+            return Ok(false);
+        }
+        let end = (self.next + self.packet).min(self.frames);
+        for frame in self.next..end {
+            out.extend_from_slice(&[frame as f32, -(frame as f32)]);
+        }
+        self.next = end;
+        Ok(true)
+    }
+}
+
+#[test]
+fn test_reader_trims_encoder_delay_and_padding() {
+    let format = AudioFormat { channels: 2, sample_rate: 1000, ..Default::default() };
+    let source = CountingSource { next: 0, frames: 100, packet: 7 };
+    let mut reader = AudioReader::with_source(format, 100, Box::new(source));
+    reader.trim(EncoderTrim { delay: 10, padding: 15, frames: None });
+    assert_eq!(reader.total_samples, 75);
+    assert_eq!(reader.format.duration, Some(std::time::Duration::from_millis(75)));
+
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 6];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+    let expected: Vec<f32> = (10..85).flat_map(|f| [f as f32, -(f as f32)]).collect();
+    assert_eq!(samples, expected);
+    assert_eq!(reader.position(), 75);
+}
+
+#[test]
+fn test_probe_opus_fixture() {
+    let path = Path::new("test/testaudio-short.opus");
+    let format = get_decoder(path).probe_format(path).unwrap();
+    assert_eq!(format.channels, 1);
+    assert_eq!(format.sample_rate, 48000);
+    // The 312-sample pre-skip is not part of the track
+    assert_eq!(format.duration, Some(std::time::Duration::from_secs(1)));
+}
+
+#[test]
+fn test_decode_opus_fixture() {
+    let (reader, samples) = decode_fixture("testaudio-short.opus");
+    assert_eq!(reader.format.channels, 1);
+    assert_eq!(samples.len(), 48000);
+}
+
+#[test]
+fn test_short_fixtures_are_one_second() {
+    // The lossless and Vorbis fixtures hold exactly one second of 48kHz mono audio
+    let fixtures = [
+        "testaudio-short.wav",
+        "testaudio-short.flac",
+        "testaudio-short.ogg",
+        "testaudio-short.aiff",
+        "testaudio-short.aifc",
+        "testaudio-short-alac.m4a",
+    ];
+    for name in fixtures {
+        let (reader, samples) = decode_fixture(name);
+        assert_eq!(reader.format.channels, 1, "{}", name);
+        assert_eq!(reader.format.sample_rate, 48000, "{}", name);
+        assert_eq!(samples.len(), 48000, "{}", name);
+    }
+}
+
+#[test]
+fn test_wav_and_flac_fixtures_match() {
+    let (_, wav_samples) = decode_fixture("testaudio-short.wav");
+    let (_, flac_samples) = decode_fixture("testaudio-short.flac");
+    assert_eq!(wav_samples.len(), flac_samples.len());
+
+    let max_diff = wav_samples.iter()
+        .zip(&flac_samples)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0f32, f32::max);
+    // Same signal; the only difference is 16-bit vs 24-bit quantization
+    assert!(max_diff < 1e-4, "max difference {}", max_diff);
+}
+
+#[test]
+fn test_pcm_containers_match_wav() {
+    // AIFF, AIFC and ALAC fixtures carry the WAV fixture's 16-bit samples unchanged
+    let (_, wav_samples) = decode_fixture("testaudio-short.wav");
+    for name in ["testaudio-short.aiff", "testaudio-short.aifc", "testaudio-short-alac.m4a"] {
+        let (_, samples) = decode_fixture(name);
+        assert_eq!(samples, wav_samples, "{}", name);
+    }
+}
+
+#[test]
+fn test_read_in_small_chunks() {
+    let (_, expected) = decode_fixture("testaudio-short.flac");
+
+    let path = Path::new("test/testaudio-short.flac");
+    let mut reader = get_decoder(path).decode(path).unwrap();
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 7];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn test_empty_reader_reads_nothing() {
+    let format = AudioFormat {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        ..Default::default()
+    };
+
+    let mut reader = AudioReader::new(format, 0);
+    let mut chunk = [0.0f32; 16];
+    assert_eq!(reader.read(&mut chunk).unwrap(), 0);
+}
+
+fn ogg_page_with_packet(packet: &[u8]) -> Vec<u8> {
+    let mut page = b"OggS".to_vec();
+    page.extend([0, 0x02]); // version, beginning-of-stream
+    page.extend([0u8; 20]); // granule, serial, sequence, CRC
+    page.push(1);
+    page.push(packet.len() as u8);
+    page.extend(packet);
+    page
+}
+
+/// Copy a fixture into a temp dir under a different name
+fn copy_fixture(fixture: &str, name: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::copy(Path::new("test").join(fixture), &path).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn test_sniff_magic_bytes() {
+    assert_eq!(detect::sniff_bytes(b"fLaC\0\0\0\x22"), Some(FileFormat::Flac));
+    assert_eq!(detect::sniff_bytes(b"RIFF\0\0\0\0WAVEfmt "), Some(FileFormat::Wav));
+    assert_eq!(detect::sniff_bytes(b"RF64\xff\xff\xff\xffWAVEds64"), Some(FileFormat::Wav));
+    assert_eq!(detect::sniff_bytes(b"RIFF\0\0\0\0AVI "), None);
+    assert_eq!(detect::sniff_bytes(b"FORM\0\0\0\0AIFFCOMM"), Some(FileFormat::Aiff));
+    assert_eq!(detect::sniff_bytes(b"FORM\0\0\0\0AIFCFVER"), Some(FileFormat::Aiff));
+    assert_eq!(detect::sniff_bytes(b"FORM\0\0\0\08SVX"), None);
+    assert_eq!(detect::sniff_bytes(b"\0\0\0\x20ftypM4A "), Some(FileFormat::Mp4(Mp4Codec::Unknown)));
+    assert_eq!(detect::sniff_bytes(b"ID3\x04\0\0\0\0\0\0"), Some(FileFormat::Mp3));
+    assert_eq!(detect::sniff_bytes(b"ID3\x04\0\0\0\0\0\x02\0\0fLaC"), Some(FileFormat::Flac));
+    assert_eq!(detect::sniff_bytes(&[0xFF, 0xFB, 0x94, 0x44]), Some(FileFormat::Mp3));
+    assert_eq!(detect::sniff_bytes(&[0xFF, 0xFF, 0xFF, 0xFF]), None);
+    assert_eq!(detect::sniff_bytes(b"plain text"), None);
+}
+
+#[test]
+fn test_sniff_ogg_codecs() {
+    let sniff = |packet: &[u8]| detect::sniff_bytes(&ogg_page_with_packet(packet));
+    assert_eq!(sniff(b"\x01vorbis\0\0\0\0"), Some(FileFormat::Ogg(OggCodec::Vorbis)));
+    assert_eq!(sniff(b"OpusHead\x01\x02"), Some(FileFormat::Ogg(OggCodec::Opus)));
+    assert_eq!(sniff(b"\x7FFLAC\x01\0"), Some(FileFormat::Ogg(OggCodec::Flac)));
+    assert_eq!(sniff(b"Speex   1.2"), Some(FileFormat::Ogg(OggCodec::Speex)));
+    assert_eq!(sniff(b"\x80theora"), Some(FileFormat::Ogg(OggCodec::Unknown)));
+    assert_eq!(detect::sniff_bytes(b"OggS"), Some(FileFormat::Ogg(OggCodec::Unknown)));
+}
+
+#[test]
+fn test_detect_fixtures_by_content() {
+    let fixtures = [
+        ("testaudio-short.flac", FileFormat::Flac),
+        ("testaudio-short.mp3", FileFormat::Mp3),
+        ("testaudio-short.ogg", FileFormat::Ogg(OggCodec::Vorbis)),
+        ("testaudio-short.wav", FileFormat::Wav),
+        ("testaudio-short.aiff", FileFormat::Aiff),
+        ("testaudio-short.aifc", FileFormat::Aiff),
+        ("testaudio-short-alac.m4a", FileFormat::Mp4(Mp4Codec::Alac)),
+        ("testaudio-silence-aac.m4a", FileFormat::Mp4(Mp4Codec::Aac)),
+        ("testaudio-short.opus", FileFormat::Ogg(OggCodec::Opus)),
+    ];
+    for (fixture, expected) in fixtures {
+        let (_dir, path) = copy_fixture(fixture, "download");
+        assert_eq!(detect_format(&path), Some(expected), "{}", fixture);
+    }
+}
+
+#[test]
+fn test_decoder_factory_new_formats() {
+    let decoder_for = |name: &str| get_decoder(&Path::new("test").join(name));
+    assert!(matches!(decoder_for("testaudio-short.aiff"), DecoderType::Aiff(_)));
+    assert!(matches!(decoder_for("testaudio-short.aifc"), DecoderType::Aiff(_)));
+    assert!(matches!(decoder_for("testaudio-short-alac.m4a"), DecoderType::Alac(_)));
+    assert!(matches!(decoder_for("testaudio-silence-aac.m4a"), DecoderType::Aac(_)));
+    assert!(matches!(decoder_for("testaudio-short.opus"), DecoderType::Opus(_)));
+
+    // Unreadable `.m4a` paths are assumed to be AAC, the common case
+    assert!(matches!(get_decoder(Path::new("missing.m4a")), DecoderType::Aac(_)));
+}
+
+#[test]
+fn test_mp4_sample_entry() {
+    let mut alac = std::fs::File::open("test/testaudio-short-alac.m4a").unwrap();
+    assert_eq!(mp4::audio_sample_entry(&mut alac).unwrap(), Some(*b"alac"));
+
+    let mut aac = std::fs::File::open("test/testaudio-silence-aac.m4a").unwrap();
+    assert_eq!(mp4::audio_sample_entry(&mut aac).unwrap(), Some(*b"mp4a"));
+
+    let mut flac = std::fs::File::open("test/testaudio-short.flac").unwrap();
+    assert_eq!(mp4::audio_sample_entry(&mut flac).unwrap(), None);
+}
+
+#[test]
+fn test_alternate_extensions() {
+    let (_dir, oga) = copy_fixture("testaudio-short.ogg", "track.oga");
+    assert!(matches!(get_decoder(&oga), DecoderType::Ogg(_)));
+
+    let (_dir, wave) = copy_fixture("testaudio-short.wav", "track.wave");
+    assert!(matches!(get_decoder(&wave), DecoderType::Wav(_)));
+    assert!(wav::WavDecoder::new().probe_format(&wave).is_ok());
+
+    // Extension-only fallback for paths that cannot be read
+    assert_eq!(detect_format(Path::new("missing.oga")), Some(FileFormat::Ogg(OggCodec::Vorbis)));
+    assert_eq!(detect_format(Path::new("missing.WAVE")), Some(FileFormat::Wav));
+    assert_eq!(detect_format(Path::new("missing")), None);
+}
+
+#[test]
+fn test_contents_win_over_extension() {
+    let (_dir, path) = copy_fixture("testaudio-short.flac", "mislabelled.mp3");
+    assert_eq!(detect_format(&path), Some(FileFormat::Flac));
+    assert!(matches!(get_decoder(&path), DecoderType::Flac(_)));
+    assert!(!mp3::Mp3Decoder::new().can_decode(&path));
+    assert!(flac::FlacDecoder::new().can_decode(&path));
+    // Each of those detected it, but only the first warned
+    assert!(!detect::first_mismatch(&path.display().to_string()));
+
+    let (_dir, path) = copy_fixture("testaudio-short.mp3", "mislabelled.wav");
+    let mut decoder = get_decoder(&path);
+    assert!(matches!(decoder, DecoderType::Mp3(_)));
+    assert!(decoder.decode(&path).is_ok());
+}
+
+#[test]
+fn test_extensionless_file_decodes() {
+    let (_dir, path) = copy_fixture("testaudio-short.flac", "download");
+    let mut decoder = get_decoder(&path);
+    let mut reader = decoder.decode(&path).unwrap();
+    let mut chunk = [0.0f32; 1024];
+    assert!(reader.read(&mut chunk).unwrap() > 0);
+}
+
+#[test]
+fn test_opus_in_ogg_is_not_vorbis() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("voice.ogg");
+    std::fs::write(&path, ogg_page_with_packet(b"OpusHead\x01\x02\x38\x01\x80\xbb\0\0\0\0\0")).unwrap();
+
+    assert_eq!(detect_format(&path), Some(FileFormat::Ogg(OggCodec::Opus)));
+    assert!(!ogg::OggDecoder::new().can_decode(&path));
+}
+
+#[test]
+fn test_unrecognized_contents_fall_back_to_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("junk-prefixed.mp3");
+    std::fs::write(&path, b"not a recognizable header").unwrap();
+    assert_eq!(detect_format(&path), Some(FileFormat::Mp3));
+}
+
+fn read_all(reader: &mut AudioReader) -> Vec<f32> {
+    let mut samples = Vec::new();
+    let mut buffer = vec![0.0f32; 4096];
+    loop {
+        match reader.read(&mut buffer).unwrap() {
+            0 => return samples,
+            read => samples.extend_from_slice(&buffer[..read]),
+        }
+    }
+}
+
+#[test]
+fn test_seek_is_sample_accurate() {
+    let fixtures = [
+        "test/testaudio-short.wav",
+        "test/testaudio-short.aiff",
+        "test/testaudio-short.flac",
+        "test/testaudio-short.mp3",
+        "test/testaudio-short.ogg",
+        "test/testaudio-short-alac.m4a",
+    ];
+    for fixture in fixtures {
+        let path = Path::new(fixture);
+        let expected = read_all(&mut get_decoder(path).decode(path).unwrap());
+        let mut reader = get_decoder(path).decode(path).unwrap();
+        let channels = reader.format.channels as usize;
+
+        // Forwards from part way in, then back again
+        let mut buffer = vec![0.0f32; 1000 * channels];
+        reader.read(&mut buffer).unwrap();
+        for frame in [43200, 14400, 0] {
+            reader.seek(frame as u64).unwrap();
+            assert_eq!(reader.position(), frame as u64, "{}", fixture);
+            let samples = read_all(&mut reader);
+            assert_eq!(samples.len(), expected.len() - frame * channels, "{} from {}", fixture, frame);
+            assert!(samples == expected[frame * channels..], "{} differs after seeking to {}", fixture, frame);
+        }
+    }
+}
+
+#[test]
+fn test_seek_bounds_and_end() {
+    let path = Path::new("test/testaudio-short.flac");
+    let mut reader = get_decoder(path).decode(path).unwrap();
+    assert!(reader.seek(48001).is_err());
+    reader.seek(48000).unwrap();
+    assert!(read_all(&mut reader).is_empty());
+
+    // Seeking deep into a long file only walks frame headers
+    let path = Path::new("test/testaudio-long.mp3");
+    let mut reader = get_decoder(path).decode(path).unwrap();
+    let frame = reader.total_samples - 4800;
+    reader.seek(frame).unwrap();
+    assert_eq!(read_all(&mut reader).len(), 4800 * reader.format.channels as usize);
+}
+
+#[test]
+fn test_forward_only_source_seeks_by_decoding() {
+    let format = AudioFormat { channels: 2, sample_rate: 1000, ..Default::default() };
+    let source = CountingSource { next: 0, frames: 100, packet: 7 };
+    let mut reader = AudioReader::with_source(format, 100, Box::new(source));
+    reader.trim(EncoderTrim { delay: 10, padding: 15, frames: None });
+
+    let mut buffer = [0.0f32; 8];
+    reader.read(&mut buffer).unwrap();
+    reader.seek(30).unwrap();
+    assert_eq!(reader.position(), 30);
+    let samples = read_all(&mut reader);
+    // Frame 30 of the trimmed stream is source frame 40, and 45 frames remain before the padding
+    assert_eq!(&samples[..2], &[40.0, -40.0]);
+    assert_eq!(samples.len(), 45 * 2);
+    assert!(reader.seek(10).is_err());
+}
+
+#[test]
+fn test_restrict_plays_part_of_the_stream() {
+    let format = AudioFormat { channels: 2, sample_rate: 1000, ..Default::default() };
+    let source = CountingSource { next: 0, frames: 100, packet: 7 };
+    let mut reader = AudioReader::with_source(format.clone(), 100, Box::new(source));
+    reader.trim(EncoderTrim { delay: 10, padding: 15, frames: None });
+    reader.restrict(20, Some(50)).unwrap();
+    assert_eq!((reader.total_samples, reader.format.duration), (30, Some(Duration::from_millis(30))));
+
+    // Frame 0 of the track is source frame 30, past the encoder delay and the track start
+    let mut buffer = [0.0f32; 2];
+    reader.read(&mut buffer).unwrap();
+    assert_eq!(buffer, [30.0, -30.0]);
+    reader.seek(25).unwrap();
+    let samples = read_all(&mut reader);
+    assert_eq!(&samples[..2], &[55.0, -55.0]);
+    assert_eq!(samples.len(), 5 * 2);
+
+    // The last track runs to the end of the audio, and no track starts past it
+    let source = CountingSource { next: 0, frames: 100, packet: 7 };
+    let mut reader = AudioReader::with_source(format.clone(), 100, Box::new(source));
+    reader.restrict(90, None).unwrap();
+    assert_eq!(read_all(&mut reader).len(), 10 * 2);
+    let mut reader = AudioReader::new(format, 100);
+    assert!(reader.restrict(101, None).is_err());
+}
+
+#[test]
+fn test_open_cue_track() {
+    let dir = tempfile::tempdir().unwrap();
+    let album = dir.path().join("album.wav");
+    std::fs::copy("test/testaudio-short.wav", &album).unwrap();
+    std::fs::write(
+        dir.path().join("album.cue"),
+        "FILE \"album.wav\" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    INDEX 01 00:00:30\n",
+    ).unwrap();
+    let expected = read_all(&mut get_decoder(&album).decode(&album).unwrap());
+
+    // 30 CD frames are 0.4s, 19200 samples at 48kHz
+    let first = read_all(&mut open_track(Path::new(&cuesheet::track_path(&album, 1))).unwrap());
+    assert!(first == expected[..19200]);
+    let mut second = open_track(Path::new(&cuesheet::track_path(&album, 2))).unwrap();
+    assert_eq!(second.total_samples, 28800);
+    assert!(read_all(&mut second) == expected[19200..]);
+    assert!(open_track(Path::new(&cuesheet::track_path(&album, 3))).is_err());
+}
@@ -1,125 +0,0 @@
-use std::error::Error;
-use std::path::Path;
-use super::{AudioDecoder, AudioFormat, AudioReader};
-
-pub struct WavDecoder {
-    // TODO: Add fields for WAV-specific decoding state
-}
-
-impl WavDecoder {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    fn read_wav_header(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        // TODO: Implement actual WAV header reading
-        // This would typically:
-        // 1. Open the file
-        // 2. Validate RIFF header
-        // 3. Find and parse fmt chunk
-        // 4. Extract format information
-        
-        // Placeholder return with typical WAV format
-        Ok(AudioFormat {
-            channels: 2,
-            sample_rate: 44100,
-            bits_per_sample: 16,
-        })
-    }
-
-    fn validate_riff_header(&self, header: &[u8]) -> bool {
-        // TODO: Implement RIFF header validation
-        // This would:
-        // 1. Check "RIFF" signature
-        // 2. Validate chunk size
-        // 3. Check "WAVE" format
-        false
-    }
-
-    fn parse_fmt_chunk(&self, chunk: &[u8]) -> Option<(u16, u32, u16)> {
-        // TODO: Implement fmt chunk parsing
-        // This would:
-        // 1. Verify chunk ID ("fmt ")
-        // 2. Parse audio format (PCM = 1)
-        // 3. Extract channels, sample rate, bits per sample
-        // Returns (channels, sample_rate, bits_per_sample)
-        None
-    }
-
-    fn find_data_chunk(&self, file_data: &[u8]) -> Option<(usize, usize)> {
-        // TODO: Implement data chunk location
-        // This would:
-        // 1. Search for "data" chunk ID
-        // 2. Parse chunk size
-        // Returns (offset, size) of audio data
-        None
-    }
-}
-
-impl AudioDecoder for WavDecoder {
-    fn can_decode(&self, path: &Path) -> bool {
-        // Check if file has .wav extension
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("wav"))
-            .unwrap_or(false)
-    }
-
-    fn probe_format(&self, path: &Path) -> Result<AudioFormat, Box<dyn Error>> {
-        if !self.can_decode(path) {
-            return Err("Not a WAV file".into());
-        }
-        self.read_wav_header(path)
-    }
-
-    fn decode(&mut self, path: &Path) -> Result<AudioReader, Box<dyn Error>> {
-        if !self.can_decode(path) {
-            return Err("Not a WAV file".into());
-        }
-
-        // Read format information
-        let format = self.read_wav_header(path)?;
-
-        // TODO: Implement actual WAV decoding
-        // This would:
-        // 1. Open file and validate RIFF structure
-        // 2. Find data chunk
-        // 3. Set up sample reading based on format
-        
-        // Calculate total samples based on data size and format
-        let total_samples = 0; // Placeholder
-
-        Ok(AudioReader::new(format, total_samples))
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
-
-    fn test_path(filename: &str) -> PathBuf {
-        PathBuf::from(filename)
-    }
-
-    #[test]
-    fn test_can_decode() {
-        let decoder = WavDecoder::new();
-        assert!(decoder.can_decode(test_path("test.wav").as_path()));
-        assert!(decoder.can_decode(test_path("test.WAV").as_path()));
-        assert!(!decoder.can_decode(test_path("test.mp3").as_path()));
-        assert!(!decoder.can_decode(test_path("test").as_path()));
-    }
-
-    #[test]
-    fn test_probe_invalid_extension() {
-        let decoder = WavDecoder::new();
-        assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
-    }
-
-    #[test]
-    fn test_decode_invalid_extension() {
-        let mut decoder = WavDecoder::new();
-        assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
-    }
-}
@@ -0,0 +1,255 @@
+use std::error::Error;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+use crate::audio::AudioFormat;
+use super::source::FrameLayout;
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_ALAW: u16 = 0x0006;
+const WAVE_FORMAT_MULAW: u16 = 0x0007;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Largest fmt chunk we are willing to buffer; real ones are 16-40 bytes
+const MAX_FMT_CHUNK_SIZE: u64 = 1024;
+
+/// Sample encoding stored in the data chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavEncoding {
+    Pcm,
+    Float,
+    ALaw,
+    MuLaw,
+}
+
+/// Container flavour from the 12-byte file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiffKind {
+    Riff,
+    /// RF64 or BW64, where 64-bit sizes live in a ds64 chunk
+    Rf64,
+}
+
+/// Parsed contents of a fmt chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FmtChunk {
+    pub encoding: WavEncoding,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub block_align: u16,
+    /// Significant bits per sample, which may be fewer than the container holds
+    pub valid_bits: u16,
+    pub channel_mask: Option<u32>,
+}
+
+impl FmtChunk {
+    /// Bytes occupied by a single sample of a single channel
+    pub fn container_bytes(&self) -> usize {
+        (self.block_align / self.channels) as usize
+    }
+
+    /// How the data chunk's frames are laid out
+    pub(crate) fn frame_layout(&self) -> FrameLayout {
+        FrameLayout {
+            encoding: self.encoding,
+            container_bytes: self.container_bytes(),
+            block_align: self.block_align as usize,
+        }
+    }
+}
+
+/// Everything needed to stream samples out of a WAVE file
+#[derive(Debug, Clone)]
+pub struct WavInfo {
+    pub format: AudioFormat,
+    pub fmt: FmtChunk,
+    pub data_offset: u64,
+    pub data_len: u64,
+    /// Number of complete sample frames in the data chunk
+    pub total_samples: u64,
+}
+
+/// Check the RIFF/RF64/BW64 signature and the WAVE form type
+pub fn validate_riff_header(header: &[u8]) -> Option<RiffKind> {
+    if header.len() < 12 || &header[8..12] != b"WAVE" {
+        return None;
+    }
+    match &header[0..4] {
+        b"RIFF" => Some(RiffKind::Riff),
+        b"RF64" | b"BW64" => Some(RiffKind::Rf64),
+        _ => None,
+    }
+}
+
+/// Parse the body of a fmt chunk, resolving WAVE_FORMAT_EXTENSIBLE to its sub-format
+pub fn parse_fmt_chunk(chunk: &[u8]) -> Option<FmtChunk> {
+    if chunk.len() < 16 {
+        return None;
+    }
+
+    let mut format_tag = u16_at(chunk, 0);
+    let channels = u16_at(chunk, 2);
+    let sample_rate = u32_at(chunk, 4);
+    let block_align = u16_at(chunk, 12);
+    let bits = u16_at(chunk, 14);
+    let mut valid_bits = bits;
+    let mut channel_mask = None;
+
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        // cbSize(2) + wValidBitsPerSample(2) + dwChannelMask(4) + SubFormat GUID(16)
+        if chunk.len() < 40 {
+            return None;
+        }
+        let extension_bits = u16_at(chunk, 18);
+        if extension_bits != 0 {
+            valid_bits = extension_bits;
+        }
+        channel_mask = Some(u32_at(chunk, 20));
+        // The GUID's leading two bytes carry the classic format tag
+        format_tag = u16_at(chunk, 24);
+    }
+
+    if channels == 0 || sample_rate == 0 || block_align == 0 || !block_align.is_multiple_of(channels) {
+        return None;
+    }
+
+    let container_bytes = block_align / channels;
+    let encoding = match (format_tag, container_bytes) {
+        (WAVE_FORMAT_PCM, 1..=4) => WavEncoding::Pcm,
+        (WAVE_FORMAT_IEEE_FLOAT, 4 | 8) => WavEncoding::Float,
+        (WAVE_FORMAT_ALAW, 1) => WavEncoding::ALaw,
+        (WAVE_FORMAT_MULAW, 1) => WavEncoding::MuLaw,
+        _ => return None,
+    };
+
+    if valid_bits == 0 || valid_bits > container_bytes * 8 {
+        valid_bits = container_bytes * 8;
+    }
+
+    if let Some(mask) = channel_mask {
+        if mask != 0 && mask.count_ones() != channels as u32 {
+            log::warn!("WAV channel mask {:#x} does not match {} channels", mask, channels);
+        }
+    }
+
+    Some(FmtChunk {
+        encoding,
+        channels,
+        sample_rate,
+        block_align,
+        valid_bits,
+        channel_mask,
+    })
+}
+
+/// Read the 64-bit data chunk size from a ds64 chunk body
+fn parse_ds64_data_size(chunk: &[u8]) -> Option<u64> {
+    (chunk.len() >= 16).then(|| u64_at(chunk, 8))
+}
+
+/// Walk the chunk list until both fmt and data have been located
+pub fn find_data_chunk<R: Read + Seek>(reader: &mut R) -> Result<WavInfo, Box<dyn Error>> {
+    let mut header = [0u8; 12];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+    let kind = validate_riff_header(&header).ok_or("Not a RIFF/WAVE file")?;
+
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut ds64_data_size = None;
+    let mut fmt = None;
+    let mut data = None;
+    let mut pos = 12u64;
+
+    while pos + 8 <= file_len {
+        let mut chunk_header = [0u8; 8];
+        reader.seek(SeekFrom::Start(pos))?;
+        reader.read_exact(&mut chunk_header)?;
+
+        let body = pos + 8;
+        let available = file_len - body;
+        let declared = u32_at(&chunk_header, 4);
+        let mut size = declared as u64;
+
+        match &chunk_header[0..4] {
+            b"ds64" => {
+                // Only the fixed header matters; the optional chunk size table is skipped
+                ds64_data_size = parse_ds64_data_size(&read_body(reader, size.min(available).min(28))?);
+            }
+            b"fmt " => {
+                if size > MAX_FMT_CHUNK_SIZE || size > available {
+                    return Err("Invalid fmt chunk".into());
+                }
+                fmt = Some(parse_fmt_chunk(&read_body(reader, size)?).ok_or("Unsupported WAV format")?);
+            }
+            b"data" => {
+                if declared == u32::MAX {
+                    // RF64 keeps the real size in ds64; plain RIFF uses it for unfinished streams
+                    size = match (kind, ds64_data_size) {
+                        (RiffKind::Rf64, Some(ds64_size)) => ds64_size,
+                        (RiffKind::Rf64, None) => return Err("RF64 file is missing its ds64 chunk".into()),
+                        (RiffKind::Riff, _) => available,
+                    };
+                }
+                if size > available {
+                    log::warn!("WAV data chunk truncated: {} bytes declared, {} present", size, available);
+                    size = available;
+                }
+                data = Some((body, size));
+                if fmt.is_some() {
+                    break;
+                }
+            }
+            _ => {}
+        }
+
+        // Chunks are word aligned, odd sizes carry a pad byte
+        pos = body + size + (size & 1);
+    }
+
+    let fmt = fmt.ok_or("WAV file has no fmt chunk")?;
+    let (data_offset, data_len) = data.ok_or("WAV file has no data chunk")?;
+
+    let bits_per_sample = match fmt.encoding {
+        // Companded samples expand to 16-bit linear PCM
+        WavEncoding::ALaw | WavEncoding::MuLaw => 16,
+        WavEncoding::Pcm | WavEncoding::Float => fmt.valid_bits,
+    };
+
+    let total_samples = data_len / fmt.block_align as u64;
+    Ok(WavInfo {
+        format: AudioFormat {
+            channels: fmt.channels,
+            sample_rate: fmt.sample_rate,
+            bits_per_sample,
+            duration: Some(Duration::from_secs_f64(total_samples as f64 / fmt.sample_rate as f64)),
+            // The fmt chunk puts no ceiling on the rate, so a bogus one may not fit
+            bit_rate: u32::try_from(u64::from(fmt.sample_rate) * u64::from(fmt.block_align) * 8 / 1000).ok(),
+        },
+        total_samples,
+        fmt,
+        data_offset,
+        data_len,
+    })
+}
+
+fn read_body<R: Read>(reader: &mut R, size: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut body = vec![0u8; size as usize];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::Path;
+use crate::media::MediaSource;
+use super::{detect_source_format, AudioDecoder, AudioFormat, AudioReader, FileFormat};
+
+mod chunks;
+mod source;
+#[cfg(test)]
+mod tests;
+
+pub use chunks::{parse_fmt_chunk, validate_riff_header, FmtChunk, RiffKind, WavEncoding, WavInfo};
+pub(crate) use source::{ByteTransform, FrameLayout, PcmSource};
+
+pub struct WavDecoder {}
+
+impl WavDecoder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Walk the RIFF chunks and describe the stream without reading samples
+    pub fn read_info(&self, path: &Path) -> Result<WavInfo, Box<dyn Error>> {
+        self.read_source_info(&MediaSource::from_path(path))
+    }
+
+    /// Describe a source as `read_info` does a file
+    pub fn read_source_info(&self, source: &MediaSource) -> Result<WavInfo, Box<dyn Error>> {
+        let mut reader = BufReader::new(source.open()?);
+        chunks::find_data_chunk(&mut reader)
+    }
+}
+
+impl AudioDecoder for WavDecoder {
+    fn can_decode_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Wav))
+    }
+
+    fn probe_source(&self, source: &MediaSource) -> Result<AudioFormat, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not a WAV file".into());
+        }
+        Ok(self.read_source_info(source)?.format)
+    }
+
+    fn decode_source(&mut self, source: &MediaSource) -> Result<AudioReader, Box<dyn Error>> {
+        if !self.can_decode_source(source) {
+            return Err("Not a WAV file".into());
+        }
+
+        let mut reader = BufReader::new(source.open()?);
+        let info = chunks::find_data_chunk(&mut reader)?;
+        reader.seek(SeekFrom::Start(info.data_offset))?;
+
+        let source = PcmSource::new(reader, info.fmt.frame_layout(), None, info.data_offset, info.data_len);
+        Ok(AudioReader::with_source(info.format, info.total_samples, Box::new(source)))
+    }
+}
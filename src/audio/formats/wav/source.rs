@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::io::{Read, Seek, SeekFrom};
+use crate::audio::formats::SampleSource;
+use super::chunks::WavEncoding;
+
+/// Sample frames converted per packet
+const FRAMES_PER_PACKET: u64 = 1024;
+
+/// How samples are stored in a stream of fixed-size frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameLayout {
+    pub encoding: WavEncoding,
+    /// Bytes occupied by a single sample of a single channel
+    pub container_bytes: usize,
+    /// Bytes occupied by one sample frame
+    pub block_align: usize,
+}
+
+/// Rewrites sample bytes in place into the layout `decode_samples` reads
+pub(crate) type ByteTransform = fn(FrameLayout, &mut [u8]);
+
+/// Streams fixed-size PCM frames, as WAVE and AIFF files store them, as normalized f32 samples
+pub(crate) struct PcmSource<R> {
+    reader: R,
+    layout: FrameLayout,
+    transform: Option<ByteTransform>,
+    /// Where the first sample frame starts, and the bytes of sample data from there
+    data_offset: u64,
+    data_len: u64,
+    remaining: u64,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read + Seek + Send> PcmSource<R> {
+    /// `reader` must already be positioned at the first sample frame
+    pub(crate) fn new(reader: R, layout: FrameLayout, transform: Option<ByteTransform>, data_offset: u64, data_len: u64) -> Self {
+        Self {
+            reader,
+            layout,
+            transform,
+            data_offset,
+            data_len,
+            remaining: data_len,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Fill `self.bytes` as far as the reader allows, returning the byte count
+    fn read_block(&mut self, len: usize) -> Result<usize, Box<dyn Error>> {
+        self.bytes.resize(len, 0);
+        let mut filled = 0;
+        while filled < len {
+            match self.reader.read(&mut self.bytes[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        Ok(filled)
+    }
+}
+
+impl<R: Read + Seek + Send> SampleSource for PcmSource<R> {
+    fn next_packet(&mut self, out: &mut Vec<f32>) -> Result<bool, Box<dyn Error>> {
+        let block_align = self.layout.block_align as u64;
+        let frames = (self.remaining / block_align).min(FRAMES_PER_PACKET);
+        if frames == 0 {
+            return Ok(false);
+        }
+
+        let wanted = (frames * block_align) as usize;
+        let filled = self.read_block(wanted)?;
+        // Drop any trailing partial frame if the file ends early
+        let usable = filled - filled % block_align as usize;
+        self.remaining = if filled < wanted { 0 } else { self.remaining - wanted as u64 };
+        if usable == 0 {
+            return Ok(false);
+        }
+
+        if let Some(transform) = self.transform {
+            transform(self.layout, &mut self.bytes[..usable]);
+        }
+        decode_samples(self.layout.encoding, self.layout.container_bytes, &self.bytes[..usable], out);
+        Ok(true)
+    }
+
+    /// Frames are a fixed size, so any frame is a direct offset into the sample data
+    fn seek(&mut self, frame: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        let offset = (frame * self.layout.block_align as u64).min(self.data_len);
+        self.reader.seek(SeekFrom::Start(self.data_offset + offset))?;
+        self.remaining = self.data_len - offset;
+        Ok(Some(frame))
+    }
+}
+
+/// Convert little-endian sample bytes to normalized f32
+pub(crate) fn decode_samples(encoding: WavEncoding, container_bytes: usize, bytes: &[u8], out: &mut Vec<f32>) {
+    out.reserve(bytes.len() / container_bytes);
+    let samples = bytes.chunks_exact(container_bytes);
+
+    match (encoding, container_bytes) {
+        // 8-bit PCM is the one unsigned variant
+        (WavEncoding::Pcm, 1) => out.extend(samples.map(|s| (s[0] as f32 - 128.0) / 128.0)),
+        (WavEncoding::Pcm, 2) => out.extend(samples.map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)),
+        // Left-justify 24-bit samples so they share the 32-bit scale
+        (WavEncoding::Pcm, 3) => out.extend(samples.map(|s| i32::from_le_bytes([0, s[0], s[1], s[2]]) as f32 / 2147483648.0)),
+        (WavEncoding::Pcm, _) => out.extend(samples.map(|s| i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2147483648.0)),
+        (WavEncoding::Float, 4) => out.extend(samples.map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]))),
+        (WavEncoding::Float, _) => out.extend(samples.map(|s| {
+            f64::from_le_bytes([s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7]]) as f32
+        })),
+        (WavEncoding::ALaw, _) => out.extend(samples.map(|s| alaw_to_linear(s[0]) as f32 / 32768.0)),
+        (WavEncoding::MuLaw, _) => out.extend(samples.map(|s| mulaw_to_linear(s[0]) as f32 / 32768.0)),
+    }
+}
+
+/// G.711 A-law expansion to 16-bit linear PCM
+fn alaw_to_linear(value: u8) -> i16 {
+    let value = value ^ 0x55;
+    let segment = (value & 0x70) >> 4;
+    let mut magnitude = ((value & 0x0F) as i16) << 4;
+    magnitude += match segment {
+        0 => 8,
+        _ => 0x108,
+    };
+    if segment > 1 {
+        magnitude <<= segment - 1;
+    }
+    if value & 0x80 != 0 { magnitude } else { -magnitude }
+}
+
+/// G.711 µ-law expansion to 16-bit linear PCM
+fn mulaw_to_linear(value: u8) -> i16 {
+    const BIAS: i16 = 0x84;
+    let value = !value;
+    let magnitude = ((((value & 0x0F) as i16) << 3) + BIAS) << ((value & 0x70) >> 4);
+    if value & 0x80 != 0 { BIAS - magnitude } else { magnitude - BIAS }
+}
@@ -0,0 +1,289 @@
+use super::*;
+use std::path::PathBuf;
+
+fn test_path(filename: &str) -> PathBuf {
+    PathBuf::from(filename)
+}
+
+#[test]
+fn test_can_decode() {
+    let decoder = WavDecoder::new();
+    assert!(decoder.can_decode(test_path("test.wav").as_path()));
+    assert!(decoder.can_decode(test_path("test.WAV").as_path()));
+    assert!(!decoder.can_decode(test_path("test.mp3").as_path()));
+    assert!(!decoder.can_decode(test_path("test").as_path()));
+}
+
+#[test]
+fn test_probe_invalid_extension() {
+    let decoder = WavDecoder::new();
+    assert!(decoder.probe_format(test_path("test.mp3").as_path()).is_err());
+}
+
+#[test]
+fn test_decode_invalid_extension() {
+    let mut decoder = WavDecoder::new();
+    assert!(decoder.decode(test_path("test.mp3").as_path()).is_err());
+}
+
+fn fmt_body(format_tag: u16, channels: u16, sample_rate: u32, bits: u16) -> Vec<u8> {
+    let block_align = channels * bits.div_ceil(8);
+    let mut body = Vec::new();
+    body.extend(format_tag.to_le_bytes());
+    body.extend(channels.to_le_bytes());
+    body.extend(sample_rate.to_le_bytes());
+    body.extend(sample_rate.wrapping_mul(block_align as u32).to_le_bytes());
+    body.extend(block_align.to_le_bytes());
+    body.extend(bits.to_le_bytes());
+    body
+}
+
+fn extensible_fmt_body(sub_format: u16, channels: u16, container_bits: u16, valid_bits: u16, mask: u32) -> Vec<u8> {
+    let mut body = fmt_body(0xFFFE, channels, 48000, container_bits);
+    body.extend(22u16.to_le_bytes());
+    body.extend(valid_bits.to_le_bytes());
+    body.extend(mask.to_le_bytes());
+    body.extend(sub_format.to_le_bytes());
+    body.extend([0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71]);
+    body
+}
+
+fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    chunk_with_size(id, body.len() as u32, body)
+}
+
+fn chunk_with_size(id: &[u8; 4], size: u32, body: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend(size.to_le_bytes());
+    bytes.extend(body);
+    if body.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn riff(id: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+    let body: Vec<u8> = chunks.concat();
+    let mut bytes = id.to_vec();
+    bytes.extend((body.len() as u32 + 4).to_le_bytes());
+    bytes.extend(b"WAVE");
+    bytes.extend(body);
+    bytes
+}
+
+fn write_wav(bytes: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".wav").tempfile().unwrap();
+    std::io::Write::write_all(&mut file, bytes).unwrap();
+    file
+}
+
+fn decode_all(bytes: &[u8]) -> (AudioReader, Vec<f32>) {
+    let file = write_wav(bytes);
+    let mut reader = WavDecoder::new().decode(file.path()).unwrap();
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 64];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+    (reader, samples)
+}
+
+fn decode_pcm(format_tag: u16, bits: u16, data: &[u8]) -> Vec<f32> {
+    let bytes = riff(b"RIFF", &[chunk(b"fmt ", &fmt_body(format_tag, 1, 8000, bits)), chunk(b"data", data)]);
+    decode_all(&bytes).1
+}
+
+#[test]
+fn test_probe_fixture() {
+    let path = Path::new("test/testaudio-short.wav");
+    let decoder = WavDecoder::new();
+    let format = decoder.probe_format(path).unwrap();
+    assert_eq!(format.channels, 1);
+    assert_eq!(format.sample_rate, 48000);
+    assert_eq!(format.bits_per_sample, 16);
+
+    let info = decoder.read_info(path).unwrap();
+    assert_eq!(info.fmt.encoding, WavEncoding::Pcm);
+    assert_eq!(info.total_samples, 48000);
+    assert_eq!(info.data_len, 96000);
+}
+
+#[test]
+fn test_validate_riff_header() {
+    assert_eq!(validate_riff_header(b"RIFF\0\0\0\0WAVE"), Some(RiffKind::Riff));
+    assert_eq!(validate_riff_header(b"RF64\xff\xff\xff\xffWAVE"), Some(RiffKind::Rf64));
+    assert_eq!(validate_riff_header(b"BW64\xff\xff\xff\xffWAVE"), Some(RiffKind::Rf64));
+    assert_eq!(validate_riff_header(b"RIFF\0\0\0\0AVI "), None);
+    assert_eq!(validate_riff_header(b"RIFX\0\0\0\0WAVE"), None);
+    assert_eq!(validate_riff_header(b"RIFF"), None);
+}
+
+#[test]
+fn test_parse_fmt_chunk() {
+    let fmt = parse_fmt_chunk(&fmt_body(1, 2, 44100, 16)).unwrap();
+    assert_eq!(fmt.encoding, WavEncoding::Pcm);
+    assert_eq!(fmt.channels, 2);
+    assert_eq!(fmt.sample_rate, 44100);
+    assert_eq!(fmt.block_align, 4);
+    assert_eq!(fmt.valid_bits, 16);
+    assert_eq!(fmt.channel_mask, None);
+
+    assert_eq!(parse_fmt_chunk(&fmt_body(3, 1, 48000, 64)).unwrap().encoding, WavEncoding::Float);
+    assert_eq!(parse_fmt_chunk(&fmt_body(6, 1, 8000, 8)).unwrap().encoding, WavEncoding::ALaw);
+    assert_eq!(parse_fmt_chunk(&fmt_body(7, 1, 8000, 8)).unwrap().encoding, WavEncoding::MuLaw);
+
+    // ADPCM, a truncated chunk and zero channels are all rejected
+    assert!(parse_fmt_chunk(&fmt_body(2, 1, 8000, 4)).is_none());
+    assert!(parse_fmt_chunk(&fmt_body(1, 2, 44100, 16)[..14]).is_none());
+    assert!(parse_fmt_chunk(&fmt_body(1, 0, 44100, 16)).is_none());
+}
+
+#[test]
+fn test_parse_extensible_fmt_chunk() {
+    let fmt = parse_fmt_chunk(&extensible_fmt_body(1, 6, 32, 24, 0x3F)).unwrap();
+    assert_eq!(fmt.encoding, WavEncoding::Pcm);
+    assert_eq!(fmt.channels, 6);
+    assert_eq!(fmt.container_bytes(), 4);
+    assert_eq!(fmt.valid_bits, 24);
+    assert_eq!(fmt.channel_mask, Some(0x3F));
+
+    let fmt = parse_fmt_chunk(&extensible_fmt_body(3, 2, 32, 32, 0x3)).unwrap();
+    assert_eq!(fmt.encoding, WavEncoding::Float);
+
+    // Missing the extension fields
+    assert!(parse_fmt_chunk(&extensible_fmt_body(1, 2, 16, 16, 0x3)[..24]).is_none());
+}
+
+#[test]
+fn test_decode_integer_pcm() {
+    assert_eq!(decode_pcm(1, 8, &[0, 128, 192]), vec![-1.0, 0.0, 0.5]);
+
+    let data: Vec<u8> = [i16::MIN, 0, 16384].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(1, 16, &data), vec![-1.0, 0.0, 0.5]);
+
+    let data = [0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40];
+    assert_eq!(decode_pcm(1, 24, &data), vec![-1.0, 0.0, 0.5]);
+
+    let data: Vec<u8> = [i32::MIN, 0, 1 << 30].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(1, 32, &data), vec![-1.0, 0.0, 0.5]);
+}
+
+#[test]
+fn test_decode_float_pcm() {
+    let data: Vec<u8> = [0.25f32, -0.5].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(3, 32, &data), vec![0.25, -0.5]);
+
+    let data: Vec<u8> = [0.75f64, -1.0].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(3, 64, &data), vec![0.75, -1.0]);
+}
+
+#[test]
+fn test_decode_companded() {
+    let scale = 32768.0;
+    assert_eq!(decode_pcm(6, 8, &[0xD5, 0x55, 0xAA, 0x2A]), vec![8.0 / scale, -8.0 / scale, 32256.0 / scale, -32256.0 / scale]);
+    assert_eq!(decode_pcm(7, 8, &[0xFF, 0x80, 0x00]), vec![0.0, 32124.0 / scale, -32124.0 / scale]);
+
+    let file = write_wav(&riff(b"RIFF", &[chunk(b"fmt ", &fmt_body(7, 1, 8000, 8)), chunk(b"data", &[0xFF])]));
+    assert_eq!(WavDecoder::new().probe_format(file.path()).unwrap().bits_per_sample, 16);
+}
+
+#[test]
+fn test_extensible_24_in_32() {
+    let data: Vec<u8> = [0x7FFF_FF00i32, -0x8000_0000].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let bytes = riff(b"RIFF", &[chunk(b"fmt ", &extensible_fmt_body(1, 2, 32, 24, 0x3)), chunk(b"data", &data)]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.format.channels, 2);
+    assert_eq!(reader.format.bits_per_sample, 24);
+    assert_eq!(reader.total_samples, 1);
+    assert_eq!(samples, vec![0x7FFF_FF00 as f32 / 2147483648.0, -1.0]);
+}
+
+#[test]
+fn test_odd_chunk_padding() {
+    let data: Vec<u8> = [1000i16, -1000].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let bytes = riff(b"RIFF", &[
+        chunk(b"LIST", b"odd"),
+        chunk(b"fmt ", &fmt_body(1, 1, 8000, 16)),
+        chunk(b"junk", b"x"),
+        chunk(b"data", &data),
+    ]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 2);
+    assert_eq!(samples, vec![1000.0 / 32768.0, -1000.0 / 32768.0]);
+}
+
+#[test]
+fn test_odd_sized_data_before_trailing_chunk() {
+    let bytes = riff(b"RIFF", &[
+        chunk(b"fmt ", &fmt_body(1, 1, 8000, 8)),
+        chunk(b"data", &[128, 255, 0]),
+        chunk(b"LIST", b"INFO"),
+    ]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 3);
+    assert_eq!(samples.len(), 3);
+}
+
+#[test]
+fn test_truncated_data_chunk() {
+    // Header promises 100 stereo frames but the file ends after 10 and a half
+    let data = vec![0u8; 42];
+    let bytes = riff(b"RIFF", &[chunk(b"fmt ", &fmt_body(1, 2, 8000, 16)), chunk_with_size(b"data", 400, &data)]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 10);
+    assert_eq!(samples.len(), 20);
+}
+
+#[test]
+fn test_extreme_sample_rate() {
+    let bytes = riff(b"RIFF", &[chunk(b"fmt ", &fmt_body(1, 64, u32::MAX, 32)), chunk(b"data", &[0; 512])]);
+    let file = write_wav(&bytes);
+    let info = WavDecoder::new().read_info(file.path()).unwrap();
+    assert_eq!(info.format.sample_rate, u32::MAX);
+    assert_eq!(info.format.bit_rate, None);
+    assert_eq!(info.total_samples, 2);
+}
+
+#[test]
+fn test_rf64() {
+    let data: Vec<u8> = [100i16, 200, 300].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mut ds64 = Vec::new();
+    ds64.extend(0u64.to_le_bytes()); // RIFF size, unused by the reader
+    ds64.extend((data.len() as u64).to_le_bytes());
+    ds64.extend(3u64.to_le_bytes());
+    ds64.extend(0u32.to_le_bytes());
+
+    let bytes = riff(b"RF64", &[
+        chunk(b"ds64", &ds64),
+        chunk(b"fmt ", &fmt_body(1, 1, 8000, 16)),
+        chunk_with_size(b"data", u32::MAX, &data),
+    ]);
+    let (reader, samples) = decode_all(&bytes);
+    assert_eq!(reader.total_samples, 3);
+    assert_eq!(samples, vec![100.0 / 32768.0, 200.0 / 32768.0, 300.0 / 32768.0]);
+}
+
+#[test]
+fn test_rf64_without_ds64() {
+    let bytes = riff(b"RF64", &[
+        chunk(b"fmt ", &fmt_body(1, 1, 8000, 16)),
+        chunk_with_size(b"data", u32::MAX, &[0, 0]),
+    ]);
+    let file = write_wav(&bytes);
+    assert!(WavDecoder::new().probe_format(file.path()).is_err());
+}
+
+#[test]
+fn test_missing_chunks() {
+    let no_data = riff(b"RIFF", &[chunk(b"fmt ", &fmt_body(1, 1, 8000, 16))]);
+    assert!(WavDecoder::new().probe_format(write_wav(&no_data).path()).is_err());
+
+    let no_fmt = riff(b"RIFF", &[chunk(b"data", &[0, 0])]);
+    assert!(WavDecoder::new().probe_format(write_wav(&no_fmt).path()).is_err());
+
+    assert!(WavDecoder::new().probe_format(write_wav(b"not a wave file").path()).is_err());
+}
@@ -9,12 +9,45 @@ pub enum PlaybackState {
     Stopped,
 }
 
+/// Where to seek to, resolved against the current position and track length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeekTarget {
+    Absolute(Duration),
+    Forward(Duration),
+    Backward(Duration),
+    /// Share of the track length, from 0 to 100
+    Percent(f32),
+}
+
+impl SeekTarget {
+    /// The position to seek to, kept within the track where its length is known
+    pub fn resolve(self, position: Duration, duration: Option<Duration>) -> Result<Duration, Box<dyn Error>> {
+        let target = match self {
+            SeekTarget::Absolute(target) => target,
+            SeekTarget::Forward(step) => position + step,
+            SeekTarget::Backward(step) => position.saturating_sub(step),
+            SeekTarget::Percent(percent) => {
+                let duration = duration.ok_or("Track length is unknown")?;
+                if !percent.is_finite() {
+                    return Err("Seek percentage is not a number".into());
+                }
+                duration.mul_f64(percent.clamp(0.0, 100.0) as f64 / 100.0)
+            }
+        };
+        Ok(duration.map_or(target, |duration| target.min(duration)))
+    }
+}
+
 /// Represents audio format metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AudioFormat {
     pub channels: u16,
     pub sample_rate: u32,
     pub bits_per_sample: u16,
+    /// Playable length, when the container reports it
+    pub duration: Option<Duration>,
+    /// Average bitrate in kbps
+    pub bit_rate: Option<u32>,
 }
 
 /// Core trait for audio playback functionality
@@ -63,5 +96,17 @@ pub trait AudioStream {
 }
 
 pub mod player;
+pub mod ring_buffer;
+pub mod convert;
+pub mod fade;
+pub mod replaygain;
+pub mod volume;
+pub mod speed;
+pub mod cue;
+pub mod record;
+pub mod tap;
+pub mod eq;
+pub mod dsp;
+pub mod output;
 pub mod stream;
 pub mod formats;
@@ -0,0 +1,78 @@
+use std::error::Error;
+use alsa::pcm::{Access, Format, HwParams, PCM};
+use alsa::{Direction, ValueOr};
+use crate::audio::{AudioFormat, AudioStream};
+
+/// Device buffer length, long enough to ride out a busy decode thread
+const BUFFER_TIME_US: u32 = 200_000;
+
+/// Plays through an ALSA PCM device, `default` unless told otherwise
+pub struct AlsaSink {
+    device: String,
+    pcm: Option<PCM>,
+    channels: usize,
+    samples: Vec<i16>,
+}
+
+impl AlsaSink {
+    pub fn new(device: Option<&str>) -> Self {
+        Self {
+            device: device.unwrap_or("default").to_string(),
+            pcm: None,
+            channels: 0,
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl AudioStream for AlsaSink {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        if format.bits_per_sample != 16 {
+            return Err("ALSA output expects 16-bit samples".into());
+        }
+
+        let pcm = PCM::new(&self.device, Direction::Playback, false)?;
+        {
+            let params = HwParams::any(&pcm)?;
+            params.set_access(Access::RWInterleaved)?;
+            params.set_format(Format::s16())?;
+            params.set_channels(format.channels as u32)?;
+            params.set_rate(format.sample_rate, ValueOr::Nearest)?;
+            params.set_buffer_time_near(BUFFER_TIME_US, ValueOr::Nearest)?;
+            pcm.hw_params(&params)?;
+        }
+        pcm.prepare()?;
+
+        self.channels = format.channels as usize;
+        self.pcm = Some(pcm);
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        let pcm = self.pcm.as_ref().ok_or("Stream not open")?;
+
+        // Incoming bytes are little-endian; ALSA wants native-endian samples
+        let frame_bytes = self.channels * 2;
+        let usable = data.len() / frame_bytes * frame_bytes;
+        self.samples.clear();
+        self.samples.extend(data[..usable].chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])));
+
+        let io = pcm.io_i16()?;
+        let frames = match io.writei(&self.samples) {
+            Ok(frames) => frames,
+            Err(e) => {
+                // Underruns are routine after a stall; recover and try once more
+                pcm.try_recover(e, true)?;
+                io.writei(&self.samples)?
+            }
+        };
+        Ok(frames * frame_bytes)
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(pcm) = self.pcm.take() {
+            pcm.drain()?;
+        }
+        Ok(())
+    }
+}
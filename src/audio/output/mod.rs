@@ -0,0 +1,130 @@
+use std::error::Error;
+use crate::audio::{AudioFormat, AudioStream};
+
+mod null;
+mod wav_file;
+#[cfg(feature = "alsa")]
+mod alsa;
+#[cfg(feature = "pacat")]
+mod pacat;
+#[cfg(test)]
+mod tests;
+
+pub use null::NullSink;
+pub use wav_file::WavFileSink;
+pub(crate) use wav_file::wav_header;
+#[cfg(feature = "alsa")]
+pub use self::alsa::AlsaSink;
+#[cfg(feature = "pacat")]
+pub use pacat::PacatSink;
+
+/// Environment variable that overrides the configured output, e.g. `PLAYTUI_OUTPUT=wav:out.wav`
+pub const OUTPUT_ENV: &str = "PLAYTUI_OUTPUT";
+
+/// Output used when nothing is configured: the first device backend built in, else `null`
+pub const DEFAULT_OUTPUT: &str = "auto";
+
+/// Enum to handle the different output backends
+pub enum OutputBackend {
+    Null(NullSink),
+    WavFile(WavFileSink),
+    #[cfg(feature = "alsa")]
+    Alsa(AlsaSink),
+    #[cfg(feature = "pacat")]
+    Pacat(PacatSink),
+}
+
+impl OutputBackend {
+    /// Build a backend from a spec such as `null`, `wav:out.wav`, `alsa:hw:0` or `pacat`
+    pub fn from_spec(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (spec, None),
+        };
+
+        match (name, arg) {
+            ("auto", None) => Self::from_spec(available_backends()[0]),
+            ("null", None) => Ok(Self::Null(NullSink::new())),
+            ("wav", Some(path)) if !path.is_empty() => Ok(Self::WavFile(WavFileSink::new(path))),
+            ("wav", _) => Err("The wav output needs a file, e.g. wav:out.wav".into()),
+            #[cfg(feature = "alsa")]
+            ("alsa", device) => Ok(Self::Alsa(AlsaSink::new(device))),
+            #[cfg(feature = "pacat")]
+            ("pacat", None) => Ok(Self::Pacat(PacatSink::new())),
+            _ => Err(format!(
+                "Unknown audio output '{}'; available: auto, {}",
+                spec,
+                available_backends().join(", "),
+            ).into()),
+        }
+    }
+
+    /// Registry name of this backend
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Null(_) => "null",
+            Self::WavFile(_) => "wav",
+            #[cfg(feature = "alsa")]
+            Self::Alsa(_) => "alsa",
+            #[cfg(feature = "pacat")]
+            Self::Pacat(_) => "pacat",
+        }
+    }
+}
+
+/// Backends compiled into this build, most preferred first
+pub fn available_backends() -> Vec<&'static str> {
+    let mut backends = Vec::new();
+    if cfg!(feature = "pacat") {
+        backends.push("pacat");
+    }
+    if cfg!(feature = "alsa") {
+        backends.push("alsa");
+    }
+    backends.extend(["null", "wav"]);
+    backends
+}
+
+/// The output spec to use: the environment override if set, else the configured one
+pub fn output_spec(configured: Option<&str>) -> String {
+    std::env::var(OUTPUT_ENV)
+        .ok()
+        .filter(|spec| !spec.is_empty())
+        .or_else(|| configured.map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_OUTPUT.to_string())
+}
+
+impl AudioStream for OutputBackend {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Null(sink) => sink.open(format),
+            Self::WavFile(sink) => sink.open(format),
+            #[cfg(feature = "alsa")]
+            Self::Alsa(sink) => sink.open(format),
+            #[cfg(feature = "pacat")]
+            Self::Pacat(sink) => sink.open(format),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        match self {
+            Self::Null(sink) => sink.write(data),
+            Self::WavFile(sink) => sink.write(data),
+            #[cfg(feature = "alsa")]
+            Self::Alsa(sink) => sink.write(data),
+            #[cfg(feature = "pacat")]
+            Self::Pacat(sink) => sink.write(data),
+        }
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Null(sink) => sink.close(),
+            Self::WavFile(sink) => sink.close(),
+            #[cfg(feature = "alsa")]
+            Self::Alsa(sink) => sink.close(),
+            #[cfg(feature = "pacat")]
+            Self::Pacat(sink) => sink.close(),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+use crate::audio::{AudioFormat, AudioStream};
+
+/// How far ahead of the wall clock writes may run, like a device buffer
+const BUFFER_AHEAD: Duration = Duration::from_millis(50);
+
+/// Discards audio while consuming it at the rate a real device would
+pub struct NullSink {
+    format: Option<AudioFormat>,
+    started: Instant,
+    /// Bytes accepted since the stream was opened
+    bytes_written: u64,
+}
+
+impl NullSink {
+    pub fn new() -> Self {
+        Self {
+            format: None,
+            started: Instant::now(),
+            bytes_written: 0,
+        }
+    }
+
+    fn bytes_per_second(format: &AudioFormat) -> u64 {
+        format.sample_rate as u64 * format.channels as u64 * (format.bits_per_sample as u64).div_ceil(8)
+    }
+}
+
+impl Default for NullSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioStream for NullSink {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        if Self::bytes_per_second(&format) == 0 {
+            return Err("Invalid output format".into());
+        }
+        self.format = Some(format);
+        self.started = Instant::now();
+        self.bytes_written = 0;
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        let format = self.format.as_ref().ok_or("Stream not open")?;
+        self.bytes_written += data.len() as u64;
+
+        // Pace against the time the stream was opened so rounding never accumulates
+        let played = Duration::from_secs_f64(self.bytes_written as f64 / Self::bytes_per_second(format) as f64);
+        let due = self.started + played.saturating_sub(BUFFER_AHEAD);
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        } else if now - due > BUFFER_AHEAD * 4 {
+            // We were starved for a while; don't rush to catch up afterwards
+            self.started = now - played.saturating_sub(BUFFER_AHEAD);
+        }
+        Ok(data.len())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.format = None;
+        Ok(())
+    }
+}
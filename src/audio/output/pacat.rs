@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use crate::audio::{AudioFormat, AudioStream};
+
+/// Latency asked of the sound server; writes block once this much is queued
+const LATENCY_MS: u32 = 100;
+
+/// Plays through PulseAudio, or PipeWire's Pulse service, by piping raw PCM into a `pacat`
+/// child process. This is not a libpulse client: it needs `pacat` on the `PATH`, always
+/// plays to the default sink, and only knows how much is buffered through `pacat`'s latency
+pub struct PacatSink {
+    child: Option<(Child, ChildStdin)>,
+}
+
+impl PacatSink {
+    pub fn new() -> Self {
+        Self { child: None }
+    }
+}
+
+impl Default for PacatSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `pacat`'s name for little-endian integer samples of a bit depth
+fn sample_format(bits_per_sample: u16) -> Result<&'static str, Box<dyn Error>> {
+    match bits_per_sample {
+        16 => Ok("s16le"),
+        24 => Ok("s24le"),
+        32 => Ok("s32le"),
+        bits => Err(format!("pacat output cannot play {}-bit samples", bits).into()),
+    }
+}
+
+impl AudioStream for PacatSink {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        let sample_format = sample_format(format.bits_per_sample)?;
+        self.close()?;
+
+        let mut child = Command::new("pacat")
+            .args(["--playback", "--raw", "--client-name=playtui"])
+            .arg(format!("--format={}", sample_format))
+            .arg(format!("--rate={}", format.sample_rate))
+            .arg(format!("--channels={}", format.channels))
+            .arg(format!("--latency-msec={}", LATENCY_MS))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start pacat, is it installed? {}", e))?;
+        let stdin = child.stdin.take().ok_or("pacat has no stdin")?;
+        self.child = Some((child, stdin));
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        let (_, stdin) = self.child.as_mut().ok_or("Stream not open")?;
+        stdin.write_all(data)?;
+        Ok(data.len())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some((mut child, stdin)) = self.child.take() {
+            // Closing stdin lets pacat drain what it has buffered and exit
+            drop(stdin);
+            child.wait()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_formats() {
+        assert_eq!(sample_format(16).unwrap(), "s16le");
+        assert_eq!(sample_format(24).unwrap(), "s24le");
+        assert_eq!(sample_format(32).unwrap(), "s32le");
+        assert!(sample_format(8).is_err());
+    }
+}
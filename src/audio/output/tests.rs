@@ -0,0 +1,171 @@
+use super::*;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::audio::formats::{AudioDecoder, AudioReader};
+use crate::audio::formats::wav::WavDecoder;
+use crate::audio::player::{EngineEvent, PlaybackEngine};
+use crate::audio::AudioPlayer;
+use crate::events::SystemEvent;
+
+fn pcm_format(channels: u16, sample_rate: u32) -> AudioFormat {
+    AudioFormat {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        ..Default::default()
+    }
+}
+
+fn read_all(mut reader: AudioReader) -> Vec<f32> {
+    let mut samples = Vec::new();
+    let mut chunk = [0.0f32; 1024];
+    loop {
+        let read = reader.read(&mut chunk).unwrap();
+        if read == 0 {
+            return samples;
+        }
+        samples.extend_from_slice(&chunk[..read]);
+    }
+}
+
+#[test]
+fn test_from_spec() {
+    assert_eq!(OutputBackend::from_spec("null").unwrap().name(), "null");
+    assert_eq!(OutputBackend::from_spec("wav:out.wav").unwrap().name(), "wav");
+    assert_eq!(OutputBackend::from_spec("auto").unwrap().name(), available_backends()[0]);
+
+    assert!(OutputBackend::from_spec("wav").is_err());
+    assert!(OutputBackend::from_spec("wav:").is_err());
+    assert!(OutputBackend::from_spec("null:extra").is_err());
+    assert!(OutputBackend::from_spec("jack").is_err());
+}
+
+#[test]
+fn test_available_backends() {
+    let backends = available_backends();
+    assert!(backends.contains(&"null"));
+    assert!(backends.contains(&"wav"));
+    assert_eq!(backends.contains(&"alsa"), cfg!(feature = "alsa"));
+    assert_eq!(backends.contains(&"pacat"), cfg!(feature = "pacat"));
+}
+
+#[test]
+fn test_output_spec_env_override() {
+    std::env::remove_var(OUTPUT_ENV);
+    assert_eq!(output_spec(None), DEFAULT_OUTPUT);
+    assert_eq!(output_spec(Some("wav:a.wav")), "wav:a.wav");
+
+    std::env::set_var(OUTPUT_ENV, "null");
+    assert_eq!(output_spec(Some("wav:a.wav")), "null");
+    std::env::remove_var(OUTPUT_ENV);
+}
+
+#[test]
+fn test_null_sink_paces_in_real_time() {
+    let mut sink = NullSink::new();
+    assert!(sink.write(&[0; 4]).is_err());
+    assert!(sink.open(pcm_format(2, 0)).is_err());
+
+    sink.open(pcm_format(2, 8000)).unwrap();
+    let started = Instant::now();
+    // 250ms of stereo 16-bit audio in 10ms writes
+    for _ in 0..25 {
+        assert_eq!(sink.write(&[0; 320]).unwrap(), 320);
+    }
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+}
+
+#[test]
+fn test_wav_sink_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.wav");
+    let samples: Vec<i16> = vec![0, 16384, -32768, 32767, -1, 1];
+    let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+    let mut sink = WavFileSink::new(&path);
+    assert!(sink.write(&bytes).is_err());
+    sink.open(pcm_format(2, 44100)).unwrap();
+    sink.write(&bytes[..4]).unwrap();
+    sink.close().unwrap();
+
+    // Reopening with the same layout keeps appending to the same file
+    sink.open(pcm_format(2, 44100)).unwrap();
+    sink.write(&bytes[4..]).unwrap();
+    sink.close().unwrap();
+
+    let file = std::fs::read(&path).unwrap();
+    assert_eq!(file.len(), 44 + bytes.len());
+    assert_eq!(&file[44..], &bytes[..]);
+
+    let reader = WavDecoder::new().decode(&path).unwrap();
+    assert_eq!(reader.format.channels, 2);
+    assert_eq!(reader.format.sample_rate, 44100);
+    assert_eq!(reader.total_samples, 3);
+    let expected: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+    assert_eq!(read_all(reader), expected);
+}
+
+#[test]
+fn test_wav_sink_starts_new_file_on_format_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mixed.wav");
+    let mut sink = WavFileSink::new(&path);
+    sink.open(pcm_format(2, 44100)).unwrap();
+    sink.write(&[1, 0, 2, 0]).unwrap();
+
+    // A track at another rate carries on in a second file, leaving the first one whole
+    sink.open(pcm_format(2, 48000)).unwrap();
+    assert_eq!(sink.current_path(), dir.path().join("mixed-2.wav"));
+    sink.write(&[3, 0, 4, 0, 5, 0, 6, 0]).unwrap();
+    sink.close().unwrap();
+
+    let first = WavDecoder::new().decode(&path).unwrap();
+    assert_eq!((first.format.sample_rate, first.total_samples), (44100, 1));
+    let second = WavDecoder::new().decode(&sink.current_path()).unwrap();
+    assert_eq!((second.format.sample_rate, second.total_samples), (48000, 2));
+
+    // Back at the first rate is a third file, as the second is still at 48kHz
+    sink.open(pcm_format(2, 44100)).unwrap();
+    assert_eq!(sink.current_path(), dir.path().join("mixed-3.wav"));
+}
+
+#[test]
+fn test_wav_sink_finalizes_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dropped.wav");
+    {
+        let mut sink = WavFileSink::new(&path);
+        sink.open(pcm_format(1, 8000)).unwrap();
+        sink.write(&[1, 0, 2, 0]).unwrap();
+    }
+    let file = std::fs::read(&path).unwrap();
+    assert_eq!(u32::from_le_bytes([file[40], file[41], file[42], file[43]]), 4);
+    assert_eq!(u32::from_le_bytes([file[4], file[5], file[6], file[7]]), 40);
+}
+
+#[test]
+fn test_render_through_engine() {
+    // Rendering a 16-bit file through the engine reproduces it exactly
+    let fixture = Path::new("test/testaudio-short.wav");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("render.wav");
+
+    let mut engine = PlaybackEngine::with_output(&format!("wav:{}", path.display())).unwrap();
+    engine.load(fixture.to_str().unwrap()).unwrap();
+    engine.play().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while engine.try_recv_event() != Some(EngineEvent::System(SystemEvent::TrackEnded)) {
+        assert!(Instant::now() < deadline, "playback did not finish");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    drop(engine);
+
+    let rendered = WavDecoder::new().decode(&path).unwrap();
+    let original = WavDecoder::new().decode(fixture).unwrap();
+    assert_eq!(rendered.format.channels, original.format.channels);
+    assert_eq!(rendered.format.sample_rate, original.format.sample_rate);
+    assert_eq!(rendered.total_samples, original.total_samples);
+    assert_eq!(read_all(rendered), read_all(original));
+}
@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::audio::{AudioFormat, AudioStream};
+
+/// Size of the canonical RIFF/WAVE header written ahead of the samples
+const HEADER_LEN: u64 = 44;
+
+/// Writes everything that would have been played to a PCM WAV file. A WAV file holds one
+/// format, so when the format changes part way through the rest goes to `out-2.wav`,
+/// `out-3.wav` and so on beside it
+pub struct WavFileSink {
+    path: PathBuf,
+    /// Which file is being written, counting from 1
+    part: u32,
+    writer: Option<BufWriter<File>>,
+    /// Format of the data already in the file, kept across close/open
+    format: Option<AudioFormat>,
+    data_len: u64,
+}
+
+impl WavFileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            part: 1,
+            writer: None,
+            format: None,
+            data_len: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file being written now: `path` until the format first changes
+    pub fn current_path(&self) -> PathBuf {
+        if self.part == 1 {
+            return self.path.clone();
+        }
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match self.path.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, self.part, ext.to_string_lossy()),
+            None => format!("{}-{}", stem, self.part),
+        };
+        self.path.with_file_name(name)
+    }
+
+    /// Rewrite the header so the RIFF and data sizes match what has been written
+    fn finalize(&mut self) -> Result<(), Box<dyn Error>> {
+        let (Some(writer), Some(format)) = (self.writer.as_mut(), self.format.as_ref()) else {
+            return Ok(());
+        };
+        writer.seek(SeekFrom::Start(0))?;
+        writer.write_all(&wav_header(format, self.data_len))?;
+        writer.seek(SeekFrom::End(0))?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl AudioStream for WavFileSink {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        self.finalize()?;
+        self.writer = None;
+
+        let same_layout = self.format.as_ref().is_some_and(|f| {
+            (f.channels, f.sample_rate, f.bits_per_sample) == (format.channels, format.sample_rate, format.bits_per_sample)
+        });
+
+        if same_layout {
+            // Reopened between tracks: keep appending to the same recording
+            let file = OpenOptions::new().read(true).write(true).open(self.current_path())?;
+            let mut writer = BufWriter::new(file);
+            writer.seek(SeekFrom::Start(HEADER_LEN + self.data_len))?;
+            self.writer = Some(writer);
+        } else {
+            if self.data_len > 0 {
+                self.part += 1;
+                self.data_len = 0;
+                log::info!("Output format changed, continuing in {}", self.current_path().display());
+            }
+            let mut writer = BufWriter::new(File::create(self.current_path())?);
+            writer.write_all(&wav_header(&format, 0))?;
+            self.writer = Some(writer);
+            self.format = Some(format);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        let writer = self.writer.as_mut().ok_or("Stream not open")?;
+        writer.write_all(data)?;
+        self.data_len += data.len() as u64;
+        Ok(data.len())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.finalize()?;
+        self.writer = None;
+        Ok(())
+    }
+}
+
+impl Drop for WavFileSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.finalize() {
+            log::error!("Failed to finalize {}: {}", self.current_path().display(), e);
+        }
+    }
+}
+
+/// Canonical 44-byte header for integer PCM
+pub(crate) fn wav_header(format: &AudioFormat, data_len: u64) -> [u8; HEADER_LEN as usize] {
+    let block_align = format.channels as u32 * (format.bits_per_sample as u32).div_ceil(8);
+    // Sizes saturate rather than wrap once a recording passes 4 GiB
+    let data_len = data_len.min((u32::MAX - 36) as u64) as u32;
+
+    let mut header = [0u8; HEADER_LEN as usize];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&(36 + data_len).to_le_bytes());
+    header[8..12].copy_from_slice(b"WAVE");
+    header[12..16].copy_from_slice(b"fmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    header[20..22].copy_from_slice(&1u16.to_le_bytes());
+    header[22..24].copy_from_slice(&format.channels.to_le_bytes());
+    header[24..28].copy_from_slice(&format.sample_rate.to_le_bytes());
+    header[28..32].copy_from_slice(&(format.sample_rate * block_align).to_le_bytes());
+    header[32..34].copy_from_slice(&(block_align as u16).to_le_bytes());
+    header[34..36].copy_from_slice(&format.bits_per_sample.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&data_len.to_le_bytes());
+    header
+}
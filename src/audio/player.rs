@@ -1,125 +0,0 @@
-use std::error::Error;
-use std::time::Duration;
-use super::{AudioPlayer, AudioFormat, PlaybackState};
-
-/// Main audio playback engine implementation
-pub struct PlaybackEngine {
-    state: PlaybackState,
-    current_format: Option<AudioFormat>,
-    current_position: Duration,
-    total_duration: Option<Duration>,
-    current_file: Option<String>,
-}
-
-impl PlaybackEngine {
-    pub fn new() -> Self {
-        Self {
-            state: PlaybackState::Stopped,
-            current_format: None,
-            current_position: Duration::from_secs(0),
-            total_duration: None,
-            current_file: None,
-        }
-    }
-
-    fn update_position(&mut self) {
-        // TODO: Implement actual position tracking based on audio stream
-        if self.state == PlaybackState::Playing {
-            // This is just a placeholder. Real implementation would sync with actual playback
-            self.current_position += Duration::from_millis(100);
-        }
-    }
-}
-
-impl AudioPlayer for PlaybackEngine {
-    fn initialize(&mut self) -> Result<(), Box<dyn Error>> {
-        // TODO: Initialize audio backend (e.g., cpal, rodio)
-        Ok(())
-    }
-
-    fn load(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement file loading and format detection
-        self.current_file = Some(path.to_string());
-        self.state = PlaybackState::Stopped;
-        self.current_position = Duration::from_secs(0);
-        Ok(())
-    }
-
-    fn play(&mut self) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement actual playback
-        self.state = PlaybackState::Playing;
-        Ok(())
-    }
-
-    fn pause(&mut self) -> Result<(), Box<dyn Error>> {
-        self.state = PlaybackState::Paused;
-        Ok(())
-    }
-
-    fn stop(&mut self) -> Result<(), Box<dyn Error>> {
-        self.state = PlaybackState::Stopped;
-        self.current_position = Duration::from_secs(0);
-        Ok(())
-    }
-
-    fn position(&self) -> Duration {
-        self.current_position
-    }
-
-    fn duration(&self) -> Option<Duration> {
-        self.total_duration
-    }
-
-    fn seek(&mut self, position: Duration) -> Result<(), Box<dyn Error>> {
-        if let Some(duration) = self.total_duration {
-            if position <= duration {
-                self.current_position = position;
-                Ok(())
-            } else {
-                Err("Seek position exceeds track duration".into())
-            }
-        } else {
-            Err("No track loaded".into())
-        }
-    }
-
-    fn state(&self) -> PlaybackState {
-        self.state
-    }
-
-    fn format(&self) -> Option<AudioFormat> {
-        self.current_format.clone()
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_new_playback_engine() {
-        let engine = PlaybackEngine::new();
-        assert_eq!(engine.state(), PlaybackState::Stopped);
-        assert_eq!(engine.position(), Duration::from_secs(0));
-        assert!(engine.duration().is_none());
-        assert!(engine.format().is_none());
-    }
-
-    #[test]
-    fn test_basic_state_transitions() {
-        let mut engine = PlaybackEngine::new();
-        
-        // Test play
-        engine.play().unwrap();
-        assert_eq!(engine.state(), PlaybackState::Playing);
-
-        // Test pause
-        engine.pause().unwrap();
-        assert_eq!(engine.state(), PlaybackState::Paused);
-
-        // Test stop
-        engine.stop().unwrap();
-        assert_eq!(engine.state(), PlaybackState::Stopped);
-        assert_eq!(engine.position(), Duration::from_secs(0));
-    }
-}
@@ -0,0 +1,399 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::audio::convert::{Converter, OutputFormat};
+use crate::audio::fade::Crossfade;
+use crate::audio::formats::{open_track, AudioReader};
+use crate::audio::ring_buffer::Producer;
+use crate::audio::AudioFormat;
+use crate::events::SystemEvent;
+use super::shared::{Shared, NO_BOUNDARY};
+use super::EngineEvent;
+
+/// Interleaved samples pulled from the reader per read
+const DECODE_CHUNK: usize = 4096;
+
+/// How long to wait for a command while there is nothing to decode
+const IDLE_WAIT: Duration = Duration::from_millis(5);
+
+pub(super) enum DecodeCommand {
+    /// Start queueing a freshly opened track from its first frame, scaled by `gain` and
+    /// converted for `output`
+    Load { reader: AudioReader, output: OutputFormat, gain: f32, generation: u64 },
+    /// Continue into this track once the current one runs out, overlapping them by `crossfade`
+    Queue { reader: AudioReader, output: OutputFormat, gain: f32, crossfade: Option<Crossfade> },
+    /// Reopen `path` and restart queueing from the given source frame
+    Seek { path: PathBuf, output: OutputFormat, gain: f32, frame: u64, generation: u64 },
+    Shutdown,
+}
+
+/// A track opened ahead of time to follow the current one
+struct QueuedTrack {
+    reader: AudioReader,
+    output: OutputFormat,
+    gain: f32,
+    crossfade: Option<Crossfade>,
+}
+
+/// Pulls samples out of the current `AudioReader` and feeds them to the ring buffer
+pub(super) struct DecodeThread {
+    shared: Arc<Shared>,
+    commands: Receiver<DecodeCommand>,
+    producer: Producer,
+    reader: Option<AudioReader>,
+    converter: Option<Converter>,
+    /// Source format and output settings of the track being decoded
+    source: AudioFormat,
+    output: OutputFormat,
+    /// ReplayGain factor for the track being decoded
+    gain: f32,
+    next: Option<QueuedTrack>,
+    /// The current track has run out and is waiting for the output to pass the last splice
+    splice_pending: bool,
+    /// Samples queued since the last restart before the current converter started
+    converter_base: u64,
+    /// Source frames fed to the current converter
+    frames_in: u64,
+    /// The current converter's tail has already been queued
+    tail_flushed: bool,
+    /// Samples straight from the reader
+    raw: Vec<f32>,
+    /// Output of the converter before it is released to `chunk`
+    converted: Vec<f32>,
+    /// The most recent converted samples, held back to be mixed with the next track
+    tail: VecDeque<f32>,
+    /// Converted samples the ring buffer had no room for yet
+    chunk: Vec<f32>,
+    chunk_pos: usize,
+}
+
+impl DecodeThread {
+    pub fn new(shared: Arc<Shared>, commands: Receiver<DecodeCommand>, producer: Producer) -> Self {
+        Self {
+            shared,
+            commands,
+            producer,
+            reader: None,
+            converter: None,
+            source: AudioFormat::default(),
+            output: OutputFormat::default(),
+            gain: 1.0,
+            next: None,
+            splice_pending: false,
+            converter_base: 0,
+            frames_in: 0,
+            tail_flushed: false,
+            raw: vec![0.0; DECODE_CHUNK],
+            converted: Vec::new(),
+            tail: VecDeque::new(),
+            chunk: Vec::new(),
+            chunk_pos: 0,
+        }
+    }
+
+    pub fn run(mut self) {
+        loop {
+            // Commands always take priority over decoding so seeks and stops land promptly
+            let command = if self.has_work() {
+                self.commands.try_recv().ok()
+            } else {
+                match self.commands.recv_timeout(IDLE_WAIT) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            };
+
+            match command {
+                Some(DecodeCommand::Shutdown) => return,
+                Some(DecodeCommand::Load { reader, output, gain, generation }) => {
+                    self.load(reader, output, gain, generation)
+                }
+                Some(DecodeCommand::Queue { reader, output, gain, crossfade }) => {
+                    self.next = Some(QueuedTrack { reader, output, gain, crossfade });
+                    self.queued();
+                }
+                Some(DecodeCommand::Seek { path, output, gain, frame, generation }) => {
+                    self.seek(path, output, gain, frame, generation)
+                }
+                None if self.has_work() => self.fill(),
+                None => {}
+            }
+        }
+    }
+
+    /// Whether there are samples to queue and room to put them
+    fn has_work(&self) -> bool {
+        let can_splice = self.splice_pending && self.shared.boundary.load(Ordering::Acquire) == NO_BOUNDARY;
+        (self.reader.is_some() || self.chunk_pos < self.chunk.len() || can_splice)
+            && !self.shared.flush_pending()
+            && self.producer.free() > 0
+    }
+
+    fn load(&mut self, reader: AudioReader, output: OutputFormat, gain: f32, generation: u64) {
+        self.begin(&reader, output, gain);
+        self.restart(Some(reader), 0, generation);
+        self.shared.send(EngineEvent::System(SystemEvent::TrackLoaded));
+    }
+
+    fn queued(&mut self) {
+        // The current track may have been queued in full before the next one arrived
+        if self.reader.is_none() && self.converter.is_some() {
+            self.splice_pending = true;
+        }
+        // Stop holding samples back if the new track won't be crossfaded into
+        self.release();
+    }
+
+    /// Reopen `path` and seek straight to `frame`
+    fn seek(&mut self, path: PathBuf, output: OutputFormat, gain: f32, frame: u64, generation: u64) {
+        let mut reader = match open_track(&path) {
+            Ok(reader) => reader,
+            Err(e) => {
+                self.restart(None, frame, generation);
+                return self.fail(&format!("Failed to reopen {}: {}", path.display(), e));
+            }
+        };
+        self.begin(&reader, output, gain);
+        if let Err(e) = reader.seek(frame) {
+            self.restart(None, frame, generation);
+            return self.fail(&format!("Failed to seek: {}", e));
+        }
+        self.restart(Some(reader), frame, generation);
+    }
+
+    /// Set up conversion for a new current track and publish its output layout
+    fn begin(&mut self, reader: &AudioReader, output: OutputFormat, gain: f32) {
+        let target = output.resolve(&reader.format);
+        let converter = Converter::new(&reader.format, &target, output.quality);
+        self.publish_layout(&target, &converter);
+        // Whole frames per read, so the converter never sees a frame split across chunks
+        let channels = reader.format.channels.max(1) as usize;
+        self.raw.resize(DECODE_CHUNK / channels * channels, 0.0);
+        self.converter = Some(converter);
+        self.source = reader.format.clone();
+        self.output = output;
+        self.gain = gain;
+    }
+
+    /// Tell the output thread what it will be fed, at the next flush or track boundary
+    fn publish_layout(&self, target: &AudioFormat, converter: &Converter) {
+        self.shared.channels.store(target.channels as u32, Ordering::Release);
+        self.shared.sample_rate.store(target.sample_rate, Ordering::Release);
+        self.shared.bits_per_sample.store(target.bits_per_sample as u32, Ordering::Release);
+        self.shared.dither.store(converter.needs_dither(), Ordering::Release);
+    }
+
+    /// Make `reader` current and have the output thread drop everything queued before it
+    fn restart(&mut self, reader: Option<AudioReader>, frame: u64, generation: u64) {
+        self.reader = reader;
+        self.chunk.clear();
+        self.chunk_pos = 0;
+        self.tail.clear();
+        // The engine queues the following track again after every load, seek or stop
+        self.next = None;
+        self.splice_pending = false;
+        self.converter_base = 0;
+        self.frames_in = 0;
+        self.tail_flushed = false;
+        if let Some(converter) = self.converter.as_mut() {
+            converter.reset();
+        }
+
+        // The output counts frames at its own rate
+        let output_rate = self.output.resolve(&self.source).sample_rate;
+        let position = match self.source.sample_rate {
+            rate if rate > 0 && rate != output_rate => (frame as f64 * output_rate as f64 / rate as f64).round() as u64,
+            _ => frame,
+        };
+        self.shared.boundary.store(NO_BOUNDARY, Ordering::Release);
+        self.shared.decode_finished.store(self.reader.is_none(), Ordering::Release);
+        self.shared.flush_position.store(position, Ordering::Release);
+        self.shared.flush_requested.store(generation, Ordering::Release);
+    }
+
+    /// Move the next chunk of converted samples into the ring buffer
+    fn fill(&mut self) {
+        if self.splice_pending {
+            self.splice();
+        }
+        if self.chunk_pos >= self.chunk.len() && self.reader.is_some() && !self.convert_chunk() {
+            return;
+        }
+        self.chunk_pos += self.producer.push(&self.chunk[self.chunk_pos..]);
+        if self.reader.is_none() && !self.splice_pending && self.chunk_pos >= self.chunk.len() {
+            self.finish();
+        }
+    }
+
+    /// Decode and convert the next chunk; false on a decode error
+    fn convert_chunk(&mut self) -> bool {
+        // The converter's tail ends the track, unless a gapless join keeps it running
+        let ends_stream = self.next.is_none() || self.crossfade().is_some();
+        let (Some(reader), Some(converter)) = (self.reader.as_mut(), self.converter.as_mut()) else {
+            return false;
+        };
+        self.chunk.clear();
+        self.chunk_pos = 0;
+        match reader.read(&mut self.raw) {
+            Ok(0) => {
+                self.reader = None;
+                if ends_stream {
+                    converter.flush(&mut self.converted);
+                    self.tail_flushed = true;
+                }
+                self.release();
+                if self.next.is_some() {
+                    self.splice_pending = true;
+                    self.splice();
+                }
+                true
+            }
+            Ok(read) => {
+                self.frames_in += (read / self.source.channels.max(1) as usize) as u64;
+                apply_gain(&mut self.raw[..read], self.gain);
+                converter.process(&self.raw[..read], &mut self.converted);
+                self.release();
+                true
+            }
+            Err(e) => {
+                self.fail(&format!("Decode error: {}", e));
+                false
+            }
+        }
+    }
+
+    /// The crossfade into the queued track, if it can share the current output layout
+    fn crossfade(&self) -> Option<Crossfade> {
+        let next = self.next.as_ref()?;
+        let layout = |format: &AudioFormat| (format.channels, format.sample_rate, format.bits_per_sample);
+        let current = self.output.resolve(&self.source);
+        let incoming = next.output.resolve(&next.reader.format);
+        next.crossfade.filter(|_| layout(&current) == layout(&incoming))
+    }
+
+    /// Move converted samples on to the ring, holding back as many as the coming crossfade needs
+    fn release(&mut self) {
+        let target = self.output.resolve(&self.source);
+        let hold = match self.crossfade() {
+            Some(fade) => fade.frames(target.sample_rate) * target.channels as usize,
+            None => 0,
+        };
+        if hold == 0 && self.tail.is_empty() {
+            self.chunk.append(&mut self.converted);
+            return;
+        }
+        self.tail.extend(self.converted.drain(..));
+        let excess = self.tail.len().saturating_sub(hold);
+        self.chunk.extend(self.tail.drain(..excess));
+    }
+
+    /// Carry on into the queued track without a gap, marking where it starts for the output thread
+    fn splice(&mut self) {
+        // Only one boundary can be outstanding; wait until the output has passed the last one
+        if self.shared.boundary.load(Ordering::Acquire) != NO_BOUNDARY {
+            return;
+        }
+        let crossfade = self.crossfade().filter(|_| !self.tail.is_empty());
+        let (Some(next), Some(converter)) = (self.next.take(), self.converter.as_mut()) else {
+            return;
+        };
+        self.splice_pending = false;
+
+        // Everything converted from the current track, including what the converter still holds,
+        // less the tail that overlaps the next one
+        let channels = converter.output_channels() as u64;
+        let boundary = self.converter_base + converter.output_frames(self.frames_in) * channels - self.tail.len() as u64;
+
+        let target = next.output.resolve(&next.reader.format);
+        let next_converter = Converter::new(&next.reader.format, &target, next.output.quality);
+        let same_layout = !self.tail_flushed
+            && next.output == self.output
+            && next.reader.format.channels == self.source.channels
+            && next.reader.format.sample_rate == self.source.sample_rate;
+        if same_layout {
+            // Keep the current converter running so its filter history spans the join
+            self.shared.dither.store(next_converter.needs_dither(), Ordering::Release);
+        } else {
+            if !self.tail_flushed {
+                converter.flush(&mut self.converted);
+            }
+            self.publish_layout(&target, &next_converter);
+            self.converter = Some(next_converter);
+            self.converter_base = boundary;
+            self.frames_in = 0;
+            self.tail_flushed = false;
+        }
+
+        let channels = next.reader.format.channels.max(1) as usize;
+        self.raw.resize(DECODE_CHUNK / channels * channels, 0.0);
+        self.source = next.reader.format.clone();
+        self.output = next.output;
+        self.gain = next.gain;
+        self.reader = Some(next.reader);
+        if let Some(fade) = crossfade {
+            self.mix_into_tail(fade);
+        }
+        self.release();
+        self.shared.decode_finished.store(false, Ordering::Release);
+        self.shared.boundary.store(boundary, Ordering::Release);
+    }
+
+    /// Decode the start of the new track and mix it over the held-back tail of the last one
+    fn mix_into_tail(&mut self, fade: Crossfade) {
+        let (Some(reader), Some(converter)) = (self.reader.as_mut(), self.converter.as_mut()) else {
+            return;
+        };
+        let mut head = Vec::with_capacity(self.tail.len());
+        while head.len() < self.tail.len() {
+            match reader.read(&mut self.raw) {
+                Ok(0) => {
+                    // The whole track fits inside the crossfade
+                    converter.flush(&mut head);
+                    self.reader = None;
+                    self.tail_flushed = true;
+                    break;
+                }
+                Ok(read) => {
+                    self.frames_in += (read / self.source.channels.max(1) as usize) as u64;
+                    apply_gain(&mut self.raw[..read], self.gain);
+                    converter.process(&self.raw[..read], &mut head);
+                }
+                Err(e) => {
+                    log::error!("Decode error: {}", e);
+                    self.reader = None;
+                    break;
+                }
+            }
+        }
+
+        let overlap = head.len().min(self.tail.len());
+        fade.mix(self.tail.make_contiguous(), &head[..overlap], converter.output_channels() as usize);
+        self.converted.extend(self.tail.drain(..));
+        self.converted.extend_from_slice(&head[overlap..]);
+    }
+
+    /// The whole track is queued; the output thread reports the end once it drains
+    fn finish(&mut self) {
+        self.reader = None;
+        self.splice_pending = false;
+        self.shared.decode_finished.store(true, Ordering::Release);
+    }
+
+    /// Give up on the current track, letting whatever is already queued play out
+    fn fail(&mut self, message: &str) {
+        log::error!("{}", message);
+        self.finish();
+        self.shared.send(EngineEvent::System(SystemEvent::Error));
+    }
+}
+
+/// Scale decoded samples by a track's ReplayGain
+fn apply_gain(samples: &mut [f32], gain: f32) {
+    if gain != 1.0 {
+        samples.iter_mut().for_each(|sample| *sample *= gain);
+    }
+}
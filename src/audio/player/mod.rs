@@ -0,0 +1,573 @@
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use crate::events::SystemEvent;
+use super::convert::{OutputFormat, Quantizer};
+use super::dsp::{default_chain, ProcessorChain, ProcessorFactory, ProcessorRegistry, ProcessorStage};
+use super::eq::{EqPreset, EqSettings, EqualizerFactory};
+use super::fade::{Crossfade, MAX_CROSSFADE};
+use super::record::{Recorder, RecordSettings, RecordTags, RecordingStatus};
+use super::replaygain::{AppliedGain, ReplayGain, ReplayGainMode, ReplayGainTags};
+use super::speed::{PlaybackSpeed, MAX_SPEED, MIN_SPEED};
+use super::tap::TapReader;
+use super::volume::{Volume, VolumeCurve, MAX_HEADROOM_DB, MAX_RANGE_DB, MIN_RANGE_DB};
+use crate::metadata::MetadataManager;
+use super::formats::{open_track, AudioReader};
+use super::output::OutputBackend;
+use super::ring_buffer::ring_buffer;
+use super::stream::AudioOutputStream;
+use super::{AudioPlayer, AudioFormat, AudioStream, PlaybackState, SeekTarget};
+
+mod decode;
+mod output;
+mod shared;
+#[cfg(test)]
+mod tests;
+
+use decode::{DecodeCommand, DecodeThread};
+use output::OutputThread;
+use shared::Shared;
+
+/// Samples buffered between the decode and output threads, about 1.4s of 48kHz stereo
+const RING_CAPACITY: usize = 1 << 17;
+
+/// Notifications sent from the engine threads back to the UI thread
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineEvent {
+    StateChanged(PlaybackState),
+    System(SystemEvent),
+}
+
+/// A track the engine has opened, as seen from the UI thread
+struct Track {
+    path: String,
+    format: AudioFormat,
+    duration: Option<Duration>,
+    /// Output settings the track was opened with
+    output: OutputFormat,
+    /// Rate the track is streamed at, after conversion
+    stream_rate: u32,
+    /// Overlap with the track before it, for a queued track
+    crossfade: Option<Crossfade>,
+    gain: AppliedGain,
+}
+
+/// Main audio playback engine implementation
+pub struct PlaybackEngine {
+    shared: Arc<Shared>,
+    commands: Sender<DecodeCommand>,
+    events: Receiver<EngineEvent>,
+    threads: Vec<JoinHandle<()>>,
+    current: Option<Track>,
+    /// Track set to follow the current one without a gap
+    queued: Option<Track>,
+    /// Value of `Shared::tracks_advanced` the current track reflects
+    advanced_seen: u64,
+    output_format: OutputFormat,
+    crossfade: Option<Crossfade>,
+    replay_gain: ReplayGain,
+    shuffle: bool,
+    volume: Volume,
+    speed: PlaybackSpeed,
+    equalizer: Option<EqPreset>,
+    eq_settings: EqSettings,
+    processors: ProcessorRegistry,
+    /// Stages of the chain the output thread runs, with the bypass flags it reads
+    chain: Vec<ProcessorStage>,
+    bypass: Vec<Arc<AtomicBool>>,
+    recorder: Option<Recorder>,
+}
+
+impl PlaybackEngine {
+    pub fn new() -> Self {
+        Self::with_stream(Box::new(AudioOutputStream::new()))
+    }
+
+    /// Create an engine for an output spec such as `null` or `wav:out.wav`
+    pub fn with_output(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let backend = OutputBackend::from_spec(spec)?;
+        Ok(Self::with_stream(Box::new(AudioOutputStream::with_backend(backend))))
+    }
+
+    /// Create an engine that plays through the given output stream
+    pub fn with_stream(stream: Box<dyn AudioStream + Send>) -> Self {
+        Self::with_ring(stream, RING_CAPACITY)
+    }
+
+    /// Create an engine that decodes at most `capacity` samples ahead of the output
+    fn with_ring(stream: Box<dyn AudioStream + Send>, capacity: usize) -> Self {
+        let (event_tx, events) = mpsc::channel();
+        let (commands, command_rx) = mpsc::channel();
+        let (producer, consumer) = ring_buffer(capacity);
+        let shared = Arc::new(Shared::new(event_tx));
+
+        let decoder = DecodeThread::new(shared.clone(), command_rx, producer);
+        let output = OutputThread::new(shared.clone(), consumer, stream);
+        let threads = vec![
+            spawn("playtui-decode", move || decoder.run()),
+            spawn("playtui-output", move || output.run()),
+        ];
+
+        let eq_settings = EqSettings::new();
+        let mut processors = ProcessorRegistry::new();
+        processors.register_processor(Arc::new(EqualizerFactory::new(eq_settings.clone())));
+
+        let mut engine = Self {
+            shared,
+            commands,
+            events,
+            threads,
+            current: None,
+            queued: None,
+            advanced_seen: 0,
+            output_format: OutputFormat::default(),
+            crossfade: None,
+            replay_gain: ReplayGain::default(),
+            shuffle: false,
+            volume: Volume::default(),
+            speed: PlaybackSpeed::default(),
+            equalizer: None,
+            eq_settings,
+            processors,
+            chain: Vec::new(),
+            bypass: Vec::new(),
+            recorder: None,
+        };
+        engine.set_processor_chain(default_chain()).expect("built-in processors are registered");
+        engine
+    }
+
+    /// Format tracks are converted to for the output; applies from the next load
+    pub fn set_output_format(&mut self, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+        Quantizer::new(format.bits_per_sample)?;
+        if format.channels == Some(0) || format.sample_rate == Some(0) {
+            return Err("Output channels and sample rate must be non-zero".into());
+        }
+        self.output_format = format;
+        Ok(())
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// Overlap between queued tracks, or `None` to join them gaplessly; applies from the next queue
+    pub fn set_crossfade(&mut self, crossfade: Option<Crossfade>) -> Result<(), Box<dyn Error>> {
+        match crossfade {
+            Some(fade) if fade.duration > MAX_CROSSFADE => {
+                Err(format!("Crossfade is limited to {}s", MAX_CROSSFADE.as_secs()).into())
+            }
+            _ => {
+                self.crossfade = crossfade.filter(|fade| !fade.duration.is_zero());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn crossfade(&self) -> Option<Crossfade> {
+        self.crossfade
+    }
+
+    /// How tracks are levelled by their ReplayGain tags; applies from the next load or queue
+    pub fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+        self.replay_gain = replay_gain;
+    }
+
+    pub fn replay_gain(&self) -> ReplayGain {
+        self.replay_gain
+    }
+
+    /// Whether the playlist is shuffled, which picks track over album gain in auto mode
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+
+    /// Gain applied to the current track
+    pub fn applied_gain(&self) -> Option<AppliedGain> {
+        self.current.as_ref().map(|track| track.gain)
+    }
+
+    /// Playback volume from 0 to 100, which also unmutes; changes are ramped in
+    pub fn set_volume(&mut self, level: u8) {
+        self.volume.set_level(level);
+        self.shared.set_volume(self.volume.gain());
+    }
+
+    /// Silence the output, keeping the volume level to return to
+    pub fn set_muted(&mut self, muted: bool) {
+        self.volume.set_muted(muted);
+        self.shared.set_volume(self.volume.gain());
+    }
+
+    /// Mute or unmute; returns whether the output is now muted
+    pub fn toggle_mute(&mut self) -> bool {
+        let muted = self.volume.toggle_mute();
+        self.shared.set_volume(self.volume.gain());
+        muted
+    }
+
+    /// How volume levels map onto decibels
+    pub fn set_volume_curve(&mut self, curve: VolumeCurve) -> Result<(), Box<dyn Error>> {
+        if !curve.is_valid() {
+            return Err(format!(
+                "Volume range must be {}-{}dB with at most {}dB headroom",
+                MIN_RANGE_DB, MAX_RANGE_DB, MAX_HEADROOM_DB
+            ).into());
+        }
+        self.volume.curve = curve;
+        self.shared.set_volume(self.volume.gain());
+        Ok(())
+    }
+
+    pub fn volume(&self) -> Volume {
+        self.volume
+    }
+
+    /// Rate tracks play at and whether the pitch follows it; takes effect straight away.
+    /// Position and duration stay in track time whatever the speed.
+    pub fn set_speed(&mut self, speed: PlaybackSpeed) -> Result<(), Box<dyn Error>> {
+        if !speed.is_valid() {
+            return Err(format!("Playback speed must be {}-{}x", MIN_SPEED, MAX_SPEED).into());
+        }
+        self.speed = speed;
+        self.shared.set_speed(speed);
+        Ok(())
+    }
+
+    pub fn speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    /// EQ applied to everything played, or `None` to bypass it; takes effect straight away
+    pub fn set_equalizer(&mut self, preset: Option<EqPreset>) -> Result<(), Box<dyn Error>> {
+        if let Some(preset) = preset.as_ref() {
+            preset.validate()?;
+        }
+        self.eq_settings.set(preset.clone());
+        self.equalizer = preset;
+        Ok(())
+    }
+
+    pub fn equalizer(&self) -> Option<&EqPreset> {
+        self.equalizer.as_ref()
+    }
+
+    /// Make a processor available to `set_processor_chain`, replacing one of the same name
+    pub fn register_processor(&mut self, factory: Arc<dyn ProcessorFactory + Send + Sync>) {
+        self.processors.register_processor(factory);
+    }
+
+    /// Names of the processors a chain can be built from
+    pub fn available_processors(&self) -> Vec<String> {
+        self.processors.names()
+    }
+
+    /// Replace the effects applied to everything played, in order; takes effect straight away
+    pub fn set_processor_chain(&mut self, stages: Vec<ProcessorStage>) -> Result<(), Box<dyn Error>> {
+        if let Some(stage) = stages.iter().find(|stage| !self.processors.supports(&stage.name)) {
+            return Err(format!("Unknown audio processor '{}'", stage.name).into());
+        }
+
+        let mut chain = ProcessorChain::new();
+        let mut bypass = Vec::with_capacity(stages.len());
+        for stage in &stages {
+            let flag = Arc::new(AtomicBool::new(stage.bypassed));
+            // Checked above, and the registry is only changed through `&mut self`
+            let processor = self.processors.create(&stage.name).expect("processor is registered");
+            chain.push(processor, flag.clone());
+            bypass.push(flag);
+        }
+        self.shared.set_chain(chain);
+        self.chain = stages;
+        self.bypass = bypass;
+        Ok(())
+    }
+
+    pub fn processor_chain(&self) -> &[ProcessorStage] {
+        &self.chain
+    }
+
+    /// Skip or restore the stage at `index` of the chain without rebuilding it
+    pub fn set_processor_bypassed(&mut self, index: usize, bypassed: bool) -> Result<(), Box<dyn Error>> {
+        let stage = self.chain.get_mut(index).ok_or("No processor at that position in the chain")?;
+        stage.bypassed = bypassed;
+        self.bypass[index].store(bypassed, Ordering::Release);
+        Ok(())
+    }
+
+    /// Delay the processor chain currently adds to the output
+    pub fn processing_latency(&self) -> Duration {
+        let frames = self.shared.latency.load(Ordering::Acquire);
+        match self.shared.sample_rate.load(Ordering::Acquire) {
+            0 => Duration::ZERO,
+            rate => Duration::from_secs_f64(frames as f64 / rate as f64),
+        }
+    }
+
+    /// Start recording everything played, after the effects but before the volume, to a new
+    /// file in the settings' directory tagged like the current track
+    pub fn start_recording(&mut self, settings: &RecordSettings) -> Result<(), Box<dyn Error>> {
+        if self.recorder.is_some() {
+            return Err("Already recording".into());
+        }
+        let tags = self.current.as_ref()
+            .and_then(|track| MetadataManager::with_format_parsers().parse_metadata(&PathBuf::from(&track.path)).ok())
+            .map(|metadata| RecordTags::from_metadata(&metadata))
+            .unwrap_or_default();
+        let recorder = Recorder::start(settings, tags)?;
+        self.shared.set_record_tap(Some(recorder.tap()));
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Stop recording and finalize the file; what was recorded, if anything was being
+    pub fn stop_recording(&mut self) -> Result<Option<RecordingStatus>, Box<dyn Error>> {
+        let Some(recorder) = self.recorder.take() else {
+            return Ok(None);
+        };
+        self.shared.set_record_tap(None);
+        recorder.finish().map(Some)
+    }
+
+    /// How far the recording in progress has got
+    pub fn recording(&self) -> Option<RecordingStatus> {
+        self.recorder.as_ref().map(Recorder::status)
+    }
+
+    /// A reader of everything sent to the output stream from now on, at the volume it is played
+    pub fn output_tap(&self) -> TapReader {
+        self.shared.tap.reader()
+    }
+
+    /// Seek to an absolute, relative or proportional position in the current track
+    pub fn seek_to(&mut self, target: SeekTarget) -> Result<(), Box<dyn Error>> {
+        let position = target.resolve(self.position(), self.duration())?;
+        self.seek(position)
+    }
+
+    /// Next pending notification from the engine threads, if any
+    pub fn try_recv_event(&mut self) -> Option<EngineEvent> {
+        let event = self.events.try_recv().ok()?;
+        if event == EngineEvent::System(SystemEvent::TrackLoaded) {
+            self.sync_track();
+        }
+        Some(event)
+    }
+
+    /// Path of the loaded track
+    pub fn current_file(&self) -> Option<&str> {
+        self.current.as_ref().map(|track| track.path.as_str())
+    }
+
+    /// Path of the track queued to follow the current one
+    pub fn queued_file(&self) -> Option<&str> {
+        self.queued.as_ref().map(|track| track.path.as_str())
+    }
+
+    /// Play `path` once the current track ends, crossfading if set up, otherwise with no gap.
+    /// Replaces any track queued before; a load, seek or stop keeps the queue.
+    pub fn queue_next(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        self.queue(path, self.crossfade)
+    }
+
+    /// Play `path` straight after the current track ends without crossfading, as for the next
+    /// track of the same album
+    pub fn queue_gapless(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        self.queue(path, None)
+    }
+
+    fn queue(&mut self, path: &str, crossfade: Option<Crossfade>) -> Result<(), Box<dyn Error>> {
+        self.sync_track();
+        if self.current.is_none() {
+            return Err("No track loaded".into());
+        }
+        let (mut track, reader) = self.open(path)?;
+        track.crossfade = crossfade;
+        self.send_queue(&track, reader)?;
+        self.queued = Some(track);
+        Ok(())
+    }
+
+    /// Open `path` far enough to know its format; the decode thread does the rest
+    fn open(&self, path: &str) -> Result<(Track, AudioReader), Box<dyn Error>> {
+        let file = PathBuf::from(path);
+        let reader = open_track(&file)?;
+        let format = reader.format.clone();
+        let duration = format.duration.or_else(|| {
+            (reader.total_samples > 0 && format.sample_rate > 0)
+                .then(|| Duration::from_secs_f64(reader.total_samples as f64 / format.sample_rate as f64))
+        });
+        let output = self.output_format;
+        let gain = match self.replay_gain.mode {
+            ReplayGainMode::Off => AppliedGain::UNITY,
+            _ => {
+                let tags = MetadataManager::with_format_parsers()
+                    .parse_metadata(&file)
+                    .map(|metadata| ReplayGainTags::from_tags(&metadata.extra))
+                    .unwrap_or_default();
+                self.replay_gain.gain_for(&tags, self.shuffle)
+            }
+        };
+        let track = Track {
+            path: path.to_string(),
+            stream_rate: output.resolve(&format).sample_rate,
+            format,
+            duration,
+            output,
+            crossfade: None,
+            gain,
+        };
+        Ok((track, reader))
+    }
+
+    fn send_queue(&self, track: &Track, reader: AudioReader) -> Result<(), Box<dyn Error>> {
+        let command = DecodeCommand::Queue {
+            reader,
+            output: track.output,
+            gain: track.gain.linear(),
+            crossfade: track.crossfade,
+        };
+        self.commands.send(command).map_err(|_| "Decode thread has stopped".into())
+    }
+
+    /// Hand the queued track to the decode thread again after a seek or stop discarded it
+    fn requeue(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(track) = self.queued.as_ref() else {
+            return Ok(());
+        };
+        let file = PathBuf::from(&track.path);
+        match open_track(&file) {
+            Ok(reader) => self.send_queue(track, reader),
+            Err(e) => {
+                self.queued = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// Promote the queued track once the output thread has moved on to it
+    fn sync_track(&mut self) {
+        let advanced = self.shared.tracks_advanced.load(Ordering::Acquire);
+        while self.advanced_seen < advanced {
+            self.advanced_seen += 1;
+            if let Some(next) = self.queued.take() {
+                self.current = Some(next);
+            }
+        }
+    }
+
+    /// Send a load or seek to the decode thread, silencing the output until it takes effect
+    fn restart(&self, command: impl FnOnce(u64) -> DecodeCommand) -> Result<(), Box<dyn Error>> {
+        let generation = self.shared.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        self.commands.send(command(generation)).map_err(|_| "Decode thread has stopped".into())
+    }
+}
+
+fn spawn(name: &str, body: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(body)
+        .expect("failed to spawn audio thread")
+}
+
+impl Drop for PlaybackEngine {
+    fn drop(&mut self) {
+        // Finalize the recording's headers before the threads feeding it go
+        if let Err(e) = self.stop_recording() {
+            log::error!("Failed to finish recording: {}", e);
+        }
+        self.shared.shutdown.store(true, Ordering::Release);
+        let _ = self.commands.send(DecodeCommand::Shutdown);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl AudioPlayer for PlaybackEngine {
+    fn initialize(&mut self) -> Result<(), Box<dyn Error>> {
+        // The output stream is opened once the first track tells us its format
+        Ok(())
+    }
+
+    fn load(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        // Opening the decoder only reads headers, the heavy lifting happens on the decode thread
+        let (track, reader) = self.open(path)?;
+
+        self.shared.set_state(PlaybackState::Stopped);
+        self.shared.frames_played.store(0, Ordering::Release);
+        let (output, gain) = (track.output, track.gain.linear());
+        self.restart(|generation| DecodeCommand::Load { reader, output, gain, generation })?;
+
+        self.current = Some(track);
+        self.queued = None;
+        self.advanced_seen = self.shared.tracks_advanced.load(Ordering::Acquire);
+        Ok(())
+    }
+
+    fn play(&mut self) -> Result<(), Box<dyn Error>> {
+        self.shared.set_state(PlaybackState::Playing);
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), Box<dyn Error>> {
+        self.shared.set_state(PlaybackState::Paused);
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), Box<dyn Error>> {
+        self.shared.set_state(PlaybackState::Stopped);
+        self.shared.frames_played.store(0, Ordering::Release);
+        self.sync_track();
+        if let Some(track) = self.current.as_ref() {
+            let (path, output, gain) = (PathBuf::from(&track.path), track.output, track.gain.linear());
+            self.restart(|generation| DecodeCommand::Seek { path, output, gain, frame: 0, generation })?;
+            self.requeue()?;
+        }
+        Ok(())
+    }
+
+    fn position(&self) -> Duration {
+        match self.current.as_ref() {
+            Some(track) if track.stream_rate > 0 => {
+                let frames = self.shared.frames_played.load(Ordering::Acquire);
+                Duration::from_secs_f64(frames as f64 / track.stream_rate as f64)
+            }
+            _ => Duration::from_secs(0),
+        }
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.current.as_ref().and_then(|track| track.duration)
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<(), Box<dyn Error>> {
+        self.sync_track();
+        let Some(track) = self.current.as_ref() else {
+            return Err("No track loaded".into());
+        };
+        if track.duration.is_some_and(|duration| position > duration) {
+            return Err("Seek position exceeds track duration".into());
+        }
+
+        let frame = (position.as_secs_f64() * track.format.sample_rate as f64).round() as u64;
+        // Report the target straight away; the output thread resumes counting from it
+        let played = (position.as_secs_f64() * track.stream_rate as f64).round() as u64;
+        self.shared.frames_played.store(played, Ordering::Release);
+        let (path, output, gain) = (PathBuf::from(&track.path), track.output, track.gain.linear());
+        self.restart(|generation| DecodeCommand::Seek { path, output, gain, frame, generation })?;
+        self.requeue()
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.shared.state()
+    }
+
+    fn format(&self) -> Option<AudioFormat> {
+        self.current.as_ref().map(|track| track.format.clone())
+    }
+}
@@ -0,0 +1,333 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::audio::convert::Quantizer;
+use crate::audio::dsp::ProcessorChain;
+use crate::audio::fade::CLICK_FADE;
+use crate::audio::record::RecordTap;
+use crate::audio::ring_buffer::Consumer;
+use crate::audio::speed::TimeStretch;
+use crate::audio::volume::{GainRamp, VOLUME_RAMP};
+use crate::audio::{AudioFormat, AudioStream, PlaybackState};
+use crate::events::SystemEvent;
+use super::shared::{Shared, NO_BOUNDARY};
+use super::EngineEvent;
+
+/// Most frames handed to the stream per write; small enough that pause takes effect quickly
+const PERIOD_FRAMES: usize = 1024;
+
+/// How long to sleep while paused, stopped or starved of samples
+const IDLE_WAIT: Duration = Duration::from_millis(2);
+
+/// Drains the ring buffer into the output stream and counts the frames it consumes
+pub(super) struct OutputThread {
+    shared: Arc<Shared>,
+    consumer: Consumer,
+    stream: Box<dyn AudioStream + Send>,
+    /// Channels, rate and bit depth the stream is currently open with
+    open_format: Option<(u16, u32, u16)>,
+    quantizer: Option<Quantizer>,
+    /// Samples taken from the ring since the last flush, to find the next track boundary
+    popped: u64,
+    /// Level of the click-free ramp applied on play, pause, stop and seek
+    gain: f32,
+    /// Plays the samples taken from the ring faster or slower
+    stretch: TimeStretch,
+    /// Effects applied to everything written, prepared for the layout of the open stream
+    chain: ProcessorChain,
+    /// Recording that gets a copy of everything played, after the effects and before the volume
+    record: Option<RecordTap>,
+    /// Playback volume, eased towards the level the UI thread last set
+    volume: GainRamp,
+    /// The next write starts a track from its first frame, which needs no fade-in
+    at_track_start: bool,
+    samples: Vec<f32>,
+    /// `samples` at the playback speed, as written to the stream
+    stretched: Vec<f32>,
+    bytes: Vec<u8>,
+}
+
+impl OutputThread {
+    pub fn new(shared: Arc<Shared>, consumer: Consumer, stream: Box<dyn AudioStream + Send>) -> Self {
+        let volume = GainRamp::new(shared.volume());
+        Self {
+            shared,
+            consumer,
+            stream,
+            open_format: None,
+            quantizer: None,
+            popped: 0,
+            gain: 0.0,
+            stretch: TimeStretch::new(),
+            chain: ProcessorChain::new(),
+            record: None,
+            volume,
+            at_track_start: false,
+            samples: Vec::new(),
+            stretched: Vec::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    pub fn run(mut self) {
+        while !self.shared.shutdown.load(Ordering::Acquire) {
+            // Ramp down whatever is audible before a pause, stop, load or seek silences it
+            let audible = self.shared.state() == PlaybackState::Playing && self.shared.settled();
+            if !audible && self.gain > 0.0 {
+                if !self.write_period(true) {
+                    self.gain = 0.0;
+                }
+                continue;
+            }
+            if self.shared.flush_pending() {
+                self.flush();
+            }
+            if self.shared.state() != PlaybackState::Playing || !self.write_period(false) {
+                std::thread::sleep(IDLE_WAIT);
+            }
+        }
+
+        if self.open_format.is_some() {
+            if let Err(e) = self.stream.close() {
+                log::warn!("Failed to close audio stream: {}", e);
+            }
+        }
+    }
+
+    /// Drop stale samples and restart the position count for the new track or seek target
+    fn flush(&mut self) {
+        let requested = self.shared.flush_requested.load(Ordering::Acquire);
+        self.consumer.clear();
+        self.popped = 0;
+        let position = self.shared.flush_position.load(Ordering::Acquire);
+        self.shared.frames_played.store(position, Ordering::Release);
+        self.gain = 0.0;
+        self.at_track_start = position == 0;
+        self.stretch.reset();
+        self.chain.reset();
+
+        self.apply_layout();
+        self.shared.flush_done.store(requested, Ordering::Release);
+    }
+
+    /// Match the stream to the layout the decode thread published
+    fn apply_layout(&mut self) {
+        let channels = self.shared.channels.load(Ordering::Acquire) as u16;
+        let sample_rate = self.shared.sample_rate.load(Ordering::Acquire);
+        let bits_per_sample = self.shared.bits_per_sample.load(Ordering::Acquire) as u16;
+        if self.open_format != Some((channels, sample_rate, bits_per_sample)) {
+            self.reopen(channels, sample_rate, bits_per_sample);
+        }
+        if let Some(quantizer) = self.quantizer.as_mut() {
+            quantizer.set_dither(self.shared.dither.load(Ordering::Acquire));
+        }
+    }
+
+    /// Every sample of the previous track has been written; carry on with the queued one
+    fn cross_boundary(&mut self, boundary: u64) {
+        let crossed = self.shared.boundary
+            .compare_exchange(boundary, NO_BOUNDARY, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        // A load or seek got in first and already discarded the queued track
+        if !crossed {
+            return;
+        }
+        self.shared.frames_played.store(0, Ordering::Release);
+        self.apply_layout();
+        self.shared.tracks_advanced.fetch_add(1, Ordering::AcqRel);
+        self.shared.send(EngineEvent::System(SystemEvent::TrackEnded));
+        self.shared.send(EngineEvent::System(SystemEvent::TrackLoaded));
+    }
+
+    fn reopen(&mut self, channels: u16, sample_rate: u32, bits_per_sample: u16) {
+        if self.open_format.take().is_some() {
+            if let Err(e) = self.stream.close() {
+                log::warn!("Failed to close audio stream: {}", e);
+            }
+        }
+
+        let format = AudioFormat {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            ..Default::default()
+        };
+        let opened = Quantizer::new(bits_per_sample).and_then(|quantizer| {
+            self.stream.open(format)?;
+            Ok(quantizer)
+        });
+        match opened {
+            Ok(quantizer) => {
+                self.quantizer = Some(quantizer);
+                self.open_format = Some((channels, sample_rate, bits_per_sample));
+            }
+            Err(e) => {
+                log::error!("Failed to open audio stream: {}", e);
+                self.shared.send(EngineEvent::System(SystemEvent::Error));
+            }
+        }
+    }
+
+    /// Write one period to the stream, or the rest of the ramp down to silence when
+    /// `fading_out`; returns false when there was nothing to write
+    fn write_period(&mut self, fading_out: bool) -> bool {
+        let Some((channels, sample_rate, _)) = self.open_format else {
+            return false;
+        };
+        let channels = channels.max(1) as usize;
+        let settled = self.shared.settled();
+
+        // Hold off from the moment a load or seek is issued until its flush completes,
+        // apart from fading out the samples that were playing when it was issued
+        if !settled && !fading_out {
+            return false;
+        }
+
+        // Never write across the start of a queued track, so it opens with the right layout
+        let boundary = self.shared.boundary.load(Ordering::Acquire);
+        if boundary != NO_BOUNDARY && self.popped >= boundary {
+            if fading_out {
+                return false;
+            }
+            // The time-stretcher may still hold the end of the previous track
+            if !self.drain_stretch(channels, sample_rate) {
+                self.cross_boundary(boundary);
+            }
+            return true;
+        }
+        let mut limit = boundary.saturating_sub(self.popped).min(usize::MAX as u64) as usize;
+
+        let step = self.fade_step(sample_rate);
+        if fading_out {
+            limit = limit.min((self.gain / step).ceil() as usize * channels);
+        }
+
+        // Checked before the ring so the final samples are never mistaken for the end
+        let finished = self.shared.decode_finished.load(Ordering::Acquire);
+        // Only whole frames, so the position never lands between channels
+        let available = self.consumer.available().min(limit) / channels * channels;
+        if available == 0 {
+            if finished && !fading_out {
+                if self.drain_stretch(channels, sample_rate) {
+                    return true;
+                }
+                self.end_of_track();
+            }
+            return false;
+        }
+
+        self.samples.resize(available.min(PERIOD_FRAMES * channels), 0.0);
+        let count = self.consumer.pop(&mut self.samples);
+        self.popped += count as u64;
+        // A track's first frame starts at full level, anywhere else fades in
+        if std::mem::take(&mut self.at_track_start) {
+            self.gain = 1.0;
+        }
+
+        // Count the track frames the stretcher has moved past, not the frames written
+        let held = self.stretch.buffered_frames();
+        self.stretch.set_speed(self.shared.speed());
+        self.stretched.clear();
+        self.stretch.process(&self.samples[..count], channels, sample_rate, &mut self.stretched);
+        let played = count / channels + held - self.stretch.buffered_frames();
+        self.render(channels, sample_rate, fading_out, played, settled);
+        true
+    }
+
+    /// Write out whatever the time-stretcher still holds unstretched; false if it held nothing
+    fn drain_stretch(&mut self, channels: usize, sample_rate: u32) -> bool {
+        let held = self.stretch.buffered_frames();
+        self.stretched.clear();
+        self.stretch.drain(&mut self.stretched);
+        if self.stretched.is_empty() && held == 0 {
+            return false;
+        }
+        self.render(channels, sample_rate, false, held, true);
+        true
+    }
+
+    /// Run `stretched` through the effects, ramps and quantizer to the stream, then advance the
+    /// position by the `played` track frames it covers
+    fn render(&mut self, channels: usize, sample_rate: u32, fading_out: bool, played: usize, settled: bool) {
+        self.update_chain(channels as u16, sample_rate);
+        self.chain.process(&mut self.stretched);
+        self.ramp(channels, self.fade_step(sample_rate), if fading_out { 0.0 } else { 1.0 });
+        self.record(channels as u16, sample_rate);
+        let ramp_frames = (VOLUME_RAMP.as_secs_f32() * sample_rate as f32) as usize;
+        self.volume.set_target(self.shared.volume(), ramp_frames);
+        self.volume.apply(&mut self.stretched, channels);
+        self.shared.tap.write(&self.stretched, channels as u16, sample_rate);
+
+        self.bytes.clear();
+        if let Some(quantizer) = self.quantizer.as_mut() {
+            quantizer.write(&self.stretched, &mut self.bytes);
+        }
+
+        let mut written = 0;
+        while written < self.bytes.len() {
+            match self.stream.write(&self.bytes[written..]) {
+                Ok(0) => break,
+                Ok(n) => written += n,
+                Err(e) => {
+                    log::error!("Audio output error: {}", e);
+                    self.shared.send(EngineEvent::System(SystemEvent::Error));
+                    break;
+                }
+            }
+        }
+
+        // A pending load or seek has already set the position it restarts from
+        if settled {
+            self.shared.frames_played.fetch_add(played as u64, Ordering::AcqRel);
+        }
+    }
+
+    /// Copy `stretched` to the recording, if there is one
+    fn record(&mut self, channels: u16, sample_rate: u32) {
+        if let Some(tap) = self.shared.take_record_tap() {
+            self.record = tap;
+        }
+        if let Some(tap) = self.record.as_ref() {
+            let bits_per_sample = self.shared.bits_per_sample.load(Ordering::Acquire) as u16;
+            tap.send((channels, sample_rate, bits_per_sample), &self.stretched);
+        }
+    }
+
+    /// Gain change per frame of the click-free ramp
+    fn fade_step(&self, sample_rate: u32) -> f32 {
+        1.0 / (CLICK_FADE.as_secs_f32() * sample_rate as f32).max(1.0)
+    }
+
+    /// Swap in a chain the UI thread built, and prepare the stages for a new layout
+    fn update_chain(&mut self, channels: u16, sample_rate: u32) {
+        if let Some(chain) = self.shared.take_chain() {
+            self.chain = chain;
+        }
+        if !self.chain.is_prepared_for(channels, sample_rate) {
+            let bits_per_sample = self.shared.bits_per_sample.load(Ordering::Acquire) as u16;
+            self.chain.prepare(&AudioFormat { channels, sample_rate, bits_per_sample, ..Default::default() });
+        }
+        self.shared.latency.store(self.chain.latency() as u64, Ordering::Release);
+    }
+
+    /// Move the gain towards `target` by `step` a frame across the stretched samples
+    fn ramp(&mut self, channels: usize, step: f32, target: f32) {
+        if self.gain == target {
+            return;
+        }
+        for frame in self.stretched.chunks_mut(channels) {
+            self.gain = if target > self.gain {
+                (self.gain + step).min(target)
+            } else {
+                (self.gain - step).max(target)
+            };
+            frame.iter_mut().for_each(|sample| *sample *= self.gain);
+        }
+    }
+
+    fn end_of_track(&mut self) {
+        if self.shared.transition(PlaybackState::Playing, PlaybackState::Stopped) {
+            self.shared.send(EngineEvent::System(SystemEvent::TrackEnded));
+        }
+    }
+}
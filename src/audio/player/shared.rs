@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use crate::audio::dsp::ProcessorChain;
+use crate::audio::record::RecordTap;
+use crate::audio::speed::{PlaybackSpeed, SpeedMode};
+use crate::audio::tap::OutputTap;
+use crate::audio::PlaybackState;
+use super::EngineEvent;
+
+/// `boundary` value while no queued track has been spliced in
+pub(super) const NO_BOUNDARY: u64 = u64::MAX;
+
+/// State read and written by the UI thread and both engine threads
+pub(super) struct Shared {
+    state: AtomicU8,
+    /// Track frames handed to the output stream, counted from the start of the track; at
+    /// other speeds this runs faster or slower than the frames actually written
+    pub frames_played: AtomicU64,
+    /// Layout of the samples currently flowing through the ring buffer
+    pub channels: AtomicU32,
+    pub sample_rate: AtomicU32,
+    /// Bit depth the output stream is opened with, and whether to dither down to it
+    pub bits_per_sample: AtomicU32,
+    pub dither: AtomicBool,
+    /// Bits of the f32 volume gain the output thread ramps towards
+    volume: AtomicU32,
+    /// Bits of the f32 playback rate, and whether the pitch follows it
+    speed: AtomicU32,
+    varispeed: AtomicBool,
+    /// Processor chain waiting for the output thread to swap it in
+    chain: Mutex<Option<ProcessorChain>>,
+    chain_changed: AtomicBool,
+    /// Recording for the output thread to copy what it plays to, set or cleared by the UI thread
+    record: Mutex<Option<RecordTap>>,
+    record_changed: AtomicBool,
+    /// Everything the output thread sends to the stream, for meters and visualizers
+    pub tap: Arc<OutputTap>,
+    /// Frames of delay added by the output thread's processor chain
+    pub latency: AtomicU64,
+    /// Set by the decode thread once the current track has been fully queued
+    pub decode_finished: AtomicBool,
+    /// Bumped by the UI thread for every load, seek or stop sent to the decode thread
+    pub generation: AtomicU64,
+    /// Generation the decode thread has started queueing, and the one the output thread
+    /// has cleared the ring for; the ring holds stale samples while the two differ
+    pub flush_requested: AtomicU64,
+    pub flush_done: AtomicU64,
+    /// Frame the output restarts counting from once the pending flush completes
+    pub flush_position: AtomicU64,
+    /// Samples since the last flush after which the next track starts, or `NO_BOUNDARY`
+    pub boundary: AtomicU64,
+    /// Bumped by the output thread each time playback crosses into a queued track
+    pub tracks_advanced: AtomicU64,
+    pub shutdown: AtomicBool,
+    events: Sender<EngineEvent>,
+}
+
+impl Shared {
+    pub fn new(events: Sender<EngineEvent>) -> Self {
+        Self {
+            state: AtomicU8::new(encode_state(PlaybackState::Stopped)),
+            frames_played: AtomicU64::new(0),
+            channels: AtomicU32::new(0),
+            sample_rate: AtomicU32::new(0),
+            bits_per_sample: AtomicU32::new(16),
+            dither: AtomicBool::new(false),
+            volume: AtomicU32::new(1f32.to_bits()),
+            speed: AtomicU32::new(1f32.to_bits()),
+            varispeed: AtomicBool::new(false),
+            chain: Mutex::new(None),
+            chain_changed: AtomicBool::new(false),
+            record: Mutex::new(None),
+            record_changed: AtomicBool::new(false),
+            tap: Arc::new(OutputTap::new()),
+            latency: AtomicU64::new(0),
+            decode_finished: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            flush_requested: AtomicU64::new(0),
+            flush_done: AtomicU64::new(0),
+            flush_position: AtomicU64::new(0),
+            boundary: AtomicU64::new(NO_BOUNDARY),
+            tracks_advanced: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+            events,
+        }
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        decode_state(self.state.load(Ordering::Acquire))
+    }
+
+    /// Change the playback state, notifying the UI if it actually changed
+    pub fn set_state(&self, state: PlaybackState) {
+        let previous = self.state.swap(encode_state(state), Ordering::AcqRel);
+        if previous != encode_state(state) {
+            self.send(EngineEvent::StateChanged(state));
+        }
+    }
+
+    /// Move from `from` to `to` only if no other thread changed the state first
+    pub fn transition(&self, from: PlaybackState, to: PlaybackState) -> bool {
+        let changed = self.state
+            .compare_exchange(encode_state(from), encode_state(to), Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if changed {
+            self.send(EngineEvent::StateChanged(to));
+        }
+        changed
+    }
+
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Acquire))
+    }
+
+    pub fn set_volume(&self, gain: f32) {
+        self.volume.store(gain.to_bits(), Ordering::Release);
+    }
+
+    pub fn speed(&self) -> PlaybackSpeed {
+        PlaybackSpeed {
+            rate: f32::from_bits(self.speed.load(Ordering::Acquire)),
+            mode: match self.varispeed.load(Ordering::Acquire) {
+                true => SpeedMode::Varispeed,
+                false => SpeedMode::PreservePitch,
+            },
+        }
+    }
+
+    pub fn set_speed(&self, speed: PlaybackSpeed) {
+        self.varispeed.store(speed.mode == SpeedMode::Varispeed, Ordering::Release);
+        self.speed.store(speed.rate.to_bits(), Ordering::Release);
+    }
+
+    pub fn set_chain(&self, chain: ProcessorChain) {
+        *self.chain.lock().unwrap() = Some(chain);
+        self.chain_changed.store(true, Ordering::Release);
+    }
+
+    /// The processor chain to switch to, if one was set since the last call
+    pub fn take_chain(&self) -> Option<ProcessorChain> {
+        if !self.chain_changed.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        self.chain.lock().unwrap().take()
+    }
+
+    pub fn set_record_tap(&self, tap: Option<RecordTap>) {
+        *self.record.lock().unwrap() = tap;
+        self.record_changed.store(true, Ordering::Release);
+    }
+
+    /// The recording to copy output to, if one was started or stopped since the last call
+    pub fn take_record_tap(&self) -> Option<Option<RecordTap>> {
+        if !self.record_changed.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        Some(self.record.lock().unwrap().take())
+    }
+
+    /// True while the ring may still hold samples from before the last load or seek
+    pub fn flush_pending(&self) -> bool {
+        self.flush_done.load(Ordering::Acquire) != self.flush_requested.load(Ordering::Acquire)
+    }
+
+    /// True once the output has caught up with the latest load, seek or stop
+    pub fn settled(&self) -> bool {
+        self.flush_done.load(Ordering::Acquire) == self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn send(&self, event: EngineEvent) {
+        // The UI may have dropped its receiver while shutting down
+        let _ = self.events.send(event);
+    }
+}
+
+fn encode_state(state: PlaybackState) -> u8 {
+    match state {
+        PlaybackState::Stopped => 0,
+        PlaybackState::Playing => 1,
+        PlaybackState::Paused => 2,
+    }
+}
+
+fn decode_state(value: u8) -> PlaybackState {
+    match value {
+        1 => PlaybackState::Playing,
+        2 => PlaybackState::Paused,
+        _ => PlaybackState::Stopped,
+    }
+}
@@ -0,0 +1,695 @@
+use super::*;
+use crate::audio::formats::{get_decoder, AudioDecoder};
+use crate::audio::fade::FadeCurve;
+use crate::audio::record::RecordFormat;
+use crate::audio::replaygain::GainSource;
+use crate::audio::dsp::{AudioProcessor, ProcessorFactory, EQUALIZER};
+use crate::audio::eq::EqPreset;
+use crate::audio::speed::SpeedMode;
+use crate::audio::volume::VOLUME_RAMP;
+use id3::TagLike;
+use std::sync::Mutex;
+use std::path::Path;
+use std::time::Instant;
+
+const FIXTURE: &str = "test/testaudio-short.wav";
+
+/// Output stream that records everything written to it
+#[derive(Clone, Default)]
+struct CaptureStream {
+    written: Arc<Mutex<Vec<u8>>>,
+    opened: Arc<Mutex<Vec<AudioFormat>>>,
+    /// Pretend each write takes this long, like a device with a full buffer
+    write_delay: Duration,
+}
+
+impl AudioStream for CaptureStream {
+    fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
+        self.opened.lock().unwrap().push(format);
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error>> {
+        std::thread::sleep(self.write_delay);
+        self.written.lock().unwrap().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+fn slow_stream() -> CaptureStream {
+    CaptureStream {
+        write_delay: Duration::from_millis(5),
+        ..Default::default()
+    }
+}
+
+/// Collect engine events until `wanted` arrives
+fn wait_for(engine: &mut PlaybackEngine, wanted: EngineEvent) -> Vec<EngineEvent> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut seen = Vec::new();
+    while Instant::now() < deadline {
+        match engine.try_recv_event() {
+            Some(event) => {
+                seen.push(event);
+                if event == wanted {
+                    return seen;
+                }
+            }
+            None => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+    panic!("timed out waiting for {:?}, saw {:?}", wanted, seen);
+}
+
+fn wait_until(condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_new_playback_engine() {
+    let engine = PlaybackEngine::new();
+    assert_eq!(engine.state(), PlaybackState::Stopped);
+    assert_eq!(engine.position(), Duration::from_secs(0));
+    assert!(engine.duration().is_none());
+    assert!(engine.format().is_none());
+}
+
+#[test]
+fn test_basic_state_transitions() {
+    let mut engine = PlaybackEngine::new();
+
+    // Test play
+    engine.play().unwrap();
+    assert_eq!(engine.state(), PlaybackState::Playing);
+
+    // Test pause
+    engine.pause().unwrap();
+    assert_eq!(engine.state(), PlaybackState::Paused);
+
+    // Test stop
+    engine.stop().unwrap();
+    assert_eq!(engine.state(), PlaybackState::Stopped);
+    assert_eq!(engine.position(), Duration::from_secs(0));
+}
+
+#[test]
+fn test_state_changes_are_reported() {
+    let mut engine = PlaybackEngine::new();
+    engine.play().unwrap();
+    engine.pause().unwrap();
+    engine.pause().unwrap();
+    engine.stop().unwrap();
+
+    let events: Vec<_> = std::iter::from_fn(|| engine.try_recv_event()).collect();
+    assert_eq!(events, vec![
+        EngineEvent::StateChanged(PlaybackState::Playing),
+        EngineEvent::StateChanged(PlaybackState::Paused),
+        EngineEvent::StateChanged(PlaybackState::Stopped),
+    ]);
+}
+
+#[test]
+fn test_load_track() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+
+    let format = engine.format().unwrap();
+    assert_eq!(format.channels, 1);
+    assert_eq!(format.sample_rate, 48000);
+    assert_eq!(engine.duration(), Some(Duration::from_secs(1)));
+    assert_eq!(engine.current_file(), Some(FIXTURE));
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackLoaded));
+
+    // Nothing reaches the output until playback starts
+    wait_until(|| !stream.opened.lock().unwrap().is_empty());
+    assert_eq!(stream.opened.lock().unwrap()[0].bits_per_sample, 16);
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(stream.written.lock().unwrap().is_empty());
+    assert_eq!(engine.position(), Duration::from_secs(0));
+}
+
+#[test]
+fn test_load_missing_file() {
+    let mut engine = PlaybackEngine::new();
+    assert!(engine.load("test/does-not-exist.wav").is_err());
+    assert!(engine.format().is_none());
+    assert!(engine.try_recv_event().is_none());
+}
+
+#[test]
+fn test_play_to_end() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+    engine.play().unwrap();
+
+    let events = wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    // TrackLoaded races with the UI's own play, but the end is always reported last
+    assert_eq!(events.len(), 4);
+    assert!(events.contains(&EngineEvent::System(SystemEvent::TrackLoaded)));
+    assert!(events.contains(&EngineEvent::StateChanged(PlaybackState::Playing)));
+    assert_eq!(events[2..], [
+        EngineEvent::StateChanged(PlaybackState::Stopped),
+        EngineEvent::System(SystemEvent::TrackEnded),
+    ]);
+    assert_eq!(engine.state(), PlaybackState::Stopped);
+
+    // Position comes from frames actually written, so it lands exactly on the end
+    assert_eq!(engine.position(), Duration::from_secs(1));
+    let written = stream.written.lock().unwrap();
+    assert_eq!(written.len(), 48000 * 2);
+
+    // The output matches the decoded file sample for sample
+    let mut reader = get_decoder(Path::new(FIXTURE)).decode(Path::new(FIXTURE)).unwrap();
+    let mut expected = vec![0.0f32; 48000];
+    assert_eq!(reader.read(&mut expected).unwrap(), 48000);
+    for (i, sample) in expected.iter().enumerate() {
+        let actual = i16::from_le_bytes([written[i * 2], written[i * 2 + 1]]);
+        assert_eq!(actual, (sample * 32768.0).round() as i16, "sample {}", i);
+    }
+}
+
+#[test]
+fn test_seek_before_play() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+    engine.seek(Duration::from_millis(250)).unwrap();
+    assert_eq!(engine.position(), Duration::from_millis(250));
+
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+    assert_eq!(stream.written.lock().unwrap().len(), 36000 * 2);
+}
+
+#[test]
+fn test_cue_track_plays_its_span() {
+    let dir = tempfile::tempdir().unwrap();
+    let album = dir.path().join("album.wav");
+    std::fs::copy(FIXTURE, &album).unwrap();
+    std::fs::write(
+        dir.path().join("album.cue"),
+        "FILE \"album.wav\" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    INDEX 01 00:00:30\n  \
+         TRACK 03 AUDIO\n    INDEX 01 00:00:60\n",
+    ).unwrap();
+    let track = crate::metadata::cuesheet::track_path(&album, 2);
+
+    // Track 2 runs from 0.4s to track 3 at 0.8s, and can't be sought past its end
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(&track).unwrap();
+    assert_eq!(engine.duration(), Some(Duration::from_millis(400)));
+    assert!(engine.seek(Duration::from_millis(500)).is_err());
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_millis(400));
+
+    let mut reader = get_decoder(Path::new(FIXTURE)).decode(Path::new(FIXTURE)).unwrap();
+    let mut expected = vec![0.0f32; 48000];
+    reader.read(&mut expected).unwrap();
+    let written = stream.written.lock().unwrap();
+    assert_eq!(written.len(), 19200 * 2);
+    for (i, sample) in expected[19200..38400].iter().enumerate() {
+        let actual = i16::from_le_bytes([written[i * 2], written[i * 2 + 1]]);
+        assert_eq!(actual, (sample * 32768.0).round() as i16, "sample {}", i);
+    }
+}
+
+#[test]
+fn test_seek_bounds() {
+    let mut engine = PlaybackEngine::new();
+    assert!(engine.seek(Duration::from_secs(0)).is_err());
+
+    engine.load(FIXTURE).unwrap();
+    assert!(engine.seek(Duration::from_secs(2)).is_err());
+    assert!(engine.seek(Duration::from_secs(1)).is_ok());
+}
+
+#[test]
+fn test_seek_targets() {
+    let second = Duration::from_secs(1);
+    let at = |target: SeekTarget, duration| target.resolve(Duration::from_millis(400), duration).unwrap();
+    assert_eq!(at(SeekTarget::Forward(second), Some(second)), second);
+    assert_eq!(at(SeekTarget::Forward(second), None), Duration::from_millis(1400));
+    assert_eq!(at(SeekTarget::Backward(second), Some(second)), Duration::ZERO);
+    assert_eq!(at(SeekTarget::Percent(25.0), Some(second)), Duration::from_millis(250));
+    assert_eq!(at(SeekTarget::Percent(150.0), Some(second)), second);
+    assert!(SeekTarget::Percent(50.0).resolve(Duration::ZERO, None).is_err());
+    assert!(SeekTarget::Percent(f32::NAN).resolve(Duration::ZERO, Some(second)).is_err());
+}
+
+#[test]
+fn test_seek_to_relative_and_percent() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+    engine.seek_to(SeekTarget::Percent(50.0)).unwrap();
+    assert_eq!(engine.position(), Duration::from_millis(500));
+    engine.seek_to(SeekTarget::Backward(Duration::from_millis(250))).unwrap();
+    assert_eq!(engine.position(), Duration::from_millis(250));
+    engine.seek_to(SeekTarget::Forward(Duration::from_millis(500))).unwrap();
+    assert_eq!(engine.position(), Duration::from_millis(750));
+
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+    assert_eq!(stream.written.lock().unwrap().len(), 12000 * 2);
+}
+
+#[test]
+fn test_pause_holds_position() {
+    let mut engine = PlaybackEngine::with_stream(Box::new(slow_stream()));
+    engine.load(FIXTURE).unwrap();
+    engine.play().unwrap();
+    wait_until(|| engine.position() > Duration::from_secs(0));
+
+    let started = Instant::now();
+    engine.pause().unwrap();
+    assert!(started.elapsed() < Duration::from_millis(5));
+
+    // At most the period already being written is counted after pausing
+    std::thread::sleep(Duration::from_millis(20));
+    let paused_at = engine.position();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(engine.position(), paused_at);
+    assert!(paused_at < Duration::from_secs(1));
+
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+}
+
+#[test]
+fn test_stop_rewinds() {
+    let stream = slow_stream();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+    engine.play().unwrap();
+    wait_until(|| engine.position() > Duration::from_secs(0));
+
+    engine.stop().unwrap();
+    assert_eq!(engine.state(), PlaybackState::Stopped);
+    assert_eq!(engine.position(), Duration::from_secs(0));
+
+    // Playing again starts over from the first frame
+    std::thread::sleep(Duration::from_millis(20));
+    let before = stream.written.lock().unwrap().len();
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(stream.written.lock().unwrap().len() - before, 48000 * 2);
+}
+
+#[test]
+fn test_converts_to_output_format() {
+    // The 96kHz/24-bit stereo sweep played through a 44.1kHz mono 24-bit output
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    assert!(engine.set_output_format(OutputFormat { bits_per_sample: 20, ..Default::default() }).is_err());
+    engine.set_output_format(OutputFormat {
+        channels: Some(1),
+        sample_rate: Some(44100),
+        bits_per_sample: 24,
+        ..Default::default()
+    }).unwrap();
+
+    engine.load("test/testaudio-sweep-96k24.wav").unwrap();
+    assert_eq!(engine.format().unwrap().sample_rate, 96000);
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+
+    let opened = stream.opened.lock().unwrap().clone();
+    assert_eq!(opened.len(), 1);
+    assert_eq!((opened[0].channels, opened[0].sample_rate, opened[0].bits_per_sample), (1, 44100, 24));
+    // 0.25s at 44.1kHz, three bytes a frame
+    assert_eq!(stream.written.lock().unwrap().len(), 11025 * 3);
+    assert_eq!(engine.position(), Duration::from_millis(250));
+
+    engine.seek(Duration::from_millis(100)).unwrap();
+    assert_eq!(engine.position(), Duration::from_millis(100));
+}
+
+#[test]
+fn test_queued_track_plays_without_gap() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    assert!(engine.queue_next(FIXTURE).is_err());
+    engine.load(FIXTURE).unwrap();
+    // The whole first track is already decoded by the time the second is queued
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackLoaded));
+    engine.queue_next(FIXTURE).unwrap();
+    assert_eq!(engine.queued_file(), Some(FIXTURE));
+    engine.play().unwrap();
+
+    // Crossing into the queued track ends one and loads the next without stopping
+    let events = wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert!(!events.contains(&EngineEvent::StateChanged(PlaybackState::Stopped)));
+    assert_eq!(wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackLoaded)).len(), 1);
+    assert_eq!(engine.current_file(), Some(FIXTURE));
+    assert!(engine.queued_file().is_none());
+    assert!(engine.position() < Duration::from_secs(1));
+
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+    assert_eq!(stream.opened.lock().unwrap().len(), 1);
+
+    // Both copies land back to back, sample for sample
+    let mut reader = get_decoder(Path::new(FIXTURE)).decode(Path::new(FIXTURE)).unwrap();
+    let mut expected = vec![0.0f32; 48000];
+    assert_eq!(reader.read(&mut expected).unwrap(), 48000);
+    let written = stream.written.lock().unwrap();
+    assert_eq!(written.len(), 2 * 48000 * 2);
+    for (i, sample) in expected.iter().chain(&expected).enumerate() {
+        let actual = i16::from_le_bytes([written[i * 2], written[i * 2 + 1]]);
+        assert_eq!(actual, (sample * 32768.0).round() as i16, "sample {}", i);
+    }
+}
+
+#[test]
+fn test_queued_track_reopens_output_for_new_layout() {
+    let stream = slow_stream();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load("test/testaudio-sweep-96k24.wav").unwrap();
+    engine.queue_next(FIXTURE).unwrap();
+    engine.play().unwrap();
+
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackLoaded));
+    assert_eq!(engine.current_file(), Some(FIXTURE));
+    assert_eq!(engine.format().unwrap().sample_rate, 48000);
+
+    // Seeking now applies to the second track and keeps it current
+    engine.seek(Duration::from_millis(500)).unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+
+    let opened = stream.opened.lock().unwrap().clone();
+    let layouts: Vec<_> = opened.iter().map(|f| (f.channels, f.sample_rate)).collect();
+    assert_eq!(layouts, vec![(2, 96000), (1, 48000)]);
+}
+
+#[test]
+fn test_crossfade_overlaps_queued_track() {
+    let stream = CaptureStream::default();
+    // A small ring keeps the first track from being decoded in full before the second is queued
+    let mut engine = PlaybackEngine::with_ring(Box::new(stream.clone()), 4096);
+    let fade = Crossfade { duration: Duration::from_millis(100), curve: FadeCurve::Linear };
+    assert!(engine.set_crossfade(Some(Crossfade { duration: Duration::from_secs(13), ..fade })).is_err());
+    engine.set_crossfade(Some(fade)).unwrap();
+    engine.load(FIXTURE).unwrap();
+    engine.queue_next(FIXTURE).unwrap();
+    engine.play().unwrap();
+
+    let events = wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert!(!events.contains(&EngineEvent::StateChanged(PlaybackState::Stopped)));
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    assert_eq!(engine.position(), Duration::from_secs(1));
+
+    // The last 100ms of the first copy play over the first 100ms of the second
+    let mut reader = get_decoder(Path::new(FIXTURE)).decode(Path::new(FIXTURE)).unwrap();
+    let mut track = vec![0.0f32; 48000];
+    assert_eq!(reader.read(&mut track).unwrap(), 48000);
+    let written = stream.written.lock().unwrap();
+    assert_eq!(written.len(), (2 * 48000 - 4800) * 2);
+    let sample = |i: usize| i16::from_le_bytes([written[i * 2], written[i * 2 + 1]]) as i32;
+    for i in 0..4800 {
+        let progress = (i as f32 + 0.5) / 4800.0;
+        let mixed = track[43200 + i] * (1.0 - progress) + track[i] * progress;
+        assert!((sample(43200 + i) - (mixed * 32768.0).round() as i32).abs() <= 1, "sample {}", i);
+    }
+    assert_eq!(sample(48000), (track[4800] * 32768.0).round() as i32);
+}
+
+#[test]
+fn test_pause_and_resume_ramp_level() {
+    let stream = slow_stream();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.load(FIXTURE).unwrap();
+    engine.play().unwrap();
+    wait_until(|| engine.position() > Duration::from_millis(100));
+    engine.pause().unwrap();
+    std::thread::sleep(Duration::from_millis(30));
+
+    // The tone dies away over the last 10ms written rather than stopping dead
+    let peak = |bytes: &[u8]| bytes.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]).unsigned_abs()).max().unwrap();
+    let paused_at = stream.written.lock().unwrap().len();
+    {
+        let written = stream.written.lock().unwrap();
+        assert!(peak(&written[paused_at - 40..]) < 1000);
+        assert!(peak(&written[paused_at - 960 * 2..paused_at - 480 * 2]) > 10000);
+    }
+
+    // And comes back in the same way
+    engine.play().unwrap();
+    wait_until(|| stream.written.lock().unwrap().len() > paused_at + 960 * 2);
+    let written = stream.written.lock().unwrap();
+    assert!(peak(&written[paused_at..paused_at + 40]) < 1000);
+    assert!(peak(&written[paused_at + 480 * 2..paused_at + 960 * 2]) > 10000);
+}
+
+#[test]
+fn test_replay_gain_scales_samples() {
+    let dir = tempfile::tempdir().unwrap();
+    let tagged = dir.path().join("tagged.mp3");
+    std::fs::copy("test/testaudio-short.mp3", &tagged).unwrap();
+    let mut tag = id3::Tag::new();
+    tag.add_frame(id3::frame::ExtendedText {
+        description: "REPLAYGAIN_TRACK_GAIN".to_string(),
+        value: "-6.02 dB".to_string(),
+    });
+    tag.write_to_path(&tagged, id3::Version::Id3v24).unwrap();
+
+    let play = |path: &Path, mode: ReplayGainMode| {
+        let stream = CaptureStream::default();
+        let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+        engine.set_replay_gain(ReplayGain { mode, ..Default::default() });
+        engine.load(path.to_str().unwrap()).unwrap();
+        let gain = engine.applied_gain().unwrap();
+        engine.play().unwrap();
+        wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+        let written = stream.written.lock().unwrap().clone();
+        (gain, written)
+    };
+
+    let (unity, reference) = play(&tagged, ReplayGainMode::Off);
+    assert_eq!(unity, AppliedGain::UNITY);
+    let (gain, halved) = play(&tagged, ReplayGainMode::Track);
+    assert_eq!(gain.source, GainSource::Track);
+    assert!((gain.db + 6.02).abs() < 1e-4);
+    assert_eq!(halved.len(), reference.len());
+
+    // -6.02dB halves every sample
+    let samples = |bytes: &[u8]| bytes.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as i32).collect::<Vec<_>>();
+    let (reference, halved) = (samples(&reference), samples(&halved));
+    assert!(reference.iter().any(|s| s.abs() > 10000));
+    for (i, (full, half)) in reference.iter().zip(&halved).enumerate() {
+        assert!((full / 2 - half).abs() <= 2, "sample {}: {} vs {}", i, full, half);
+    }
+}
+
+#[test]
+fn test_volume_ramps_to_mute() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.set_volume(80);
+    assert!(engine.toggle_mute());
+    assert_eq!(engine.volume().level(), 80);
+    engine.load("test/testaudio-short.wav").unwrap();
+    let rate = engine.format().unwrap().sample_rate as usize;
+    let channels = engine.format().unwrap().channels as usize;
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+
+    // The track still plays through, silent once the ramp down from full volume is over
+    let written = stream.written.lock().unwrap().clone();
+    let samples: Vec<i16> = written.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]])).collect();
+    let ramp = (VOLUME_RAMP.as_secs_f64() * rate as f64) as usize * channels;
+    assert!(samples.len() > ramp * 2);
+    assert!(samples[ramp..].iter().all(|&s| s == 0));
+
+    assert!(engine.set_volume_curve(VolumeCurve { range_db: 200.0, headroom_db: 0.0 }).is_err());
+}
+
+#[test]
+fn test_speed_keeps_position_in_track_time() {
+    let faster = PlaybackSpeed { rate: 2.0, mode: SpeedMode::PreservePitch };
+    let slower = PlaybackSpeed { rate: 0.5, mode: SpeedMode::Varispeed };
+    for speed in [faster, slower] {
+        let stream = CaptureStream::default();
+        let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+        engine.set_speed(speed).unwrap();
+        engine.load(FIXTURE).unwrap();
+        engine.play().unwrap();
+        wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+
+        // The whole track plays in half or twice the time, bar the last few tens of
+        // milliseconds the stretcher holds, which play out at normal speed
+        assert_eq!(engine.position(), Duration::from_secs(1));
+        assert_eq!(engine.duration(), Some(Duration::from_secs(1)));
+        let frames = stream.written.lock().unwrap().len() / 2;
+        let expected = 48000.0 / speed.rate;
+        assert!((frames as f32 - expected).abs() < 0.1 * 48000.0, "{:?} wrote {} frames", speed, frames);
+    }
+
+    let mut engine = PlaybackEngine::new();
+    assert!(engine.set_speed(PlaybackSpeed { rate: 4.0, ..faster }).is_err());
+    assert_eq!(engine.speed(), PlaybackSpeed::default());
+}
+
+#[test]
+fn test_recording_taps_output_before_volume() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = RecordSettings { directory: dir.path().to_path_buf(), format: RecordFormat::Wav };
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.set_volume(40);
+    engine.load(FIXTURE).unwrap();
+    engine.start_recording(&settings).unwrap();
+    assert!(engine.start_recording(&settings).is_err());
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+    let status = engine.stop_recording().unwrap().unwrap();
+    assert!(engine.recording().is_none());
+
+    // Everything played is in the file, at full level whatever the volume
+    let written = stream.written.lock().unwrap().clone();
+    let played: Vec<i16> = written.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]])).collect();
+    assert_eq!(status.elapsed, Duration::from_secs(1));
+    let mut reader = get_decoder(&status.path).decode(&status.path).unwrap();
+    let mut recorded = vec![0.0; played.len() + 1];
+    assert_eq!(reader.read(&mut recorded).unwrap(), played.len());
+    // Past the volume ramp at the start
+    let peak = |samples: &mut dyn Iterator<Item = f32>| samples.skip(4800).fold(0.0, |peak: f32, s| peak.max(s.abs()));
+    let played_peak = peak(&mut played.iter().map(|&s| s as f32 / 32768.0));
+    let recorded_peak = peak(&mut recorded.into_iter());
+    assert!(recorded_peak > 2.0 * played_peak, "recorded {} against {} played", recorded_peak, played_peak);
+}
+
+#[test]
+fn test_output_tap_sees_what_is_played() {
+    let stream = CaptureStream::default();
+    let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+    engine.set_volume(40);
+    let mut tap = engine.output_tap();
+    engine.load(FIXTURE).unwrap();
+    engine.play().unwrap();
+    wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+
+    let mut tapped = Vec::new();
+    tap.read(&mut tapped);
+    assert_eq!(tap.layout(), (1, 48000));
+    let written = stream.written.lock().unwrap().clone();
+    let played: Vec<f32> = written.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0).collect();
+    assert_eq!(tapped.len(), played.len());
+    // The volume is applied; only the quantization differs
+    for (tapped, played) in tapped.iter().zip(&played) {
+        assert!((tapped - played).abs() <= 1.0 / 32768.0, "{} against {}", tapped, played);
+    }
+}
+
+#[test]
+fn test_equalizer_filters_output() {
+    let play = |preset: Option<EqPreset>| {
+        let stream = CaptureStream::default();
+        let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+        engine.set_equalizer(preset).unwrap();
+        engine.load("test/testaudio-short.wav").unwrap();
+        engine.play().unwrap();
+        wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+        let written = stream.written.lock().unwrap().clone();
+        written.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as i32).collect::<Vec<_>>()
+    };
+
+    let reference = play(None);
+    assert_eq!(play(Some(EqPreset::flat())), reference);
+
+    // A flat curve 6.02dB down halves every sample
+    let mut quieter = EqPreset::flat();
+    quieter.preamp_db = -6.02;
+    let halved = play(Some(quieter));
+    assert_eq!(halved.len(), reference.len());
+    for (i, (full, half)) in reference.iter().zip(&halved).enumerate() {
+        assert!((full / 2 - half).abs() <= 2, "sample {}: {} vs {}", i, full, half);
+    }
+
+    let mut engine = PlaybackEngine::with_stream(Box::new(CaptureStream::default()));
+    let mut invalid = EqPreset::flat();
+    invalid.bands[0].gain_db = 40.0;
+    assert!(engine.set_equalizer(Some(invalid)).is_err());
+    assert!(engine.equalizer().is_none());
+}
+
+/// Third-party stage inverting the polarity of every sample
+struct Invert;
+
+impl AudioProcessor for Invert {
+    fn prepare(&mut self, _format: &AudioFormat) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        samples.iter_mut().for_each(|sample| *sample = -*sample);
+    }
+
+    fn reset(&mut self) {}
+
+    fn latency(&self) -> usize {
+        256
+    }
+}
+
+impl ProcessorFactory for Invert {
+    fn name(&self) -> &str {
+        "invert"
+    }
+
+    fn create(&self) -> Box<dyn AudioProcessor> {
+        Box::new(Invert)
+    }
+}
+
+#[test]
+fn test_registered_processor_runs_in_chain() {
+    let play = |stages: Vec<ProcessorStage>| {
+        let stream = CaptureStream::default();
+        let mut engine = PlaybackEngine::with_stream(Box::new(stream.clone()));
+        engine.register_processor(Arc::new(Invert));
+        engine.set_processor_chain(stages).unwrap();
+        engine.load("test/testaudio-short.wav").unwrap();
+        engine.play().unwrap();
+        wait_for(&mut engine, EngineEvent::System(SystemEvent::TrackEnded));
+        let written = stream.written.lock().unwrap().clone();
+        let samples: Vec<i32> = written.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as i32).collect();
+        (samples, engine.processing_latency())
+    };
+
+    let (reference, latency) = play(vec![ProcessorStage::new(EQUALIZER)]);
+    assert_eq!(latency, Duration::ZERO);
+    assert!(reference.iter().any(|&s| s != 0));
+
+    let (inverted, latency) = play(vec![ProcessorStage::new(EQUALIZER), ProcessorStage::new("invert")]);
+    assert_eq!(inverted.len(), reference.len());
+    // Quantizing is asymmetric about zero by a step
+    assert!(inverted.iter().zip(&reference).all(|(a, b)| (a + b).abs() <= 1));
+    assert!(latency > Duration::ZERO);
+
+    let bypassed = ProcessorStage { bypassed: true, ..ProcessorStage::new("invert") };
+    assert_eq!(play(vec![bypassed]).0, reference);
+
+    let mut engine = PlaybackEngine::with_stream(Box::new(CaptureStream::default()));
+    assert_eq!(engine.available_processors(), vec![EQUALIZER]);
+    assert!(engine.set_processor_chain(vec![ProcessorStage::new("invert")]).is_err());
+    assert_eq!(engine.processor_chain(), &[ProcessorStage::new(EQUALIZER)]);
+    assert!(engine.set_processor_bypassed(0, true).is_ok());
+    assert!(engine.processor_chain()[0].bypassed);
+    assert!(engine.set_processor_bypassed(1, true).is_err());
+}
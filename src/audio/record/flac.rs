@@ -0,0 +1,325 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::audio::AudioFormat;
+use super::RecordTags;
+
+/// Frames per FLAC block, the size reference encoders use at CD rates
+const BLOCK_FRAMES: usize = 4096;
+
+/// Highest fixed-predictor order FLAC defines
+const MAX_ORDER: usize = 4;
+
+/// Largest Rice parameter that fits the 4-bit field without the escape code
+const MAX_RICE: u32 = 14;
+
+/// Where STREAMINFO starts, after the `fLaC` marker and its block header
+const STREAMINFO_OFFSET: u64 = 8;
+
+/// Encodes integer PCM to a FLAC file with fixed linear predictors and Rice-coded residuals
+pub(super) struct FlacWriter {
+    writer: BufWriter<File>,
+    format: AudioFormat,
+    /// Interleaved samples waiting for a full block
+    pending: Vec<i32>,
+    frames_written: u64,
+    blocks_written: u64,
+    min_frame_len: u32,
+    max_frame_len: u32,
+    bytes: u64,
+}
+
+impl FlacWriter {
+    pub fn create(path: &Path, format: AudioFormat, tags: &RecordTags) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut header = b"fLaC".to_vec();
+        header.extend_from_slice(&block_header(false, 0, 34));
+        header.extend_from_slice(&streaminfo(&format, BLOCK_FRAMES as u16, 0, 0, 0));
+        let comments = vorbis_comment(tags);
+        header.extend_from_slice(&block_header(true, 4, comments.len() as u32));
+        header.extend_from_slice(&comments);
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            format,
+            pending: Vec::with_capacity(BLOCK_FRAMES * 2),
+            frames_written: 0,
+            blocks_written: 0,
+            min_frame_len: u32::MAX,
+            max_frame_len: 0,
+            bytes: header.len() as u64,
+        })
+    }
+
+    /// Queue interleaved `samples`, writing out every block they complete
+    pub fn write(&mut self, samples: &[i32]) -> io::Result<()> {
+        let block_len = BLOCK_FRAMES * self.format.channels.max(1) as usize;
+        self.pending.extend_from_slice(samples);
+        while self.pending.len() >= block_len {
+            let block: Vec<i32> = self.pending.drain(..block_len).collect();
+            self.write_block(&block)?;
+        }
+        Ok(())
+    }
+
+    /// Bytes in the file so far
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Write the last, shorter block and fill in the stream length and frame sizes, returning
+    /// the size of the finished file
+    pub fn finish(mut self) -> io::Result<u64> {
+        if !self.pending.is_empty() {
+            let block = std::mem::take(&mut self.pending);
+            self.write_block(&block)?;
+        }
+        let (min, max) = match self.blocks_written {
+            0 => (0, 0),
+            _ => (self.min_frame_len, self.max_frame_len),
+        };
+        let info = streaminfo(&self.format, BLOCK_FRAMES as u16, min, max, self.frames_written);
+        self.writer.seek(SeekFrom::Start(STREAMINFO_OFFSET))?;
+        self.writer.write_all(&info)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.bytes)
+    }
+
+    fn write_block(&mut self, block: &[i32]) -> io::Result<()> {
+        let frame = encode_frame(&self.format, self.blocks_written, block);
+        self.writer.write_all(&frame)?;
+        self.blocks_written += 1;
+        self.frames_written += (block.len() / self.format.channels.max(1) as usize) as u64;
+        self.min_frame_len = self.min_frame_len.min(frame.len() as u32);
+        self.max_frame_len = self.max_frame_len.max(frame.len() as u32);
+        self.bytes += frame.len() as u64;
+        Ok(())
+    }
+}
+
+fn block_header(last: bool, kind: u8, len: u32) -> [u8; 4] {
+    let [_, a, b, c] = len.to_be_bytes();
+    [(last as u8) << 7 | kind, a, b, c]
+}
+
+fn streaminfo(format: &AudioFormat, block_frames: u16, min_frame: u32, max_frame: u32, total: u64) -> [u8; 34] {
+    let mut bits = BitWriter::default();
+    bits.put(block_frames as u64, 16);
+    bits.put(block_frames as u64, 16);
+    bits.put(min_frame as u64, 24);
+    bits.put(max_frame as u64, 24);
+    bits.put(format.sample_rate as u64, 20);
+    bits.put(format.channels.max(1) as u64 - 1, 3);
+    bits.put(format.bits_per_sample as u64 - 1, 5);
+    bits.put(total, 36);
+    // An all-zero MD5 signature means none was computed
+    bits.put(0, 64);
+    bits.put(0, 64);
+    bits.bytes.try_into().expect("STREAMINFO is 34 bytes")
+}
+
+fn vorbis_comment(tags: &RecordTags) -> Vec<u8> {
+    let fields: Vec<String> = tags.fields.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+    let mut out = Vec::new();
+    push_string(&mut out, "playtui");
+    out.extend_from_slice(&(fields.len() as u32).to_le_bytes());
+    for field in &fields {
+        push_string(&mut out, field);
+    }
+    out
+}
+
+/// A Vorbis comment string: its length in bytes, then the UTF-8
+fn push_string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// One frame holding `block`, each channel coded separately
+fn encode_frame(format: &AudioFormat, number: u64, block: &[i32]) -> Vec<u8> {
+    let channels = format.channels.max(1) as usize;
+    let frames = block.len() / channels;
+    let bits_per_sample = format.bits_per_sample as u32;
+    let mut bits = BitWriter::default();
+
+    // Sync code with fixed block sizes; the size follows the header as 16 bits and the
+    // rate comes from STREAMINFO
+    bits.put(0xFFF8, 16);
+    bits.put(0b0111, 4);
+    bits.put(0b0000, 4);
+    bits.put(channels as u64 - 1, 4);
+    let size_code = match bits_per_sample {
+        8 => 0b001,
+        16 => 0b100,
+        24 => 0b110,
+        _ => 0b000,
+    };
+    bits.put(size_code, 3);
+    bits.put(0, 1);
+    bits.put_utf8(number);
+    bits.put(frames as u64 - 1, 16);
+    let crc = crc8(&bits.bytes);
+    bits.put(crc as u64, 8);
+
+    let mut samples = Vec::with_capacity(frames);
+    for channel in 0..channels {
+        samples.clear();
+        samples.extend(block.iter().skip(channel).step_by(channels).map(|&sample| sample as i64));
+        encode_subframe(&mut bits, &samples, bits_per_sample);
+    }
+    bits.align();
+    let crc = crc16(&bits.bytes);
+    bits.put(crc as u64, 16);
+    bits.bytes
+}
+
+/// Code a channel as a constant, the cheapest fixed predictor, or verbatim if nothing saves space
+fn encode_subframe(bits: &mut BitWriter, samples: &[i64], bits_per_sample: u32) {
+    if samples.iter().all(|&sample| sample == samples[0]) {
+        bits.put(0, 8);
+        bits.put_signed(samples[0], bits_per_sample);
+        return;
+    }
+
+    let (order, residuals) = (0..=MAX_ORDER.min(samples.len() - 1))
+        .map(|order| (order, fixed_residuals(samples, order)))
+        .min_by_key(|(_, residuals)| residuals.iter().map(|r| r.unsigned_abs()).sum::<u64>())
+        .expect("order 0 always applies");
+    let (rice, residual_bits) = rice_parameter(&residuals);
+    let fixed_bits = (order as u64) * bits_per_sample as u64 + 10 + residual_bits;
+    if fixed_bits >= samples.len() as u64 * bits_per_sample as u64 {
+        bits.put(0b000001 << 1, 8);
+        samples.iter().for_each(|&sample| bits.put_signed(sample, bits_per_sample));
+        return;
+    }
+
+    bits.put((0b001000 | order as u64) << 1, 8);
+    samples[..order].iter().for_each(|&sample| bits.put_signed(sample, bits_per_sample));
+    // Rice coding with 4-bit parameters and a single partition
+    bits.put(0b00, 2);
+    bits.put(0, 4);
+    bits.put(rice as u64, 4);
+    for &residual in &residuals {
+        let folded = zigzag(residual);
+        bits.put_unary(folded >> rice);
+        bits.put(folded & ((1 << rice) - 1), rice);
+    }
+}
+
+/// What is left of each sample after predicting it from the `order` before it
+fn fixed_residuals(samples: &[i64], order: usize) -> Vec<i64> {
+    samples.windows(order + 1).map(|w| {
+        let n = order;
+        match order {
+            0 => w[n],
+            1 => w[n] - w[n - 1],
+            2 => w[n] - 2 * w[n - 1] + w[n - 2],
+            3 => w[n] - 3 * w[n - 1] + 3 * w[n - 2] - w[n - 3],
+            _ => w[n] - 4 * w[n - 1] + 6 * w[n - 2] - 4 * w[n - 3] + w[n - 4],
+        }
+    }).collect()
+}
+
+/// The Rice parameter coding `residuals` in the fewest bits, and that many bits
+fn rice_parameter(residuals: &[i64]) -> (u32, u64) {
+    (0..=MAX_RICE)
+        .map(|rice| {
+            let quotients: u64 = residuals.iter().map(|&r| zigzag(r) >> rice).sum();
+            (rice, quotients + residuals.len() as u64 * (rice as u64 + 1))
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .expect("at least one parameter")
+}
+
+/// Interleave signs so small magnitudes of either sign get small codes
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// CRC-8 with polynomial x^8 + x^2 + x + 1, over frame headers
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// CRC-16 with polynomial x^16 + x^15 + x^2 + 1, over whole frames
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// Packs values most significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits not yet making up a whole byte, in the low `pending` bits
+    accumulator: u64,
+    pending: u32,
+}
+
+impl BitWriter {
+    /// The low `count` bits of `value`, up to 32 at a time
+    fn put(&mut self, value: u64, count: u32) {
+        if count > 32 {
+            self.put(value >> 32, count - 32);
+            self.put(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+        if count == 0 {
+            return;
+        }
+        self.accumulator = (self.accumulator << count) | (value & ((1 << count) - 1));
+        self.pending += count;
+        while self.pending >= 8 {
+            self.pending -= 8;
+            self.bytes.push((self.accumulator >> self.pending) as u8);
+        }
+        self.accumulator &= (1 << self.pending) - 1;
+    }
+
+    fn put_signed(&mut self, value: i64, count: u32) {
+        self.put(value as u64, count);
+    }
+
+    /// `value` zeros and a one
+    fn put_unary(&mut self, mut value: u64) {
+        while value >= 32 {
+            self.put(0, 32);
+            value -= 32;
+        }
+        self.put(1, value as u32 + 1);
+    }
+
+    /// A frame number in FLAC's extension of UTF-8 to 36 bits
+    fn put_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.put(value, 8);
+            return;
+        }
+        // Each continuation byte carries six bits, the lead byte what is left
+        let continuations = (1..=6u32).find(|&c| value < 1 << (5 * c + 6)).unwrap_or(6);
+        let lead = (0xFF00u64 >> (continuations + 1)) & 0xFF;
+        self.put(lead | value >> (6 * continuations), 8);
+        for shift in (0..continuations).rev() {
+            self.put(0x80 | (value >> (6 * shift)) & 0x3F, 8);
+        }
+    }
+
+    /// Zero-pad to the next byte boundary
+    fn align(&mut self) {
+        if self.pending > 0 {
+            self.put(0, 8 - self.pending);
+        }
+    }
+}
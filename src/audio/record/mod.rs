@@ -0,0 +1,359 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::audio::convert::Quantizer;
+use crate::audio::AudioFormat;
+use crate::metadata::Metadata;
+
+mod flac;
+mod wav;
+
+#[cfg(test)]
+mod tests;
+
+use flac::FlacWriter;
+use wav::WavWriter;
+
+/// Container recordings are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordFormat {
+    Wav,
+    /// Lossless and about half the size
+    #[default]
+    Flac,
+}
+
+impl RecordFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            RecordFormat::Wav => "wav",
+            RecordFormat::Flac => "flac",
+        }
+    }
+}
+
+/// Where recordings go and what they are written as
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordSettings {
+    pub directory: PathBuf,
+    pub format: RecordFormat,
+}
+
+impl Default for RecordSettings {
+    fn default() -> Self {
+        Self {
+            directory: default_directory(),
+            format: RecordFormat::default(),
+        }
+    }
+}
+
+/// A `playtui` folder in the user's music directory, or the working directory without one
+pub fn default_directory() -> PathBuf {
+    directories::UserDirs::new()
+        .and_then(|dirs| dirs.audio_dir().map(|dir| dir.join("playtui")))
+        .unwrap_or_else(|| PathBuf::from("recordings"))
+}
+
+/// Tags written into recordings, as Vorbis comment fields such as `TITLE` and `ARTIST`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordTags {
+    pub fields: Vec<(String, String)>,
+}
+
+impl RecordTags {
+    /// The tags of the track a recording starts in
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let text = [
+            ("TITLE", metadata.title.clone()),
+            ("ARTIST", metadata.artist.clone()),
+            ("ALBUM", metadata.album.clone()),
+            ("GENRE", metadata.genre.clone()),
+            ("DATE", metadata.year.map(|year| year.to_string())),
+            ("TRACKNUMBER", metadata.track.map(|track| track.to_string())),
+        ];
+        Self {
+            fields: text.into_iter()
+                .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field == key).map(|(_, value)| value.as_str())
+    }
+}
+
+/// How far a recording has got
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingStatus {
+    pub path: PathBuf,
+    /// Length of audio recorded so far
+    pub elapsed: Duration,
+    /// Size on disk, across every file when the stream changed format part way
+    pub bytes: u64,
+}
+
+/// Counters the writer thread keeps for the UI
+#[derive(Default)]
+struct Progress {
+    nanos: AtomicU64,
+    bytes: AtomicU64,
+}
+
+enum RecordMessage {
+    /// Samples as written to the stream, and the channels, rate and bit depth it is open with
+    Samples { layout: (u16, u32, u16), samples: Vec<f32> },
+    Finish,
+}
+
+/// The output thread's end of a recording
+#[derive(Clone)]
+pub struct RecordTap {
+    sender: Sender<RecordMessage>,
+}
+
+impl RecordTap {
+    /// Hand the writer a copy of `samples`; dropped if the recording has stopped
+    pub fn send(&self, layout: (u16, u32, u16), samples: &[f32]) {
+        if !samples.is_empty() {
+            let _ = self.sender.send(RecordMessage::Samples { layout, samples: samples.to_vec() });
+        }
+    }
+}
+
+/// A recording in progress, written on its own thread so file I/O never holds up playback
+pub struct Recorder {
+    path: PathBuf,
+    sender: Sender<RecordMessage>,
+    progress: Arc<Progress>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Recorder {
+    /// Start a recording in a new file named after the current time
+    pub fn start(settings: &RecordSettings, tags: RecordTags) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(&settings.directory)?;
+        let path = unique_path(&settings.directory, settings.format, SystemTime::now());
+        // Claim the name now so a directory we can't write to fails here, not on the writer
+        File::create(&path)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let progress = Arc::new(Progress::default());
+        let writer = Writer {
+            path: path.clone(),
+            format: settings.format,
+            tags,
+            progress: progress.clone(),
+        };
+        let thread = std::thread::Builder::new()
+            .name("playtui-record".to_string())
+            .spawn(move || writer.run(receiver))?;
+        Ok(Self {
+            path,
+            sender,
+            progress,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn tap(&self) -> RecordTap {
+        RecordTap { sender: self.sender.clone() }
+    }
+
+    pub fn status(&self) -> RecordingStatus {
+        RecordingStatus {
+            path: self.path.clone(),
+            elapsed: Duration::from_nanos(self.progress.nanos.load(Ordering::Acquire)),
+            bytes: self.progress.bytes.load(Ordering::Acquire),
+        }
+    }
+
+    /// Write out everything received and finalize the file headers
+    pub fn finish(mut self) -> Result<RecordingStatus, Box<dyn Error>> {
+        self.join()?;
+        Ok(self.status())
+    }
+
+    fn join(&mut self) -> io::Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        let _ = self.sender.send(RecordMessage::Finish);
+        thread.join().map_err(|_| io::Error::other("Recording thread panicked"))?
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.join() {
+            log::error!("Failed to finish recording {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// The file a recording is being written to
+enum Encoder {
+    Wav(WavWriter),
+    Flac(FlacWriter),
+}
+
+impl Encoder {
+    fn create(kind: RecordFormat, path: &Path, format: AudioFormat, tags: &RecordTags) -> io::Result<Self> {
+        Ok(match kind {
+            RecordFormat::Wav => Encoder::Wav(WavWriter::create(path, format, tags)?),
+            RecordFormat::Flac => Encoder::Flac(FlacWriter::create(path, format, tags)?),
+        })
+    }
+
+    fn write(&mut self, samples: &[i32]) -> io::Result<()> {
+        match self {
+            Encoder::Wav(writer) => writer.write(samples),
+            Encoder::Flac(writer) => writer.write(samples),
+        }
+    }
+
+    fn bytes(&self) -> u64 {
+        match self {
+            Encoder::Wav(writer) => writer.bytes(),
+            Encoder::Flac(writer) => writer.bytes(),
+        }
+    }
+
+    /// Size of the finished file
+    fn finish(self) -> io::Result<u64> {
+        match self {
+            Encoder::Wav(writer) => writer.finish(),
+            Encoder::Flac(writer) => writer.finish(),
+        }
+    }
+}
+
+/// The recording thread: quantizes what the output thread sends and encodes it
+struct Writer {
+    path: PathBuf,
+    format: RecordFormat,
+    tags: RecordTags,
+    progress: Arc<Progress>,
+}
+
+/// The file being written and the layout it holds
+struct Part {
+    encoder: Encoder,
+    layout: (u16, u32, u16),
+    quantizer: Quantizer,
+    frames: u64,
+}
+
+impl Part {
+    /// Length of the audio in this file
+    fn nanos(&self) -> u64 {
+        let sample_rate = self.layout.1.max(1) as u128;
+        (self.frames as u128 * 1_000_000_000 / sample_rate) as u64
+    }
+}
+
+impl Writer {
+    fn run(self, receiver: Receiver<RecordMessage>) -> io::Result<()> {
+        let mut part: Option<Part> = None;
+        let (mut parts, mut samples) = (0, Vec::new());
+        // Totals for the files already finished
+        let (mut finished_nanos, mut finished_bytes) = (0, 0);
+
+        // Carries on until told to finish, or until the engine has gone
+        while let Ok(RecordMessage::Samples { layout, samples: block }) = receiver.recv() {
+            if part.as_ref().map(|part| part.layout) != Some(layout) {
+                // Neither container can change format part way, so carry on in a new file
+                if let Some(done) = part.take() {
+                    finished_nanos += done.nanos();
+                    finished_bytes += done.encoder.finish()?;
+                }
+                parts += 1;
+                part = Some(self.open_part(parts, layout)?);
+            }
+            let part = part.as_mut().expect("opened above");
+            samples.clear();
+            samples.extend(block.iter().map(|&sample| part.quantizer.quantize(sample)));
+            part.encoder.write(&samples)?;
+            part.frames += (block.len() / layout.0.max(1) as usize) as u64;
+            self.report(finished_nanos + part.nanos(), finished_bytes + part.encoder.bytes());
+        }
+
+        match part {
+            Some(part) => {
+                let nanos = finished_nanos + part.nanos();
+                let bytes = finished_bytes + part.encoder.finish()?;
+                self.report(nanos, bytes);
+                Ok(())
+            }
+            // Nothing was played while recording, so leave no empty file behind
+            None => fs::remove_file(&self.path),
+        }
+    }
+
+    fn report(&self, nanos: u64, bytes: u64) {
+        self.progress.nanos.store(nanos, Ordering::Release);
+        self.progress.bytes.store(bytes, Ordering::Release);
+    }
+
+    fn open_part(&self, number: u32, layout: (u16, u32, u16)) -> io::Result<Part> {
+        let (channels, sample_rate, bits_per_sample) = layout;
+        // FLAC decoders commonly stop at 24 bits, which is beyond any DAC's real resolution anyway
+        let bits_per_sample = bits_per_sample.min(24);
+        let mut quantizer = Quantizer::new(bits_per_sample).map_err(|e| io::Error::other(e.to_string()))?;
+        quantizer.set_dither(bits_per_sample < 24);
+        let format = AudioFormat { channels, sample_rate, bits_per_sample, ..Default::default() };
+        let encoder = Encoder::create(self.format, &part_path(&self.path, number), format, &self.tags)?;
+        Ok(Part { encoder, layout, quantizer, frames: 0 })
+    }
+}
+
+/// `playtui-YYYYMMDD-HHMMSS` in UTC, with a counter added if a recording by that name exists
+fn unique_path(directory: &Path, format: RecordFormat, time: SystemTime) -> PathBuf {
+    let stem = format!("playtui-{}", timestamp(time));
+    let mut path = directory.join(format!("{}.{}", stem, format.extension()));
+    let mut counter = 1;
+    while path.exists() {
+        counter += 1;
+        path = directory.join(format!("{}-{}.{}", stem, counter, format.extension()));
+    }
+    path
+}
+
+/// The file for `number`th part of a recording; the first is the recording's own path
+fn part_path(path: &Path, number: u32) -> PathBuf {
+    if number <= 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}-part{}.{}", stem, number, extension))
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, of_day) = (seconds / 86_400, seconds % 86_400);
+    // Civil date from days since 1970-01-01, counting in 400-year eras from 0000-03-01
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, of_day / 3600, of_day / 60 % 60, of_day % 60,
+    )
+}
@@ -0,0 +1,126 @@
+use super::*;
+use crate::audio::formats::{get_decoder, AudioDecoder};
+use crate::metadata::MetadataManager;
+
+/// Half a second of a 1kHz tone at half scale, then silence, in stereo at 48kHz
+fn tone() -> Vec<f32> {
+    (0..48000)
+        .flat_map(|i| {
+            let sample = match i < 24000 {
+                true => 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin(),
+                false => 0.0,
+            };
+            [sample, -sample]
+        })
+        .collect()
+}
+
+fn tags() -> RecordTags {
+    RecordTags {
+        fields: vec![("TITLE".to_string(), "Take one".to_string()), ("ARTIST".to_string(), "Someone".to_string())],
+    }
+}
+
+/// Record `samples` in pieces the size the output thread writes, returning the file
+fn record(format: RecordFormat, layout: (u16, u32, u16), samples: &[f32], dir: &Path) -> RecordingStatus {
+    let settings = RecordSettings { directory: dir.to_path_buf(), format };
+    let recorder = Recorder::start(&settings, tags()).unwrap();
+    let tap = recorder.tap();
+    for chunk in samples.chunks(2048) {
+        tap.send(layout, chunk);
+    }
+    recorder.finish().unwrap()
+}
+
+fn decode(path: &Path) -> (AudioFormat, Vec<f32>) {
+    let mut reader = get_decoder(path).decode(path).unwrap();
+    let mut samples = Vec::new();
+    let mut buffer = vec![0.0; 4096];
+    loop {
+        let count = reader.read(&mut buffer).unwrap();
+        if count == 0 {
+            break;
+        }
+        samples.extend_from_slice(&buffer[..count]);
+    }
+    (reader.format.clone(), samples)
+}
+
+#[test]
+fn test_records_lossless_flac_and_wav() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = tone();
+    for (format, bits) in [(RecordFormat::Flac, 16), (RecordFormat::Flac, 24), (RecordFormat::Wav, 24)] {
+        let status = record(format, (2, 48000, bits), &input, dir.path());
+        assert_eq!(status.path.extension().unwrap(), format.extension());
+        assert_eq!(status.elapsed, Duration::from_secs(1));
+        assert_eq!(status.bytes, fs::metadata(&status.path).unwrap().len());
+
+        let (decoded_format, decoded) = decode(&status.path);
+        assert_eq!((decoded_format.channels, decoded_format.sample_rate, decoded_format.bits_per_sample), (2, 48000, bits));
+        assert_eq!(decoded.len(), input.len(), "{:?} at {} bits", format, bits);
+        // Only the quantization and dither are lost
+        let step = 2.0 / (1u32 << bits) as f32;
+        let error = decoded.iter().zip(&input).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        assert!(error <= 2.0 * step, "{:?} at {} bits is off by {}", format, bits, error);
+        fs::remove_file(&status.path).unwrap();
+    }
+}
+
+#[test]
+fn test_recordings_carry_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = record(RecordFormat::Flac, (2, 48000, 16), &tone(), dir.path());
+    let metadata = MetadataManager::with_format_parsers().parse_metadata(&status.path).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Take one"));
+    assert_eq!(metadata.artist.as_deref(), Some("Someone"));
+
+    let status = record(RecordFormat::Wav, (2, 48000, 16), &tone(), dir.path());
+    let bytes = fs::read(&status.path).unwrap();
+    let list = bytes.windows(4).position(|window| window == b"LIST").unwrap();
+    assert_eq!(&bytes[list + 8..list + 12], b"INFO");
+    assert!(bytes[list..].windows(8).any(|window| window == b"Take one"));
+    // The RIFF size covers the tags after the samples
+    assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+}
+
+#[test]
+fn test_format_change_starts_new_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = RecordSettings { directory: dir.path().to_path_buf(), format: RecordFormat::Flac };
+    let recorder = Recorder::start(&settings, RecordTags::default()).unwrap();
+    let tap = recorder.tap();
+    tap.send((2, 48000, 16), &[0.25; 9600]);
+    tap.send((1, 44100, 16), &[0.25; 4410]);
+    let status = recorder.finish().unwrap();
+
+    assert_eq!(status.elapsed, Duration::from_millis(200));
+    let second = part_path(&status.path, 2);
+    assert_eq!(decode(&status.path).1.len(), 9600);
+    assert_eq!(decode(&second).0.sample_rate, 44100);
+    assert_eq!(status.bytes, fs::metadata(&status.path).unwrap().len() + fs::metadata(&second).unwrap().len());
+}
+
+#[test]
+fn test_empty_recording_leaves_no_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = RecordSettings { directory: dir.path().join("new"), format: RecordFormat::Wav };
+    let recorder = Recorder::start(&settings, RecordTags::default()).unwrap();
+    let status = recorder.finish().unwrap();
+    assert_eq!(status.bytes, 0);
+    assert!(!status.path.exists());
+}
+
+#[test]
+fn test_file_names() {
+    let time = UNIX_EPOCH + Duration::from_secs(1_792_245_845);
+    assert_eq!(timestamp(time), "20261017-140405");
+    assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)), "20000229-000000");
+
+    let dir = tempfile::tempdir().unwrap();
+    let first = unique_path(dir.path(), RecordFormat::Flac, time);
+    assert_eq!(first.file_name().unwrap(), "playtui-20261017-140405.flac");
+    File::create(&first).unwrap();
+    assert_eq!(unique_path(dir.path(), RecordFormat::Flac, time).file_name().unwrap(), "playtui-20261017-140405-2.flac");
+    assert_eq!(part_path(&first, 3).file_name().unwrap(), "playtui-20261017-140405-part3.flac");
+}
@@ -0,0 +1,98 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::audio::output::wav_header;
+use crate::audio::AudioFormat;
+use super::RecordTags;
+
+/// RIFF INFO chunk ids for the Vorbis comment fields WAV players understand
+const INFO_IDS: [(&str, &[u8; 4]); 7] = [
+    ("TITLE", b"INAM"),
+    ("ARTIST", b"IART"),
+    ("ALBUM", b"IPRD"),
+    ("GENRE", b"IGNR"),
+    ("DATE", b"ICRD"),
+    ("TRACKNUMBER", b"ITRK"),
+    ("COMMENT", b"ICMT"),
+];
+
+/// Writes integer PCM to a WAV file, with the tags in a LIST/INFO chunk after the samples
+pub(super) struct WavWriter {
+    writer: BufWriter<File>,
+    format: AudioFormat,
+    tags: RecordTags,
+    data_len: u64,
+    bytes: Vec<u8>,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, format: AudioFormat, tags: &RecordTags) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&wav_header(&format, 0))?;
+        Ok(Self {
+            writer,
+            format,
+            tags: tags.clone(),
+            data_len: 0,
+            bytes: Vec::new(),
+        })
+    }
+
+    pub fn write(&mut self, samples: &[i32]) -> io::Result<()> {
+        let width = self.format.bits_per_sample as usize / 8;
+        self.bytes.clear();
+        for sample in samples {
+            self.bytes.extend_from_slice(&sample.to_le_bytes()[..width]);
+        }
+        self.writer.write_all(&self.bytes)?;
+        self.data_len += self.bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Bytes in the file so far
+    pub fn bytes(&self) -> u64 {
+        wav_header(&self.format, 0).len() as u64 + self.data_len
+    }
+
+    /// Append the tags and fill in the chunk sizes, returning the size of the finished file
+    pub fn finish(mut self) -> io::Result<u64> {
+        // Chunks start on even offsets
+        if self.data_len % 2 == 1 {
+            self.writer.write_all(&[0])?;
+        }
+        let info = info_chunk(&self.tags);
+        self.writer.write_all(&info)?;
+
+        let mut header = wav_header(&self.format, self.data_len);
+        let riff_len = (4 + 8 + 16 + 8 + self.data_len.next_multiple_of(2) + info.len() as u64).min(u32::MAX as u64);
+        header[4..8].copy_from_slice(&(riff_len as u32).to_le_bytes());
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&header)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(riff_len + 8)
+    }
+}
+
+/// `LIST` chunk of `INFO` strings, or nothing without any tags it can hold
+fn info_chunk(tags: &RecordTags) -> Vec<u8> {
+    let mut body = b"INFO".to_vec();
+    for (key, id) in INFO_IDS {
+        let Some(value) = tags.get(key) else {
+            continue;
+        };
+        // Null-terminated and padded to an even length
+        let len = value.len() + 1;
+        body.extend_from_slice(id.as_slice());
+        body.extend_from_slice(&(len as u32).to_le_bytes());
+        body.extend_from_slice(value.as_bytes());
+        body.resize(body.len() + 1 + len % 2, 0);
+    }
+    if body.len() == 4 {
+        return Vec::new();
+    }
+    let mut chunk = b"LIST".to_vec();
+    chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&body);
+    chunk
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Loudness the ReplayGain tags of Opus files (`R128_*`, EBU R128) are relative to, in LUFS,
+/// and the level ReplayGain 2.0 targets
+const R128_REFERENCE: f32 = -23.0;
+pub const REPLAYGAIN_REFERENCE: f32 = -18.0;
+
+/// Largest preamp or fallback gain accepted from preferences, either way
+pub const MAX_GAIN_DB: f32 = 20.0;
+
+/// Which ReplayGain value is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplayGainMode {
+    #[default]
+    Off,
+    Track,
+    Album,
+    /// Track gain while shuffling, album gain when playing in order
+    Auto,
+}
+
+/// Gains in dB and peaks as linear sample values, as read from a track's tags
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ReplayGainTags {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGainTags {
+    /// Read `REPLAYGAIN_*` values, or the `R128_*` gains Opus files carry instead,
+    /// from a track's extra metadata; keys may be in any case
+    pub fn from_tags(extra: &HashMap<String, String>) -> Self {
+        let find = |name: &str| {
+            extra
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        };
+        let gain = |name: &str| find(name).and_then(parse_gain);
+        let peak = |name: &str| find(name).and_then(|value| value.trim().parse::<f32>().ok()).filter(|&p| p > 0.0);
+        // Q7.8 fixed point relative to -23 LUFS
+        let r128 = |name: &str| {
+            find(name)
+                .and_then(|value| value.trim().parse::<i16>().ok())
+                .map(|q| q as f32 / 256.0 + REPLAYGAIN_REFERENCE - R128_REFERENCE)
+        };
+
+        Self {
+            track_gain: gain("REPLAYGAIN_TRACK_GAIN").or_else(|| r128("R128_TRACK_GAIN")),
+            track_peak: peak("REPLAYGAIN_TRACK_PEAK"),
+            album_gain: gain("REPLAYGAIN_ALBUM_GAIN").or_else(|| r128("R128_ALBUM_GAIN")),
+            album_peak: peak("REPLAYGAIN_ALBUM_PEAK"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.track_gain.is_none() && self.album_gain.is_none()
+    }
+}
+
+/// Parse a gain such as `-6.54 dB` or `+1.2`
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let number = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .or_else(|| value.strip_suffix("DB"))
+        .unwrap_or(value);
+    number.trim().trim_start_matches('+').parse().ok().filter(|gain: &f32| gain.is_finite())
+}
+
+/// Where an applied gain came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GainSource {
+    Off,
+    Track,
+    Album,
+    /// The track has no ReplayGain tags
+    Fallback,
+}
+
+/// Gain the engine applies to a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AppliedGain {
+    pub db: f32,
+    pub source: GainSource,
+    /// Lowered from the tagged gain so the track's peak stays below full scale
+    pub peak_limited: bool,
+}
+
+impl AppliedGain {
+    pub const UNITY: AppliedGain = AppliedGain { db: 0.0, source: GainSource::Off, peak_limited: false };
+
+    /// Factor each sample is multiplied by
+    pub fn linear(&self) -> f32 {
+        10f32.powf(self.db / 20.0)
+    }
+}
+
+/// How ReplayGain is applied to the tracks the engine plays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    pub mode: ReplayGainMode,
+    /// Added to the tagged gain, in dB
+    pub preamp_db: f32,
+    /// Applied instead to tracks without tags, in dB
+    pub fallback_db: f32,
+    /// Limit the gain so the tagged peak never exceeds full scale
+    pub prevent_clipping: bool,
+}
+
+impl Default for ReplayGain {
+    fn default() -> Self {
+        Self {
+            mode: ReplayGainMode::Off,
+            preamp_db: 0.0,
+            fallback_db: 0.0,
+            prevent_clipping: true,
+        }
+    }
+}
+
+impl ReplayGain {
+    /// The gain for a track with `tags`; `shuffle` picks between track and album gain in auto mode
+    pub fn gain_for(&self, tags: &ReplayGainTags, shuffle: bool) -> AppliedGain {
+        let prefer_album = match self.mode {
+            ReplayGainMode::Off => return AppliedGain::UNITY,
+            ReplayGainMode::Track => false,
+            ReplayGainMode::Album => true,
+            ReplayGainMode::Auto => !shuffle,
+        };
+
+        // Either gain stands in for the other when only one is tagged
+        let track = tags.track_gain.map(|gain| (gain, tags.track_peak, GainSource::Track));
+        let album = tags.album_gain.map(|gain| (gain, tags.album_peak.or(tags.track_peak), GainSource::Album));
+        let chosen = if prefer_album { album.or(track) } else { track.or(album) };
+        let Some((gain, peak, source)) = chosen else {
+            return AppliedGain { db: self.fallback_db, source: GainSource::Fallback, peak_limited: false };
+        };
+
+        let db = gain + self.preamp_db;
+        match peak.filter(|_| self.prevent_clipping) {
+            Some(peak) if db > -20.0 * peak.log10() => AppliedGain { db: -20.0 * peak.log10(), source, peak_limited: true },
+            _ => AppliedGain { db, source, peak_limited: false },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> ReplayGainTags {
+        let extra = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ReplayGainTags::from_tags(&extra)
+    }
+
+    #[test]
+    fn test_read_tags() {
+        let read = tags(&[
+            ("replaygain_track_gain", "-6.50 dB"),
+            ("REPLAYGAIN_TRACK_PEAK", "0.988"),
+            ("REPLAYGAIN_ALBUM_GAIN", "+1.25 dB"),
+            ("REPLAYGAIN_ALBUM_PEAK", "junk"),
+        ]);
+        assert_eq!(read.track_gain, Some(-6.5));
+        assert_eq!(read.track_peak, Some(0.988));
+        assert_eq!(read.album_gain, Some(1.25));
+        assert_eq!(read.album_peak, None);
+        assert!(tags(&[("TITLE", "x")]).is_empty());
+
+        // Opus gains are Q7.8 against -23 LUFS, 5dB quieter than the ReplayGain reference
+        let opus = tags(&[("R128_TRACK_GAIN", "-512")]);
+        assert_eq!(opus.track_gain, Some(3.0));
+    }
+
+    #[test]
+    fn test_gain_for_mode() {
+        let tagged = tags(&[("REPLAYGAIN_TRACK_GAIN", "-8 dB"), ("REPLAYGAIN_ALBUM_GAIN", "-6 dB")]);
+        let mut settings = ReplayGain { preamp_db: 2.0, fallback_db: -3.0, ..Default::default() };
+        assert_eq!(settings.gain_for(&tagged, false), AppliedGain::UNITY);
+
+        settings.mode = ReplayGainMode::Track;
+        assert_eq!(settings.gain_for(&tagged, false).db, -6.0);
+        settings.mode = ReplayGainMode::Album;
+        assert_eq!(settings.gain_for(&tagged, false).db, -4.0);
+        settings.mode = ReplayGainMode::Auto;
+        assert_eq!(settings.gain_for(&tagged, false).source, GainSource::Album);
+        assert_eq!(settings.gain_for(&tagged, true).source, GainSource::Track);
+
+        // Album mode falls back to the track gain, and untagged files to the fallback without preamp
+        settings.mode = ReplayGainMode::Album;
+        let track_only = tags(&[("REPLAYGAIN_TRACK_GAIN", "-8 dB")]);
+        assert_eq!(settings.gain_for(&track_only, false).source, GainSource::Track);
+        let untagged = settings.gain_for(&ReplayGainTags::default(), false);
+        assert_eq!((untagged.db, untagged.source), (-3.0, GainSource::Fallback));
+    }
+
+    #[test]
+    fn test_clipping_prevention() {
+        // +6dB on a track peaking at 0.8 would clip; the limit is about +1.94dB
+        let loud = tags(&[("REPLAYGAIN_TRACK_GAIN", "+6 dB"), ("REPLAYGAIN_TRACK_PEAK", "0.8")]);
+        let mut settings = ReplayGain { mode: ReplayGainMode::Track, ..Default::default() };
+        let gain = settings.gain_for(&loud, false);
+        assert!(gain.peak_limited);
+        assert!((gain.linear() * 0.8 - 1.0).abs() < 1e-5);
+
+        settings.prevent_clipping = false;
+        assert_eq!(settings.gain_for(&loud, false).db, 6.0);
+
+        // Attenuation never needs limiting
+        settings.prevent_clipping = true;
+        let quiet = tags(&[("REPLAYGAIN_TRACK_GAIN", "-6 dB"), ("REPLAYGAIN_TRACK_PEAK", "1.2")]);
+        assert!(!settings.gain_for(&quiet, false).peak_limited);
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Fixed-size sample storage shared by one producer and one consumer
+struct Ring {
+    /// Samples stored as raw `f32` bits so they can live in atomics
+    slots: Box<[AtomicU32]>,
+    /// Total samples ever read; only the consumer advances it
+    read: AtomicUsize,
+    /// Total samples ever written; only the producer advances it
+    write: AtomicUsize,
+}
+
+impl Ring {
+    fn len(&self) -> usize {
+        self.write.load(Ordering::Acquire).wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+}
+
+/// Writing half of a lock-free single-producer single-consumer sample queue
+pub struct Producer {
+    ring: Arc<Ring>,
+}
+
+/// Reading half of a lock-free single-producer single-consumer sample queue
+pub struct Consumer {
+    ring: Arc<Ring>,
+}
+
+/// Create a queue holding up to `capacity` samples
+pub fn ring_buffer(capacity: usize) -> (Producer, Consumer) {
+    assert!(capacity > 0, "ring buffer capacity must be non-zero");
+    let ring = Arc::new(Ring {
+        slots: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+        read: AtomicUsize::new(0),
+        write: AtomicUsize::new(0),
+    });
+    (Producer { ring: ring.clone() }, Consumer { ring })
+}
+
+impl Producer {
+    /// Queue as many of `samples` as fit, returning how many were taken
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        let ring = &*self.ring;
+        let capacity = ring.slots.len();
+        let write = ring.write.load(Ordering::Relaxed);
+        let read = ring.read.load(Ordering::Acquire);
+        let count = samples.len().min(capacity - write.wrapping_sub(read));
+
+        for (i, sample) in samples[..count].iter().enumerate() {
+            ring.slots[write.wrapping_add(i) % capacity].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        ring.write.store(write.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    /// Space left for new samples
+    pub fn free(&self) -> usize {
+        self.ring.slots.len() - self.ring.len()
+    }
+}
+
+impl Consumer {
+    /// Take up to `out.len()` samples, returning how many were copied
+    pub fn pop(&mut self, out: &mut [f32]) -> usize {
+        let ring = &*self.ring;
+        let capacity = ring.slots.len();
+        let read = ring.read.load(Ordering::Relaxed);
+        let write = ring.write.load(Ordering::Acquire);
+        let count = out.len().min(write.wrapping_sub(read));
+
+        for (i, sample) in out[..count].iter_mut().enumerate() {
+            *sample = f32::from_bits(ring.slots[read.wrapping_add(i) % capacity].load(Ordering::Relaxed));
+        }
+        ring.read.store(read.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    /// Samples waiting to be read
+    pub fn available(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Drop everything queued so far
+    pub fn clear(&mut self) {
+        let write = self.ring.write.load(Ordering::Acquire);
+        self.ring.read.store(write, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let (mut producer, mut consumer) = ring_buffer(4);
+        assert_eq!(producer.push(&[0.1, 0.2, 0.3]), 3);
+        assert_eq!(consumer.available(), 3);
+        assert_eq!(producer.free(), 1);
+
+        let mut out = [0.0; 2];
+        assert_eq!(consumer.pop(&mut out), 2);
+        assert_eq!(out, [0.1, 0.2]);
+
+        // Wraps around the end of the storage
+        assert_eq!(producer.push(&[0.4, 0.5, 0.6, 0.7]), 3);
+        let mut out = [0.0; 8];
+        assert_eq!(consumer.pop(&mut out), 4);
+        assert_eq!(&out[..4], &[0.3, 0.4, 0.5, 0.6]);
+        assert_eq!(consumer.pop(&mut out), 0);
+    }
+
+    #[test]
+    fn test_clear() {
+        let (mut producer, mut consumer) = ring_buffer(8);
+        producer.push(&[1.0; 5]);
+        consumer.clear();
+        assert_eq!(consumer.available(), 0);
+        assert_eq!(producer.free(), 8);
+
+        producer.push(&[2.0]);
+        let mut out = [0.0; 4];
+        assert_eq!(consumer.pop(&mut out), 1);
+        assert_eq!(out[0], 2.0);
+    }
+
+    #[test]
+    fn test_threaded_transfer() {
+        let (mut producer, mut consumer) = ring_buffer(64);
+        let writer = std::thread::spawn(move || {
+            let samples: Vec<f32> = (0..10_000).map(|i| i as f32).collect();
+            let mut sent = 0;
+            while sent < samples.len() {
+                sent += producer.push(&samples[sent..(sent + 37).min(samples.len())]);
+                std::thread::yield_now();
+            }
+        });
+
+        let mut received = Vec::new();
+        let mut out = [0.0; 23];
+        while received.len() < 10_000 {
+            let count = consumer.pop(&mut out);
+            received.extend_from_slice(&out[..count]);
+            std::thread::yield_now();
+        }
+        writer.join().unwrap();
+        assert!(received.iter().enumerate().all(|(i, &s)| s == i as f32));
+    }
+}
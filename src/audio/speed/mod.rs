@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+mod varispeed;
+mod wsola;
+
+#[cfg(test)]
+mod tests;
+
+use varispeed::Varispeed;
+use wsola::Wsola;
+
+/// Slowest and fastest rates tracks can play at
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+
+/// How far each press of the speed keys moves the rate
+pub const SPEED_STEP: f32 = 0.1;
+
+/// What happens to the pitch when the rate changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpeedMode {
+    /// Time-stretch, so voices keep their pitch
+    #[default]
+    PreservePitch,
+    /// Pitch rises and falls with the rate, like a turntable run fast or slow
+    Varispeed,
+}
+
+/// Rate tracks play at relative to normal, and how it is reached
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackSpeed {
+    pub rate: f32,
+    pub mode: SpeedMode,
+}
+
+impl Default for PlaybackSpeed {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            mode: SpeedMode::default(),
+        }
+    }
+}
+
+impl PlaybackSpeed {
+    /// Whether the rate is within `MIN_SPEED` to `MAX_SPEED`
+    pub fn is_valid(&self) -> bool {
+        (MIN_SPEED..=MAX_SPEED).contains(&self.rate)
+    }
+
+    /// The rate `steps` presses of the speed keys away, snapped to the step and kept in range
+    pub fn stepped(self, steps: i32) -> Self {
+        let notches = (self.rate / SPEED_STEP).round() + steps as f32;
+        // Rounded to hundredths so ten steps up from 1.0 land on exactly 2.0
+        let rate = (notches * SPEED_STEP * 100.0).round() / 100.0;
+        Self {
+            rate: rate.clamp(MIN_SPEED, MAX_SPEED),
+            ..self
+        }
+    }
+}
+
+/// Plays interleaved samples faster or slower, stretching or resampling them by the speed's mode
+pub struct TimeStretch {
+    speed: PlaybackSpeed,
+    stage: Option<Stage>,
+}
+
+/// The stretcher for one mode and layout, holding whatever input it has not played yet
+enum Stage {
+    Wsola(Wsola),
+    Varispeed(Varispeed),
+}
+
+impl Default for TimeStretch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeStretch {
+    pub fn new() -> Self {
+        Self {
+            speed: PlaybackSpeed::default(),
+            stage: None,
+        }
+    }
+
+    /// Applies from the next `process`; rate changes within a mode are seamless
+    pub fn set_speed(&mut self, speed: PlaybackSpeed) {
+        self.speed = speed;
+    }
+
+    pub fn speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    /// Play `input` at the current speed, appending whatever output is ready. At normal speed
+    /// samples pass straight through once anything held back has been played out.
+    pub fn process(&mut self, input: &[f32], channels: usize, sample_rate: u32, out: &mut Vec<f32>) {
+        let channels = channels.max(1);
+        if let Some(stage) = self.stage.as_ref() {
+            if !stage.fits(channels, sample_rate) {
+                self.stage = None;
+            } else if stage.mode() != self.speed.mode || self.speed.rate == 1.0 {
+                self.drain(out);
+            }
+        }
+
+        if self.speed.rate == 1.0 {
+            out.extend_from_slice(input);
+            return;
+        }
+        let mode = self.speed.mode;
+        let stage = self.stage.get_or_insert_with(|| match mode {
+            SpeedMode::PreservePitch => Stage::Wsola(Wsola::new(channels, sample_rate)),
+            SpeedMode::Varispeed => Stage::Varispeed(Varispeed::new(channels, sample_rate)),
+        });
+        match stage {
+            Stage::Wsola(wsola) => wsola.process(input, self.speed.rate as f64, out),
+            Stage::Varispeed(varispeed) => varispeed.process(input, self.speed.rate as f64, out),
+        }
+    }
+
+    /// Play out everything held back as it is, before the stream ends or changes layout
+    pub fn drain(&mut self, out: &mut Vec<f32>) {
+        match self.stage.take() {
+            Some(Stage::Wsola(wsola)) => wsola.drain(out),
+            Some(Stage::Varispeed(varispeed)) => varispeed.drain(out),
+            None => {}
+        }
+    }
+
+    /// Forget everything held back, as after a seek
+    pub fn reset(&mut self) {
+        self.stage = None;
+    }
+
+    /// Input frames taken in but not yet played, in track time
+    pub fn buffered_frames(&self) -> usize {
+        match self.stage.as_ref() {
+            Some(Stage::Wsola(wsola)) => wsola.buffered_frames(),
+            Some(Stage::Varispeed(varispeed)) => varispeed.buffered_frames(),
+            None => 0,
+        }
+    }
+}
+
+impl Stage {
+    fn mode(&self) -> SpeedMode {
+        match self {
+            Stage::Wsola(_) => SpeedMode::PreservePitch,
+            Stage::Varispeed(_) => SpeedMode::Varispeed,
+        }
+    }
+
+    fn fits(&self, channels: usize, sample_rate: u32) -> bool {
+        match self {
+            Stage::Wsola(wsola) => wsola.layout() == (channels, sample_rate),
+            Stage::Varispeed(varispeed) => varispeed.layout() == (channels, sample_rate),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::*;
+
+const RATE: u32 = 48000;
+
+/// Two seconds of a 440Hz tone at half scale, in stereo
+fn tone() -> Vec<f32> {
+    (0..2 * RATE as usize)
+        .flat_map(|i| {
+            let sample = 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin();
+            [sample, sample]
+        })
+        .collect()
+}
+
+/// Run `input` through at `speed` in period-sized pieces, returning the output and the
+/// input frames consumed
+fn stretch(speed: PlaybackSpeed, input: &[f32]) -> (Vec<f32>, usize) {
+    let mut stretch = TimeStretch::new();
+    stretch.set_speed(speed);
+    let mut out = Vec::new();
+    for chunk in input.chunks(2048) {
+        stretch.process(chunk, 2, RATE, &mut out);
+    }
+    (out, input.len() / 2 - stretch.buffered_frames())
+}
+
+/// Frequency of the left channel, from its rising zero crossings away from the edges
+fn frequency(samples: &[f32]) -> f32 {
+    let left: Vec<f32> = samples.iter().step_by(2).copied().collect();
+    let middle = &left[left.len() / 4..left.len() * 3 / 4];
+    let crossings = middle.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+    crossings as f32 * RATE as f32 / middle.len() as f32
+}
+
+#[test]
+fn test_normal_speed_passes_through() {
+    let input = tone();
+    let (out, consumed) = stretch(PlaybackSpeed::default(), &input);
+    assert_eq!(out, input);
+    assert_eq!(consumed, 2 * RATE as usize);
+}
+
+#[test]
+fn test_stretch_keeps_pitch() {
+    let input = tone();
+    for rate in [0.5, 1.5, 3.0] {
+        let speed = PlaybackSpeed { rate, mode: SpeedMode::PreservePitch };
+        let (out, consumed) = stretch(speed, &input);
+        // Output lasts the consumed input divided by the rate
+        let expected = consumed as f32 / rate;
+        assert!(((out.len() / 2) as f32 - expected).abs() < 0.02 * RATE as f32, "{}x", rate);
+        assert!((frequency(&out) - 440.0).abs() < 5.0, "{}x played at {}Hz", rate, frequency(&out));
+        assert!(out.iter().all(|sample| sample.abs() <= 0.55), "{}x", rate);
+    }
+}
+
+#[test]
+fn test_varispeed_moves_pitch() {
+    let input = tone();
+    for rate in [0.5, 2.0] {
+        let speed = PlaybackSpeed { rate, mode: SpeedMode::Varispeed };
+        let (out, consumed) = stretch(speed, &input);
+        assert!(((out.len() / 2) as f32 - consumed as f32 / rate).abs() < 2.0, "{}x", rate);
+        assert!((frequency(&out) - 440.0 * rate).abs() < 5.0, "{}x played at {}Hz", rate, frequency(&out));
+    }
+}
+
+#[test]
+fn test_speed_changes_join_smoothly() {
+    let input = tone();
+    let mut stretch = TimeStretch::new();
+    let mut out = Vec::new();
+    let mut consumed = 0;
+    // Through both modes and back to normal, then again without draining first
+    let speeds = [
+        PlaybackSpeed { rate: 1.5, mode: SpeedMode::PreservePitch },
+        PlaybackSpeed { rate: 0.7, mode: SpeedMode::PreservePitch },
+        PlaybackSpeed { rate: 1.3, mode: SpeedMode::Varispeed },
+        PlaybackSpeed::default(),
+        PlaybackSpeed { rate: 2.0, mode: SpeedMode::PreservePitch },
+    ];
+    for (chunk, speed) in input.chunks(input.len() / speeds.len()).zip(speeds) {
+        let held = stretch.buffered_frames();
+        stretch.set_speed(speed);
+        stretch.process(chunk, 2, RATE, &mut out);
+        consumed += chunk.len() / 2 + held - stretch.buffered_frames();
+    }
+    let held = stretch.buffered_frames();
+    stretch.drain(&mut out);
+    assert_eq!(stretch.buffered_frames(), 0);
+    // Every input frame is accounted for once the last of it is played out
+    assert_eq!(consumed + held, input.len() / 2);
+
+    // A 440Hz tone at half scale moves less than 0.03 a frame; no join skips more than two frames
+    let largest_step = out.windows(3).step_by(2).map(|pair| (pair[2] - pair[0]).abs()).fold(0.0, f32::max);
+    assert!(largest_step < 0.06, "jumped by {}", largest_step);
+}
+
+#[test]
+fn test_layout_change_drops_held_input() {
+    let mut stretch = TimeStretch::new();
+    stretch.set_speed(PlaybackSpeed { rate: 2.0, mode: SpeedMode::PreservePitch });
+    let mut out = Vec::new();
+    stretch.process(&[0.1; 2000], 2, RATE, &mut out);
+    assert_eq!(stretch.buffered_frames(), 1000);
+
+    // Mono at another rate can't be joined to stereo, so it starts afresh
+    stretch.process(&[0.1; 10], 1, 44100, &mut out);
+    assert_eq!(stretch.buffered_frames(), 10);
+    stretch.reset();
+    assert_eq!(stretch.buffered_frames(), 0);
+}
+
+#[test]
+fn test_speed_steps() {
+    let normal = PlaybackSpeed::default();
+    assert!(normal.is_valid());
+    assert_eq!(normal.stepped(1).rate, 1.1);
+    assert_eq!(normal.stepped(10).rate, 2.0);
+    assert_eq!(normal.stepped(-3).rate, 0.7);
+    assert_eq!(normal.stepped(-10).rate, MIN_SPEED);
+    assert_eq!(normal.stepped(50).rate, MAX_SPEED);
+
+    // Off-grid rates snap to the nearest step
+    let odd = PlaybackSpeed { rate: 1.23, mode: SpeedMode::Varispeed };
+    assert_eq!(odd.stepped(1), PlaybackSpeed { rate: 1.3, mode: SpeedMode::Varispeed });
+
+    assert!(!PlaybackSpeed { rate: 0.25, ..normal }.is_valid());
+    assert!(!PlaybackSpeed { rate: f32::NAN, ..normal }.is_valid());
+}
@@ -0,0 +1,67 @@
+/// Reads through the input `rate` frames per output frame with cubic interpolation, so the
+/// pitch moves with the rate. Rate changes take effect from the next frame without a seam.
+pub(super) struct Varispeed {
+    channels: usize,
+    sample_rate: u32,
+    /// Input from the frame before the read position on
+    input: Vec<f32>,
+    /// Where the next output frame is read from, in frames into `input`
+    position: f64,
+}
+
+impl Varispeed {
+    pub fn new(channels: usize, sample_rate: u32) -> Self {
+        Self {
+            channels,
+            sample_rate,
+            input: Vec::new(),
+            position: 0.0,
+        }
+    }
+
+    pub fn layout(&self) -> (usize, u32) {
+        (self.channels, self.sample_rate)
+    }
+
+    pub fn process(&mut self, input: &[f32], rate: f64, out: &mut Vec<f32>) {
+        let channels = self.channels;
+        self.input.extend_from_slice(input);
+        let frames = self.input.len() / channels;
+
+        while (self.position as usize) + 2 < frames {
+            let index = self.position as usize;
+            let t = (self.position - index as f64) as f32;
+            // The frame before the very first one is taken to be a repeat of it
+            let frame = |offset: usize| &self.input[offset * channels..(offset + 1) * channels];
+            let (p0, p1, p2, p3) = (frame(index.saturating_sub(1)), frame(index), frame(index + 1), frame(index + 2));
+            for channel in 0..channels {
+                out.push(catmull_rom(p0[channel], p1[channel], p2[channel], p3[channel], t));
+            }
+            self.position += rate;
+        }
+
+        // Keep the frame before the read position for the next interpolation
+        let consumed = (self.position as usize).saturating_sub(1).min(frames);
+        self.input.drain(..consumed * channels);
+        self.position -= consumed as f64;
+    }
+
+    /// Everything not yet played, at normal speed from the frame nearest the read position
+    pub fn drain(self, out: &mut Vec<f32>) {
+        let from = self.position.round() as usize * self.channels;
+        out.extend_from_slice(&self.input[from.min(self.input.len())..]);
+    }
+
+    pub fn buffered_frames(&self) -> usize {
+        let frames = (self.input.len() / self.channels) as f64;
+        (frames - self.position).max(0.0).round() as usize
+    }
+}
+
+/// Value `t` of the way from `p1` to `p2` on the curve through all four points
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let c1 = 0.5 * (p2 - p0);
+    let c2 = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c3 = 0.5 * (p3 - p0) + 1.5 * (p1 - p2);
+    ((c3 * t + c2) * t + c1) * t + p1
+}
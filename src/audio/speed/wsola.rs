@@ -0,0 +1,143 @@
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// Length of each overlapping segment; long enough to span a few periods of a low voice
+const SEGMENT: Duration = Duration::from_millis(40);
+
+/// How far either side of its ideal position a segment may move to line up with the last one
+const SEARCH: Duration = Duration::from_millis(12);
+
+/// Stride of the coarse search, and of the frames compared when scoring a candidate
+const COARSE_STEP: usize = 4;
+
+/// Waveform-similarity overlap-add: plays overlapping segments of the input at a fixed hop,
+/// taking each from wherever near its ideal position best continues the previous one, so the
+/// tempo changes while the pitch stays put
+pub(super) struct Wsola {
+    channels: usize,
+    sample_rate: u32,
+    /// Frames played per segment, half the segment length
+    hop: usize,
+    search: usize,
+    /// Periodic Hann window over a whole segment; its two halves sum to one
+    window: Vec<f32>,
+    /// Input not yet out of reach of the next segment
+    input: Vec<f32>,
+    /// Where the next segment would ideally start, in frames into `input`
+    analysis: f64,
+    /// Where the last segment played carries on, once there has been one
+    natural: Option<usize>,
+    /// Falling half of the last segment, to overlap with the rising half of the next
+    overlap: Vec<f32>,
+}
+
+impl Wsola {
+    pub fn new(channels: usize, sample_rate: u32) -> Self {
+        let hop = ((SEGMENT.as_secs_f64() * sample_rate as f64) as usize / 2).max(1);
+        let window = (0..2 * hop)
+            .map(|n| (0.5 - 0.5 * (PI * n as f64 / hop as f64).cos()) as f32)
+            .collect();
+        Self {
+            channels,
+            sample_rate,
+            hop,
+            search: (SEARCH.as_secs_f64() * sample_rate as f64) as usize,
+            window,
+            input: Vec::new(),
+            analysis: 0.0,
+            natural: None,
+            overlap: Vec::new(),
+        }
+    }
+
+    pub fn layout(&self) -> (usize, u32) {
+        (self.channels, self.sample_rate)
+    }
+
+    /// Take in `input` and play as many hops as it allows, moving `rate` hops through the input
+    /// for every hop played
+    pub fn process(&mut self, input: &[f32], rate: f64, out: &mut Vec<f32>) {
+        let channels = self.channels;
+        self.input.extend_from_slice(input);
+        loop {
+            let frames = self.input.len() / channels;
+            let ideal = self.analysis.round() as usize;
+            let (first, last) = match self.natural {
+                Some(_) => (ideal.saturating_sub(self.search), ideal + self.search),
+                None => (ideal, ideal),
+            };
+            let natural_end = self.natural.map_or(0, |natural| natural + self.hop);
+            if last + 2 * self.hop > frames || natural_end > frames {
+                break;
+            }
+
+            let start = match self.natural {
+                Some(natural) => self.best_match(natural, first, last),
+                // Nothing to line up with yet, so the first segment plays from its start unfaded
+                None => ideal,
+            };
+            let segment = &self.input[start * channels..(start + 2 * self.hop) * channels];
+            let (rising, falling) = segment.split_at(self.hop * channels);
+            if self.natural.is_some() {
+                out.extend(rising.iter().zip(&self.overlap).enumerate().map(|(i, (&sample, &overlap))| {
+                    overlap + sample * self.window[i / channels]
+                }));
+            } else {
+                out.extend_from_slice(rising);
+            }
+            self.overlap.clear();
+            self.overlap.extend(falling.iter().enumerate().map(|(i, &sample)| {
+                sample * self.window[self.hop + i / channels]
+            }));
+
+            // Drop input no later segment can reach
+            self.analysis += self.hop as f64 * rate;
+            let natural = start + self.hop;
+            let reach = (self.analysis.floor() as usize).saturating_sub(self.search).min(natural);
+            self.input.drain(..reach * channels);
+            self.analysis -= reach as f64;
+            self.natural = Some(natural - reach);
+        }
+    }
+
+    /// Everything not yet played, unstretched. The overlap plus the rising half of the
+    /// natural continuation is the continuation itself, so this joins without a seam.
+    pub fn drain(self, out: &mut Vec<f32>) {
+        let from = self.natural.unwrap_or(self.analysis.round() as usize) * self.channels;
+        out.extend_from_slice(&self.input[from.min(self.input.len())..]);
+    }
+
+    pub fn buffered_frames(&self) -> usize {
+        let frames = (self.input.len() / self.channels) as f64;
+        (frames - self.analysis).max(0.0).round() as usize
+    }
+
+    /// The start within `first..=last` whose opening best continues the waveform from `natural`
+    fn best_match(&self, natural: usize, first: usize, last: usize) -> usize {
+        let best = |starts: &mut dyn Iterator<Item = usize>| {
+            starts
+                .map(|start| (start, self.similarity(natural, start)))
+                .fold((first, f32::MIN), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+                .0
+        };
+        let coarse = best(&mut (first..=last).step_by(COARSE_STEP));
+        // Refine around the best coarse match frame by frame
+        let from = coarse.saturating_sub(COARSE_STEP - 1).max(first);
+        best(&mut (from..=(coarse + COARSE_STEP - 1).min(last)))
+    }
+
+    /// Normalised cross-correlation between a hop from `natural` and one from `start`
+    fn similarity(&self, natural: usize, start: usize) -> f32 {
+        let channels = self.channels;
+        let (mut correlation, mut energy) = (0.0, 1e-9);
+        for frame in (0..self.hop).step_by(COARSE_STEP) {
+            let reference = &self.input[(natural + frame) * channels..(natural + frame + 1) * channels];
+            let candidate = &self.input[(start + frame) * channels..(start + frame + 1) * channels];
+            for (&a, &b) in reference.iter().zip(candidate) {
+                correlation += a * b;
+                energy += b * b;
+            }
+        }
+        correlation / energy.sqrt()
+    }
+}
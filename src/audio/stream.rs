@@ -1,8 +1,10 @@
 use std::error::Error;
+use super::output::{NullSink, OutputBackend};
 use super::{AudioStream, AudioFormat};
 
 /// Manages the audio output stream
 pub struct AudioOutputStream {
+    backend: OutputBackend,
     format: Option<AudioFormat>,
     is_open: bool,
     buffer_size: usize,
@@ -10,7 +12,13 @@ pub struct AudioOutputStream {
 
 impl AudioOutputStream {
     pub fn new() -> Self {
+        Self::with_backend(OutputBackend::Null(NullSink::new()))
+    }
+
+    /// Create a stream that plays through the given backend
+    pub fn with_backend(backend: OutputBackend) -> Self {
         Self {
+            backend,
             format: None,
             is_open: false,
             buffer_size: 4096, // Default buffer size
@@ -32,15 +40,19 @@ impl AudioOutputStream {
     pub fn get_buffer_size(&self) -> usize {
         self.buffer_size
     }
+
+    /// Registry name of the backend in use
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
 }
 
 impl AudioStream for AudioOutputStream {
     fn open(&mut self, format: AudioFormat) -> Result<(), Box<dyn Error>> {
-        // TODO: Initialize actual audio output device with the specified format
-        // This would typically involve:
-        // 1. Setting up the audio device
-        // 2. Configuring the output stream with the given format
-        // 3. Preparing buffers
+        if self.is_open {
+            self.close()?;
+        }
+        self.backend.open(format.clone())?;
         self.format = Some(format);
         self.is_open = true;
         Ok(())
@@ -50,21 +62,13 @@ impl AudioStream for AudioOutputStream {
         if !self.is_open {
             return Err("Stream not open".into());
         }
-
-        // TODO: Implement actual audio data writing
-        // This would typically:
-        // 1. Convert data to appropriate format if necessary
-        // 2. Write to audio device buffer
-        // 3. Handle underrun/overrun
-        
-        Ok(data.len()) // Placeholder return
+        self.backend.write(data)
     }
 
     fn close(&mut self) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement proper stream cleanup
         self.is_open = false;
         self.format = None;
-        Ok(())
+        self.backend.close()
     }
 }
 
@@ -88,6 +92,7 @@ mod tests {
             channels: 2,
             sample_rate: 44100,
             bits_per_sample: 16,
+            ..Default::default()
         };
 
         // Test opening stream
@@ -109,4 +114,19 @@ mod tests {
         let data = vec![0u8; 1024];
         assert!(stream.write(&data).is_err());
     }
+
+    #[test]
+    fn test_default_backend_is_null() {
+        let mut stream = AudioOutputStream::new();
+        assert_eq!(stream.backend_name(), "null");
+
+        let format = AudioFormat {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            ..Default::default()
+        };
+        stream.open(format).unwrap();
+        assert_eq!(stream.write(&[0u8; 160]).unwrap(), 160);
+    }
 }
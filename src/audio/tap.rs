@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Samples kept for readers; over a second of stereo at 48kHz
+const CAPACITY: usize = 1 << 17;
+
+/// Samples at the write end readers leave alone, as the output thread may be overwriting them
+const MARGIN: usize = 8192;
+
+/// The most recent samples sent to the output stream, for meters and visualizers to read
+/// without ever holding up the output thread
+pub struct OutputTap {
+    /// Samples stored as raw `f32` bits so they can live in atomics
+    slots: Box<[AtomicU32]>,
+    /// Total samples ever written
+    written: AtomicUsize,
+    /// Channels in the high 32 bits and sample rate in the low, and the sample they start at
+    layout: AtomicU64,
+    layout_start: AtomicUsize,
+}
+
+impl OutputTap {
+    pub fn new() -> Self {
+        Self {
+            slots: (0..CAPACITY).map(|_| AtomicU32::new(0)).collect(),
+            written: AtomicUsize::new(0),
+            layout: AtomicU64::new(0),
+            layout_start: AtomicUsize::new(0),
+        }
+    }
+
+    /// Keep `samples`, overwriting the oldest; called by the output thread only
+    pub fn write(&self, samples: &[f32], channels: u16, sample_rate: u32) {
+        let written = self.written.load(Ordering::Relaxed);
+        let layout = (channels as u64) << 32 | sample_rate as u64;
+        if self.layout.load(Ordering::Relaxed) != layout {
+            self.layout_start.store(written, Ordering::Release);
+            self.layout.store(layout, Ordering::Release);
+        }
+        for (i, sample) in samples.iter().enumerate() {
+            self.slots[(written + i) % CAPACITY].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.written.store(written + samples.len(), Ordering::Release);
+    }
+
+    /// A reader that sees everything written from now on
+    pub fn reader(self: &Arc<Self>) -> TapReader {
+        TapReader {
+            tap: self.clone(),
+            read: self.written.load(Ordering::Acquire),
+        }
+    }
+}
+
+impl Default for OutputTap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads what the output thread has written to an `OutputTap` since the last read
+pub struct TapReader {
+    tap: Arc<OutputTap>,
+    read: usize,
+}
+
+impl TapReader {
+    /// Channels and sample rate of the samples last written, zero before any were
+    pub fn layout(&self) -> (u16, u32) {
+        let layout = self.tap.layout.load(Ordering::Acquire);
+        ((layout >> 32) as u16, layout as u32)
+    }
+
+    /// Append the whole frames written since the last call to `out`; frames the output thread
+    /// got too far ahead to keep, or written before the layout last changed, are skipped
+    pub fn read(&mut self, out: &mut Vec<f32>) {
+        let tap = &*self.tap;
+        let written = tap.written.load(Ordering::Acquire);
+        let start = tap.layout_start.load(Ordering::Acquire).min(written);
+        let channels = (self.layout().0 as usize).max(1);
+
+        let oldest = written.saturating_sub(CAPACITY - MARGIN).max(start);
+        let mut from = self.read.clamp(oldest, written);
+        from += (channels - (from - start) % channels) % channels;
+        out.extend((from..written).map(|i| f32::from_bits(tap.slots[i % CAPACITY].load(Ordering::Relaxed))));
+        self.read = written;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_what_was_written_since_last_read() {
+        let tap = Arc::new(OutputTap::new());
+        tap.write(&[1.0, 2.0], 2, 48000);
+        let mut reader = tap.reader();
+        let mut out = Vec::new();
+        reader.read(&mut out);
+        assert!(out.is_empty());
+
+        tap.write(&[3.0, 4.0, 5.0, 6.0], 2, 48000);
+        reader.read(&mut out);
+        assert_eq!(out, [3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(reader.layout(), (2, 48000));
+    }
+
+    #[test]
+    fn test_skips_overwritten_and_old_layout_samples() {
+        let tap = Arc::new(OutputTap::new());
+        let mut reader = tap.reader();
+        let block: Vec<f32> = (0..CAPACITY).map(|i| i as f32).collect();
+        tap.write(&block, 2, 44100);
+        tap.write(&block, 2, 44100);
+        let mut out = Vec::new();
+        reader.read(&mut out);
+        assert_eq!(out.len(), CAPACITY - MARGIN);
+        assert_eq!(out.last(), Some(&((CAPACITY - 1) as f32)));
+
+        // A new layout starts afresh so frames never straddle the change
+        tap.write(&[0.5; 3], 1, 44100);
+        tap.write(&[0.25; 6], 3, 48000);
+        out.clear();
+        reader.read(&mut out);
+        assert_eq!(out, [0.25; 6]);
+        assert_eq!(reader.layout(), (3, 48000));
+    }
+}
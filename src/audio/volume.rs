@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+/// Time a volume change takes to reach its new level; long enough to avoid zipper noise
+pub const VOLUME_RAMP: Duration = Duration::from_millis(20);
+
+/// Attenuation at 1% relative to 100%, by default
+pub const DEFAULT_RANGE_DB: f32 = 50.0;
+
+/// Narrowest and widest ranges the volume scale may span
+pub const MIN_RANGE_DB: f32 = 10.0;
+pub const MAX_RANGE_DB: f32 = 96.0;
+
+/// Most attenuation that may be kept in reserve at 100%
+pub const MAX_HEADROOM_DB: f32 = 24.0;
+
+/// Maps the 0-100 volume scale onto decibels, evenly spaced so each step sounds alike
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeCurve {
+    /// Attenuation at 1%, relative to 100%; 0% is always silent
+    pub range_db: f32,
+    /// Attenuation at 100%, leaving room below full scale for ReplayGain or EQ boosts
+    pub headroom_db: f32,
+}
+
+impl Default for VolumeCurve {
+    fn default() -> Self {
+        Self {
+            range_db: DEFAULT_RANGE_DB,
+            headroom_db: 0.0,
+        }
+    }
+}
+
+impl VolumeCurve {
+    /// Level of `percent` in dB, or `None` for silence
+    pub fn db(&self, percent: u8) -> Option<f32> {
+        if percent == 0 {
+            return None;
+        }
+        let below_full = 1.0 - (percent.min(100) - 1) as f32 / 99.0;
+        // Subtracted from zero so full volume reads 0dB rather than -0dB
+        Some(0.0 - (self.range_db * below_full + self.headroom_db))
+    }
+
+    /// Factor each sample is multiplied by at `percent`
+    pub fn gain(&self, percent: u8) -> f32 {
+        self.db(percent).map_or(0.0, |db| 10f32.powf(db / 20.0))
+    }
+
+    /// Whether the range and headroom are within the limits above
+    pub fn is_valid(&self) -> bool {
+        (MIN_RANGE_DB..=MAX_RANGE_DB).contains(&self.range_db)
+            && (0.0..=MAX_HEADROOM_DB).contains(&self.headroom_db)
+    }
+}
+
+/// Playback volume on the 0-100 scale, which mute silences without forgetting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Volume {
+    level: u8,
+    muted: bool,
+    pub curve: VolumeCurve,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl Volume {
+    pub fn new(level: u8) -> Self {
+        Self {
+            level: level.min(100),
+            muted: false,
+            curve: VolumeCurve::default(),
+        }
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Change the level, which also unmutes
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level.min(100);
+        self.muted = false;
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Mute, or return to the level from before muting; returns whether now muted
+    pub fn toggle_mute(&mut self) -> bool {
+        self.muted = !self.muted;
+        self.muted
+    }
+
+    /// Level in dB, or `None` while silent
+    pub fn db(&self) -> Option<f32> {
+        if self.muted {
+            return None;
+        }
+        self.curve.db(self.level)
+    }
+
+    /// Factor each sample is multiplied by
+    pub fn gain(&self) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        self.curve.gain(self.level)
+    }
+}
+
+/// Moves a gain to its target in even steps over a fixed number of frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainRamp {
+    current: f32,
+    target: f32,
+    step: f32,
+}
+
+impl GainRamp {
+    pub fn new(gain: f32) -> Self {
+        Self {
+            current: gain,
+            target: gain,
+            step: 0.0,
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Head for `target`, arriving `frames` frames from now
+    pub fn set_target(&mut self, target: f32, frames: usize) {
+        if target != self.target {
+            self.target = target;
+            self.step = (target - self.current).abs() / frames.max(1) as f32;
+        }
+    }
+
+    /// Scale interleaved `samples`, advancing the ramp one step per frame
+    pub fn apply(&mut self, samples: &mut [f32], channels: usize) {
+        if self.current == self.target {
+            if self.current != 1.0 {
+                samples.iter_mut().for_each(|sample| *sample *= self.current);
+            }
+            return;
+        }
+        for frame in samples.chunks_mut(channels.max(1)) {
+            self.current = if self.target > self.current {
+                (self.current + self.step).min(self.target)
+            } else {
+                (self.current - self.step).max(self.target)
+            };
+            frame.iter_mut().for_each(|sample| *sample *= self.current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_spans_range() {
+        let curve = VolumeCurve::default();
+        assert_eq!(curve.db(0), None);
+        assert_eq!(curve.gain(0), 0.0);
+        assert_eq!(curve.db(1), Some(-50.0));
+        assert_eq!(curve.db(100), Some(0.0));
+        assert_eq!(curve.gain(100), 1.0);
+        assert_eq!(curve.db(150), Some(0.0));
+
+        // Evenly spaced in dB, so equal steps sound alike
+        let step = curve.db(60).unwrap() - curve.db(50).unwrap();
+        assert!((curve.db(90).unwrap() - curve.db(80).unwrap() - step).abs() < 1e-4);
+
+        let quiet = VolumeCurve { range_db: 30.0, headroom_db: 6.0 };
+        assert_eq!(quiet.db(1), Some(-36.0));
+        assert_eq!(quiet.db(100), Some(-6.0));
+        assert!(quiet.is_valid());
+        assert!(!VolumeCurve { range_db: 5.0, headroom_db: 0.0 }.is_valid());
+        assert!(!VolumeCurve { range_db: 50.0, headroom_db: -1.0 }.is_valid());
+    }
+
+    #[test]
+    fn test_mute_remembers_level() {
+        let mut volume = Volume::new(70);
+        assert!(volume.toggle_mute());
+        assert_eq!(volume.gain(), 0.0);
+        assert_eq!(volume.db(), None);
+        assert_eq!(volume.level(), 70);
+
+        assert!(!volume.toggle_mute());
+        assert_eq!(volume.gain(), volume.curve.gain(70));
+
+        // Changing the level unmutes
+        volume.set_muted(true);
+        volume.set_level(40);
+        assert!(!volume.is_muted());
+        assert_eq!(volume.level(), 40);
+    }
+
+    #[test]
+    fn test_ramp_reaches_target_over_frames() {
+        let mut ramp = GainRamp::new(1.0);
+        let mut samples = vec![1.0; 8];
+        ramp.apply(&mut samples, 2);
+        assert_eq!(samples, vec![1.0; 8]);
+
+        // Four stereo frames from 1.0 to 0.0, a quarter per frame
+        ramp.set_target(0.0, 4);
+        ramp.apply(&mut samples, 2);
+        assert_eq!(samples, vec![0.75, 0.75, 0.5, 0.5, 0.25, 0.25, 0.0, 0.0]);
+        assert_eq!(ramp.current(), 0.0);
+
+        let mut samples = vec![1.0; 4];
+        ramp.apply(&mut samples, 2);
+        assert_eq!(samples, vec![0.0; 4]);
+    }
+}
@@ -28,6 +28,13 @@ mod actions;
 #[cfg(test)]
 mod tests;
 
+impl Controls {
+    /// Light the record button only while the engine is actually recording
+    pub fn show_recording(&mut self, recording: bool) {
+        self.is_recording = recording;
+    }
+}
+
 impl Component for Controls {
     fn new() -> Self {
         Controls {
@@ -0,0 +1,54 @@
+use std::time::Duration;
+use crate::audio::SeekTarget;
+use crate::events::{Event, Action, KeyEvent, MouseEvent, PlayerAction};
+use super::state::InfoState;
+
+pub fn handle_event(state: &mut InfoState, event: Event, focused: bool) -> Option<Action> {
+    // The chapter hotkeys work whichever component has focus
+    match event {
+        Event::Key(KeyEvent::NextChapter) => return Some(Action::Player(PlayerAction::NextChapter)),
+        Event::Key(KeyEvent::PreviousChapter) => return Some(Action::Player(PlayerAction::PreviousChapter)),
+        _ => {}
+    }
+    if !focused {
+        return None;
+    }
+
+    match event {
+        Event::Key(key_event) => handle_key_event(state, key_event),
+        Event::Mouse(mouse_event) => handle_mouse_event(state, mouse_event),
+        _ => None,
+    }
+}
+
+fn handle_key_event(state: &mut InfoState, key_event: KeyEvent) -> Option<Action> {
+    match key_event {
+        KeyEvent::Up => state.select(-1),
+        KeyEvent::Down => state.select(1),
+        KeyEvent::PageUp => state.select(-10),
+        KeyEvent::PageDown => state.select(10),
+        KeyEvent::Home => state.select(isize::MIN),
+        KeyEvent::End => state.select(isize::MAX),
+        KeyEvent::Enter => return seek(state.selected_start()?),
+        _ => return None,
+    }
+    Some(Action::Refresh)
+}
+
+fn handle_mouse_event(state: &mut InfoState, event: MouseEvent) -> Option<Action> {
+    match event {
+        // Clicking a chapter plays from its start
+        MouseEvent::Click { x, y } => {
+            state.selected = state.chapter_at(x, y)?;
+            seek(state.selected_start()?)
+        }
+        MouseEvent::Scroll { delta } => {
+            state.select(-delta as isize);
+            Some(Action::Refresh)
+        }
+    }
+}
+
+fn seek(start: Duration) -> Option<Action> {
+    Some(Action::Player(PlayerAction::Seek(SeekTarget::Absolute(start))))
+}
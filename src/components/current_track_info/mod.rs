@@ -0,0 +1,62 @@
+use std::time::Duration;
+use ratatui::prelude::*;
+use super::{Component, ComponentState};
+use crate::events::{Event, Action, MetadataAction, PlayerAction};
+use crate::theme::Theme;
+
+mod state;
+mod events;
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+use state::InfoState;
+
+#[derive(Clone)]
+pub struct CurrentTrackInfo {
+    component_state: ComponentState,
+    state: InfoState,
+}
+
+impl CurrentTrackInfo {
+    /// Follow playback through the current track's chapters
+    pub fn show_position(&mut self, position: Duration) {
+        self.state.set_position(position);
+    }
+}
+
+impl Component for CurrentTrackInfo {
+    fn new() -> Self {
+        Self {
+            component_state: ComponentState::default(),
+            state: InfoState::default(),
+        }
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+        view::render(&self.state, frame, area, focused, theme);
+    }
+
+    fn update(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::Metadata(MetadataAction::Update(metadata)) => self.state.set_track(Some(metadata)),
+            Action::Metadata(MetadataAction::Clear) | Action::Player(PlayerAction::StopEject) => self.state.set_track(None),
+            _ => {}
+        }
+        None
+    }
+
+    fn focused(&self) -> bool {
+        self.component_state.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.component_state.focused = focused;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
+        let is_focused = self.focused();
+        events::handle_event(&mut self.state, event, is_focused)
+    }
+}
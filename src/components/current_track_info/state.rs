@@ -0,0 +1,72 @@
+use std::cell::RefCell;
+use std::time::Duration;
+use ratatui::prelude::*;
+use crate::events::TrackMetadata;
+use crate::metadata::{chapters, Chapter};
+
+#[derive(Clone, Default)]
+pub struct InfoState {
+    /// Tags and chapters of the track playing, if any
+    pub track: Option<TrackMetadata>,
+    pub position: Duration,
+    /// Chapter highlighted in the list
+    pub selected: usize,
+    /// First chapter shown, scrolled to keep the highlighted one in view
+    pub offset: RefCell<usize>,
+    /// Where the chapter list was last drawn, for clicks
+    pub list_area: RefCell<Option<Rect>>,
+}
+
+impl InfoState {
+    pub fn chapters(&self) -> &[Chapter] {
+        self.track.as_ref().map_or(&[], |track| &track.chapters)
+    }
+
+    /// Index of the chapter playing
+    pub fn current_chapter(&self) -> Option<usize> {
+        chapters::current(self.chapters(), self.position)
+    }
+
+    /// `Chapter 3/12: Title`, once playback reaches the first chapter
+    pub fn chapter_label(&self) -> Option<String> {
+        let chapters = self.chapters();
+        let index = self.current_chapter()?;
+        Some(format!("Chapter {}/{}: {}", index + 1, chapters.len(), chapters[index].label(index)))
+    }
+
+    pub fn set_track(&mut self, track: Option<TrackMetadata>) {
+        self.track = track;
+        self.position = Duration::ZERO;
+        self.selected = 0;
+        *self.offset.borrow_mut() = 0;
+    }
+
+    /// Follow playback, keeping the highlight on the chapter playing as it moves on
+    pub fn set_position(&mut self, position: Duration) {
+        let before = self.current_chapter();
+        self.position = position;
+        let now = self.current_chapter();
+        if now != before && before.is_none_or(|before| before == self.selected) {
+            self.selected = now.unwrap_or(0);
+        }
+    }
+
+    /// Move the highlight by `step` chapters, staying within the list
+    pub fn select(&mut self, step: isize) {
+        let last = self.chapters().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(step).min(last);
+    }
+
+    /// Start of the highlighted chapter
+    pub fn selected_start(&self) -> Option<Duration> {
+        self.chapters().get(self.selected).map(|chapter| chapter.start)
+    }
+
+    /// Chapter listed at row `y` of the screen, if it falls on one
+    pub fn chapter_at(&self, x: u16, y: u16) -> Option<usize> {
+        let area = (*self.list_area.borrow())?;
+        let inside = x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height;
+        let index = *self.offset.borrow() + (y - area.y) as usize;
+        (inside && index < self.chapters().len()).then_some(index)
+    }
+}
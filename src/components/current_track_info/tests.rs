@@ -0,0 +1,76 @@
+use super::*;
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use crate::audio::SeekTarget;
+use crate::events::{KeyEvent, MouseEvent, TrackMetadata};
+use crate::metadata::Chapter;
+
+fn audiobook() -> TrackMetadata {
+    let chapter = |title: Option<&str>, start: u64| Chapter {
+        title: title.map(str::to_string),
+        start: Duration::from_secs(start),
+        end: None,
+    };
+    TrackMetadata {
+        title: Some("The Book".to_string()),
+        artist: Some("The Author".to_string()),
+        chapters: vec![chapter(Some("Opening"), 0), chapter(None, 60), chapter(Some("Ending"), 120)],
+        ..Default::default()
+    }
+}
+
+fn render(info: &CurrentTrackInfo) -> String {
+    let theme = crate::theme::Theme::load_default().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+    terminal.draw(|frame| info.render(frame, Rect::new(0, 0, 40, 10), true, &theme)).unwrap();
+    terminal.backend().buffer().content.iter().map(|cell| cell.symbol.as_str()).collect()
+}
+
+fn seek(secs: u64) -> Option<Action> {
+    Some(Action::Player(PlayerAction::Seek(SeekTarget::Absolute(Duration::from_secs(secs)))))
+}
+
+#[test]
+fn test_shows_current_chapter() {
+    let mut info = CurrentTrackInfo::new();
+    assert!(render(&info).contains("No track playing"));
+
+    info.update(Action::Metadata(MetadataAction::Update(audiobook())));
+    info.show_position(Duration::from_secs(75));
+    let screen = render(&info);
+    assert!(screen.contains("The Book"));
+    assert!(screen.contains("Chapter 2/3: Chapter 2"));
+    assert!(screen.contains("▶     1:00  Chapter 2"));
+
+    info.update(Action::Player(PlayerAction::StopEject));
+    assert!(render(&info).contains("No track playing"));
+}
+
+#[test]
+fn test_chapter_hotkeys_work_without_focus() {
+    let mut info = CurrentTrackInfo::new();
+    assert_eq!(info.handle_event(Event::Key(KeyEvent::NextChapter)), Some(Action::Player(PlayerAction::NextChapter)));
+    assert_eq!(info.handle_event(Event::Key(KeyEvent::PreviousChapter)), Some(Action::Player(PlayerAction::PreviousChapter)));
+    assert_eq!(info.handle_event(Event::Key(KeyEvent::Enter)), None);
+}
+
+#[test]
+fn test_chapter_list_seeks() {
+    let mut info = CurrentTrackInfo::new();
+    info.set_focused(true);
+    info.update(Action::Metadata(MetadataAction::Update(audiobook())));
+
+    // The highlight follows playback until moved by hand
+    info.show_position(Duration::from_secs(61));
+    assert_eq!(info.state.selected, 1);
+    info.handle_event(Event::Key(KeyEvent::Down));
+    info.handle_event(Event::Key(KeyEvent::Down));
+    assert_eq!(info.handle_event(Event::Key(KeyEvent::Enter)), seek(120));
+    info.show_position(Duration::from_secs(5));
+    assert_eq!(info.state.selected, 2);
+
+    // Clicking a row below the title, artist and chapter lines plays that chapter
+    render(&info);
+    assert_eq!(info.handle_event(Event::Mouse(MouseEvent::Click { x: 5, y: 4 })), seek(0));
+    assert_eq!(info.handle_event(Event::Mouse(MouseEvent::Click { x: 5, y: 8 })), None);
+}
@@ -0,0 +1,65 @@
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use crate::components::create_block;
+use crate::components::playback_status::format_time;
+use crate::theme::Theme;
+use super::state::InfoState;
+
+/// Rows above the chapter list: title, artist and album, current chapter
+const HEADER_ROWS: u16 = 3;
+
+pub fn render(state: &InfoState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let block = create_block("Current Track Info", focused, theme);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(track) = state.track.as_ref() else {
+        *state.list_area.borrow_mut() = None;
+        frame.render_widget(Paragraph::new(Line::styled("No track playing", theme.get_style("text_dim"))), inner);
+        return;
+    };
+
+    let by = [track.artist.as_deref(), track.album.as_deref()].into_iter().flatten().collect::<Vec<_>>().join(" · ");
+    let header = vec![
+        Line::styled(track.title.as_deref().unwrap_or("Unknown Title").to_string(), theme.get_style("text_normal")),
+        Line::styled(by, theme.get_style("text_dim")),
+        Line::styled(state.chapter_label().unwrap_or_default(), theme.get_style("text_normal")),
+    ];
+    frame.render_widget(Paragraph::new(header), inner);
+
+    let list = Rect {
+        y: inner.y + HEADER_ROWS.min(inner.height),
+        height: inner.height.saturating_sub(HEADER_ROWS),
+        ..inner
+    };
+    *state.list_area.borrow_mut() = Some(list);
+    render_chapters(state, frame, list, focused, theme);
+}
+
+/// One row per chapter with its start time, marking the one playing
+fn render_chapters(state: &InfoState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let rows = area.height as usize;
+    if rows == 0 {
+        return;
+    }
+    let mut offset = state.offset.borrow_mut();
+    if state.selected < *offset {
+        *offset = state.selected;
+    } else if state.selected >= *offset + rows {
+        *offset = state.selected + 1 - rows;
+    }
+
+    let current = state.current_chapter();
+    let lines: Vec<Line> = state.chapters().iter().enumerate().skip(*offset).take(rows)
+        .map(|(index, chapter)| {
+            let marker = if current == Some(index) { "▶ " } else { "  " };
+            let style = match (focused && index == state.selected, current == Some(index)) {
+                (true, _) => theme.get_style("list_selected"),
+                (false, true) => theme.get_style("playing_item"),
+                (false, false) => theme.get_style("list_item"),
+            };
+            Line::styled(format!("{}{:>8}  {}", marker, format_time(chapter.start), chapter.label(index)), style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+}
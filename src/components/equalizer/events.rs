@@ -0,0 +1,95 @@
+use crate::events::{Event, Action, EqualizerAction, KeyEvent, MouseEvent};
+use super::state::{EqualizerState, GAIN_STEP};
+use super::view::{column_width, plot_area, row_gain};
+
+pub fn handle_event(state: &mut EqualizerState, event: Event, focused: bool) -> Option<Action> {
+    if !focused {
+        return None;
+    }
+
+    match event {
+        Event::Key(key_event) => handle_key_event(state, key_event),
+        Event::Mouse(mouse_event) => handle_mouse_event(state, mouse_event),
+        _ => None,
+    }
+}
+
+fn handle_key_event(state: &mut EqualizerState, key_event: KeyEvent) -> Option<Action> {
+    match key_event {
+        KeyEvent::Left => {
+            state.select_band(-1);
+            return None;
+        }
+        KeyEvent::Right => {
+            state.select_band(1);
+            return None;
+        }
+        KeyEvent::Up => state.adjust_gain(GAIN_STEP),
+        KeyEvent::Down => state.adjust_gain(-GAIN_STEP),
+        KeyEvent::Char('[') => state.step_frequency(false),
+        KeyEvent::Char(']') => state.step_frequency(true),
+        KeyEvent::Char(',') => state.step_q(false),
+        KeyEvent::Char('.') => state.step_q(true),
+        KeyEvent::Enter => state.next_preset(),
+        KeyEvent::Char('e') => state.toggle_enabled(),
+        KeyEvent::Char('a') => return Some(Action::Equalizer(EqualizerAction::SavePreset(state.save_preset()))),
+        _ => return None,
+    }
+    Some(state.apply())
+}
+
+fn handle_mouse_event(state: &mut EqualizerState, event: MouseEvent) -> Option<Action> {
+    match event {
+        MouseEvent::Click { x, y } => {
+            let plot = plot_area(state.get_area()?);
+            let bands = state.current.bands.len();
+            let width = column_width(plot, bands) as u16;
+            let inside = x >= plot.x && y >= plot.y && y < plot.y + plot.height;
+            let band = (x.saturating_sub(plot.x) / width) as usize;
+            if !inside || band >= bands {
+                return None;
+            }
+            // Clicking a band's column sets its gain to the level clicked
+            state.set_gain(band, row_gain(y - plot.y, plot.height));
+        }
+        MouseEvent::Scroll { delta } => {
+            state.adjust_gain(if delta > 0 { -GAIN_STEP } else { GAIN_STEP });
+        }
+    }
+    Some(state.apply())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::prelude::*;
+
+    #[test]
+    fn test_mouse_click_sets_gain() {
+        let mut state = EqualizerState::default();
+        // Ten 2-column bands over a 25-row plot, one dB per row
+        state.set_area(Rect::new(0, 0, 22, 29));
+
+        let result = handle_event(&mut state, Event::Mouse(MouseEvent::Click { x: 7, y: 1 }), true);
+        assert_eq!(state.selected_band, 3);
+        assert_eq!(state.current.bands[3].gain_db, 12.0);
+        assert!(matches!(result, Some(Action::Equalizer(EqualizerAction::Apply { .. }))));
+
+        handle_event(&mut state, Event::Mouse(MouseEvent::Click { x: 1, y: 19 }), true);
+        assert_eq!((state.selected_band, state.current.bands[0].gain_db), (0, -6.0));
+
+        // The frequency labels below the plot and the border are not part of it
+        assert_eq!(handle_event(&mut state, Event::Mouse(MouseEvent::Click { x: 1, y: 27 }), true), None);
+        assert_eq!(handle_event(&mut state, Event::Mouse(MouseEvent::Click { x: 21, y: 5 }), true), None);
+    }
+
+    #[test]
+    fn test_mouse_scroll() {
+        let mut state = EqualizerState::default();
+        handle_event(&mut state, Event::Mouse(MouseEvent::Scroll { delta: -1 }), true);
+        assert_eq!(state.current.bands[0].gain_db, 1.0);
+        handle_event(&mut state, Event::Mouse(MouseEvent::Scroll { delta: 1 }), true);
+        assert_eq!(state.current.bands[0].gain_db, 0.0);
+        assert_eq!(handle_event(&mut state, Event::Mouse(MouseEvent::Scroll { delta: 1 }), false), None);
+    }
+}
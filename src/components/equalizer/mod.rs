@@ -0,0 +1,55 @@
+use crate::audio::eq::EqPreset;
+use crate::components::{Component, ComponentState};
+use crate::events::{Event, Action};
+
+mod state;
+mod events;
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+use state::EqualizerState;
+
+#[derive(Clone)]
+pub struct EqualizerControl {
+    component_state: ComponentState,
+    state: EqualizerState,
+}
+
+impl EqualizerControl {
+    /// Show the EQ settings restored from preferences
+    pub fn restore(&mut self, enabled: bool, selected: &EqPreset, user_presets: &[EqPreset]) {
+        self.state.restore(enabled, selected, user_presets);
+    }
+}
+
+impl Component for EqualizerControl {
+    fn new() -> Self {
+        Self {
+            component_state: ComponentState::default(),
+            state: EqualizerState::default(),
+        }
+    }
+
+    fn render(&self, frame: &mut ratatui::prelude::Frame, area: ratatui::prelude::Rect, focused: bool, theme: &crate::theme::Theme) {
+        view::render(&self.state, frame, area, focused, theme);
+    }
+
+    fn update(&mut self, _action: Action) -> Option<Action> {
+        None
+    }
+
+    fn focused(&self) -> bool {
+        self.component_state.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.component_state.focused = focused;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
+        let is_focused = self.focused();
+        events::handle_event(&mut self.state, event, is_focused)
+    }
+}
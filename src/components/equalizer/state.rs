@@ -0,0 +1,168 @@
+use std::cell::RefCell;
+use ratatui::prelude::*;
+use crate::audio::eq::{builtin_presets, EqPreset, MAX_BAND_GAIN_DB, MAX_FREQUENCY, MAX_Q, MIN_FREQUENCY, MIN_Q};
+use crate::events::{Action, EqualizerAction};
+
+/// Name shown once a preset's bands have been edited
+pub const CUSTOM: &str = "Custom";
+
+/// Change per key press: a decibel of gain, a third of an octave, a quarter-octave of bandwidth
+pub const GAIN_STEP: f32 = 1.0;
+const FREQUENCY_STEP: f32 = 1.259_921;
+const Q_STEP: f32 = 1.189_207;
+
+#[derive(Clone)]
+pub struct EqualizerState {
+    pub enabled: bool,
+    /// Built-in presets followed by the user's
+    pub presets: Vec<EqPreset>,
+    /// Which of `presets` is in use, `None` once a band has been edited
+    pub preset_index: Option<usize>,
+    pub current: EqPreset,
+    pub selected_band: usize,
+    pub area: RefCell<Option<Rect>>,
+}
+
+impl Default for EqualizerState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            presets: builtin_presets(),
+            preset_index: Some(0),
+            current: EqPreset::flat(),
+            selected_band: 0,
+            area: RefCell::new(None),
+        }
+    }
+}
+
+impl EqualizerState {
+    /// Show the saved settings: whether the EQ is on, the user's presets and the one selected
+    pub fn restore(&mut self, enabled: bool, selected: &EqPreset, user_presets: &[EqPreset]) {
+        self.enabled = enabled;
+        self.presets = builtin_presets();
+        self.presets.extend(user_presets.iter().cloned());
+        self.preset_index = self.presets.iter().position(|preset| preset.name == selected.name);
+        self.current = selected.clone();
+        self.selected_band = 0;
+    }
+
+    /// The action telling the engine about the current settings
+    pub fn apply(&self) -> Action {
+        Action::Equalizer(EqualizerAction::Apply {
+            enabled: self.enabled,
+            preset: self.current.clone(),
+        })
+    }
+
+    pub fn toggle_enabled(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Switch to the preset after the current one, wrapping round
+    pub fn next_preset(&mut self) {
+        let index = self.preset_index.map_or(0, |index| (index + 1) % self.presets.len());
+        self.preset_index = Some(index);
+        self.current = self.presets[index].clone();
+        self.selected_band = self.selected_band.min(self.current.bands.len().saturating_sub(1));
+    }
+
+    /// Move the band selection by `delta`, stopping at either end
+    pub fn select_band(&mut self, delta: isize) {
+        let last = self.current.bands.len().saturating_sub(1);
+        self.selected_band = self.selected_band.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn adjust_gain(&mut self, delta: f32) {
+        if let Some(band) = self.current.bands.get(self.selected_band) {
+            self.set_gain(self.selected_band, band.gain_db + delta);
+        }
+    }
+
+    /// Select `band` and set its gain, rounded to whole decibels
+    pub fn set_gain(&mut self, band: usize, gain_db: f32) {
+        let Some(edited) = self.current.bands.get_mut(band) else {
+            return;
+        };
+        edited.gain_db = gain_db.round().clamp(-MAX_BAND_GAIN_DB, MAX_BAND_GAIN_DB);
+        self.selected_band = band;
+        self.edited();
+    }
+
+    /// Move the selected band up (`up`) or down a third of an octave
+    pub fn step_frequency(&mut self, up: bool) {
+        if let Some(band) = self.current.bands.get_mut(self.selected_band) {
+            let factor = if up { FREQUENCY_STEP } else { 1.0 / FREQUENCY_STEP };
+            band.frequency = (band.frequency * factor).round().clamp(MIN_FREQUENCY, MAX_FREQUENCY);
+            self.edited();
+        }
+    }
+
+    /// Narrow (`narrower`) or widen the selected band
+    pub fn step_q(&mut self, narrower: bool) {
+        if let Some(band) = self.current.bands.get_mut(self.selected_band) {
+            let factor = if narrower { Q_STEP } else { 1.0 / Q_STEP };
+            band.q = (band.q * factor).clamp(MIN_Q, MAX_Q);
+            self.edited();
+        }
+    }
+
+    /// The curve no longer matches a preset; keep the loudest boost from clipping
+    fn edited(&mut self) {
+        self.preset_index = None;
+        self.current.name = CUSTOM.to_string();
+        let boost = self.current.bands.iter().fold(0f32, |max, band| max.max(band.gain_db));
+        self.current.preamp_db = 0.0 - boost;
+    }
+
+    /// Keep the current curve as a new user preset named `User N`, selecting it
+    pub fn save_preset(&mut self) -> EqPreset {
+        let name = (1..)
+            .map(|n| format!("User {}", n))
+            .find(|name| !self.presets.iter().any(|preset| &preset.name == name))
+            .unwrap();
+        self.current.name = name;
+        self.presets.push(self.current.clone());
+        self.preset_index = Some(self.presets.len() - 1);
+        self.current.clone()
+    }
+
+    /// Title text such as `EQ: Rock` or `EQ: off (Rock)`
+    pub fn label(&self) -> String {
+        if self.enabled {
+            format!("EQ: {}", self.current.name)
+        } else {
+            format!("EQ: off ({})", self.current.name)
+        }
+    }
+
+    /// Settings of the selected band, e.g. `1k  Q1.41  +3 dB`
+    pub fn band_label(&self) -> String {
+        match self.current.bands.get(self.selected_band) {
+            Some(band) => format!("{}  Q{:.2}  {:+.0} dB", frequency_label(band.frequency), band.q, band.gain_db),
+            None => String::new(),
+        }
+    }
+
+    pub fn set_area(&self, area: Rect) {
+        *self.area.borrow_mut() = Some(area);
+    }
+
+    pub fn get_area(&self) -> Option<Rect> {
+        *self.area.borrow()
+    }
+}
+
+/// Short frequency label such as `62` or `16k`
+pub fn frequency_label(frequency: f32) -> String {
+    if frequency >= 1000.0 {
+        let khz = frequency / 1000.0;
+        if khz.fract() < 0.05 {
+            format!("{:.0}k", khz)
+        } else {
+            format!("{:.1}k", khz)
+        }
+    } else {
+        format!("{:.0}", frequency)
+    }
+}
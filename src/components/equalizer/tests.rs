@@ -0,0 +1,92 @@
+use super::*;
+use super::state::CUSTOM;
+use crate::audio::eq::{builtin_presets, GRAPHIC_Q};
+use crate::events::{EqualizerAction, KeyEvent};
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+fn applied(action: Option<Action>) -> (bool, EqPreset) {
+    match action {
+        Some(Action::Equalizer(EqualizerAction::Apply { enabled, preset })) => (enabled, preset),
+        other => panic!("Expected Apply, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_keys_adjust_selected_band() {
+    let mut eq = EqualizerControl::new();
+    eq.set_focused(true);
+    assert_eq!(eq.state.label(), "EQ: off (Flat)");
+
+    assert_eq!(eq.handle_event(Event::Key(KeyEvent::Right)), None);
+    let (enabled, preset) = applied(eq.handle_event(Event::Key(KeyEvent::Up)));
+    assert!(!enabled);
+    assert_eq!(preset.name, CUSTOM);
+    assert_eq!(preset.bands[1].gain_db, 1.0);
+    assert_eq!(preset.preamp_db, -1.0, "Boosts are offset to avoid clipping");
+
+    let (_, preset) = applied(eq.handle_event(Event::Key(KeyEvent::Char(']'))));
+    assert_eq!(preset.bands[1].frequency, 78.0);
+    let (_, preset) = applied(eq.handle_event(Event::Key(KeyEvent::Char('.'))));
+    assert!(preset.bands[1].q > GRAPHIC_Q);
+    assert!(eq.state.band_label().starts_with("78  Q1.68  +1 dB"));
+
+    let (enabled, _) = applied(eq.handle_event(Event::Key(KeyEvent::Char('e'))));
+    assert!(enabled);
+    assert_eq!(eq.state.label(), "EQ: Custom");
+
+    // Gains stop at ±12dB
+    for _ in 0..30 {
+        eq.handle_event(Event::Key(KeyEvent::Down));
+    }
+    assert_eq!(eq.state.current.bands[1].gain_db, -12.0);
+
+    eq.set_focused(false);
+    assert_eq!(eq.handle_event(Event::Key(KeyEvent::Up)), None);
+}
+
+#[test]
+fn test_presets_cycle_and_save() {
+    let mut eq = EqualizerControl::new();
+    eq.set_focused(true);
+    let builtin = builtin_presets();
+
+    let (_, preset) = applied(eq.handle_event(Event::Key(KeyEvent::Enter)));
+    assert_eq!(preset, builtin[1]);
+    for _ in 1..builtin.len() {
+        eq.handle_event(Event::Key(KeyEvent::Enter));
+    }
+    assert_eq!(eq.state.current, builtin[0], "Presets wrap round");
+
+    eq.handle_event(Event::Key(KeyEvent::Up));
+    let saved = match eq.handle_event(Event::Key(KeyEvent::Char('a'))) {
+        Some(Action::Equalizer(EqualizerAction::SavePreset(preset))) => preset,
+        other => panic!("Expected SavePreset, got {:?}", other),
+    };
+    assert_eq!(saved.name, "User 1");
+    assert_eq!(saved.bands[0].gain_db, 1.0);
+    assert_eq!(eq.state.preset_index, Some(builtin.len()));
+
+    // Saved presets come back after the built-in ones
+    let mut restored = EqualizerControl::new();
+    restored.restore(true, &saved, std::slice::from_ref(&saved));
+    assert_eq!(restored.state.label(), "EQ: User 1");
+    assert_eq!(restored.state.presets.len(), builtin.len() + 1);
+    assert_eq!(restored.state.preset_index, Some(builtin.len()));
+}
+
+#[test]
+fn test_render() {
+    let mut eq = EqualizerControl::new();
+    eq.state.set_gain(5, 12.0);
+    let theme = crate::theme::Theme::load_default().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(32, 10)).unwrap();
+    terminal.draw(|frame| eq.render(frame, Rect::new(0, 0, 32, 10), true, &theme)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let row = |y: u16| (0..32).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>();
+    assert!(row(0).contains("EQ: off (Custom)"));
+    // The boosted 1k band reaches the top of the plot
+    assert!(row(1).contains('█'));
+    assert!(row(7).contains("1k"));
+    assert!(row(8).contains("1k  Q1.41  +12 dB"));
+}
@@ -0,0 +1,85 @@
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use crate::audio::eq::MAX_BAND_GAIN_DB;
+use crate::components::create_block;
+use crate::theme::Theme;
+use super::state::{frequency_label, EqualizerState};
+
+pub fn render(state: &EqualizerState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let title = state.label();
+    let block = create_block(title.as_str(), focused, theme);
+    let inner = block.inner(area);
+
+    // Store the area for mouse interaction calculations
+    state.set_area(area);
+    frame.render_widget(block, area);
+
+    let bands = &state.current.bands;
+    let plot = plot_area(area);
+    let width = column_width(plot, bands.len());
+    let style_for = |band: usize| match (state.enabled, band == state.selected_band && focused) {
+        (_, true) => theme.get_style("list_selected"),
+        (true, false) => theme.get_style("volume_indicator"),
+        (false, false) => theme.get_style("text_dim"),
+    };
+
+    let mut lines: Vec<Line> = (0..plot.height)
+        .map(|row| {
+            let zero = gain_row(0.0, plot.height);
+            Line::from(bands.iter().enumerate().map(|(index, band)| {
+                let level = gain_row(band.gain_db, plot.height);
+                let glyph = if band.gain_db != 0.0 && row >= zero.min(level) && row <= zero.max(level) {
+                    "█"
+                } else if row == zero {
+                    "─"
+                } else {
+                    " "
+                };
+                Span::styled(format!("{:^width$}", glyph, width = width), style_for(index))
+            }).collect::<Vec<_>>())
+        })
+        .collect();
+
+    if inner.height > plot.height {
+        lines.push(Line::from(bands.iter().enumerate().map(|(index, band)| {
+            let mut label = frequency_label(band.frequency);
+            label.truncate(width);
+            Span::styled(format!("{:^width$}", label, width = width), style_for(index))
+        }).collect::<Vec<_>>()));
+    }
+    if inner.height > plot.height + 1 {
+        lines.push(Line::styled(state.band_label(), theme.get_style("text_normal")));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The part of the component showing the bands' gains, above the frequency and detail rows
+pub fn plot_area(area: Rect) -> Rect {
+    let inner = Rect {
+        x: area.x.saturating_add(1),
+        y: area.y.saturating_add(1),
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    };
+    Rect { height: inner.height.saturating_sub(2), ..inner }
+}
+
+/// Columns given to each band
+pub fn column_width(plot: Rect, bands: usize) -> usize {
+    (plot.width as usize / bands.max(1)).max(1)
+}
+
+/// Row of the plot showing `gain_db`, +12dB at the top and -12dB at the bottom
+fn gain_row(gain_db: f32, height: u16) -> u16 {
+    let last = height.saturating_sub(1) as f32;
+    ((MAX_BAND_GAIN_DB - gain_db) / (2.0 * MAX_BAND_GAIN_DB) * last).round() as u16
+}
+
+/// Gain shown at `row` of the plot
+pub fn row_gain(row: u16, height: u16) -> f32 {
+    let last = height.saturating_sub(1);
+    if last == 0 {
+        return 0.0;
+    }
+    MAX_BAND_GAIN_DB - 2.0 * MAX_BAND_GAIN_DB * row.min(last) as f32 / last as f32
+}
@@ -1,11 +1,24 @@
 use std::path::PathBuf;
 
+/// What a file system entry is, which decides how it is shown and what Enter does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    /// An audio file split into tracks by a CUE sheet, browsed like a directory
+    Album,
+    /// A track of an album, played from its part of the album file
+    CueTrack,
+    /// A ZIP or tar archive, browsed like a directory
+    Archive,
+    File,
+}
+
 /// Represents a file system entry (file or directory)
 #[derive(Clone, Debug)]
 pub struct FSEntry {
     path: PathBuf,
     name: String,
-    is_dir: bool,
+    kind: EntryKind,
 }
 
 impl FSEntry {
@@ -15,8 +28,13 @@ impl FSEntry {
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
-        let is_dir = path.is_dir();
-        Self { path, name, is_dir }
+        let kind = if path.is_dir() { EntryKind::Directory } else { EntryKind::File };
+        Self { path, name, kind }
+    }
+
+    /// An entry shown as `name` rather than its file name
+    pub fn with_kind(path: PathBuf, name: String, kind: EntryKind) -> Self {
+        Self { path, name, kind }
     }
 
     pub fn path(&self) -> &PathBuf {
@@ -27,8 +45,17 @@ impl FSEntry {
         &self.name
     }
 
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
     pub fn is_dir(&self) -> bool {
-        self.is_dir
+        self.kind == EntryKind::Directory
+    }
+
+    /// Whether Enter opens the entry to list what is inside
+    pub fn is_browsable(&self) -> bool {
+        matches!(self.kind, EntryKind::Directory | EntryKind::Album | EntryKind::Archive)
     }
 }
 
@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
-use super::{FSEntry, FSState, FSAction};
+use crate::media::{archive, ArchiveKind};
+use crate::metadata::{cuesheet, MetadataManager};
+use crate::metadata::cuesheet::SidecarIndex;
+use super::{EntryKind, FSEntry, FSState, FSAction};
 
 /// Handles filesystem navigation and entry listing
 #[derive(Clone)]
@@ -20,17 +23,45 @@ impl FSNavigator {
         let mut entries = Vec::new();
         
         // Always try to add parent directory entry if it exists
-        if let Some(parent) = self.state.current_dir().parent() {
-            entries.push(FSEntry::new(parent.to_path_buf()));
+        if let Some(parent) = parent_dir(self.state.current_dir()) {
+            entries.push(parent);
+        }
+
+        // An archive, or a directory inside one, lists what the archive holds there
+        if let Some((archive, dir)) = archive_dir(self.state.current_dir()) {
+            let names = archive::list(&archive).context("Failed to read archive")?;
+            entries.extend(archive_entries(&archive, &dir, &names));
+            self.state.set_entries(entries);
+            return Ok(());
+        }
+
+        let metadata = MetadataManager::with_format_parsers();
+        // An album lists the tracks of its CUE sheet
+        if self.state.current_dir().is_file() {
+            let album = self.state.current_dir().clone();
+            let sheet = metadata.cue_sheet(&album).context("Album has no CUE sheet")?;
+            entries.extend(sheet.tracks.iter().map(|track| {
+                let path = cuesheet::track_path(&album, track.number).into();
+                FSEntry::with_kind(path, sheet.track_name(track.number), EntryKind::CueTrack)
+            }));
+            self.state.set_entries(entries);
+            return Ok(());
         }
 
         // Attempt to scan directory entries
         match std::fs::read_dir(self.state.current_dir()) {
             Ok(dir_entries) => {
+                let sidecars = SidecarIndex::read(self.state.current_dir());
                 // Process readable entries
                 for entry in dir_entries {
                     if let Ok(entry) = entry {
-                        entries.push(FSEntry::new(entry.path()));
+                        let mut entry = FSEntry::new(entry.path());
+                        if !entry.is_dir() && ArchiveKind::from_path(entry.path()).is_some() {
+                            entry = FSEntry::with_kind(entry.path().clone(), entry.name().to_string(), EntryKind::Archive);
+                        } else if !entry.is_dir() && metadata.supports_format(entry.path()) && metadata.cue_sheet_with(entry.path(), &sidecars).is_some() {
+                            entry = FSEntry::with_kind(entry.path().clone(), entry.name().to_string(), EntryKind::Album);
+                        }
+                        entries.push(entry);
                     }
                 }
 
@@ -62,8 +93,8 @@ impl FSNavigator {
         match action {
             FSAction::NavigateToParent => {
                 // Always attempt to navigate to parent if it exists
-                if let Some(parent) = self.state.current_dir().parent() {
-                    self.state.navigate_to(parent.to_path_buf());
+                if let Some(parent) = parent_dir(self.state.current_dir()) {
+                    self.state.navigate_to(parent.path().clone());
                     // Even if scanning fails, we've already updated the path
                     let _ = self.scan_current_dir();
                 }
@@ -71,7 +102,7 @@ impl FSNavigator {
             FSAction::NavigateToSelected => {
                 if let Some(index) = self.state.selected_index() {
                     if let Some(entry) = self.state.entries().get(index) {
-                        if entry.is_dir() {
+                        if entry.is_browsable() {
                             // Store the current directory in case we need to revert
                             let previous_dir = self.state.current_dir().clone();
                             
@@ -109,3 +140,119 @@ impl FSNavigator {
         &mut self.state
     }
 }
+
+/// Entry for the directory above `path`, which inside an archive may be the archive itself
+fn parent_dir(path: &Path) -> Option<FSEntry> {
+    let Some((archive, entry)) = archive::split_entry_path(path) else {
+        return path.parent().map(|parent| FSEntry::new(parent.to_path_buf()));
+    };
+    let parent = match entry.rsplit_once('/') {
+        Some((parent, _)) => {
+            let name = parent.rsplit('/').next().unwrap_or(parent).to_string();
+            FSEntry::with_kind(archive::entry_path(&archive, parent).into(), name, EntryKind::Directory)
+        }
+        None => FSEntry::with_kind(archive.clone(), archive.file_name()?.to_string_lossy().into_owned(), EntryKind::Archive),
+    };
+    Some(parent)
+}
+
+/// The archive and the directory in it that `path` browses, the archive's top level being ""
+fn archive_dir(path: &Path) -> Option<(PathBuf, String)> {
+    match archive::split_entry_path(path) {
+        Some(inside) => Some(inside),
+        None => (ArchiveKind::from_path(path).is_some() && path.is_file()).then(|| (path.to_path_buf(), String::new())),
+    }
+}
+
+/// Directories and files directly in `dir` of an archive holding files `names`, directories
+/// first; directories have no entries of their own in many archives, so come from the names
+fn archive_entries(archive: &Path, dir: &str, names: &[String]) -> Vec<FSEntry> {
+    let prefix = match dir.is_empty() {
+        true => String::new(),
+        false => format!("{}/", dir),
+    };
+    let (mut dirs, mut files) = (Vec::new(), Vec::new());
+    for rest in names.iter().filter_map(|name| name.strip_prefix(&prefix)) {
+        match rest.split_once('/') {
+            Some((sub, _)) if !dirs.contains(&sub) => dirs.push(sub),
+            Some(_) => {}
+            None => files.push(rest),
+        }
+    }
+    dirs.sort();
+    files.sort();
+
+    let entry = |name: &str, kind| {
+        let path = archive::entry_path(archive, &format!("{}{}", prefix, name));
+        FSEntry::with_kind(path.into(), name.to_string(), kind)
+    };
+    dirs.into_iter().map(|name| entry(name, EntryKind::Directory))
+        .chain(files.into_iter().map(|name| entry(name, EntryKind::File)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browse_album_tracks() {
+        let dir = tempfile::tempdir().unwrap();
+        let album = dir.path().join("album.flac");
+        std::fs::copy("test/testaudio-short.flac", &album).unwrap();
+        std::fs::copy("test/testaudio-short.flac", dir.path().join("single.flac")).unwrap();
+        std::fs::write(
+            dir.path().join("album.cue"),
+            "FILE \"album.flac\" WAVE\n  TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    INDEX 01 00:00:30\n",
+        ).unwrap();
+
+        let mut navigator = FSNavigator::new(dir.path().to_path_buf());
+        navigator.scan_current_dir().unwrap();
+        let kinds: Vec<_> = navigator.state().entries().iter().map(|entry| (entry.name().to_string(), entry.kind())).collect();
+        assert!(kinds.contains(&("album.flac".to_string(), EntryKind::Album)));
+        assert!(kinds.contains(&("single.flac".to_string(), EntryKind::File)));
+
+        // Entering the album lists its tracks after the way back out
+        let index = kinds.iter().position(|(name, _)| name == "album.flac").unwrap();
+        navigator.handle_action(FSAction::Select(index)).unwrap();
+        navigator.handle_action(FSAction::NavigateToSelected).unwrap();
+        let entries = navigator.state().entries();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir());
+        assert_eq!((entries[1].name(), entries[2].name()), ("01 Intro", "02 Track 2"));
+        assert_eq!(entries[2].kind(), EntryKind::CueTrack);
+        assert_eq!(entries[2].path(), &PathBuf::from(cuesheet::track_path(&album.canonicalize().unwrap(), 2)));
+    }
+
+    #[test]
+    fn test_browse_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip = dir.path().join("album.zip");
+        std::fs::copy("test/testaudio-album.zip", &zip).unwrap();
+        let zip = zip.canonicalize().unwrap();
+
+        let mut navigator = FSNavigator::new(dir.path().to_path_buf());
+        navigator.scan_current_dir().unwrap();
+        let index = navigator.state().entries().iter().position(|entry| entry.name() == "album.zip").unwrap();
+        assert_eq!(navigator.state().entries()[index].kind(), EntryKind::Archive);
+
+        // The archive's folders have no entries of their own, yet list before its files
+        navigator.handle_action(FSAction::Select(index)).unwrap();
+        navigator.handle_action(FSAction::NavigateToSelected).unwrap();
+        let names: Vec<_> = navigator.state().entries().iter().map(|entry| (entry.name().to_string(), entry.kind())).collect();
+        assert_eq!(&names[1..], [("Album".to_string(), EntryKind::Directory), ("notes.txt".to_string(), EntryKind::File)]);
+
+        navigator.handle_action(FSAction::Select(1)).unwrap();
+        navigator.handle_action(FSAction::NavigateToSelected).unwrap();
+        let entries = navigator.state().entries();
+        assert_eq!((entries[0].name(), entries[0].kind()), ("album.zip", EntryKind::Archive));
+        assert_eq!((entries[1].name(), entries[2].name()), ("Bonus", "01 Short.flac"));
+        assert_eq!(entries[2].path(), &PathBuf::from(archive::entry_path(&zip, "Album/01 Short.flac")));
+
+        // Back out through the archive to the directory holding it
+        navigator.handle_action(FSAction::NavigateToParent).unwrap();
+        assert_eq!(navigator.state().current_dir(), &zip);
+        navigator.handle_action(FSAction::NavigateToParent).unwrap();
+        assert_eq!(navigator.state().current_dir(), &dir.path().canonicalize().unwrap());
+    }
+}
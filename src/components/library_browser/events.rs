@@ -1,4 +1,4 @@
-use crate::events::{Event, Action, KeyEvent, NavigationEvent, EventHandler, EventResult, MouseEvent};
+use crate::events::{Event, Action, KeyEvent, MetadataAction, NavigationEvent, EventHandler, EventResult, MouseEvent, PlaylistAction};
 use super::state::LibraryBrowserState;
 
 pub fn process_event(state: &mut LibraryBrowserState, event: &Event) -> Option<Action> {
@@ -29,6 +29,9 @@ fn handle_key_event(state: &mut LibraryBrowserState, key_event: &KeyEvent) -> Op
             Some(Action::Refresh)
         },
         KeyEvent::Right | KeyEvent::Enter => {
+            if let Some(path) = state.selected_track() {
+                return Some(Action::Playlist(PlaylistAction::AddTrack(path)));
+            }
             if let Err(e) = state.navigate_to_selected() {
                 eprintln!("Error selecting entry: {}", e);
             }
@@ -40,6 +43,10 @@ fn handle_key_event(state: &mut LibraryBrowserState, key_event: &KeyEvent) -> Op
             }
             Some(Action::Refresh)
         },
+        KeyEvent::Char('l') => {
+            let dir = state.selected_directory();
+            Some(Action::Metadata(MetadataAction::ScanLoudness(dir.to_string_lossy().into_owned())))
+        },
         _ => None,
     }
 }
@@ -54,6 +61,7 @@ fn handle_mouse_event(state: &mut LibraryBrowserState, mouse_event: &MouseEvent)
             
             // Check if click is within valid range
             if clicked_index <= max_index {
+                let was_selected = state.get_selected_index() == Some(clicked_index);
                 // First select the clicked item
                 if let Err(e) = state.select_index(clicked_index) {
                     eprintln!("Error selecting item: {}", e);
@@ -62,6 +70,12 @@ fn handle_mouse_event(state: &mut LibraryBrowserState, mouse_event: &MouseEvent)
                 
                 // If clicking the same item that's already selected, treat as Enter key
                 if state.get_selected_index() == Some(clicked_index) {
+                    // Files need a second click, so that picking one out doesn't queue it
+                    match state.selected_track() {
+                        Some(path) if was_selected => return Some(Action::Playlist(PlaylistAction::AddTrack(path))),
+                        Some(_) => return Some(Action::Refresh),
+                        None => {}
+                    }
                     if let Err(e) = state.navigate_to_selected() {
                         eprintln!("Error navigating to selected: {}", e);
                     }
@@ -103,6 +117,7 @@ pub fn can_handle_event(state: &LibraryBrowserState, event: &Event) -> bool {
         Event::Key(KeyEvent::Right) |
         Event::Key(KeyEvent::Up) |
         Event::Key(KeyEvent::Down) |
+        Event::Key(KeyEvent::Char('l')) |
         Event::Mouse(_) |
         Event::Navigation(_) => state.focused(),
         
@@ -1,5 +1,7 @@
 use std::{path::PathBuf, cell::RefCell};
-use crate::components::filesystem::{FSNavigator, FSAction};
+use crate::audio::formats::detect_format;
+use crate::media::archive;
+use crate::components::filesystem::{EntryKind, FSNavigator, FSAction};
 use crate::components::ComponentState;
 
 #[derive(Clone)]
@@ -74,6 +76,38 @@ impl LibraryBrowserState {
         self.fs_navigator.borrow().state().entries().to_vec()
     }
 
+    /// Path to play for the selected entry, when it is an audio file or a track of an album
+    pub fn selected_track(&self) -> Option<String> {
+        let navigator = self.fs_navigator.borrow();
+        let state = navigator.state();
+        let entry = state.entries().get(state.selected_index()?)?;
+        let playable = match entry.kind() {
+            EntryKind::CueTrack => true,
+            EntryKind::File => detect_format(entry.path()).is_some(),
+            EntryKind::Directory | EntryKind::Album | EntryKind::Archive => false,
+        };
+        playable.then(|| entry.path().to_string_lossy().into_owned())
+    }
+
+    /// The selected directory, or the one being browsed when a file or the parent is selected
+    pub fn selected_directory(&self) -> PathBuf {
+        let navigator = self.fs_navigator.borrow();
+        let state = navigator.state();
+        // Inside an album or archive, the directory holding it
+        let browsed = archive::split_entry_path(state.current_dir())
+            .map(|(archive, _)| archive)
+            .unwrap_or_else(|| state.current_dir().clone());
+        let current = match browsed.is_file() {
+            true => browsed.parent().unwrap_or(&browsed),
+            false => &browsed,
+        };
+        state.selected_index()
+            .and_then(|index| state.entries().get(index))
+            .filter(|entry| entry.path().is_dir() && Some(entry.path().as_path()) != current.parent())
+            .map(|entry| entry.path().clone())
+            .unwrap_or_else(|| current.to_path_buf())
+    }
+
     pub fn get_selected_index(&self) -> Option<usize> {
         self.fs_navigator.borrow().state().selected_index()
     }
@@ -3,6 +3,7 @@ use ratatui::{
     widgets::{List, ListItem, ListState},
 };
 use crate::components::create_block;
+use crate::components::filesystem::EntryKind;
 use crate::theme::Theme;
 use super::state::LibraryBrowserState;
 
@@ -18,7 +19,13 @@ pub fn render(state: &LibraryBrowserState, frame: &mut Frame, area: Rect, focuse
         .iter()
         .enumerate()
         .map(|(index, entry)| {
-            let prefix = if entry.is_dir() { "📁 " } else { "📄 " };
+            let prefix = match entry.kind() {
+                EntryKind::Directory => "📁 ",
+                EntryKind::Album => "💿 ",
+                EntryKind::CueTrack => "🎵 ",
+                EntryKind::Archive => "📦 ",
+                EntryKind::File => "📄 ",
+            };
             let style = if Some(index) == selected {
                 if focused {
                     Style::default()
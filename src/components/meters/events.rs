@@ -0,0 +1,15 @@
+use crate::events::{Event, Action, KeyEvent, MouseEvent};
+use super::state::MeterState;
+
+pub fn handle_event(state: &mut MeterState, event: Event, focused: bool) -> Option<Action> {
+    if !focused {
+        return None;
+    }
+
+    match event {
+        Event::Key(KeyEvent::Enter) | Event::Mouse(MouseEvent::Click { .. }) => state.meter.reset_clip(),
+        Event::Key(KeyEvent::Left | KeyEvent::Right) | Event::Mouse(MouseEvent::Scroll { .. }) => state.next_ballistics(),
+        _ => return None,
+    }
+    Some(Action::Refresh)
+}
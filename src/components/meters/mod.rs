@@ -0,0 +1,67 @@
+use std::time::Instant;
+use crate::analysis::Ballistics;
+use crate::components::{Component, ComponentState};
+use crate::events::{Event, Action};
+
+mod state;
+mod events;
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+use state::MeterState;
+
+#[derive(Clone)]
+pub struct Meters {
+    component_state: ComponentState,
+    state: MeterState,
+}
+
+impl Meters {
+    /// Use the ballistics from preferences
+    pub fn configure(&mut self, ballistics: Ballistics) {
+        self.state.meter.set_ballistics(ballistics);
+    }
+
+    /// Show the interleaved samples played since the last call; call once per UI tick, even
+    /// without any, so the readings fall when playback stops
+    pub fn show_output(&mut self, samples: &[f32], channels: u16, sample_rate: u32) {
+        self.state.show_output(samples, channels, sample_rate, Instant::now());
+    }
+
+    /// Show the gain the engine applies on top of the track, in dB, or `None` when silent
+    pub fn show_gain(&mut self, gain_db: Option<f32>) {
+        self.state.gain_db = gain_db;
+    }
+}
+
+impl Component for Meters {
+    fn new() -> Self {
+        Self {
+            component_state: ComponentState::default(),
+            state: MeterState::default(),
+        }
+    }
+
+    fn render(&self, frame: &mut ratatui::prelude::Frame, area: ratatui::prelude::Rect, focused: bool, theme: &crate::theme::Theme) {
+        view::render(&self.state, frame, area, focused, theme);
+    }
+
+    fn update(&mut self, _action: Action) -> Option<Action> {
+        None
+    }
+
+    fn focused(&self) -> bool {
+        self.component_state.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.component_state.focused = focused;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
+        let is_focused = self.focused();
+        events::handle_event(&mut self.state, event, is_focused)
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+use crate::analysis::LevelMeter;
+
+#[derive(Clone, Default)]
+pub struct MeterState {
+    pub meter: LevelMeter,
+    /// Volume, ReplayGain and EQ preamp together, in dB; `None` when nothing is heard
+    pub gain_db: Option<f32>,
+    last_update: Option<Instant>,
+}
+
+impl MeterState {
+    pub fn next_ballistics(&mut self) {
+        self.meter.set_ballistics(self.meter.ballistics().next());
+    }
+
+    /// Take in the samples played since the last call, at `now`
+    pub fn show_output(&mut self, samples: &[f32], channels: u16, sample_rate: u32, now: Instant) {
+        let channels = channels.max(1) as usize;
+        if samples.len() >= channels {
+            self.meter.push(samples, channels, sample_rate);
+        } else {
+            let elapsed = self.last_update.map_or(Duration::ZERO, |last| now.duration_since(last));
+            self.meter.decay(elapsed);
+        }
+        self.last_update = Some(now);
+    }
+
+    /// Title text such as `Peak | Gain -6.5 dB`
+    pub fn label(&self) -> String {
+        let gain = match self.gain_db {
+            Some(db) => format!("{:+.1} dB", db),
+            None => "silent".to_string(),
+        };
+        format!("{} | Gain {}", self.meter.ballistics().label(), gain)
+    }
+}
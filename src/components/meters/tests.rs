@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+use super::*;
+use crate::events::{KeyEvent, MouseEvent};
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+/// Interleaved stereo 1kHz sine at 48kHz, with the right channel at half the amplitude
+fn sine(amplitude: f32, frames: usize) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|n| {
+            let sample = amplitude * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin();
+            [sample, sample / 2.0]
+        })
+        .collect()
+}
+
+fn draw(meters: &Meters, width: u16, height: u16) -> Vec<String> {
+    let theme = crate::theme::Theme::load_default().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| meters.render(frame, Rect::new(0, 0, width, height), false, &theme)).unwrap();
+    let buffer = terminal.backend().buffer().clone();
+    (0..height).map(|y| (0..width).map(|x| buffer.get(x, y).symbol.clone()).collect()).collect()
+}
+
+#[test]
+fn test_keys_switch_ballistics_and_reset_clip() {
+    let mut meters = Meters::new();
+    meters.state.show_output(&[1.0, 0.0], 2, 48000, Instant::now());
+    assert!(meters.state.meter.clipped());
+    assert_eq!(meters.handle_event(Event::Key(KeyEvent::Enter)), None);
+
+    meters.set_focused(true);
+    assert_eq!(meters.handle_event(Event::Key(KeyEvent::Enter)), Some(Action::Refresh));
+    assert!(!meters.state.meter.clipped());
+
+    meters.show_gain(Some(-6.54));
+    assert_eq!(meters.state.label(), "Peak | Gain -6.5 dB");
+    meters.handle_event(Event::Key(KeyEvent::Right));
+    meters.handle_event(Event::Mouse(MouseEvent::Scroll { delta: 1 }));
+    meters.show_gain(None);
+    assert_eq!(meters.state.label(), "PPM | Gain silent");
+    meters.handle_event(Event::Mouse(MouseEvent::Click { x: 0, y: 0 }));
+    assert_eq!(meters.state.meter.ballistics(), Ballistics::Ppm);
+}
+
+#[test]
+fn test_readings_fall_without_output() {
+    let mut meters = Meters::new();
+    let now = Instant::now();
+    meters.state.show_output(&sine(0.5, 4800), 2, 48000, now);
+    let playing = meters.state.meter.levels();
+    assert!((playing[0].level_db + 6.0).abs() < 0.2);
+
+    meters.state.show_output(&[], 2, 48000, now + Duration::from_millis(300));
+    let stopped = meters.state.meter.levels();
+    assert!(stopped[0].level_db < playing[0].level_db - 3.0);
+    assert!(stopped[0].rms_db < playing[0].rms_db);
+}
+
+#[test]
+fn test_render_bars_and_clip_light() {
+    let mut meters = Meters::new();
+    meters.show_gain(Some(0.0));
+    meters.state.show_output(&sine(1.0, 48000), 2, 48000, Instant::now());
+
+    let rows = draw(&meters, 40, 4);
+    assert!(rows[0].contains("Peak | Gain +0.0 dB"));
+    // 28 cells over 60dB: the left channel peaks at 0dB with its RMS 3dB down, the right 6dB lower
+    assert!(rows[1].starts_with("│L ") && rows[1].contains(&format!("{}▒ ", "█".repeat(27))));
+    assert!(rows[1].contains("  0.0 ●"));
+    assert!(rows[2].starts_with("│R ") && rows[2].contains(&format!("{}▒    ", "█".repeat(24))));
+    assert!(rows[2].contains(" -6.0 ○"));
+}
@@ -0,0 +1,87 @@
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use crate::analysis::meter::{ChannelLevel, FLOOR_DB};
+use crate::components::create_block;
+use crate::theme::Theme;
+use super::state::MeterState;
+
+/// Cells above this level take the hot colour
+const HOT_DB: f32 = -6.0;
+
+/// Columns around the bar: name and space before, space, reading, space and clip light after
+const LABEL_WIDTH: usize = 2;
+const READOUT_WIDTH: usize = 8;
+
+pub fn render(state: &MeterState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let title = state.label();
+    let block = create_block(title.as_str(), focused, theme);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    let levels = state.meter.levels();
+    let width = (inner.width as usize).saturating_sub(LABEL_WIDTH + READOUT_WIDTH);
+    let lines: Vec<Line> = levels
+        .iter()
+        .enumerate()
+        .take(inner.height as usize)
+        .map(|(index, level)| channel_line(channel_name(index, levels.len()), level, width, theme))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// `L ██████▒▒     -3.2 ●`: RMS solid, the ballistic reading shaded beyond it
+fn channel_line(name: String, level: &ChannelLevel, width: usize, theme: &Theme) -> Line<'static> {
+    let rms = cells(level.rms_db, width);
+    let reading = cells(level.level_db, width);
+    let mut spans = vec![Span::styled(format!("{} ", name), theme.get_style("text_normal"))];
+    spans.extend((0..width).map(|cell| {
+        let glyph = if cell < rms { "█" } else if cell < reading { "▒" } else { " " };
+        let style = match cell_level(cell + 1, width) > HOT_DB {
+            true => theme.get_style("visualizer_high"),
+            false => theme.get_style("visualizer_low"),
+        };
+        Span::styled(glyph, style)
+    }));
+
+    let readout = match level.level_db > FLOOR_DB {
+        // Rounded and added to zero so a reading just under full scale shows 0.0 rather than -0.0
+        true => format!("{:.1}", (level.level_db * 10.0).round() / 10.0 + 0.0),
+        false => "-inf".to_string(),
+    };
+    spans.push(Span::styled(format!(" {:>5} ", readout), theme.get_style("text_normal")));
+    spans.push(match level.clipped {
+        true => Span::styled("●", clip_style(theme)),
+        false => Span::styled("○", theme.get_style("text_dim")),
+    });
+    Line::from(spans)
+}
+
+/// Left and right for stereo, otherwise numbered from 1
+fn channel_name(index: usize, channels: usize) -> String {
+    match (channels, index) {
+        (1, _) => "M".to_string(),
+        (2, 0) => "L".to_string(),
+        (2, _) => "R".to_string(),
+        _ => ((index + 1) % 10).to_string(),
+    }
+}
+
+/// Cells lit by `level_db` on a bar `width` cells long
+fn cells(level_db: f32, width: usize) -> usize {
+    let fraction = ((level_db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+    (fraction * width as f32).round() as usize
+}
+
+/// Level at the end of the `cells`th cell
+fn cell_level(cells: usize, width: usize) -> f32 {
+    FLOOR_DB - FLOOR_DB * cells as f32 / width.max(1) as f32
+}
+
+fn clip_style(theme: &Theme) -> Style {
+    Style::default()
+        .fg(theme.get_color("error").unwrap_or(Color::Red))
+        .add_modifier(Modifier::BOLD)
+}
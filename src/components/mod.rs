@@ -11,6 +11,9 @@ pub mod library_browser;
 pub mod track_list;
 pub mod track_details;
 pub mod volume_control;
+pub mod equalizer;
+pub mod visualizer;
+pub mod meters;
 pub mod playlist;
 pub mod filesystem;
 
@@ -25,6 +28,9 @@ pub use library_browser::LibraryBrowser;
 pub use track_list::TrackList;
 pub use track_details::TrackDetails;
 pub use volume_control::VolumeControl;
+pub use equalizer::EqualizerControl;
+pub use visualizer::Visualizer;
+pub use meters::Meters;
 pub use playlist::Playlist;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -0,0 +1,93 @@
+use std::time::Duration;
+use crate::audio::speed::{PlaybackSpeed, SpeedMode};
+use crate::audio::SeekTarget;
+use crate::events::{Event, Action, KeyEvent, MouseEvent, PlayerAction};
+use super::state::{parse_time, StatusState, SEEK_STEP};
+use super::view::bar_area;
+
+pub fn handle_event(state: &mut StatusState, event: Event, focused: bool) -> Option<Action> {
+    // The seek hotkeys work whichever component has focus
+    match event {
+        Event::Key(KeyEvent::SeekBackward) => return seek(SeekTarget::Backward(SEEK_STEP)),
+        Event::Key(KeyEvent::SeekForward) => return seek(SeekTarget::Forward(SEEK_STEP)),
+        Event::Key(KeyEvent::SpeedDown) => return set_speed(state.speed.stepped(-1)),
+        Event::Key(KeyEvent::SpeedUp) => return set_speed(state.speed.stepped(1)),
+        _ => {}
+    }
+    if !focused {
+        return None;
+    }
+
+    match event {
+        Event::Key(key_event) => handle_key_event(state, key_event),
+        Event::Mouse(mouse_event) => handle_mouse_event(state, mouse_event),
+        _ => None,
+    }
+}
+
+fn handle_key_event(state: &mut StatusState, key_event: KeyEvent) -> Option<Action> {
+    match key_event {
+        KeyEvent::Left => seek(SeekTarget::Backward(SEEK_STEP)),
+        KeyEvent::Right => seek(SeekTarget::Forward(SEEK_STEP)),
+        KeyEvent::Home => seek(SeekTarget::Absolute(Duration::ZERO)),
+        KeyEvent::Up => set_speed(state.speed.stepped(1)),
+        KeyEvent::Down => set_speed(state.speed.stepped(-1)),
+        KeyEvent::Char('=') => set_speed(PlaybackSpeed { rate: 1.0, ..state.speed }),
+        KeyEvent::Char('v') => set_speed(PlaybackSpeed {
+            mode: match state.speed.mode {
+                SpeedMode::PreservePitch => SpeedMode::Varispeed,
+                SpeedMode::Varispeed => SpeedMode::PreservePitch,
+            },
+            ..state.speed
+        }),
+        // Typing a time and Enter seeks to it; typing a number and `%` seeks to that share
+        KeyEvent::Char(c) if c.is_ascii_digit() || c == ':' || c == '.' => {
+            state.entry.push(c);
+            None
+        }
+        KeyEvent::Enter => parse_time(&std::mem::take(&mut state.entry))
+            .and_then(|time| seek(SeekTarget::Absolute(time))),
+        KeyEvent::Char('%') => std::mem::take(&mut state.entry)
+            .parse()
+            .ok()
+            .and_then(|percent| seek(SeekTarget::Percent(percent))),
+        KeyEvent::Backspace => {
+            state.entry.pop();
+            None
+        }
+        KeyEvent::Escape => {
+            state.entry.clear();
+            None
+        }
+        _ => None,
+    }
+}
+
+fn handle_mouse_event(state: &StatusState, event: MouseEvent) -> Option<Action> {
+    match event {
+        // Clicking along the bar seeks to that share of the track
+        MouseEvent::Click { x, y } => {
+            let bar = bar_area(state.get_area()?);
+            let inside = x >= bar.x && x < bar.x + bar.width && y >= bar.y && y < bar.y + bar.height;
+            if !inside {
+                return None;
+            }
+            let percent = match bar.width {
+                1 => 0.0,
+                width => (x - bar.x) as f32 / (width - 1) as f32 * 100.0,
+            };
+            seek(SeekTarget::Percent(percent))
+        }
+        MouseEvent::Scroll { delta } => {
+            seek(if delta > 0 { SeekTarget::Backward(SEEK_STEP) } else { SeekTarget::Forward(SEEK_STEP) })
+        }
+    }
+}
+
+fn seek(target: SeekTarget) -> Option<Action> {
+    Some(Action::Player(PlayerAction::Seek(target)))
+}
+
+fn set_speed(speed: PlaybackSpeed) -> Option<Action> {
+    Some(Action::Player(PlayerAction::SetSpeed(speed)))
+}
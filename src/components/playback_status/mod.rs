@@ -0,0 +1,90 @@
+use std::time::Duration;
+use crate::audio::record::RecordingStatus;
+use crate::audio::speed::PlaybackSpeed;
+use ratatui::prelude::*;
+use crate::components::{Component, ComponentState};
+use crate::events::{Event, Action, PlayerAction};
+use crate::theme::Theme;
+
+mod state;
+mod events;
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+use state::{PlaybackState, StatusState};
+pub(crate) use state::format_time;
+
+#[derive(Clone)]
+pub struct PlaybackStatus {
+    component_state: ComponentState,
+    state: StatusState,
+}
+
+impl PlaybackStatus {
+    /// Show how far playback has got through the current track
+    pub fn show_progress(&mut self, position: Duration, duration: Option<Duration>) {
+        self.state.position = position;
+        self.state.duration = duration;
+    }
+
+    /// Show the rate the engine is playing at
+    pub fn show_speed(&mut self, speed: PlaybackSpeed) {
+        self.state.speed = speed;
+    }
+
+    /// Show how long the recording in progress has run and how big its file is
+    pub fn show_recording(&mut self, recording: Option<RecordingStatus>) {
+        self.state.recording = recording;
+    }
+}
+
+impl Component for PlaybackStatus {
+    fn new() -> Self {
+        Self {
+            component_state: ComponentState::default(),
+            state: StatusState::default(),
+        }
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+        view::render(&self.state, frame, area, focused, theme);
+    }
+
+    fn update(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::Play => {
+                self.state.playback_state = PlaybackState::Playing;
+                None
+            }
+            Action::Pause => {
+                self.state.playback_state = PlaybackState::Paused;
+                None
+            }
+            Action::Stop => {
+                self.state.playback_state = PlaybackState::Stopped;
+                None
+            }
+            // Keep stepping from the new rate before the app confirms it
+            Action::Player(PlayerAction::SetSpeed(speed)) => {
+                self.state.speed = speed;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn focused(&self) -> bool {
+        self.component_state.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.component_state.focused = focused;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
+        let is_focused = self.focused();
+        events::handle_event(&mut self.state, event, is_focused)
+    }
+}
@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::time::Duration;
+use ratatui::prelude::*;
+use crate::audio::record::RecordingStatus;
+use crate::audio::speed::{PlaybackSpeed, SpeedMode};
+
+/// How far the seek keys jump
+pub const SEEK_STEP: Duration = Duration::from_secs(10);
+
+#[derive(Clone, PartialEq)]
+pub enum PlaybackState {
+    Stopped,
+    Playing,
+    Paused,
+}
+
+#[derive(Clone)]
+pub struct StatusState {
+    pub playback_state: PlaybackState,
+    pub position: Duration,
+    pub duration: Option<Duration>,
+    pub speed: PlaybackSpeed,
+    /// The recording in progress, if any
+    pub recording: Option<RecordingStatus>,
+    /// Time or percentage being typed in to seek to
+    pub entry: String,
+    pub area: RefCell<Option<Rect>>,
+}
+
+impl Default for StatusState {
+    fn default() -> Self {
+        Self {
+            playback_state: PlaybackState::Stopped,
+            position: Duration::ZERO,
+            duration: None,
+            speed: PlaybackSpeed::default(),
+            recording: None,
+            entry: String::new(),
+            area: RefCell::new(None),
+        }
+    }
+}
+
+impl StatusState {
+    pub fn label(&self) -> String {
+        let status = match self.playback_state {
+            PlaybackState::Stopped => "Stopped",
+            PlaybackState::Playing => "Playing",
+            PlaybackState::Paused => "Paused",
+        };
+        let label = format!("Status: {} · {}", status, speed_label(self.speed));
+        match self.recording.as_ref() {
+            Some(recording) => format!("{} · ● REC {} {}", label, format_time(recording.elapsed), format_size(recording.bytes)),
+            None => label,
+        }
+    }
+
+    /// Elapsed and total time, or the seek target being typed in
+    pub fn progress_label(&self) -> String {
+        if !self.entry.is_empty() {
+            return format!("Seek to: {}_", self.entry);
+        }
+        match self.duration {
+            Some(duration) => format!("{} / {}", format_time(self.position), format_time(duration)),
+            None => format_time(self.position),
+        }
+    }
+
+    /// Share of the track played, from 0 to 1
+    pub fn ratio(&self) -> f64 {
+        match self.duration {
+            Some(duration) if !duration.is_zero() => (self.position.as_secs_f64() / duration.as_secs_f64()).min(1.0),
+            _ => 0.0,
+        }
+    }
+
+    pub fn set_area(&self, area: Rect) {
+        *self.area.borrow_mut() = Some(area);
+    }
+
+    pub fn get_area(&self) -> Option<Rect> {
+        *self.area.borrow()
+    }
+}
+
+/// The rate with as few decimals as it needs, such as `1.0×` or `1.25×`, and the mode if not
+/// keeping the pitch
+pub fn speed_label(speed: PlaybackSpeed) -> String {
+    let rate = format!("{:.2}", speed.rate);
+    let rate = rate.strip_suffix('0').unwrap_or(&rate);
+    match speed.mode {
+        SpeedMode::PreservePitch => format!("{}×", rate),
+        SpeedMode::Varispeed => format!("{}× varispeed", rate),
+    }
+}
+
+/// File size in kB, MB or GB, to a sensible precision
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=999_999 => format!("{} kB", bytes / 1000),
+        1_000_000..=999_999_999 => format!("{:.1} MB", bytes as f64 / 1e6),
+        _ => format!("{:.2} GB", bytes as f64 / 1e9),
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour up
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+/// Parse seconds, `m:ss` or `h:mm:ss`, with an optional fraction of a second
+pub fn parse_time(entry: &str) -> Option<Duration> {
+    let fields: Vec<&str> = entry.split(':').collect();
+    if fields.len() > 3 {
+        return None;
+    }
+    let (seconds, larger) = fields.split_last()?;
+    let mut total: f64 = seconds.parse().ok().filter(|s: &f64| s.is_finite())?;
+    for (unit, field) in larger.iter().rev().enumerate() {
+        let value: u64 = field.parse().ok()?;
+        total += value as f64 * 60f64.powi(unit as i32 + 1);
+    }
+    Some(Duration::from_secs_f64(total))
+}
@@ -0,0 +1,117 @@
+use super::*;
+use super::state::{format_size, format_time, parse_time, SEEK_STEP};
+use crate::audio::speed::{PlaybackSpeed, SpeedMode, MAX_SPEED};
+use crate::audio::SeekTarget;
+use crate::events::{KeyEvent, MouseEvent, PlayerAction};
+
+fn seek(target: SeekTarget) -> Option<Action> {
+    Some(Action::Player(PlayerAction::Seek(target)))
+}
+
+fn set_speed(rate: f32, mode: SpeedMode) -> Option<Action> {
+    Some(Action::Player(PlayerAction::SetSpeed(PlaybackSpeed { rate, mode })))
+}
+
+#[test]
+fn test_time_formatting() {
+    assert_eq!(format_time(Duration::from_millis(83_900)), "1:23");
+    assert_eq!(format_time(Duration::from_secs(3723)), "1:02:03");
+
+    assert_eq!(parse_time("90"), Some(Duration::from_secs(90)));
+    assert_eq!(parse_time("1:30.5"), Some(Duration::from_millis(90_500)));
+    assert_eq!(parse_time("1:02:03"), Some(Duration::from_secs(3723)));
+    assert_eq!(parse_time(""), None);
+    assert_eq!(parse_time("1::2"), None);
+    assert_eq!(parse_time("1:2:3:4"), None);
+}
+
+#[test]
+fn test_seek_keys() {
+    let mut status = PlaybackStatus::new();
+    // The hotkeys work without focus, the arrows only with it
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::SeekForward)), seek(SeekTarget::Forward(SEEK_STEP)));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Left)), None);
+
+    status.set_focused(true);
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Left)), seek(SeekTarget::Backward(SEEK_STEP)));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Home)), seek(SeekTarget::Absolute(Duration::ZERO)));
+}
+
+#[test]
+fn test_typed_seek_targets() {
+    let mut status = PlaybackStatus::new();
+    status.set_focused(true);
+    status.show_progress(Duration::from_secs(5), Some(Duration::from_secs(200)));
+    assert_eq!(status.state.progress_label(), "0:05 / 3:20");
+
+    for c in "1:25".chars() {
+        assert_eq!(status.handle_event(Event::Key(KeyEvent::Char(c))), None);
+    }
+    assert_eq!(status.state.progress_label(), "Seek to: 1:25_");
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Enter)), seek(SeekTarget::Absolute(Duration::from_secs(85))));
+    assert!(status.state.entry.is_empty());
+
+    status.handle_event(Event::Key(KeyEvent::Char('7')));
+    status.handle_event(Event::Key(KeyEvent::Char('5')));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Char('%'))), seek(SeekTarget::Percent(75.0)));
+
+    status.handle_event(Event::Key(KeyEvent::Char('3')));
+    status.handle_event(Event::Key(KeyEvent::Escape));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Enter)), None);
+}
+
+#[test]
+fn test_click_seeks_to_share() {
+    let mut status = PlaybackStatus::new();
+    status.set_focused(true);
+    // 101 columns inside the borders, one per percent
+    status.state.set_area(Rect::new(10, 5, 103, 4));
+
+    let click = |status: &mut PlaybackStatus, x, y| status.handle_event(Event::Mouse(MouseEvent::Click { x, y }));
+    assert_eq!(click(&mut status, 11, 6), seek(SeekTarget::Percent(0.0)));
+    assert_eq!(click(&mut status, 61, 7), seek(SeekTarget::Percent(50.0)));
+    assert_eq!(click(&mut status, 111, 6), seek(SeekTarget::Percent(100.0)));
+    // The border is not part of the bar
+    assert_eq!(click(&mut status, 10, 6), None);
+    assert_eq!(click(&mut status, 50, 5), None);
+}
+
+#[test]
+fn test_speed_keys() {
+    let mut status = PlaybackStatus::new();
+    assert_eq!(status.state.label(), "Status: Stopped · 1.0×");
+
+    // The speed hotkeys work without focus, stepping from the speed last set
+    let faster = status.handle_event(Event::Key(KeyEvent::SpeedUp));
+    assert_eq!(faster, set_speed(1.1, SpeedMode::PreservePitch));
+    status.update(faster.unwrap());
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::SpeedUp)), set_speed(1.2, SpeedMode::PreservePitch));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Up)), None);
+
+    status.set_focused(true);
+    status.show_speed(PlaybackSpeed { rate: MAX_SPEED, mode: SpeedMode::PreservePitch });
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Up)), set_speed(MAX_SPEED, SpeedMode::PreservePitch));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Down)), set_speed(2.9, SpeedMode::PreservePitch));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Char('v'))), set_speed(MAX_SPEED, SpeedMode::Varispeed));
+    assert_eq!(status.handle_event(Event::Key(KeyEvent::Char('='))), set_speed(1.0, SpeedMode::PreservePitch));
+
+    status.show_speed(PlaybackSpeed { rate: 1.25, mode: SpeedMode::Varispeed });
+    status.update(Action::Play);
+    assert_eq!(status.state.label(), "Status: Playing · 1.25× varispeed");
+}
+
+#[test]
+fn test_recording_shown() {
+    let mut status = PlaybackStatus::new();
+    status.show_recording(Some(RecordingStatus {
+        path: "take.flac".into(),
+        elapsed: Duration::from_secs(83),
+        bytes: 7_340_000,
+    }));
+    assert_eq!(status.state.label(), "Status: Stopped · 1.0× · ● REC 1:23 7.3 MB");
+
+    assert_eq!(format_size(512_000), "512 kB");
+    assert_eq!(format_size(2_500_000_000), "2.50 GB");
+    status.show_recording(None);
+    assert_eq!(status.state.label(), "Status: Stopped · 1.0×");
+}
@@ -0,0 +1,39 @@
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use crate::components::create_block;
+use crate::theme::Theme;
+use super::state::StatusState;
+
+pub fn render(state: &StatusState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let title = state.label();
+    let block = create_block(title.as_str(), focused, theme);
+
+    // Store the area for mouse interaction calculations
+    state.set_area(area);
+    frame.render_widget(block, area);
+
+    let bar = bar_area(area);
+    if bar.width == 0 || bar.height == 0 {
+        return;
+    }
+    let width = bar.width as usize;
+    let filled = ((state.ratio() * width as f64).round() as usize).min(width);
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("━".repeat(filled), theme.get_style("progress_bar")),
+            Span::styled("─".repeat(width - filled), theme.get_style("text_dim")),
+        ]),
+        Line::styled(state.progress_label(), theme.get_style("text_normal")),
+    ];
+    frame.render_widget(Paragraph::new(lines), bar);
+}
+
+/// The inside of the borders, where clicking seeks
+pub fn bar_area(area: Rect) -> Rect {
+    Rect {
+        x: area.x.saturating_add(1),
+        y: area.y.saturating_add(1),
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    }
+}
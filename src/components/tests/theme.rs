@@ -94,6 +94,9 @@ pub fn create_test_theme() -> Theme {
                 bg: None,
                 modifiers: None,
             },
+            visualizer_low: None,
+            visualizer_high: None,
+            visualizer_peak: None,
         },
     }
 }
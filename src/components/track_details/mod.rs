@@ -1,6 +1,8 @@
 use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use crate::audio::replaygain::{AppliedGain, GainSource};
 use crate::components::{Component, ComponentState, create_block};
-use crate::events::{Event, Action};
+use crate::events::{Event, Action, MetadataAction};
 use crate::theme::Theme;
 
 #[cfg(test)]
@@ -9,21 +11,41 @@ mod tests;
 #[derive(Clone)]
 pub struct TrackDetails {
     state: ComponentState,
+    /// Gain the engine applies to the current track
+    replay_gain: Option<AppliedGain>,
+    /// Latest word from a loudness scan
+    scan_status: Option<String>,
 }
 
 impl Component for TrackDetails {
     fn new() -> Self {
         Self {
             state: ComponentState::default(),
+            replay_gain: None,
+            scan_status: None,
         }
     }
 
     fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
         let block = create_block("Track Details", focused, theme);
+        let inner = block.inner(area);
         frame.render_widget(block, area);
+
+        let lines: Vec<Line> = self.replay_gain.map(|gain| replay_gain_label(&gain))
+            .into_iter()
+            .chain(self.scan_status.clone())
+            .map(Line::from)
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
     }
 
-    fn update(&mut self, _action: Action) -> Option<Action> {
+    fn update(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::Metadata(MetadataAction::ReplayGain(gain)) => self.replay_gain = gain,
+            Action::Metadata(MetadataAction::Clear) => self.replay_gain = None,
+            Action::Metadata(MetadataAction::LoudnessScan(progress)) => self.scan_status = Some(progress.to_string()),
+            _ => {}
+        }
         None
     }
 
@@ -39,3 +61,15 @@ impl Component for TrackDetails {
         None
     }
 }
+
+/// Describe an applied gain, e.g. `ReplayGain: -6.02 dB (album, peak-limited)`
+fn replay_gain_label(gain: &AppliedGain) -> String {
+    let source = match gain.source {
+        GainSource::Off => return "ReplayGain: off".to_string(),
+        GainSource::Track => "track",
+        GainSource::Album => "album",
+        GainSource::Fallback => "untagged",
+    };
+    let limited = if gain.peak_limited { ", peak-limited" } else { "" };
+    format!("ReplayGain: {:+.2} dB ({}{})", gain.db, source, limited)
+}
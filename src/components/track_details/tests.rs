@@ -1,4 +1,5 @@
 use super::*;
+use crate::analysis::ScanProgress;
 use crate::events::{Event, KeyEvent, Action, TrackMetadata, MetadataAction};
 use ratatui::{
     backend::TestBackend,
@@ -64,6 +65,34 @@ fn test_track_details_event_handling() {
     assert!(result.is_none(), "Should not handle any events currently");
 }
 
+#[test]
+fn test_track_details_shows_replay_gain() {
+    let mut details = TrackDetails::new();
+    details.update(Action::Metadata(MetadataAction::ReplayGain(Some(AppliedGain {
+        db: -6.02,
+        source: GainSource::Album,
+        peak_limited: true,
+    }))));
+
+    let theme = crate::theme::Theme::load_default().unwrap();
+    let area = Rect::new(0, 0, 60, 3);
+    let mut terminal = Terminal::new(TestBackend::new(60, 3)).unwrap();
+    let mut render = |details: &TrackDetails| {
+        terminal.draw(|frame| details.render(frame, area, false, &theme)).unwrap();
+        terminal.backend().buffer().content.iter().map(|cell| cell.symbol.clone()).collect::<String>()
+    };
+    assert!(render(&details).contains("ReplayGain: -6.02 dB (album, peak-limited)"));
+
+    details.update(Action::Metadata(MetadataAction::ReplayGain(Some(AppliedGain::UNITY))));
+    assert!(render(&details).contains("ReplayGain: off"));
+
+    details.update(Action::Metadata(MetadataAction::Clear));
+    assert!(!render(&details).contains("ReplayGain"));
+
+    details.update(Action::Metadata(MetadataAction::LoudnessScan(ScanProgress::Analysing { done: 2, total: 5 })));
+    assert!(render(&details).contains("Loudness scan: 2/5 files"));
+}
+
 // Tests for future functionality - marked as ignored until implemented
 #[test]
 #[ignore]
@@ -74,6 +103,7 @@ fn test_track_details_metadata_display() {
         artist: Some("Test Artist".to_string()),
         album: Some("Test Album".to_string()),
         duration: Some(180), // 3 minutes
+        chapters: Vec::new(),
     };
     
     // Future: Test updating metadata
@@ -133,6 +163,7 @@ fn test_track_details_partial_metadata() {
         artist: None,
         album: None,
         duration: Some(180),
+        chapters: Vec::new(),
     };
     
     // Future: Test updating with partial metadata
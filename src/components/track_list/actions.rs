@@ -34,6 +34,10 @@ pub fn handle_action(state: &mut TrackListState, action: Action) -> Option<Actio
             state.clear_selection();
             Some(Action::Refresh)
         }
+        Action::Playlist(PlaylistAction::ToggleShuffle) => {
+            state.shuffle = !state.shuffle;
+            Some(Action::Refresh)
+        }
         _ => None,
     }
 }
@@ -56,6 +60,7 @@ fn handle_key_event(state: &mut TrackListState, key_event: KeyEvent) -> Option<A
         },
         KeyEvent::Enter => Some(Action::Select),
         KeyEvent::Escape => Some(Action::Back),
+        KeyEvent::Char('z') | KeyEvent::Char('Z') => Some(Action::Playlist(PlaylistAction::ToggleShuffle)),
         _ => None,
     }
 }
@@ -5,6 +5,8 @@ pub struct TrackListState {
     pub state: ComponentState,
     pub tracks: Vec<String>,
     pub selected_index: Option<usize>,
+    /// Whether the playlist is playing in shuffled order
+    pub shuffle: bool,
 }
 
 impl Default for TrackListState {
@@ -13,6 +15,7 @@ impl Default for TrackListState {
             state: ComponentState::default(),
             tracks: Vec::new(),
             selected_index: None,
+            shuffle: false,
         }
     }
 }
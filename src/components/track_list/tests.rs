@@ -132,3 +132,16 @@ fn test_mouse_scroll() {
     assert_eq!(track_list.state.selected_index, Some(1));
     assert_eq!(result, Some(Action::Refresh));
 }
+
+#[test]
+fn test_track_list_toggles_shuffle() {
+    let mut track_list = setup_track_list();
+    track_list.set_focused(true);
+
+    let toggle = track_list.handle_event(Event::Key(KeyEvent::Char('z')));
+    assert_eq!(toggle, Some(Action::Playlist(PlaylistAction::ToggleShuffle)));
+    // The list shows shuffle once the app has acted on it
+    assert!(!track_list.state.shuffle);
+    track_list.update(toggle.unwrap());
+    assert!(track_list.state.shuffle);
+}
@@ -6,7 +6,8 @@ use crate::theme::Theme;
 use super::{create_block, state::TrackListState};
 
 pub fn render(state: &TrackListState, frame: &mut Frame, area: Rect, theme: &Theme) {
-    let block = create_block("Track List", state.focused(), theme);
+    let title = if state.shuffle { "Track List (Shuffle)" } else { "Track List" };
+    let block = create_block(title, state.focused(), theme);
     
     // Create a list of tracks
     let entries: Vec<ListItem> = state.tracks
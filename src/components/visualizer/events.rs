@@ -0,0 +1,16 @@
+use crate::events::{Event, Action, KeyEvent, MouseEvent};
+use super::state::VisualizerState;
+
+pub fn handle_event(state: &mut VisualizerState, event: Event, focused: bool) -> Option<Action> {
+    if !focused {
+        return None;
+    }
+
+    match event {
+        Event::Key(KeyEvent::Enter) | Event::Mouse(MouseEvent::Click { .. }) => state.next_mode(),
+        Event::Key(KeyEvent::Up) | Event::Mouse(MouseEvent::Scroll { delta: 1.. }) => state.step_bands(true),
+        Event::Key(KeyEvent::Down) | Event::Mouse(MouseEvent::Scroll { .. }) => state.step_bands(false),
+        _ => return None,
+    }
+    Some(Action::Refresh)
+}
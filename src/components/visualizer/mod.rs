@@ -0,0 +1,124 @@
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use crate::analysis::SpectrumSettings;
+use crate::components::{Component, ComponentState};
+use crate::events::{Event, Action};
+
+mod state;
+mod events;
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+use state::VisualizerState;
+
+/// What the visualizer draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VisualizerMode {
+    /// Levels of log-spaced frequency bands
+    #[default]
+    Spectrum,
+    /// The waveform of the latest samples
+    Oscilloscope,
+    /// Left against right, as a goniometer: mono is vertical, out of phase horizontal
+    Phase,
+}
+
+impl VisualizerMode {
+    pub fn next(self) -> Self {
+        match self {
+            VisualizerMode::Spectrum => VisualizerMode::Oscilloscope,
+            VisualizerMode::Oscilloscope => VisualizerMode::Phase,
+            VisualizerMode::Phase => VisualizerMode::Spectrum,
+        }
+    }
+}
+
+/// Pane of the layout the visualizer shares with the component already there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VisualizerPane {
+    LibraryBrowser,
+    TrackList,
+    #[default]
+    TrackDetails,
+    CurrentTrackInfo,
+    PlaybackStatus,
+    Equalizer,
+    Controls,
+    VolumeControl,
+    /// Not shown at all
+    Hidden,
+}
+
+impl VisualizerPane {
+    /// Name of the component the visualizer shares its pane with
+    pub fn component(self) -> Option<&'static str> {
+        Some(match self {
+            VisualizerPane::LibraryBrowser => "library_browser",
+            VisualizerPane::TrackList => "track_list",
+            VisualizerPane::TrackDetails => "track_details",
+            VisualizerPane::CurrentTrackInfo => "current_track_info",
+            VisualizerPane::PlaybackStatus => "playback_status",
+            VisualizerPane::Equalizer => "equalizer",
+            VisualizerPane::Controls => "controls",
+            VisualizerPane::VolumeControl => "volume_control",
+            VisualizerPane::Hidden => return None,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Visualizer {
+    component_state: ComponentState,
+    state: VisualizerState,
+}
+
+impl Visualizer {
+    /// Use the mode, bars and placement from preferences
+    pub fn configure(&mut self, mode: VisualizerMode, spectrum: SpectrumSettings, pane: VisualizerPane) {
+        self.state.configure(mode, spectrum, pane);
+    }
+
+    pub fn pane(&self) -> VisualizerPane {
+        self.state.pane
+    }
+
+    /// Show the interleaved samples played since the last call; call once per UI tick, even
+    /// without any, so the display settles when playback stops
+    pub fn show_output(&mut self, samples: &[f32], channels: u16, sample_rate: u32) {
+        self.state.show_output(samples, channels, sample_rate, Instant::now());
+    }
+}
+
+impl Component for Visualizer {
+    fn new() -> Self {
+        Self {
+            component_state: ComponentState::default(),
+            state: VisualizerState::default(),
+        }
+    }
+
+    fn render(&self, frame: &mut ratatui::prelude::Frame, area: ratatui::prelude::Rect, focused: bool, theme: &crate::theme::Theme) {
+        view::render(&self.state, frame, area, focused, theme);
+    }
+
+    fn update(&mut self, _action: Action) -> Option<Action> {
+        None
+    }
+
+    fn focused(&self) -> bool {
+        self.component_state.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.component_state.focused = focused;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
+        let is_focused = self.focused();
+        events::handle_event(&mut self.state, event, is_focused)
+    }
+}
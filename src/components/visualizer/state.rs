@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::analysis::{SpectrumAnalyzer, SpectrumSettings};
+use super::{VisualizerMode, VisualizerPane};
+
+/// Frames kept for the oscilloscope and goniometer; the scope shows half, from a zero crossing
+pub const SCOPE_FRAMES: usize = 2048;
+
+/// Bars added or removed per key press
+pub const BAND_STEP: usize = 4;
+
+/// Without new samples for this long the scope and goniometer are cleared
+const IDLE: Duration = Duration::from_millis(150);
+
+#[derive(Clone)]
+pub struct VisualizerState {
+    pub mode: VisualizerMode,
+    pub pane: VisualizerPane,
+    pub analyzer: SpectrumAnalyzer,
+    /// The latest interleaved frames, newest last
+    pub recent: VecDeque<f32>,
+    pub channels: usize,
+    pub sample_rate: u32,
+    last_update: Option<Instant>,
+    last_samples: Option<Instant>,
+}
+
+impl Default for VisualizerState {
+    fn default() -> Self {
+        Self {
+            mode: VisualizerMode::default(),
+            pane: VisualizerPane::default(),
+            analyzer: SpectrumAnalyzer::new(SpectrumSettings::default()),
+            recent: VecDeque::new(),
+            channels: 2,
+            sample_rate: 44100,
+            last_update: None,
+            last_samples: None,
+        }
+    }
+}
+
+impl VisualizerState {
+    pub fn configure(&mut self, mode: VisualizerMode, spectrum: SpectrumSettings, pane: VisualizerPane) {
+        self.mode = mode;
+        self.pane = pane;
+        self.analyzer = SpectrumAnalyzer::new(spectrum);
+    }
+
+    pub fn next_mode(&mut self) {
+        self.mode = self.mode.next();
+    }
+
+    /// Add or remove `BAND_STEP` bars
+    pub fn step_bands(&mut self, more: bool) {
+        let bands = self.analyzer.settings().bands;
+        self.analyzer.set_bands(if more { bands + BAND_STEP } else { bands.saturating_sub(BAND_STEP) });
+    }
+
+    /// Take in the samples played since the last call, at `now`
+    pub fn show_output(&mut self, samples: &[f32], channels: u16, sample_rate: u32, now: Instant) {
+        let channels = channels.max(1) as usize;
+        if channels != self.channels {
+            self.recent.clear();
+            self.channels = channels;
+        }
+        if sample_rate > 0 {
+            self.sample_rate = sample_rate;
+        }
+
+        if samples.len() >= channels {
+            let keep = SCOPE_FRAMES * channels;
+            let start = (samples.len() / channels).saturating_sub(SCOPE_FRAMES) * channels;
+            self.recent.extend(&samples[start..samples.len() / channels * channels]);
+            let excess = self.recent.len().saturating_sub(keep);
+            self.recent.drain(..excess);
+            self.analyzer.push(samples, channels);
+            self.last_samples = Some(now);
+        } else if self.last_samples.is_none_or(|last| now.duration_since(last) >= IDLE) {
+            self.recent.clear();
+        }
+
+        let elapsed = self.last_update.map_or(Duration::ZERO, |last| now.duration_since(last));
+        self.analyzer.update(self.sample_rate, elapsed);
+        self.last_update = Some(now);
+    }
+
+    /// `points` of the mono waveform, starting where it last rose through zero so the display
+    /// holds still on steady tones
+    pub fn scope(&self, points: usize) -> Vec<f32> {
+        let mono: Vec<f32> = self.frames().map(|(left, right)| (left + right) / 2.0).collect();
+        let shown = (SCOPE_FRAMES / 2).min(mono.len());
+        let search = mono.len() - shown;
+        let start = (1..=search)
+            .rev()
+            .find(|&i| mono[i - 1] < 0.0 && mono[i] >= 0.0)
+            .unwrap_or(search);
+        let window = &mono[start..start + shown];
+        if window.is_empty() {
+            return Vec::new();
+        }
+        (0..points)
+            .map(|point| window[point * window.len() / points.max(1)])
+            .collect()
+    }
+
+    /// Each frame as a goniometer point: side across, leaning left for the left channel, and mid up
+    pub fn phase(&self) -> Vec<(f32, f32)> {
+        self.frames()
+            .map(|(left, right)| ((right - left) / 2f32.sqrt(), (left + right) / 2f32.sqrt()))
+            .collect()
+    }
+
+    /// Correlation of left and right from -1 (out of phase) through 0 (unrelated) to +1 (mono)
+    pub fn correlation(&self) -> f32 {
+        let (mut product, mut left_energy, mut right_energy) = (0f32, 0f32, 0f32);
+        for (left, right) in self.frames() {
+            product += left * right;
+            left_energy += left * left;
+            right_energy += right * right;
+        }
+        let energy = (left_energy * right_energy).sqrt();
+        if energy > 0.0 { product / energy } else { 0.0 }
+    }
+
+    /// Title text such as `Spectrum (24 bands)` or `Phase +0.85`
+    pub fn label(&self) -> String {
+        match self.mode {
+            VisualizerMode::Spectrum => format!("Spectrum ({} bands)", self.analyzer.settings().bands),
+            VisualizerMode::Oscilloscope => "Oscilloscope".to_string(),
+            VisualizerMode::Phase => format!("Phase {:+.2}", self.correlation()),
+        }
+    }
+
+    /// Left and right of each recent frame; mono plays the same on both, and further channels are left out
+    fn frames(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let channels = self.channels;
+        (0..self.recent.len() / channels).map(move |frame| {
+            let left = self.recent[frame * channels];
+            let right = if channels > 1 { self.recent[frame * channels + 1] } else { left };
+            (left, right)
+        })
+    }
+}
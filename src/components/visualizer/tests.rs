@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+use super::*;
+use crate::events::{KeyEvent, MouseEvent};
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+/// Interleaved stereo 1kHz sine at 48kHz, with the right channel scaled by `right`
+fn sine(frames: usize, right: f32) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|n| {
+            let sample = 0.8 * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin();
+            [sample, right * sample]
+        })
+        .collect()
+}
+
+#[test]
+fn test_keys_switch_mode_and_bands() {
+    let mut visualizer = Visualizer::new();
+    assert_eq!(visualizer.handle_event(Event::Key(KeyEvent::Enter)), None);
+
+    visualizer.set_focused(true);
+    assert_eq!(visualizer.handle_event(Event::Key(KeyEvent::Enter)), Some(Action::Refresh));
+    assert_eq!(visualizer.state.mode, VisualizerMode::Oscilloscope);
+    visualizer.handle_event(Event::Mouse(MouseEvent::Click { x: 0, y: 0 }));
+    assert_eq!(visualizer.state.label(), "Phase +0.00");
+    visualizer.handle_event(Event::Key(KeyEvent::Enter));
+    assert_eq!(visualizer.state.label(), "Spectrum (24 bands)");
+
+    visualizer.handle_event(Event::Key(KeyEvent::Up));
+    assert_eq!(visualizer.state.analyzer.levels().len(), 28);
+    for _ in 0..10 {
+        visualizer.handle_event(Event::Mouse(MouseEvent::Scroll { delta: -1 }));
+    }
+    assert_eq!(visualizer.state.analyzer.levels().len(), crate::analysis::spectrum::MIN_BANDS);
+}
+
+#[test]
+fn test_scope_and_phase_follow_output() {
+    let mut visualizer = Visualizer::new();
+    let now = Instant::now();
+    visualizer.state.show_output(&sine(4096, 1.0), 2, 48000, now);
+
+    // The scope starts on a rising zero crossing, so steady tones hold still
+    let scope = visualizer.state.scope(64);
+    assert_eq!(scope.len(), 64);
+    assert!(scope[0].abs() < 0.1 && scope[1] > scope[0]);
+    assert!((visualizer.state.correlation() - 1.0).abs() < 1e-4);
+    assert!(visualizer.state.phase().iter().all(|(side, _)| side.abs() < 1e-6));
+
+    visualizer.state.show_output(&sine(4096, -1.0), 2, 48000, now + Duration::from_millis(20));
+    assert!((visualizer.state.correlation() + 1.0).abs() < 1e-4);
+    assert_eq!(visualizer.state.recent.len(), state::SCOPE_FRAMES * 2);
+
+    // Once the output goes quiet the traces clear
+    visualizer.state.show_output(&[], 2, 48000, now + Duration::from_millis(500));
+    assert!(visualizer.state.scope(64).is_empty());
+    assert_eq!(visualizer.state.correlation(), 0.0);
+}
+
+#[test]
+fn test_render_modes() {
+    let mut visualizer = Visualizer::new();
+    visualizer.configure(VisualizerMode::Spectrum, SpectrumSettings { bands: 8, ..Default::default() }, VisualizerPane::Controls);
+    assert_eq!(visualizer.pane(), VisualizerPane::Controls);
+    visualizer.state.show_output(&sine(4096, 1.0), 2, 48000, Instant::now());
+
+    let theme = crate::theme::Theme::load_default().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(34, 12)).unwrap();
+    let mut draw = |visualizer: &Visualizer| {
+        terminal.draw(|frame| visualizer.render(frame, Rect::new(0, 0, 34, 12), false, &theme)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        (0..12).map(|y| (0..34).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>()).collect::<Vec<_>>()
+    };
+
+    let rows = draw(&visualizer);
+    assert!(rows[0].contains("Spectrum (8 bands)"));
+    // Only the band holding 1kHz lights up, nearly to the top, three columns wide and a gap
+    assert!(rows[1].contains(" ▅▅▅ "));
+    assert!(rows[2..11].iter().all(|row| row.matches('█').count() == 3 && row.contains("███ ")));
+
+    visualizer.state.next_mode();
+    let rows = draw(&visualizer);
+    assert!(rows[0].contains("Oscilloscope"));
+    assert!(rows[1..11].iter().any(|row| row.chars().any(|c| ('\u{2801}'..='\u{28ff}').contains(&c))));
+
+    visualizer.state.next_mode();
+    let rows = draw(&visualizer);
+    assert!(rows[0].contains("Phase +1.00"));
+}
@@ -0,0 +1,122 @@
+use ratatui::prelude::*;
+use ratatui::symbols::Marker;
+use ratatui::widgets::canvas::{Canvas, Line as CanvasLine, Points};
+use ratatui::widgets::Paragraph;
+use crate::analysis::spectrum::FLOOR_DB;
+use crate::components::create_block;
+use crate::theme::Theme;
+use super::state::VisualizerState;
+use super::VisualizerMode;
+
+/// Bar tops in eighths of a cell
+const EIGHTHS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+/// Rows above this level take the hot colour
+const HOT_DB: f32 = -12.0;
+
+pub fn render(state: &VisualizerState, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    let title = state.label();
+    let block = create_block(title.as_str(), focused, theme);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    match state.mode {
+        VisualizerMode::Spectrum => render_spectrum(state, frame, inner, theme),
+        VisualizerMode::Oscilloscope => render_scope(state, frame, inner, theme),
+        VisualizerMode::Phase => render_phase(state, frame, inner, theme),
+    }
+}
+
+fn render_spectrum(state: &VisualizerState, frame: &mut Frame, inner: Rect, theme: &Theme) {
+    let levels = state.analyzer.levels();
+    let peaks = state.analyzer.peaks();
+    let width = bar_width(inner.width, levels.len());
+    // A column between bars once they are wide enough to spare one
+    let filled = if width > 2 { width - 1 } else { width };
+    let height = inner.height as usize;
+
+    let lines: Vec<Line> = (0..height)
+        .map(|row| {
+            let from_bottom = height - 1 - row;
+            let style = match row_level(from_bottom + 1, height) > HOT_DB {
+                true => theme.get_style("visualizer_high"),
+                false => theme.get_style("visualizer_low"),
+            };
+            Line::from(levels.iter().zip(peaks).flat_map(|(&level, &peak)| {
+                let fill = eighths(level, height).saturating_sub(from_bottom * 8).min(8);
+                let peak_row = eighths(peak, height).saturating_sub(1) / 8;
+                let (glyph, style) = match fill == 0 && peak_row == from_bottom && peak > FLOOR_DB {
+                    true => ("▔", theme.get_style("visualizer_peak")),
+                    false => (EIGHTHS[fill], style),
+                };
+                [
+                    Span::styled(glyph.repeat(filled), style),
+                    Span::raw(" ".repeat(width - filled)),
+                ]
+            }).collect::<Vec<_>>())
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_scope(state: &VisualizerState, frame: &mut Frame, inner: Rect, theme: &Theme) {
+    // Two Braille dots across each cell
+    let points = inner.width as usize * 2;
+    let samples = state.scope(points);
+    let color = style_color(theme, "visualizer_low");
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .x_bounds([0.0, points.saturating_sub(1).max(1) as f64])
+        .y_bounds([-1.0, 1.0])
+        .paint(|ctx| {
+            for (x, pair) in samples.windows(2).enumerate() {
+                let (from, to) = (pair[0].clamp(-1.0, 1.0) as f64, pair[1].clamp(-1.0, 1.0) as f64);
+                ctx.draw(&CanvasLine::new(x as f64, from, x as f64 + 1.0, to, color));
+            }
+        });
+    frame.render_widget(canvas, inner);
+}
+
+fn render_phase(state: &VisualizerState, frame: &mut Frame, inner: Rect, theme: &Theme) {
+    let coords: Vec<(f64, f64)> = state.phase()
+        .into_iter()
+        .map(|(side, mid)| (side.clamp(-1.0, 1.0) as f64, mid.clamp(-1.0, 1.0) as f64))
+        .collect();
+    let axes = style_color(theme, "text_dim");
+    let color = style_color(theme, "visualizer_low");
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .x_bounds([-1.0, 1.0])
+        .y_bounds([-1.0, 1.0])
+        .paint(|ctx| {
+            // The left and right channel diagonals
+            ctx.draw(&CanvasLine::new(-1.0, -1.0, 1.0, 1.0, axes));
+            ctx.draw(&CanvasLine::new(-1.0, 1.0, 1.0, -1.0, axes));
+            ctx.layer();
+            ctx.draw(&Points { coords: &coords, color });
+        });
+    frame.render_widget(canvas, inner);
+}
+
+/// Columns given to each bar, gap included
+pub fn bar_width(width: u16, bands: usize) -> usize {
+    (width as usize / bands.max(1)).max(1)
+}
+
+/// Height of a bar at `level_db` in eighths of a row, over `height` rows
+fn eighths(level_db: f32, height: usize) -> usize {
+    let fraction = ((level_db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+    (fraction * (height * 8) as f32).round() as usize
+}
+
+/// Level at the top of the `rows`th row from the bottom
+fn row_level(rows: usize, height: usize) -> f32 {
+    FLOOR_DB - FLOOR_DB * rows as f32 / height.max(1) as f32
+}
+
+fn style_color(theme: &Theme, style: &str) -> Color {
+    theme.get_style(style).fg.unwrap_or(Color::Reset)
+}
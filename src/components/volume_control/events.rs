@@ -2,6 +2,13 @@ use crate::events::{Event, Action, KeyEvent, MouseEvent};
 use super::state::VolumeState;
 
 pub fn handle_event(state: &mut VolumeState, event: Event, focused: bool) -> Option<Action> {
+    // The volume hotkeys work whichever component has focus
+    match event {
+        Event::Key(KeyEvent::VolumeUp) => return Some(Action::VolumeUp),
+        Event::Key(KeyEvent::VolumeDown) => return Some(Action::VolumeDown),
+        Event::Key(KeyEvent::Mute) => return Some(Action::ToggleMute),
+        _ => {}
+    }
     if !focused {
         return None;
     }
@@ -1,5 +1,6 @@
 use crate::components::{Component, ComponentState};
 use crate::events::{Event, Action};
+use crate::audio::volume::Volume;
 
 mod state;
 mod events;
@@ -24,6 +25,17 @@ impl Clone for VolumeControl {
     }
 }
 
+impl VolumeControl {
+    /// Show the engine's volume, as restored from preferences
+    pub fn show_volume(&mut self, volume: Volume) {
+        self.state.volume = volume;
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.state.get_volume()
+    }
+}
+
 impl Component for VolumeControl {
     fn new() -> Self {
         Self {
@@ -50,6 +62,7 @@ impl Component for VolumeControl {
                 self.state.set_volume(vol);
                 None
             }
+            Action::ToggleMute => Some(Action::SetMuted(self.state.toggle_mute())),
             _ => None,
         }
     }
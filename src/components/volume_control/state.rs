@@ -1,16 +1,17 @@
 use std::cell::RefCell;
 use ratatui::prelude::*;
+use crate::audio::volume::Volume;
 
 #[derive(Clone)]
 pub struct VolumeState {
-    pub volume: u8,
+    pub volume: Volume,
     pub area: RefCell<Option<Rect>>,
 }
 
 impl Default for VolumeState {
     fn default() -> Self {
         Self {
-            volume: 50, // Default volume 50%
+            volume: Volume::new(50), // Default volume 50%
             area: RefCell::new(None),
         }
     }
@@ -18,25 +19,35 @@ impl Default for VolumeState {
 
 impl VolumeState {
     pub fn increase_volume(&mut self) -> u8 {
-        if self.volume < 100 {
-            self.volume = self.volume.saturating_add(5);
-        }
-        self.volume
+        self.volume.set_level(self.volume.level().saturating_add(5));
+        self.volume.level()
     }
 
     pub fn decrease_volume(&mut self) -> u8 {
-        if self.volume > 0 {
-            self.volume = self.volume.saturating_sub(5);
-        }
-        self.volume
+        self.volume.set_level(self.volume.level().saturating_sub(5));
+        self.volume.level()
     }
 
     pub fn set_volume(&mut self, vol: u8) {
-        self.volume = vol.min(100);
+        self.volume.set_level(vol);
     }
 
     pub fn get_volume(&self) -> u8 {
-        self.volume
+        self.volume.level()
+    }
+
+    pub fn toggle_mute(&mut self) -> bool {
+        self.volume.toggle_mute()
+    }
+
+    /// Title text such as `Volume: 50% (-25.3 dB)`
+    pub fn label(&self) -> String {
+        let level = match self.volume.db() {
+            Some(db) => format!("{:.1} dB", db),
+            None if self.volume.is_muted() => "muted".to_string(),
+            None => "silent".to_string(),
+        };
+        format!("Volume: {}% ({})", self.volume.level(), level)
     }
 
     pub fn set_area(&self, area: Rect) {
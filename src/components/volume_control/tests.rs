@@ -82,3 +82,33 @@ fn test_unfocused_control() {
     let result = control.handle_event(Event::Key(KeyEvent::Up));
     assert_eq!(result, None, "Unfocused control should not handle events");
 }
+
+#[test]
+fn test_mute_toggle() {
+    let mut control = VolumeControl::new();
+    control.update(Action::SetVolume(70));
+
+    let result = control.handle_event(Event::Key(KeyEvent::Mute));
+    assert_eq!(result, Some(Action::ToggleMute), "Mute hotkey works without focus");
+    assert_eq!(control.update(Action::ToggleMute), Some(Action::SetMuted(true)));
+    assert_eq!(control.state.label(), "Volume: 70% (muted)");
+
+    assert_eq!(control.update(Action::ToggleMute), Some(Action::SetMuted(false)));
+    assert_eq!(control.state.get_volume(), 70, "Unmuting returns to the previous level");
+
+    // Changing the volume unmutes
+    control.update(Action::ToggleMute);
+    control.update(Action::VolumeDown);
+    assert!(!control.state.volume.is_muted());
+}
+
+#[test]
+fn test_label_shows_decibels() {
+    let mut control = VolumeControl::new();
+    control.update(Action::SetVolume(100));
+    assert_eq!(control.state.label(), "Volume: 100% (0.0 dB)");
+    control.update(Action::SetVolume(1));
+    assert_eq!(control.state.label(), "Volume: 1% (-50.0 dB)");
+    control.update(Action::SetVolume(0));
+    assert_eq!(control.state.label(), "Volume: 0% (silent)");
+}
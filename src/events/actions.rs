@@ -1,5 +1,12 @@
 use super::types::FocusDirection;
 use super::KeyEvent;
+use crate::analysis::ScanProgress;
+use crate::audio::dsp::ProcessorStage;
+use crate::audio::eq::EqPreset;
+use crate::audio::replaygain::AppliedGain;
+use crate::audio::speed::PlaybackSpeed;
+use crate::audio::SeekTarget;
+use crate::metadata::{Chapter, Metadata};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
@@ -24,12 +31,15 @@ pub enum Action {
     VolumeUp,
     VolumeDown,
     SetVolume(u8),
+    ToggleMute,
+    SetMuted(bool),
     
     // Player state actions
     Player(PlayerAction),
     Playlist(PlaylistAction),
     UI(UIAction),
     Metadata(MetadataAction),
+    Equalizer(EqualizerAction),
     App(AppAction),
 }
 
@@ -40,6 +50,16 @@ pub enum PlayerAction {
     Stop,
     SetVolume(u8),
     LoadTrack(String),
+    Seek(SeekTarget),
+    SetSpeed(PlaybackSpeed),
+    /// Seek to the start of the next chapter of the current track
+    NextChapter,
+    /// Restart the current chapter, or go back to the one before just after it starts
+    PreviousChapter,
+    /// Replace the effects applied to everything played, in order
+    SetProcessorChain(Vec<ProcessorStage>),
+    /// Skip or restore the stage at `index` of the processor chain
+    BypassProcessor { index: usize, bypassed: bool },
     // New player actions
     Record,
     FastForward,
@@ -53,6 +73,8 @@ pub enum PlaylistAction {
     AddTrack(String),
     RemoveTrack(usize),
     Clear,
+    /// Play the playlist in random order, or go back to the order tracks were added in
+    ToggleShuffle,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -66,9 +88,22 @@ pub enum UIAction {
 pub enum MetadataAction {
     Load(String),
     Update(TrackMetadata),
+    /// Gain the engine applies to the current track
+    ReplayGain(Option<AppliedGain>),
+    /// Measure the loudness of every track below a directory and tag them with ReplayGain
+    ScanLoudness(String),
+    LoudnessScan(ScanProgress),
     Clear,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum EqualizerAction {
+    /// Have the engine use `preset`, or bypass the EQ when not `enabled`
+    Apply { enabled: bool, preset: EqPreset },
+    /// Keep a preset among the user presets in preferences
+    SavePreset(EqPreset),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppAction {
     Quit,
@@ -77,10 +112,23 @@ pub enum AppAction {
     NoOp,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct TrackMetadata {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
     pub duration: Option<u64>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl From<&Metadata> for TrackMetadata {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            title: metadata.title.clone(),
+            artist: metadata.artist.clone(),
+            album: metadata.album.clone(),
+            duration: metadata.duration.map(|seconds| seconds.round() as u64),
+            chapters: metadata.chapters.clone(),
+        }
+    }
 }
@@ -15,7 +15,7 @@ pub use error::{EventError, EventResult};
 pub use types::{Event, KeyEvent, MouseEvent, SystemEvent, FocusDirection, NavigationEvent};
 pub use actions::{
     Action, PlayerAction, PlaylistAction, UIAction, MetadataAction, 
-    AppAction, TrackMetadata
+    EqualizerAction, AppAction, TrackMetadata
 };
 pub use handler::{EventHandler, EventDispatcher};
 pub use filter::EventFilter;
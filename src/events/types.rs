@@ -48,6 +48,13 @@ pub enum KeyEvent {
     Previous,     // Direct previous track
     VolumeUp,     // Direct volume up
     VolumeDown,   // Direct volume down
+    Mute,         // Direct mute toggle
+    SeekBackward, // Jump back a few seconds
+    SeekForward,  // Jump ahead a few seconds
+    SpeedDown,    // Play slower
+    SpeedUp,      // Play faster
+    PreviousChapter, // Restart the chapter, or go back to the one before
+    NextChapter,  // Skip to the next chapter
     Record,       // Direct record control
     FastForward,  // Direct fast forward
     Rewind,       // Direct rewind
@@ -73,6 +80,13 @@ impl From<KeyCode> for KeyEvent {
                 'b' | 'B' => KeyEvent::Previous,
                 '+' => KeyEvent::VolumeUp,
                 '-' => KeyEvent::VolumeDown,
+                'm' | 'M' => KeyEvent::Mute,
+                '<' => KeyEvent::SeekBackward,
+                '>' => KeyEvent::SeekForward,
+                '{' => KeyEvent::SpeedDown,
+                '}' => KeyEvent::SpeedUp,
+                '[' => KeyEvent::PreviousChapter,
+                ']' => KeyEvent::NextChapter,
                 'u' | 'U' => KeyEvent::Pause,
                 'r' | 'R' => KeyEvent::Record,
                 'f' | 'F' => KeyEvent::FastForward,
@@ -1,3 +1,4 @@
+pub mod analysis;
 pub mod app;
 pub mod audio;
 pub mod components;
@@ -8,6 +9,7 @@ pub mod state;
 pub mod theme;
 pub mod ui;
 pub mod logger;
+pub mod media;
 
 use crate::metadata::{MetadataManager, FileMetadataCache};
 use std::time::Duration;
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent,
@@ -10,10 +10,12 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 use std::io::{self, BufWriter};
+use std::path::Path;
 use std::fs::{OpenOptions, File};
 use std::io::Write;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use playtui::analysis::{LoudnessScanner, ScanProgress};
 use playtui::app::App;
 use playtui::events::{Event, KeyEvent, MouseEvent};
 
@@ -30,7 +32,51 @@ fn log_raw_event(writer: &mut BufWriter<File>, event: &CrosstermEvent) -> std::i
     Ok(())
 }
 
+/// `playtui scan-loudness [--dry-run] <dir>`: measure every track below `dir` and tag it with ReplayGain
+fn scan_loudness(args: &[String]) -> Result<()> {
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let Some(dir) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        bail!("usage: playtui scan-loudness [--dry-run] <dir>");
+    };
+
+    let scanner = if dry_run { LoudnessScanner::new().dry_run() } else { LoudnessScanner::new() };
+    let report = scanner.scan(Path::new(dir), |progress| {
+        if let ScanProgress::Analysing { done, total } = progress {
+            eprint!("\rAnalysing {}/{}", done + 1, total);
+        }
+    });
+    eprintln!();
+
+    let lufs = |value: Option<f64>| value.map_or("silent".to_string(), |lufs| format!("{:.1} LUFS", lufs));
+    for track in &report.tracks {
+        let loudness = &track.loudness;
+        println!(
+            "{}: {}, LRA {:.1} LU, peak {:.1} dBTP",
+            track.path.display(), lufs(loudness.integrated), loudness.range, 20.0 * loudness.true_peak.log10()
+        );
+    }
+    for album in &report.albums {
+        let loudness = &album.loudness;
+        println!(
+            "Album {} ({}): {}, LRA {:.1} LU, peak {:.1} dBTP",
+            album.album, album.album_artist, lufs(loudness.integrated), loudness.range, 20.0 * loudness.true_peak.log10()
+        );
+    }
+    for (path, error) in &report.failed {
+        eprintln!("{}: {}", path.display(), error);
+    }
+    if !report.failed.is_empty() {
+        bail!("{} files could not be measured or tagged", report.failed.len());
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("scan-loudness") {
+        return scan_loudness(&args[1..]);
+    }
+
     // Create logs directory if it doesn't exist
     std::fs::create_dir_all("logs")?;
 
@@ -75,6 +121,12 @@ fn main() -> Result<()> {
 
     // Main loop
     loop {
+        // Pick up track and state changes from the playback engine
+        if let Err(e) = app.poll_player_events() {
+            eprintln!("Error handling player event: {}", e);
+        }
+        app.poll_loudness_scan();
+
         // Render UI
         terminal.draw(|frame| playtui::ui::render(frame, &mut app))?;
 
@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use flate2::read::{DeflateDecoder, GzDecoder};
+use xz2::read::XzDecoder;
+use zip::{CompressionMethod, ZipArchive};
+use super::{MediaStream, RestartReader, SliceReader};
+
+/// Separates an archive from the path of a file inside it, as in `album.zip!/01.flac`
+pub const ENTRY_SEPARATOR: &str = "!/";
+
+/// Archive formats the library can browse into
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+}
+
+impl ArchiveKind {
+    /// Kind of archive a file is, going by its name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let kind = if name.ends_with(".zip") {
+            Self::Zip
+        } else if name.ends_with(".tar") {
+            Self::Tar
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::TarGz
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Self::TarXz
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+}
+
+/// Path that names `entry` inside `archive`
+pub fn entry_path(archive: &Path, entry: &str) -> String {
+    format!("{}{}{}", archive.display(), ENTRY_SEPARATOR, entry)
+}
+
+/// The archive and the file or directory inside it that a path names
+pub fn split_entry_path(path: &Path) -> Option<(PathBuf, String)> {
+    let path = path.to_str()?;
+    path.match_indices(ENTRY_SEPARATOR)
+        .map(|(index, _)| (&path[..index], &path[index + ENTRY_SEPARATOR.len()..]))
+        .find(|(archive, _)| ArchiveKind::from_path(Path::new(archive)).is_some())
+        .map(|(archive, entry)| (PathBuf::from(archive), entry.trim_end_matches('/').to_string()))
+}
+
+/// Paths of the files in an archive, in the order it stores them
+pub fn list(archive: &Path) -> io::Result<Vec<String>> {
+    let kind = ArchiveKind::from_path(archive).ok_or_else(not_an_archive)?;
+    if kind == ArchiveKind::Zip {
+        let zip = ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+        return Ok(zip.file_names()
+            .filter_map(Result::ok)
+            .filter(|name| !name.ends_with('/'))
+            .map(|name| clean_name(&name))
+            .collect());
+    }
+
+    let mut names = Vec::new();
+    let mut tar = tar::Archive::new(decompress(archive, kind)?);
+    for entry in tar.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            names.push(clean_name(&entry.path()?.to_string_lossy()));
+        }
+    }
+    Ok(names)
+}
+
+/// Open a file inside an archive. Stored files are read straight from the archive; packed
+/// ones are unpacked as they are read, starting over when reading goes back
+pub fn open(archive: &Path, entry: &str) -> io::Result<MediaStream> {
+    match ArchiveKind::from_path(archive).ok_or_else(not_an_archive)? {
+        ArchiveKind::Zip => open_zip(archive, entry),
+        ArchiveKind::Tar => {
+            let mut tar = tar::Archive::new(File::open(archive)?);
+            let (offset, len) = tar.entries_with_seek()?
+                .filter_map(Result::ok)
+                .filter(|file| file.header().entry_type().is_file())
+                .find(|file| file.path().is_ok_and(|path| clean_name(&path.to_string_lossy()) == entry))
+                .map(|file| (file.raw_file_position(), file.size()))
+                .ok_or_else(|| not_found(entry))?;
+            Ok(MediaStream::new(SliceReader::new(File::open(archive)?, offset, len)?, Some(len)))
+        }
+        kind => {
+            let (reader, len) = open_packed_tar(archive, kind, entry)?;
+            let (archive, entry) = (archive.to_path_buf(), entry.to_string());
+            let restart = move || open_packed_tar(&archive, kind, &entry).map(|(reader, _)| reader);
+            Ok(MediaStream::new(RestartReader::new(reader, len, restart), Some(len)))
+        }
+    }
+}
+
+fn open_zip(archive: &Path, entry: &str) -> io::Result<MediaStream> {
+    let mut zip = ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+    let index = (0..zip.len())
+        .find(|&index| zip.name_for_index(index).and_then(Result::ok).is_some_and(|name| clean_name(&name) == entry))
+        .ok_or_else(|| not_found(entry))?;
+    let file = zip.by_index_raw(index).map_err(io::Error::other)?;
+    let start = file.data_start().ok_or_else(|| not_found(entry))?;
+    let (packed, len, method) = (file.compressed_size(), file.size(), file.compression());
+    if file.encrypted() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "Encrypted ZIP entries are not supported"));
+    }
+
+    match method {
+        CompressionMethod::Stored => Ok(MediaStream::new(SliceReader::new(File::open(archive)?, start, len)?, Some(len))),
+        CompressionMethod::DEFLATE => {
+            let archive = archive.to_path_buf();
+            let inflate = move || -> io::Result<_> {
+                Ok(DeflateDecoder::new(SliceReader::new(File::open(&archive)?, start, packed)?).take(len))
+            };
+            Ok(MediaStream::new(RestartReader::new(inflate()?, len, inflate), Some(len)))
+        }
+        method => Err(io::Error::new(io::ErrorKind::Unsupported, format!("ZIP compression {:?} is not supported", method))),
+    }
+}
+
+/// Reader positioned at the start of `entry` in a compressed tar, and the entry's length
+fn open_packed_tar(archive: &Path, kind: ArchiveKind, entry: &str) -> io::Result<(io::Take<Box<dyn Read + Send + Sync>>, u64)> {
+    let mut tar = tar::Archive::new(decompress(archive, kind)?);
+    let mut len = None;
+    for file in tar.entries()? {
+        let file = file?;
+        if file.header().entry_type().is_file() && clean_name(&file.path()?.to_string_lossy()) == entry {
+            len = Some(file.size());
+            break;
+        }
+    }
+    let len = len.ok_or_else(|| not_found(entry))?;
+    // The entry's header has been read, which leaves the stream at its contents
+    Ok((tar.into_inner().take(len), len))
+}
+
+fn decompress(archive: &Path, kind: ArchiveKind) -> io::Result<Box<dyn Read + Send + Sync>> {
+    let file = File::open(archive)?;
+    Ok(match kind {
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+        ArchiveKind::TarXz => Box::new(XzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+/// Entry name without the `./` or `/` some archivers start it with
+fn clean_name(name: &str) -> String {
+    let mut name = name;
+    while let Some(rest) = name.strip_prefix("./").or_else(|| name.strip_prefix('/')) {
+        name = rest;
+    }
+    name.to_string()
+}
+
+fn not_an_archive() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Not a ZIP or tar archive")
+}
+
+fn not_found(entry: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("No {} in the archive", entry))
+}
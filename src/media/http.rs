@@ -0,0 +1,231 @@
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Redirects followed before giving up
+const MAX_REDIRECTS: usize = 5;
+/// How far ahead a seek reads through the open response rather than asking for a new range
+const SKIP_AHEAD: u64 = 64 * 1024;
+/// How long to wait on the server before failing the read
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Reads a file over plain HTTP, asking the server for the rest of it from wherever
+/// reading continues; seeking elsewhere drops the response and the next read asks again
+pub struct HttpReader {
+    url: String,
+    len: Option<u64>,
+    pos: u64,
+    /// Body of the current response, positioned at `pos`
+    body: Option<io::Take<BufReader<TcpStream>>>,
+}
+
+impl HttpReader {
+    /// Connect to `url` and start reading from the beginning
+    pub fn open(url: &str) -> io::Result<Self> {
+        let mut reader = Self {
+            url: url.to_string(),
+            len: None,
+            pos: 0,
+            body: None,
+        };
+        reader.request()?;
+        Ok(reader)
+    }
+
+    /// Length of the file in bytes, when the server says
+    pub fn byte_len(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Ask for the file from `pos` on, following redirects
+    fn request(&mut self) -> io::Result<()> {
+        for _ in 0..=MAX_REDIRECTS {
+            let (authority, host, port, target) = split_url(&self.url)?;
+            let mut stream = connect(&host, port)?;
+            write!(
+                stream,
+                "GET {} HTTP/1.1\r\nHost: {}\r\nRange: bytes={}-\r\nUser-Agent: playtui\r\nConnection: close\r\n\r\n",
+                target, authority, self.pos
+            )?;
+
+            let mut reader = BufReader::new(stream);
+            let head = Head::read(&mut reader)?;
+            if head.header("transfer-encoding").is_some_and(|coding| coding.eq_ignore_ascii_case("chunked")) {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "Chunked HTTP responses are not supported"));
+            }
+            let content_length = head.header("content-length").and_then(|len| len.parse().ok());
+
+            match head.status {
+                301 | 302 | 303 | 307 | 308 => {
+                    let location = head.header("location").ok_or_else(|| invalid("Redirect without a location"))?;
+                    self.url = match location.starts_with('/') {
+                        true => format!("http://{}{}", authority, location),
+                        false => location.to_string(),
+                    };
+                    continue;
+                }
+                206 => {
+                    self.len = head.total_len().or(self.len);
+                    self.body = Some(reader.take(content_length.unwrap_or(u64::MAX)));
+                }
+                // The server ignores ranges and sends it all, so read up to the position
+                200 => {
+                    self.len = content_length.or(self.len);
+                    let mut body = reader.take(content_length.unwrap_or(u64::MAX));
+                    let skipped = io::copy(&mut body.by_ref().take(self.pos), &mut io::sink())?;
+                    if skipped < self.pos {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Response ended before the position asked for"));
+                    }
+                    self.body = Some(body);
+                }
+                // Asked for nothing but the end of the file
+                416 => {
+                    self.len = head.total_len().or(self.len);
+                    self.body = Some(reader.take(0));
+                }
+                status => return Err(io::Error::other(format!("HTTP {} from {}", status, self.url))),
+            }
+            return Ok(());
+        }
+        Err(io::Error::other(format!("Too many redirects from {}", self.url)))
+    }
+
+    /// Read on from the current response, asking for one if there is none
+    fn read_body(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.body.is_none() {
+            self.request()?;
+        }
+        match self.body.as_mut() {
+            Some(body) => body.read(buf),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Read for HttpReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.len.is_some_and(|len| self.pos >= len) {
+            return Ok(0);
+        }
+        let mut read = self.read_body(buf)?;
+        // The connection may drop partway; pick up again where it stopped
+        if read == 0 && self.len.is_some_and(|len| self.pos < len) {
+            self.body = None;
+            read = self.read_body(buf)?;
+        }
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for HttpReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => {
+                let len = self.len.ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "Length of the file is unknown"))?;
+                len.checked_add_signed(offset)
+            }
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        let target = target.ok_or_else(|| invalid("Seek before the start of the file"))?;
+        if target == self.pos {
+            return Ok(target);
+        }
+
+        match self.body.as_mut() {
+            Some(body) if target > self.pos && target - self.pos <= SKIP_AHEAD => {
+                let wanted = target - self.pos;
+                if io::copy(&mut body.by_ref().take(wanted), &mut io::sink())? < wanted {
+                    self.body = None;
+                }
+            }
+            _ => self.body = None,
+        }
+        self.pos = target;
+        Ok(target)
+    }
+}
+
+/// Status and headers of a response, header names in lower case
+struct Head {
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl Head {
+    fn read(reader: &mut impl BufRead) -> io::Result<Self> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let status = line.split_whitespace().nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| invalid("Not an HTTP response"))?;
+
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        Ok(Self { status, headers })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Length of the whole file from `Content-Range: bytes 0-99/1234`
+    fn total_len(&self) -> Option<u64> {
+        self.header("content-range")?.rsplit_once('/')?.1.parse().ok()
+    }
+}
+
+/// Authority, host, port and request target of an `http://` URL
+fn split_url(url: &str) -> io::Result<(String, String, u16, String)> {
+    let rest = match url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None if url.starts_with("https://") => {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "HTTPS is not supported"));
+        }
+        None => return Err(invalid("Not an http:// URL")),
+    };
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (authority, target) = match rest.find(['/', '?']) {
+        Some(index) if rest[index..].starts_with('?') => (&rest[..index], format!("/{}", &rest[index..])),
+        Some(index) => (&rest[..index], rest[index..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            (host, port.parse().map_err(|_| invalid("Bad port"))?)
+        }
+        _ => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(invalid("URL without a host"));
+    }
+    Ok((authority.to_string(), host.trim_matches(['[', ']']).to_string(), port, target))
+}
+
+fn connect(host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut last_error = invalid("Host has no addresses");
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
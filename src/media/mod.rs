@@ -0,0 +1,157 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub mod archive;
+mod http;
+mod restart;
+mod slice;
+#[cfg(test)]
+mod tests;
+
+pub use archive::ArchiveKind;
+pub use http::HttpReader;
+pub use restart::RestartReader;
+pub use slice::SliceReader;
+
+/// Where the bytes of a track come from, for decoders and metadata parsers alike
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaSource {
+    /// A file on disk
+    File(PathBuf),
+    /// Bytes held in memory, with a name standing in for the file name
+    Memory { name: String, data: Arc<[u8]> },
+    /// A file stored uncompressed in an archive, `len` bytes from `offset`
+    ArchiveEntry { archive: PathBuf, entry: String, offset: u64, len: u64 },
+    /// A file in a ZIP or tar archive, found and unpacked when opened
+    InArchive { archive: PathBuf, entry: String },
+    /// A file served over HTTP, fetched in ranges as it is read
+    Http(String),
+}
+
+impl MediaSource {
+    /// Source a path from the library or a playlist stands for; `http://` URLs are
+    /// fetched from the network, and `album.zip!/01.flac` is a file inside an archive
+    pub fn from_path(path: &Path) -> Self {
+        if let Some((archive, entry)) = archive::split_entry_path(path) {
+            return Self::InArchive { archive, entry };
+        }
+        match path.to_str() {
+            Some(url) if url.starts_with("http://") => Self::Http(url.to_string()),
+            _ => Self::File(path.to_path_buf()),
+        }
+    }
+
+    /// Bytes in memory under the given file name
+    pub fn memory(name: &str, data: impl Into<Arc<[u8]>>) -> Self {
+        Self::Memory { name: name.to_string(), data: data.into() }
+    }
+
+    /// The file on disk, if the source is a plain file
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Name the source would have as a file, for telling its format by extension
+    pub fn file_name(&self) -> &Path {
+        match self {
+            Self::File(path) => path,
+            Self::Memory { name, .. } => Path::new(name),
+            Self::ArchiveEntry { entry, .. } | Self::InArchive { entry, .. } => Path::new(entry),
+            Self::Http(url) => Path::new(url.split(['?', '#']).next().unwrap_or(url)),
+        }
+    }
+
+    /// Extension of the file name, as written
+    pub fn extension(&self) -> Option<&str> {
+        self.file_name().extension()?.to_str()
+    }
+
+    /// Open the bytes for reading from the start
+    pub fn open(&self) -> io::Result<MediaStream> {
+        match self {
+            Self::File(path) => {
+                let file = File::open(path)?;
+                let len = file.metadata()?.len();
+                Ok(MediaStream::new(file, Some(len)))
+            }
+            Self::Memory { data, .. } => Ok(MediaStream::new(Cursor::new(Arc::clone(data)), Some(data.len() as u64))),
+            Self::ArchiveEntry { archive, offset, len, .. } => {
+                Ok(MediaStream::new(SliceReader::new(File::open(archive)?, *offset, *len)?, Some(*len)))
+            }
+            Self::InArchive { archive, entry } => archive::open(archive, entry),
+            Self::Http(url) => {
+                let reader = HttpReader::open(url)?;
+                let len = reader.byte_len();
+                Ok(MediaStream::new(reader, len))
+            }
+        }
+    }
+}
+
+impl From<&Path> for MediaSource {
+    fn from(path: &Path) -> Self {
+        Self::from_path(path)
+    }
+}
+
+impl fmt::Display for MediaSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Memory { name, .. } => write!(f, "{}", name),
+            Self::ArchiveEntry { archive, entry, .. } | Self::InArchive { archive, entry } => {
+                write!(f, "{}", archive::entry_path(archive, entry))
+            }
+            Self::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+trait ReadSeek: Read + Seek + Send + Sync {}
+
+impl<T: Read + Seek + Send + Sync> ReadSeek for T {}
+
+/// Opened bytes of a `MediaSource`, readable by symphonia as well as our own parsers
+pub struct MediaStream {
+    reader: Box<dyn ReadSeek>,
+    len: Option<u64>,
+}
+
+impl MediaStream {
+    fn new(reader: impl Read + Seek + Send + Sync + 'static, len: Option<u64>) -> Self {
+        Self { reader: Box::new(reader), len }
+    }
+
+    /// Length in bytes, when known
+    pub fn byte_len(&self) -> Option<u64> {
+        self.len
+    }
+}
+
+impl Read for MediaStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Seek for MediaStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+impl symphonia::core::io::MediaSource for MediaStream {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.len
+    }
+}
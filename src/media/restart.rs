@@ -0,0 +1,49 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Makes a stream that can only be read forwards seekable: seeks ahead read through to the
+/// target, and seeks back start the stream over from the beginning
+pub struct RestartReader<R> {
+    reader: R,
+    restart: Box<dyn Fn() -> io::Result<R> + Send + Sync>,
+    len: u64,
+    pos: u64,
+}
+
+impl<R: Read> RestartReader<R> {
+    /// Read `len` bytes from `reader`, which `restart` opens again from the start
+    pub fn new(reader: R, len: u64, restart: impl Fn() -> io::Result<R> + Send + Sync + 'static) -> Self {
+        Self { reader, restart: Box::new(restart), len, pos: 0 }
+    }
+}
+
+impl<R: Read> Read for RestartReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read> Seek for RestartReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        let target = target.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start of the stream"))?;
+
+        if target < self.pos {
+            self.reader = (self.restart)()?;
+            self.pos = 0;
+        }
+        // Past the end reads nothing, as with files
+        let wanted = target.min(self.len).saturating_sub(self.pos);
+        let skipped = io::copy(&mut self.reader.by_ref().take(wanted), &mut io::sink())?;
+        if skipped < wanted {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Stream ended before its length"));
+        }
+        self.pos = target;
+        Ok(target)
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Reads `len` bytes of a larger stream from `start`, as if they were a stream of their own
+pub struct SliceReader<R> {
+    inner: R,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl<R: Read + Seek> SliceReader<R> {
+    pub fn new(mut inner: R, start: u64, len: u64) -> io::Result<Self> {
+        inner.seek(SeekFrom::Start(start))?;
+        Ok(Self { inner, start, len, pos: 0 })
+    }
+}
+
+impl<R: Read + Seek> Read for SliceReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.len.saturating_sub(self.pos);
+        let count = (buf.len() as u64).min(left) as usize;
+        if count == 0 {
+            return Ok(0);
+        }
+        let read = self.inner.read(&mut buf[..count])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for SliceReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        let target = target.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start of the entry"))?;
+        // Past the end reads nothing, as with files
+        self.inner.seek(SeekFrom::Start(self.start + target.min(self.len)))?;
+        self.pos = target;
+        Ok(target)
+    }
+}
@@ -0,0 +1,244 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use super::*;
+use crate::audio::formats::{open_source, open_track, AudioDecoder, DecoderType};
+use crate::metadata::MetadataManager;
+
+/// First `count` samples a source decodes to, after seeking to `from`
+fn samples(source: &MediaSource, from: u64, count: usize) -> Vec<f32> {
+    let mut reader = open_source(source).unwrap();
+    reader.seek(from).unwrap();
+    let mut buffer = vec![0.0; count];
+    let mut filled = 0;
+    while filled < count {
+        match reader.read(&mut buffer[filled..]).unwrap() {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    buffer.truncate(filled);
+    buffer
+}
+
+/// Serve `data` on a local port, honouring `Range` and sending `/old` on to `/track.flac`
+fn serve(data: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let (mut target, mut from) = (String::new(), 0);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                if let Some(request) = line.strip_prefix("GET ") {
+                    target = request.split(' ').next().unwrap().to_string();
+                }
+                if let Some(range) = line.strip_prefix("Range: bytes=") {
+                    from = range.trim().trim_end_matches('-').parse().unwrap();
+                }
+                line.clear();
+            }
+
+            let len = data.len();
+            let _ = if target == "/old" {
+                write!(stream, "HTTP/1.1 302 Found\r\nLocation: /track.flac\r\nContent-Length: 0\r\n\r\n")
+            } else if from >= len {
+                write!(stream, "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\n\r\n", len)
+            } else {
+                write!(stream, "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n\r\n", from, len - 1, len, len - from)
+                    .and_then(|_| stream.write_all(&data[from..]))
+            };
+        }
+    });
+    format!("http://{}", address)
+}
+
+#[test]
+fn test_memory_source() {
+    let source = MediaSource::memory("clip.WAV", b"0123456789".to_vec());
+    assert_eq!(source.extension(), Some("WAV"));
+    assert_eq!(source.path(), None);
+    assert_eq!(source.to_string(), "clip.WAV");
+
+    let mut stream = source.open().unwrap();
+    assert_eq!(stream.byte_len(), Some(10));
+    stream.seek(SeekFrom::End(-3)).unwrap();
+    let mut rest = String::new();
+    stream.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "789");
+}
+
+#[test]
+fn test_from_path() {
+    let file = MediaSource::from_path(Path::new("music/a.flac"));
+    assert_eq!(file.path(), Some(Path::new("music/a.flac")));
+
+    let url = MediaSource::from_path(Path::new("http://radio.example/live/a.ogg?session=1"));
+    assert_eq!(url, MediaSource::Http("http://radio.example/live/a.ogg?session=1".to_string()));
+    assert_eq!(url.extension(), Some("ogg"));
+}
+
+#[test]
+fn test_slice_reader() {
+    let mut slice = SliceReader::new(Cursor::new(b"headerBODYtrailer".to_vec()), 6, 4).unwrap();
+    let mut body = String::new();
+    slice.read_to_string(&mut body).unwrap();
+    assert_eq!(body, "BODY");
+
+    assert_eq!(slice.seek(SeekFrom::Current(-2)).unwrap(), 2);
+    let mut tail = [0; 8];
+    assert_eq!(slice.read(&mut tail).unwrap(), 2);
+    assert_eq!(&tail[..2], b"DY");
+    assert_eq!(slice.seek(SeekFrom::Start(10)).unwrap(), 10);
+    assert_eq!(slice.read(&mut tail).unwrap(), 0);
+    assert!(slice.seek(SeekFrom::End(-5)).is_err());
+}
+
+#[test]
+fn test_decoders_read_memory() {
+    let manager = MetadataManager::with_format_parsers();
+    for name in ["testaudio-short.mp3", "testaudio-short.ogg", "testaudio-short.flac", "testaudio-short.aiff", "testaudio-short-alac.m4a"] {
+        let file = MediaSource::from_path(&Path::new("test").join(name));
+        let memory = MediaSource::memory(name, std::fs::read(file.path().unwrap()).unwrap());
+
+        let decoder = DecoderType::for_source(&memory);
+        assert_eq!(decoder.probe_source(&memory).unwrap().duration, decoder.probe_source(&file).unwrap().duration, "{}", name);
+        assert_eq!(samples(&memory, 1000, 512), samples(&file, 1000, 512), "{}", name);
+
+        let (metadata, expected) = (manager.parse_source(&memory).unwrap(), manager.parse_source(&file).unwrap());
+        assert_eq!(metadata.title, expected.title, "{}", name);
+        assert_eq!(metadata.duration, expected.duration, "{}", name);
+        assert_eq!(metadata.bit_rate, expected.bit_rate, "{}", name);
+    }
+}
+
+#[test]
+fn test_archive_entry_decodes_like_file() {
+    let wav = std::fs::read("test/testaudio-short.wav").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("bundle.bin");
+    std::fs::write(&archive, [vec![0xAA; 100], wav.clone(), vec![0x55; 50]].concat()).unwrap();
+
+    let entry = MediaSource::ArchiveEntry {
+        archive: archive.clone(),
+        entry: "disc/track.wav".to_string(),
+        offset: 100,
+        len: wav.len() as u64,
+    };
+    assert_eq!(entry.to_string(), format!("{}!/disc/track.wav", archive.display()));
+    assert_eq!(entry.open().unwrap().byte_len(), Some(wav.len() as u64));
+
+    let file = MediaSource::from_path(Path::new("test/testaudio-short.wav"));
+    assert_eq!(samples(&entry, 0, 4096), samples(&file, 0, 4096));
+    assert_eq!(samples(&entry, 20_000, 4096), samples(&file, 20_000, 4096));
+}
+
+#[test]
+fn test_http_source() {
+    let flac = std::fs::read("test/testaudio-short.flac").unwrap();
+    let url = serve(flac.clone());
+
+    // Reads past the end stop at the length the server reported
+    let mut stream = MediaSource::Http(format!("{}/old", url)).open().unwrap();
+    assert_eq!(stream.byte_len(), Some(flac.len() as u64));
+    stream.seek(SeekFrom::End(-4)).unwrap();
+    let mut tail = Vec::new();
+    stream.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, flac[flac.len() - 4..]);
+
+    let remote = MediaSource::from_path(Path::new(&format!("{}/track.flac", url)));
+    let file = MediaSource::from_path(Path::new("test/testaudio-short.flac"));
+    assert_eq!(samples(&remote, 0, 2048), samples(&file, 0, 2048));
+    assert_eq!(samples(&remote, 30_000, 2048), samples(&file, 30_000, 2048));
+
+    let manager = MetadataManager::with_format_parsers();
+    let (metadata, expected) = (manager.parse_source(&remote).unwrap(), manager.parse_source(&file).unwrap());
+    assert_eq!(metadata.duration, expected.duration);
+    assert_eq!(metadata.sample_rate, expected.sample_rate);
+}
+
+/// `files` from `test/` packed under `Album/` into a tar of each kind
+fn write_tars(dir: &Path, files: &[&str]) -> Vec<PathBuf> {
+    let mut tar = tar::Builder::new(Vec::new());
+    for name in files {
+        tar.append_path_with_name(Path::new("test").join(name), Path::new("./Album").join(name)).unwrap();
+    }
+    let tar = tar.into_inner().unwrap();
+
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(&tar).unwrap();
+    let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+    xz.write_all(&tar).unwrap();
+
+    let archives = [("album.tar", tar), ("album.tar.gz", gz.finish().unwrap()), ("album.tar.xz", xz.finish().unwrap())];
+    archives.into_iter().map(|(name, bytes)| {
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }).collect()
+}
+
+#[test]
+fn test_split_entry_path() {
+    let path = Path::new("/music/best!/of.zip!/Disc 1/01.flac");
+    assert_eq!(archive::split_entry_path(path), Some((PathBuf::from("/music/best!/of.zip"), "Disc 1/01.flac".to_string())));
+    assert_eq!(archive::split_entry_path(Path::new("/music/a.TGZ!/Disc 1/")), Some((PathBuf::from("/music/a.TGZ"), "Disc 1".to_string())));
+    assert_eq!(archive::split_entry_path(Path::new("/music/bang!/01.flac")), None);
+    assert_eq!(MediaSource::from_path(path).to_string(), path.to_str().unwrap());
+    assert_eq!(MediaSource::from_path(path).extension(), Some("flac"));
+}
+
+#[test]
+fn test_zip_entries() {
+    let zip = Path::new("test/testaudio-album.zip");
+    assert_eq!(archive::list(zip).unwrap(), ["Album/01 Short.flac", "Album/Bonus/02 Short.aiff", "notes.txt"]);
+
+    // Deflated and stored entries both play like the files they were made from, seeking back too
+    let manager = MetadataManager::with_format_parsers();
+    for (entry, file) in [("Album/01 Short.flac", "testaudio-short.flac"), ("Album/Bonus/02 Short.aiff", "testaudio-short.aiff")] {
+        let path = PathBuf::from(archive::entry_path(zip, entry));
+        let (packed, file) = (MediaSource::from_path(&path), MediaSource::from_path(&Path::new("test").join(file)));
+        assert_eq!(packed.open().unwrap().byte_len(), file.open().unwrap().byte_len(), "{}", entry);
+
+        let mut reader = open_track(&path).unwrap();
+        let mut buffer = vec![0.0; 4096];
+        reader.seek(30_000).unwrap();
+        reader.read(&mut buffer).unwrap();
+        reader.seek(1000).unwrap();
+        reader.read(&mut buffer).unwrap();
+        assert_eq!(buffer, samples(&file, 1000, 4096), "{}", entry);
+
+        let (metadata, expected) = (manager.parse_metadata(&path).unwrap(), manager.parse_source(&file).unwrap());
+        assert_eq!((metadata.title, metadata.duration), (expected.title, expected.duration), "{}", entry);
+    }
+
+    let missing = MediaSource::from_path(&PathBuf::from(archive::entry_path(zip, "Album/03.flac")));
+    assert_eq!(missing.open().err().map(|e| e.kind()), Some(io::ErrorKind::NotFound));
+}
+
+#[test]
+fn test_tar_entries() {
+    let flac = std::fs::read("test/testaudio-short.flac").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    for tar in write_tars(dir.path(), &["testaudio-short.wav", "testaudio-short.flac"]) {
+        let name = tar.display().to_string();
+        assert_eq!(archive::list(&tar).unwrap(), ["Album/testaudio-short.wav", "Album/testaudio-short.flac"], "{}", name);
+
+        let packed = MediaSource::from_path(&PathBuf::from(archive::entry_path(&tar, "Album/testaudio-short.flac")));
+        let file = MediaSource::from_path(Path::new("test/testaudio-short.flac"));
+        assert_eq!(samples(&packed, 30_000, 2048), samples(&file, 30_000, 2048), "{}", name);
+        assert_eq!(samples(&packed, 0, 2048), samples(&file, 0, 2048), "{}", name);
+
+        // Reading back over an entry in a compressed tar unpacks it again
+        let mut stream = packed.open().unwrap();
+        let mut tail = [0; 4];
+        stream.seek(SeekFrom::End(-4)).unwrap();
+        stream.read_exact(&mut tail).unwrap();
+        let mut head = [0; 4];
+        stream.seek(SeekFrom::Start(0)).unwrap();
+        stream.read_exact(&mut head).unwrap();
+        assert_eq!((&head, &tail[..]), (b"fLaC", &flac[flac.len() - 4..]), "{}", name);
+    }
+}
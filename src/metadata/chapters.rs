@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use id3::frame::{Chapter as Id3Chapter, Content};
+use id3::Tag;
+
+/// How far into a chapter going back restarts it rather than moving to the one before
+pub const RESTART_THRESHOLD: Duration = Duration::from_secs(3);
+
+/// Nesting of ID3 tables of contents followed before giving up, as they may refer to each other
+const MAX_TOC_DEPTH: usize = 8;
+
+/// A titled stretch of a track, such as a chapter of an audiobook or a tune in a mix
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chapter {
+    pub title: Option<String>,
+    pub start: Duration,
+    /// Where the chapter ends, when the tags or the track length tell
+    pub end: Option<Duration>,
+}
+
+impl Chapter {
+    /// Title to show, falling back to the chapter's number counted from 1
+    pub fn label(&self, index: usize) -> String {
+        self.title.clone().unwrap_or_else(|| format!("Chapter {}", index + 1))
+    }
+}
+
+/// Index of the chapter playing at `position`, none before the first one starts
+pub fn current(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    chapters.iter().rposition(|chapter| chapter.start <= position)
+}
+
+/// Index of the first chapter starting after `position`
+pub fn next(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    chapters.iter().position(|chapter| chapter.start > position)
+}
+
+/// Chapter to go back to from `position`: the start of the current one once it has played
+/// for a moment, as Previous does with tracks, otherwise the one before it
+pub fn previous(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    let current = current(chapters, position)?;
+    match position.saturating_sub(chapters[current].start) > RESTART_THRESHOLD {
+        true => Some(current),
+        false => Some(current.saturating_sub(1)),
+    }
+}
+
+/// Chapters from `CHAPTERxxx=HH:MM:SS.mmm` and `CHAPTERxxxNAME` comments, the Vorbis comment
+/// convention; each runs to the next one, the last to the end of the track
+pub fn from_vorbis_comments(comments: &HashMap<String, String>, duration: Option<Duration>) -> Vec<Chapter> {
+    let mut starts = Vec::new();
+    let mut names = HashMap::new();
+    for (key, value) in comments {
+        let key = key.to_ascii_uppercase();
+        let Some(rest) = key.strip_prefix("CHAPTER") else {
+            continue;
+        };
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let Ok(number) = rest[..digits].parse::<u32>() else {
+            continue;
+        };
+        match &rest[digits..] {
+            "" => starts.extend(parse_timestamp(value).map(|start| (number, start))),
+            "NAME" => {
+                names.insert(number, value.trim().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    starts.sort_by_key(|&(number, start)| (start, number));
+    let mut chapters: Vec<Chapter> = starts
+        .into_iter()
+        .map(|(number, start)| Chapter {
+            title: names.remove(&number).filter(|name| !name.is_empty()),
+            start,
+            end: None,
+        })
+        .collect();
+    fill_ends(&mut chapters, duration);
+    chapters
+}
+
+/// Chapters from ID3 `CHAP` frames, in the order of the top-level `CTOC` when there is one
+/// and otherwise by start time
+pub fn from_id3(tag: &Tag) -> Vec<Chapter> {
+    let frames: HashMap<&str, &Id3Chapter> = tag.chapters().map(|chapter| (chapter.element_id.as_str(), chapter)).collect();
+    let tables: HashMap<&str, &Vec<String>> = tag.tables_of_contents()
+        .map(|toc| (toc.element_id.as_str(), &toc.elements))
+        .collect();
+
+    let mut ordered = Vec::new();
+    match tag.tables_of_contents().find(|toc| toc.top_level) {
+        Some(root) => {
+            let mut seen = HashSet::new();
+            collect_toc(&root.elements, &frames, &tables, &mut seen, &mut ordered, 0);
+        }
+        None => {
+            ordered.extend(frames.values().copied());
+            ordered.sort_by_key(|chapter| (chapter.start_time, chapter.element_id.clone()));
+        }
+    }
+
+    ordered
+        .into_iter()
+        .map(|chapter| Chapter {
+            title: chapter.frames.iter().find(|frame| frame.id() == "TIT2").and_then(|frame| match frame.content() {
+                Content::Text(text) => Some(text.trim_end_matches('\0').to_string()),
+                _ => None,
+            }),
+            start: Duration::from_millis(chapter.start_time as u64),
+            end: (chapter.end_time > chapter.start_time).then(|| Duration::from_millis(chapter.end_time as u64)),
+        })
+        .collect()
+}
+
+/// Chapters a table of contents lists, descending into the tables it lists in turn
+fn collect_toc<'a>(
+    elements: &'a [String],
+    frames: &HashMap<&str, &'a Id3Chapter>,
+    tables: &HashMap<&str, &'a Vec<String>>,
+    seen: &mut HashSet<&'a str>,
+    ordered: &mut Vec<&'a Id3Chapter>,
+    depth: usize,
+) {
+    for element in elements {
+        if !seen.insert(element.as_str()) {
+            continue;
+        }
+        if let Some(chapter) = frames.get(element.as_str()) {
+            ordered.push(chapter);
+        } else if let Some(children) = tables.get(element.as_str()).filter(|_| depth < MAX_TOC_DEPTH) {
+            collect_toc(children, frames, tables, seen, ordered, depth + 1);
+        }
+    }
+}
+
+/// End chapters without one where the next begins, or the last at the end of the track
+fn fill_ends(chapters: &mut [Chapter], duration: Option<Duration>) {
+    let starts: Vec<Duration> = chapters.iter().skip(1).map(|chapter| chapter.start).collect();
+    for (chapter, end) in chapters.iter_mut().zip(starts.into_iter().map(Some).chain([duration])) {
+        chapter.end = chapter.end.or(end);
+    }
+}
+
+/// An `HH:MM:SS.mmm` time, also accepting `MM:SS` and any number of decimals
+fn parse_timestamp(value: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for field in value.trim().split(':') {
+        let field: f64 = field.trim().parse().ok()?;
+        if !(0.0..f64::MAX).contains(&field) {
+            return None;
+        }
+        seconds = seconds * 60.0 + field;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::frame::{Frame, TableOfContents};
+    use id3::TagLike;
+
+    fn chapter(title: &str, start: u64, end: u64) -> Chapter {
+        Chapter {
+            title: Some(title.to_string()),
+            start: Duration::from_secs(start),
+            end: Some(Duration::from_secs(end)),
+        }
+    }
+
+    #[test]
+    fn test_vorbis_comment_chapters() {
+        let comments: HashMap<String, String> = [
+            ("CHAPTER002", "00:10:30.500"),
+            ("chapter002name", "Second"),
+            ("CHAPTER001", "00:00:00.000"),
+            ("CHAPTER001NAME", "First"),
+            ("CHAPTER003", "1:00:00"),
+            ("CHAPTER004", "not a time"),
+            ("CHAPTERS", "ignored"),
+        ].into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+
+        let chapters = from_vorbis_comments(&comments, Some(Duration::from_secs(4000)));
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[0].title.as_deref(), Some("First"));
+        assert_eq!(chapters[0].end, Some(Duration::from_millis(630_500)));
+        assert_eq!(chapters[1].title.as_deref(), Some("Second"));
+        assert_eq!(chapters[2].start, Duration::from_secs(3600));
+        assert_eq!((chapters[2].label(2), chapters[2].end), ("Chapter 3".to_string(), Some(Duration::from_secs(4000))));
+    }
+
+    #[test]
+    fn test_id3_chapters_follow_table_of_contents() {
+        let mut tag = Tag::new();
+        let chap = |id: &str, title: &str, start, end| Id3Chapter {
+            element_id: id.to_string(),
+            start_time: start,
+            end_time: end,
+            start_offset: u32::MAX,
+            end_offset: u32::MAX,
+            frames: vec![Frame::text("TIT2", title)],
+        };
+        tag.add_frame(chap("ch1", "Opening", 0, 5000));
+        tag.add_frame(chap("ch0", "Credits", 9000, 10000));
+        tag.add_frame(chap("ch2", "Middle", 5000, 9000));
+        tag.add_frame(TableOfContents {
+            element_id: "parts".to_string(),
+            top_level: false,
+            ordered: true,
+            elements: vec!["ch2".to_string(), "ch0".to_string()],
+            frames: Vec::new(),
+        });
+
+        // Without a top-level table, chapters go by start time
+        let titles = |chapters: Vec<Chapter>| chapters.into_iter().map(|c| c.title.unwrap()).collect::<Vec<_>>();
+        assert_eq!(titles(from_id3(&tag)), ["Opening", "Middle", "Credits"]);
+
+        tag.add_frame(TableOfContents {
+            element_id: "toc".to_string(),
+            top_level: true,
+            ordered: true,
+            elements: vec!["ch1".to_string(), "parts".to_string(), "toc".to_string()],
+            frames: Vec::new(),
+        });
+        let chapters = from_id3(&tag);
+        assert_eq!(chapters[1], chapter("Middle", 5, 9));
+        assert_eq!(titles(chapters), ["Opening", "Middle", "Credits"]);
+    }
+
+    #[test]
+    fn test_current_next_and_previous() {
+        let chapters = [chapter("A", 10, 60), chapter("B", 60, 120), chapter("C", 120, 180)];
+        let at = Duration::from_secs;
+        assert_eq!(current(&chapters, at(5)), None);
+        assert_eq!(current(&chapters, at(60)), Some(1));
+        assert_eq!(next(&chapters, at(5)), Some(0));
+        assert_eq!(next(&chapters, at(120)), None);
+
+        // Just into a chapter goes back one, further in restarts it
+        assert_eq!(previous(&chapters, at(62)), Some(0));
+        assert_eq!(previous(&chapters, at(70)), Some(1));
+        assert_eq!(previous(&chapters, at(11)), Some(0));
+        assert_eq!(previous(&chapters, at(5)), None);
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::metadata::{Metadata, MetadataError};
+
+#[cfg(test)]
+mod tests;
+
+/// CD frames per second, the unit of the last field of `MM:SS:FF` times
+const FRAMES_PER_SECOND: u64 = 75;
+
+/// Separates an album file from a track number in the path of a CUE track, as in `album.flac#3`
+const TRACK_SEPARATOR: char = '#';
+
+/// A CUE sheet splitting one audio file into tracks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// Audio file the sheet describes, as named on its `FILE` line
+    pub file: Option<String>,
+    /// `REM` comments before the first track, such as `GENRE` and `DATE`
+    pub comments: Vec<(String, String)>,
+    pub tracks: Vec<CueTrack>,
+}
+
+/// One track of a CUE sheet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CueTrack {
+    pub number: u32,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// Where the track starts in the file, its `INDEX 01`
+    pub start: Duration,
+    /// Where the gap before the track starts, its `INDEX 00`, when it has one
+    pub pregap: Option<Duration>,
+    /// `REM` comments within the track, such as its ReplayGain
+    pub comments: Vec<(String, String)>,
+}
+
+impl CueSheet {
+    /// Parse the text of a `.cue` file; only the tracks of its first `FILE` are kept, as
+    /// only single-file sheets describe one album file
+    pub fn parse(text: &str) -> Result<Self, MetadataError> {
+        let mut sheet = CueSheet::default();
+        // Whether the lines are inside a data track, and which tracks have had their INDEX 01
+        let mut skipping = false;
+        let mut started = Vec::new();
+        for (line_number, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+            let error = |message: &str| MetadataError::ParseError(format!("CUE sheet line {}: {}", line_number + 1, message));
+            let (command, rest) = split_word(line.trim());
+            let command = command.to_ascii_uppercase();
+            if skipping && command != "TRACK" && command != "FILE" {
+                continue;
+            }
+            let track = sheet.tracks.last_mut();
+            match command.as_str() {
+                "FILE" if sheet.file.is_some() => break,
+                "FILE" => sheet.file = Some(unquote(rest_without_type(rest))),
+                "TRACK" => {
+                    let (number, kind) = split_word(rest);
+                    let number = number.parse().map_err(|_| error("bad track number"))?;
+                    // Data tracks of mixed-mode discs have no audio to play
+                    skipping = !kind.eq_ignore_ascii_case("AUDIO");
+                    if !skipping {
+                        sheet.tracks.push(CueTrack { number, ..Default::default() });
+                    }
+                }
+                "INDEX" => {
+                    let (index, time) = split_word(rest);
+                    let time = parse_time(time).ok_or_else(|| error("bad index time"))?;
+                    match (track, index.parse::<u32>()) {
+                        (Some(track), Ok(0)) => track.pregap = Some(time),
+                        (Some(track), Ok(1)) => {
+                            track.start = time;
+                            started.push(track.number);
+                        }
+                        (Some(_), Ok(_)) => {}
+                        (None, _) => return Err(error("INDEX before any TRACK")),
+                        (_, Err(_)) => return Err(error("bad index number")),
+                    }
+                }
+                "TITLE" => match track {
+                    Some(track) => track.title = Some(unquote(rest)),
+                    None => sheet.title = Some(unquote(rest)),
+                },
+                "PERFORMER" => match track {
+                    Some(track) => track.performer = Some(unquote(rest)),
+                    None => sheet.performer = Some(unquote(rest)),
+                },
+                "REM" => {
+                    let (key, value) = split_word(rest);
+                    let comment = (key.to_ascii_uppercase(), unquote(value));
+                    match track {
+                        Some(track) => track.comments.push(comment),
+                        None => sheet.comments.push(comment),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if sheet.tracks.is_empty() {
+            return Err(MetadataError::ParseError("CUE sheet has no audio tracks".to_string()));
+        }
+        if let Some(track) = sheet.tracks.iter().find(|track| !started.contains(&track.number)) {
+            return Err(MetadataError::MissingField(format!("INDEX 01 of track {}", track.number)));
+        }
+        Ok(sheet)
+    }
+
+    /// Read a `.cue` file, which older rippers wrote in Latin-1 rather than UTF-8
+    pub fn read(path: &Path) -> Result<Self, MetadataError> {
+        let bytes = std::fs::read(path).map_err(MetadataError::IoError)?;
+        match String::from_utf8(bytes) {
+            Ok(text) => Self::parse(&text),
+            Err(e) => Self::parse(&e.into_bytes().iter().map(|&byte| byte as char).collect::<String>()),
+        }
+    }
+
+    pub fn track(&self, number: u32) -> Option<&CueTrack> {
+        self.tracks.iter().find(|track| track.number == number)
+    }
+
+    /// Where track `number` starts and ends in the file, `None` for the end of the file.
+    /// Tracks run on to the next one's `INDEX 01`, so pregaps are heard at the end of the
+    /// track before and playing the tracks in turn leaves nothing out.
+    pub fn span(&self, number: u32) -> Option<(Duration, Option<Duration>)> {
+        let position = self.tracks.iter().position(|track| track.number == number)?;
+        let end = self.tracks.get(position + 1).map(|next| next.start);
+        Some((self.tracks[position].start, end))
+    }
+
+    /// Metadata of track `number`, which is the album file's with the sheet's details on top
+    pub fn track_metadata(&self, number: u32, album: &Metadata) -> Option<Metadata> {
+        let track = self.track(number)?;
+        let (start, end) = self.span(number)?;
+        let mut metadata = album.clone();
+        metadata.title = track.title.clone().or(metadata.title);
+        metadata.artist = track.performer.clone().or_else(|| self.performer.clone()).or(metadata.artist);
+        metadata.album = self.title.clone().or(metadata.album);
+        metadata.track = Some(number);
+        metadata.duration = match end {
+            Some(end) => Some(end.saturating_sub(start).as_secs_f64()),
+            None => album.duration.map(|duration| (duration - start.as_secs_f64()).max(0.0)),
+        };
+        // The album's chapters mark times in the whole file, not in the track
+        metadata.chapters.clear();
+        metadata.extra.retain(|key, _| !key.eq_ignore_ascii_case("CUESHEET"));
+        for (key, value) in self.comments.iter().chain(&track.comments) {
+            match key.as_str() {
+                "GENRE" => metadata.genre = Some(value.clone()),
+                "DATE" => metadata.year = value.split('-').next().and_then(|year| year.parse().ok()).or(metadata.year),
+                _ => {}
+            }
+            metadata.extra.insert(key.clone(), value.clone());
+        }
+        Some(metadata)
+    }
+
+    /// Name to list track `number` under, such as `03 Title`
+    pub fn track_name(&self, number: u32) -> String {
+        let title = self.track(number).and_then(|track| track.title.as_deref());
+        match title {
+            Some(title) => format!("{:02} {}", number, title),
+            None => format!("{:02} Track {}", number, number),
+        }
+    }
+}
+
+/// Path that plays track `number` of the CUE sheet for `file`
+pub fn track_path(file: &Path, number: u32) -> String {
+    format!("{}{}{}", file.display(), TRACK_SEPARATOR, number)
+}
+
+/// The album file and track number of a CUE track's path; files whose own name looks like
+/// one are left alone
+pub fn split_track_path(path: &Path) -> Option<(PathBuf, u32)> {
+    let (file, number) = path.to_str()?.rsplit_once(TRACK_SEPARATOR)?;
+    let number = number.parse().ok()?;
+    (!path.exists()).then(|| (PathBuf::from(file), number))
+}
+
+/// A `.cue` file beside `file` describing it: one with the same name, or failing that one
+/// whose `FILE` line names it
+pub fn find_sidecar(file: &Path) -> Option<CueSheet> {
+    SidecarIndex::read(directory_of(file)).find(file).cloned()
+}
+
+/// The `.cue` files of one directory, read once so every file in it can be matched against
+/// them without reading them again
+#[derive(Debug, Default)]
+pub struct SidecarIndex {
+    sheets: Vec<CueSheet>,
+    /// Sheets by the name of their `.cue` file
+    by_name: HashMap<OsString, usize>,
+    /// Sheets by the lowercased name of the file on their `FILE` line
+    by_file: HashMap<String, usize>,
+}
+
+impl SidecarIndex {
+    /// Read and parse every `.cue` file in `dir`; ones that fail to parse are left out
+    pub fn read(dir: &Path) -> Self {
+        let mut index = Self::default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return index;
+        };
+        let cue_files = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("cue")));
+        for path in cue_files {
+            let (Some(name), Ok(sheet)) = (path.file_name(), CueSheet::read(&path)) else {
+                continue;
+            };
+            let position = index.sheets.len();
+            index.by_name.insert(name.to_os_string(), position);
+            if let Some(named) = sheet.file.as_deref().and_then(|named| Path::new(named).file_name()) {
+                index.by_file.entry(named.to_string_lossy().to_lowercase()).or_insert(position);
+            }
+            index.sheets.push(sheet);
+        }
+        index
+    }
+
+    /// The sheet describing `file`, which should be in the directory that was read
+    pub fn find(&self, file: &Path) -> Option<&CueSheet> {
+        let name = file.file_name()?.to_string_lossy().into_owned();
+        let named = [file.with_extension("cue"), file.with_file_name(format!("{}.cue", name))];
+        // Sheets named after the file are trusted even if their FILE line names a different
+        // format, as when a rip was transcoded after the sheet was written
+        named.iter()
+            .filter_map(|path| self.by_name.get(path.file_name()?))
+            .chain(self.by_file.get(&name.to_lowercase()))
+            .next()
+            .map(|&position| &self.sheets[position])
+    }
+}
+
+/// Directory holding `file`, the current one for a bare file name
+pub fn directory_of(file: &Path) -> &Path {
+    file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."))
+}
+
+/// First word of `line` and the rest, trimmed
+fn split_word(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (line, ""),
+    }
+}
+
+/// A `FILE` line's name without the file type after it
+fn rest_without_type(rest: &str) -> &str {
+    match rest.rsplit_once(char::is_whitespace) {
+        Some((name, _kind)) => name.trim(),
+        None => rest,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value.strip_prefix('"').and_then(|value| value.strip_suffix('"')).unwrap_or(value).to_string()
+}
+
+/// A `MM:SS:FF` time, in minutes, seconds and 75ths of a second
+fn parse_time(time: &str) -> Option<Duration> {
+    let mut fields = time.split(':').map(|field| field.trim().parse::<u64>().ok());
+    let (minutes, seconds, frames) = (fields.next()??, fields.next()??, fields.next()??);
+    if fields.next().is_some() || seconds >= 60 || frames >= FRAMES_PER_SECOND {
+        return None;
+    }
+    let frames = (minutes * 60 + seconds) * FRAMES_PER_SECOND + frames;
+    Some(Duration::from_nanos(frames * 1_000_000_000 / FRAMES_PER_SECOND))
+}
@@ -0,0 +1,118 @@
+use super::*;
+
+const SHEET: &str = "\u{feff}REM GENRE \"Jazz\"
+REM DATE 1959
+PERFORMER \"Miles Davis\"
+TITLE \"Kind of Blue\"
+FILE \"Kind of Blue.flac\" WAVE
+  TRACK 01 AUDIO
+    TITLE \"So What\"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE \"Freddie Freeloader\"
+    PERFORMER \"Miles Davis Sextet\"
+    REM REPLAYGAIN_TRACK_GAIN -3.20 dB
+    INDEX 00 09:20:70
+    INDEX 01 09:22:00
+  TRACK 03 AUDIO
+    INDEX 01 19:00:37
+";
+
+#[test]
+fn test_parse_sheet() {
+    let sheet = CueSheet::parse(SHEET).unwrap();
+    assert_eq!(sheet.title.as_deref(), Some("Kind of Blue"));
+    assert_eq!(sheet.performer.as_deref(), Some("Miles Davis"));
+    assert_eq!(sheet.file.as_deref(), Some("Kind of Blue.flac"));
+    assert_eq!(sheet.comments[0], ("GENRE".to_string(), "Jazz".to_string()));
+    assert_eq!(sheet.tracks.len(), 3);
+
+    let second = sheet.track(2).unwrap();
+    assert_eq!(second.title.as_deref(), Some("Freddie Freeloader"));
+    assert_eq!(second.performer.as_deref(), Some("Miles Davis Sextet"));
+    assert_eq!(second.pregap, Some(Duration::from_secs(560) + Duration::from_nanos(70 * 1_000_000_000 / 75)));
+    assert_eq!(second.start, Duration::from_secs(562));
+    assert_eq!(sheet.track(3).unwrap().start, Duration::from_secs(1140) + Duration::from_nanos(37 * 1_000_000_000 / 75));
+
+    // Each track runs on to the next one's INDEX 01, so the gap before track 2 ends track 1
+    assert_eq!(sheet.span(1), Some((Duration::ZERO, Some(Duration::from_secs(562)))));
+    assert_eq!(sheet.span(3).unwrap().1, None);
+    assert_eq!(sheet.span(4), None);
+}
+
+#[test]
+fn test_parse_errors_and_skipped_tracks() {
+    assert!(CueSheet::parse("TITLE \"Nothing\"\n").is_err());
+    assert!(CueSheet::parse("FILE a.wav WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:61:00\n").is_err());
+    assert!(CueSheet::parse("INDEX 01 00:00:00\n").is_err());
+    assert!(CueSheet::parse("FILE a.wav WAVE\n  TRACK 01 AUDIO\n    INDEX 00 00:00:00\n").is_err());
+
+    // Data tracks and the tracks of any second file are left out
+    let sheet = CueSheet::parse(
+        "FILE a.bin BINARY\n  TRACK 01 MODE1/2352\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    INDEX 01 01:00:00\n\
+         FILE b.wav WAVE\n  TRACK 03 AUDIO\n    INDEX 01 00:00:00\n",
+    ).unwrap();
+    assert_eq!(sheet.file.as_deref(), Some("a.bin"));
+    assert_eq!(sheet.tracks.iter().map(|track| track.number).collect::<Vec<_>>(), vec![2]);
+    assert_eq!(sheet.track_name(2), "02 Track 2");
+}
+
+#[test]
+fn test_track_metadata_overrides_album() {
+    let sheet = CueSheet::parse(SHEET).unwrap();
+    let album = Metadata {
+        title: Some("Kind of Blue (full album)".to_string()),
+        artist: Some("Someone".to_string()),
+        duration: Some(2760.0),
+        sample_rate: Some(44100),
+        extra: [("CUESHEET".to_string(), SHEET.to_string())].into_iter().collect(),
+        ..Default::default()
+    };
+
+    let second = sheet.track_metadata(2, &album).unwrap();
+    assert_eq!(second.title.as_deref(), Some("Freddie Freeloader"));
+    assert_eq!(second.artist.as_deref(), Some("Miles Davis Sextet"));
+    assert_eq!(second.album.as_deref(), Some("Kind of Blue"));
+    assert_eq!((second.track, second.year, second.genre.as_deref()), (Some(2), Some(1959), Some("Jazz")));
+    assert_eq!(second.sample_rate, Some(44100));
+    assert_eq!(second.extra.get("REPLAYGAIN_TRACK_GAIN").map(String::as_str), Some("-3.20 dB"));
+    assert!(!second.extra.contains_key("CUESHEET"));
+    assert!((second.duration.unwrap() - (1140.0 + 37.0 / 75.0 - 562.0)).abs() < 1e-6);
+
+    let third = sheet.track_metadata(3, &album).unwrap();
+    assert_eq!((third.title.as_deref(), third.artist.as_deref()), (Some("Kind of Blue (full album)"), Some("Miles Davis")));
+    assert!((third.duration.unwrap() - (2760.0 - 1140.0 - 37.0 / 75.0)).abs() < 1e-6);
+}
+
+#[test]
+fn test_track_paths_and_sidecar() {
+    let dir = tempfile::tempdir().unwrap();
+    let album = dir.path().join("Kind of Blue.flac");
+    std::fs::write(&album, b"").unwrap();
+    assert_eq!(split_track_path(Path::new(&track_path(&album, 12))), Some((album.clone(), 12)));
+    assert_eq!(split_track_path(&album), None);
+    assert!(find_sidecar(&album).is_none());
+
+    // A sheet under another name is found through its FILE line, written in Latin-1
+    let mut latin1 = SHEET.replace("So What", "So Wh\u{e4}t").chars().map(|c| c as u8).collect::<Vec<_>>();
+    latin1.drain(..1);
+    std::fs::write(dir.path().join("rip.cue"), latin1).unwrap();
+    assert_eq!(find_sidecar(&album).unwrap().track(1).unwrap().title.as_deref(), Some("So Wh\u{e4}t"));
+
+    // One named after the file wins whatever its FILE line says
+    std::fs::write(album.with_extension("cue"), SHEET.replace("Kind of Blue.flac", "Kind of Blue.wav")).unwrap();
+    assert_eq!(find_sidecar(&album).unwrap().file.as_deref(), Some("Kind of Blue.wav"));
+
+    // One read of the directory matches every file in it
+    let other = dir.path().join("Live.flac");
+    std::fs::write(dir.path().join("live.cue"), SHEET.replace("Kind of Blue.flac", "LIVE.FLAC")).unwrap();
+    let sidecars = SidecarIndex::read(dir.path());
+    assert_eq!(sidecars.find(&album).unwrap().file.as_deref(), Some("Kind of Blue.wav"));
+    assert_eq!(sidecars.find(&other).unwrap().file.as_deref(), Some("LIVE.FLAC"));
+    assert!(sidecars.find(&dir.path().join("Other.flac")).is_none());
+
+    // A file really named like a track path is played as itself
+    let literal = dir.path().join("odd#2");
+    std::fs::write(&literal, b"").unwrap();
+    assert_eq!(split_track_path(&literal), None);
+}
@@ -0,0 +1,106 @@
+use id3::Tag;
+
+use crate::audio::formats::aiff::AiffDecoder;
+use crate::audio::formats::{detect_source_format, FileFormat};
+use crate::media::MediaSource;
+use crate::metadata::{Metadata, MetadataError, MetadataParser};
+use super::Id3Parser;
+
+pub struct AiffParser;
+
+impl AiffParser {
+    pub fn new() -> Self {
+        AiffParser
+    }
+
+    fn parse_audio_properties(&self, source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        let format = AiffDecoder::new()
+            .read_source_info(source)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?
+            .format;
+
+        Ok((
+            format.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0),
+            format.sample_rate,
+            format.channels as u8,
+            format.bit_rate.unwrap_or(0),
+        ))
+    }
+}
+
+impl Default for AiffParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataParser for AiffParser {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
+        // id3 finds the optional "ID3 " chunk; untagged files still have audio properties
+        let stream = source.open().map_err(MetadataError::IoError)?;
+        let tag = id3::no_tag_ok(Tag::read_from2(stream))
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?;
+        let mut metadata = tag
+            .map(|tag| Id3Parser::new().parse_id3_tag(&tag))
+            .unwrap_or_default();
+
+        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(source) {
+            metadata.duration = Some(duration);
+            metadata.sample_rate = Some(sample_rate);
+            metadata.channels = Some(channels);
+            metadata.bit_rate = Some(bit_rate);
+        }
+
+        Ok(metadata)
+    }
+
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Aiff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_supports_format() {
+        let parser = AiffParser::new();
+        assert!(parser.supports_format(Path::new("test.aiff")));
+        assert!(parser.supports_format(Path::new("test.aifc")));
+        assert!(!parser.supports_format(Path::new("test.wav")));
+        assert!(!parser.supports_format(Path::new("test.mp3")));
+    }
+
+    #[test]
+    fn test_parse_id3_chunk() {
+        let parser = AiffParser::new();
+        for name in ["test/testaudio-short.aiff", "test/testaudio-short.aifc"] {
+            let metadata = parser.parse(Path::new(name)).unwrap();
+            assert_eq!(metadata.title.as_deref(), Some("Test Audio Short"), "{}", name);
+            assert_eq!(metadata.artist.as_deref(), Some("PlayTUI"), "{}", name);
+            assert_eq!(metadata.track, Some(3), "{}", name);
+            assert_eq!(metadata.duration, Some(1.0), "{}", name);
+            assert_eq!(metadata.sample_rate, Some(48000), "{}", name);
+            assert_eq!(metadata.bit_rate, Some(768), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_untagged() {
+        // Drop the trailing ID3 chunk and fix up the FORM size
+        let mut bytes = std::fs::read("test/testaudio-short.aiff").unwrap();
+        let ssnd_end = 12 + 8 + 18 + 8 + 8 + 96000;
+        bytes.truncate(ssnd_end);
+        bytes[4..8].copy_from_slice(&(ssnd_end as u32 - 8).to_be_bytes());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("untagged.aiff");
+        std::fs::write(&path, bytes).unwrap();
+
+        let metadata = AiffParser::new().parse(&path).unwrap();
+        assert_eq!(metadata.title, None);
+        assert_eq!(metadata.sample_rate, Some(48000));
+    }
+}
@@ -1,13 +1,14 @@
 use std::path::Path;
-use metaflac::Tag;
+use std::time::Duration;
+use metaflac::{Block, BlockType, Tag};
 use symphonia::core::probe::Hint;
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
-use std::fs::File;
 
-use crate::metadata::{Metadata, MetadataError, MetadataParser};
-use super::matches_extension;
+use crate::metadata::{chapters, CueSheet, CueTrack, Metadata, MetadataError, MetadataParser};
+use crate::audio::formats::{detect_source_format, FileFormat};
+use crate::media::MediaSource;
 
 pub struct FlacMetadataParser;
 
@@ -57,9 +58,9 @@ impl FlacMetadataParser {
         metadata
     }
 
-    fn parse_audio_properties(&self, path: &Path) -> Result<(f64, u32, u8, u32), MetadataError> {
-        let file = File::open(path).map_err(MetadataError::IoError)?;
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    fn parse_audio_properties(&self, source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        let stream = source.open().map_err(MetadataError::IoError)?;
+        let mss = MediaSourceStream::new(Box::new(stream), Default::default());
 
         let hint = Hint::new();
         let format_opts = FormatOptions::default();
@@ -93,26 +94,72 @@ impl FlacMetadataParser {
 }
 
 impl MetadataParser for FlacMetadataParser {
-    fn parse(&self, path: &Path) -> Result<Metadata, MetadataError> {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
         // Read FLAC metadata
-        let tag = Tag::read_from_path(path)
+        let mut stream = source.open().map_err(MetadataError::IoError)?;
+        let tag = Tag::read_from(&mut stream)
             .map_err(|e| MetadataError::ParseError(e.to_string()))?;
         
         let mut metadata = self.parse_vorbis_comments(&tag);
 
         // Add audio properties
-        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(path) {
+        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(source) {
             metadata.duration = Some(duration);
             metadata.sample_rate = Some(sample_rate);
             metadata.channels = Some(channels);
             metadata.bit_rate = Some(bit_rate);
         }
+        metadata.chapters = chapters::from_vorbis_comments(&metadata.extra, metadata.duration.map(Duration::from_secs_f64));
 
         Ok(metadata)
     }
 
-    fn supports_format(&self, path: &Path) -> bool {
-        matches_extension(path, &["flac"])
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Flac))
+    }
+
+    fn write_tags(&self, path: &Path, tags: &[(String, String)]) -> Result<(), MetadataError> {
+        let mut tag = Tag::read_from_path(path)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?;
+
+        let comments = tag.vorbis_comments_mut();
+        for (key, value) in tags {
+            // Field names are case-insensitive, so drop any spelling of the key
+            comments.comments.retain(|existing, _| !existing.eq_ignore_ascii_case(key));
+            comments.set(key.clone(), vec![value.clone()]);
+        }
+
+        tag.save().map_err(|e| MetadataError::ParseError(e.to_string()))
+    }
+
+    fn cue_sheet(&self, path: &Path) -> Option<CueSheet> {
+        let tag = Tag::read_from_path(path).ok()?;
+        let tagged = tag.get_vorbis("CUESHEET").and_then(|mut values| values.next()).map(CueSheet::parse);
+        if let Some(Ok(sheet)) = tagged {
+            return Some(sheet);
+        }
+
+        let sample_rate = tag.get_streaminfo()?.sample_rate.max(1) as u64;
+        let at = |samples: u64| Duration::from_nanos(samples * 1_000_000_000 / sample_rate);
+        let block = tag.get_blocks(BlockType::CueSheet).find_map(|block| match block {
+            Block::CueSheet(cue_sheet) => Some(cue_sheet),
+            _ => None,
+        })?;
+
+        // Numbers past 99 are the lead-out, which marks the end of the last track
+        let tracks: Vec<CueTrack> = block.tracks.iter()
+            .filter(|track| track.is_audio && (1..=99).contains(&track.number))
+            .filter_map(|track| {
+                let index = |point: u8| track.indices.iter().find(|index| index.point_num == point);
+                Some(CueTrack {
+                    number: track.number as u32,
+                    start: at(track.offset + index(1)?.offset),
+                    pregap: index(0).map(|index| at(track.offset + index.offset)),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        (!tracks.is_empty()).then(|| CueSheet { tracks, ..Default::default() })
     }
 }
 
@@ -144,4 +191,52 @@ mod tests {
             assert!(metadata.duration.is_some());
         }
     }
+
+    #[test]
+    fn test_write_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tagged.flac");
+        std::fs::copy("test/testaudio-short.flac", &path).unwrap();
+        let parser = FlacMetadataParser::new();
+
+        let tags = |value: &str| vec![("REPLAYGAIN_ALBUM_GAIN".to_string(), value.to_string())];
+        parser.write_tags(&path, &tags("-3.00 dB")).unwrap();
+        parser.write_tags(&path, &tags("+1.50 dB")).unwrap();
+
+        let metadata = parser.parse(&path).unwrap();
+        assert_eq!(metadata.extra.get("REPLAYGAIN_ALBUM_GAIN").map(String::as_str), Some("+1.50 dB"));
+        assert_eq!(metadata.duration, parser.parse(Path::new("test/testaudio-short.flac")).unwrap().duration);
+    }
+
+    #[test]
+    fn test_cue_sheet_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("album.flac");
+        std::fs::copy("test/testaudio-short.flac", &path).unwrap();
+        let parser = FlacMetadataParser::new();
+        assert!(parser.cue_sheet(&path).is_none());
+
+        let index = |point_num, offset| metaflac::block::CueSheetTrackIndex { offset, point_num };
+        let track = |number, offset, indices| metaflac::block::CueSheetTrack {
+            number,
+            offset,
+            indices,
+            ..metaflac::block::CueSheetTrack::new()
+        };
+        let mut cue_sheet = metaflac::block::CueSheet::new();
+        cue_sheet.tracks = vec![
+            track(1, 0, vec![index(1, 0)]),
+            track(2, 19200, vec![index(0, 0), index(1, 9600)]),
+            track(170, 48000, vec![]),
+        ];
+        let mut tag = Tag::read_from_path(&path).unwrap();
+        tag.push_block(Block::CueSheet(cue_sheet));
+        tag.save().unwrap();
+
+        // Offsets are in samples at 48kHz, index points counted from their track's offset
+        let sheet = parser.cue_sheet(&path).unwrap();
+        assert_eq!(sheet.tracks.len(), 2);
+        assert_eq!(sheet.tracks[1].pregap, Some(Duration::from_millis(400)));
+        assert_eq!(sheet.span(2), Some((Duration::from_millis(600), None)));
+    }
 }
@@ -1,13 +1,11 @@
 use std::path::Path;
-use id3::{Tag, TagLike};
-use symphonia::core::probe::Hint;
-use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
-use symphonia::core::meta::MetadataOptions;
-use std::fs::File;
+use id3::frame::ExtendedText;
+use id3::{Tag, TagLike, Version};
 
-use crate::metadata::{Metadata, MetadataError, MetadataParser};
-use super::matches_extension;
+use crate::audio::formats::{detect_source_format, FileFormat};
+use crate::media::MediaSource;
+use crate::audio::formats::mp3::Mp3Decoder;
+use crate::metadata::{chapters, Metadata, MetadataError, MetadataParser};
 
 pub struct Id3Parser;
 
@@ -16,7 +14,7 @@ impl Id3Parser {
         Id3Parser
     }
 
-    fn parse_id3_tag(&self, tag: &Tag) -> Metadata {
+    pub(crate) fn parse_id3_tag(&self, tag: &Tag) -> Metadata {
         let mut metadata = Metadata::default();
 
         metadata.title = tag.title().map(String::from);
@@ -26,68 +24,48 @@ impl Id3Parser {
         metadata.year = tag.year().map(|y| y as u32);
         metadata.genre = tag.genre().map(String::from);
 
-        // Add any additional ID3 frames to extra
-        for frame in tag.frames() {
+        // Add any additional ID3 frames to extra; chapters are read into their own list
+        metadata.chapters = chapters::from_id3(tag);
+        for frame in tag.frames().filter(|frame| !matches!(frame.id(), "CHAP" | "CTOC")) {
             metadata.extra.insert(
                 frame.id().to_string(),
                 frame.content().to_string()
             );
         }
 
+        // TXXX frames share an ID, so key each by its description (REPLAYGAIN_TRACK_GAIN, ...)
+        for text in tag.extended_texts() {
+            metadata.extra.insert(text.description.to_uppercase(), text.value.clone());
+        }
+
         metadata
     }
 
-    fn parse_audio_properties(&self, path: &Path) -> Result<(f64, u32, u8, u32), MetadataError> {
-        let file = File::open(path).map_err(MetadataError::IoError)?;
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
-
-        let hint = Hint::new();
-        let format_opts = FormatOptions::default();
-        let metadata_opts = MetadataOptions::default();
-
-        let probed = symphonia::default::get_probe()
-            .format(&hint, mss, &format_opts, &metadata_opts)
+    fn parse_audio_properties(&self, source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        // Frame headers plus Xing/VBRI/LAME tags give exact timing, even for VBR streams
+        let info = Mp3Decoder::new()
+            .read_source_info(source)
             .map_err(|e| MetadataError::ParseError(e.to_string()))?;
 
-        let track = probed
-            .format
-            .default_track()
-            .ok_or_else(|| MetadataError::ParseError("No default track found".to_string()))?;
-
-        let params = &track.codec_params;
-        
-        // Calculate duration from total frames and sample rate
-        let duration = params.n_frames
-            .map(|frames| frames as f64 / params.sample_rate.unwrap_or(44100) as f64)
-            .unwrap_or_default();
-        
-        let sample_rate = params.sample_rate.unwrap_or_default();
-        let channels = params.channels.map(|ch| ch.count() as u8).unwrap_or_default();
-        
-        // For MP3, estimate bitrate from file size and duration
-        let bit_rate = if duration > 0.0 {
-            let file_size = std::fs::metadata(path)
-                .map(|m| m.len())
-                .unwrap_or(0) as f64;
-            ((file_size * 8.0) / (duration * 1000.0)) as u32 // Convert to kbps
-        } else {
-            0
-        };
-
-        Ok((duration, sample_rate, channels, bit_rate))
+        Ok((
+            info.duration.as_secs_f64(),
+            info.header.sample_rate,
+            info.header.channels as u8,
+            info.bit_rate,
+        ))
     }
 }
 
 impl MetadataParser for Id3Parser {
-    fn parse(&self, path: &Path) -> Result<Metadata, MetadataError> {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
         // Read ID3 tags
-        let tag = Tag::read_from_path(path)
+        let tag = Tag::read_from2(source.open().map_err(MetadataError::IoError)?)
             .map_err(|e| MetadataError::ParseError(e.to_string()))?;
         
         let mut metadata = self.parse_id3_tag(&tag);
 
         // Add audio properties
-        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(path) {
+        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(source) {
             metadata.duration = Some(duration);
             metadata.sample_rate = Some(sample_rate);
             metadata.channels = Some(channels);
@@ -97,8 +75,31 @@ impl MetadataParser for Id3Parser {
         Ok(metadata)
     }
 
-    fn supports_format(&self, path: &Path) -> bool {
-        matches_extension(path, &["mp3"])
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Mp3))
+    }
+
+    fn write_tags(&self, path: &Path, tags: &[(String, String)]) -> Result<(), MetadataError> {
+        let mut tag = match Tag::read_from_path(path) {
+            Ok(tag) => tag,
+            Err(e) if matches!(e.kind, id3::ErrorKind::NoTag) => Tag::new(),
+            Err(e) => return Err(MetadataError::ParseError(e.to_string())),
+        };
+
+        for (key, value) in tags {
+            // Other taggers write these descriptions in lower case
+            let stale: Vec<String> = tag.extended_texts()
+                .filter(|text| text.description.eq_ignore_ascii_case(key))
+                .map(|text| text.description.clone())
+                .collect();
+            for description in stale {
+                tag.remove_extended_text(Some(&description), None);
+            }
+            tag.add_frame(ExtendedText { description: key.clone(), value: value.clone() });
+        }
+
+        tag.write_to_path(path, Version::Id3v24)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))
     }
 }
 
@@ -130,4 +131,88 @@ mod tests {
             assert!(metadata.duration.is_some());
         }
     }
+
+    #[test]
+    fn test_supports_format_by_content() {
+        let parser = Id3Parser::new();
+        let dir = tempfile::tempdir().unwrap();
+
+        let extensionless = dir.path().join("download");
+        std::fs::copy("test/testaudio-short.mp3", &extensionless).unwrap();
+        assert!(parser.supports_format(&extensionless));
+        assert!(parser.parse(&extensionless).is_ok());
+
+        let mislabelled = dir.path().join("track.mp3");
+        std::fs::copy("test/testaudio-short.flac", &mislabelled).unwrap();
+        assert!(!parser.supports_format(&mislabelled));
+    }
+
+    #[test]
+    fn test_exact_mp3_timing() {
+        let parser = Id3Parser::new();
+        let metadata = parser.parse(Path::new("test/testaudio-short.mp3")).unwrap();
+
+        assert_eq!(metadata.duration, Some(1.0));
+        assert_eq!(metadata.sample_rate, Some(48000));
+        assert_eq!(metadata.channels, Some(1));
+        assert_eq!(metadata.bit_rate, Some(64));
+    }
+
+    #[test]
+    fn test_extended_text_frames_keyed_by_description() {
+        let mut tag = Tag::new();
+        tag.add_frame(id3::frame::ExtendedText {
+            description: "replaygain_track_gain".to_string(),
+            value: "-6.02 dB".to_string(),
+        });
+        tag.add_frame(id3::frame::ExtendedText {
+            description: "REPLAYGAIN_TRACK_PEAK".to_string(),
+            value: "0.5".to_string(),
+        });
+
+        let metadata = Id3Parser::new().parse_id3_tag(&tag);
+        assert_eq!(metadata.extra.get("REPLAYGAIN_TRACK_GAIN").map(String::as_str), Some("-6.02 dB"));
+        assert_eq!(metadata.extra.get("REPLAYGAIN_TRACK_PEAK").map(String::as_str), Some("0.5"));
+    }
+
+    #[test]
+    fn test_write_tags_replaces_extended_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tagged.mp3");
+        std::fs::copy("test/testaudio-short.mp3", &path).unwrap();
+        let parser = Id3Parser::new();
+        let gain = |value: &str| vec![("REPLAYGAIN_TRACK_GAIN".to_string(), value.to_string())];
+
+        parser.write_tags(&path, &gain("-3.00 dB")).unwrap();
+        parser.write_tags(&path, &gain("+1.50 dB")).unwrap();
+
+        let tag = Tag::read_from_path(&path).unwrap();
+        assert_eq!(tag.extended_texts().filter(|text| text.description == "REPLAYGAIN_TRACK_GAIN").count(), 1);
+        let metadata = parser.parse(&path).unwrap();
+        assert_eq!(metadata.extra.get("REPLAYGAIN_TRACK_GAIN").map(String::as_str), Some("+1.50 dB"));
+        assert!(metadata.duration.is_some());
+    }
+
+    #[test]
+    fn test_chapter_frames_become_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.mp3");
+        std::fs::copy("test/testaudio-short.mp3", &path).unwrap();
+        let mut tag = Tag::new();
+        tag.add_frame(id3::frame::Chapter {
+            element_id: "ch0".to_string(),
+            start_time: 250,
+            end_time: 1000,
+            start_offset: u32::MAX,
+            end_offset: u32::MAX,
+            frames: vec![id3::frame::Frame::text("TIT2", "Prologue")],
+        });
+        tag.write_to_path(&path, Version::Id3v24).unwrap();
+
+        let metadata = Id3Parser::new().parse(&path).unwrap();
+        assert_eq!(metadata.chapters.len(), 1);
+        assert_eq!(metadata.chapters[0].title.as_deref(), Some("Prologue"));
+        assert_eq!(metadata.chapters[0].start, std::time::Duration::from_millis(250));
+        assert!(!metadata.extra.contains_key("CHAP"));
+    }
 }
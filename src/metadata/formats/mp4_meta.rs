@@ -0,0 +1,199 @@
+use std::io::{BufReader, Read, Seek};
+
+use crate::audio::formats::mp4;
+use crate::audio::formats::{detect_source_format, AudioDecoder, DecoderType, FileFormat};
+use crate::media::MediaSource;
+use crate::metadata::{Metadata, MetadataError, MetadataParser};
+
+/// Largest `data` payload we buffer; cover art is only flagged, never loaded
+const MAX_TEXT_ITEM_SIZE: u64 = 64 * 1024;
+
+/// Reads iTunes-style `ilst` atoms from MP4/M4A files, whatever codec the track uses
+pub struct Mp4Parser;
+
+impl Mp4Parser {
+    pub fn new() -> Self {
+        Mp4Parser
+    }
+
+    fn parse_ilst<R: Read + Seek>(&self, reader: &mut R) -> std::io::Result<Metadata> {
+        let mut metadata = Metadata::default();
+        let Some(ilst) = mp4::find_ilst(reader)? else {
+            return Ok(metadata);
+        };
+
+        for item in mp4::read_boxes(reader, ilst.start, ilst.end())? {
+            let children = mp4::read_boxes(reader, item.start, item.end())?;
+            let Some(data) = children.iter().find(|b| &b.kind == b"data") else {
+                continue;
+            };
+
+            if &item.kind == b"covr" {
+                metadata.extra.insert("HAS_COVER_ART".to_string(), "true".to_string());
+                continue;
+            }
+
+            // data payload: 4-byte type indicator, 4-byte locale, then the value
+            let payload = mp4::read_payload(reader, data, MAX_TEXT_ITEM_SIZE)?;
+            let Some(value) = payload.get(8..) else {
+                continue;
+            };
+            let text = || String::from_utf8_lossy(value).trim_end_matches('\0').to_string();
+
+            match &item.kind {
+                b"\xA9nam" => metadata.title = Some(text()),
+                b"\xA9ART" => metadata.artist = Some(text()),
+                b"\xA9alb" => metadata.album = Some(text()),
+                b"\xA9gen" => metadata.genre = Some(text()),
+                b"\xA9day" => metadata.year = text().get(0..4).and_then(|y| y.parse().ok()),
+                b"trkn" => {
+                    let (track, total) = number_pair(value);
+                    metadata.track = track;
+                    if let Some(total) = total {
+                        metadata.extra.insert("TRACKTOTAL".to_string(), total.to_string());
+                    }
+                }
+                b"disk" => {
+                    let (disc, total) = number_pair(value);
+                    if let Some(disc) = disc {
+                        metadata.extra.insert("DISCNUMBER".to_string(), disc.to_string());
+                    }
+                    if let Some(total) = total {
+                        metadata.extra.insert("DISCTOTAL".to_string(), total.to_string());
+                    }
+                }
+                b"aART" => {
+                    metadata.extra.insert("ALBUMARTIST".to_string(), text());
+                }
+                b"\xA9wrt" => {
+                    metadata.extra.insert("COMPOSER".to_string(), text());
+                }
+                b"\xA9too" => {
+                    metadata.extra.insert("ENCODER".to_string(), text());
+                }
+                b"cprt" => {
+                    metadata.extra.insert("COPYRIGHT".to_string(), text());
+                }
+                b"\xA9cmt" => {
+                    metadata.extra.insert("COMMENT".to_string(), text());
+                }
+                // Freeform `----` items name themselves (iTunSMPB, replaygain_track_gain, ...)
+                b"----" => {
+                    if let Some(name) = children.iter().find(|b| &b.kind == b"name") {
+                        let name = mp4::read_payload(reader, name, 256)?;
+                        if let Some(name) = name.get(4..) {
+                            metadata.extra.insert(String::from_utf8_lossy(name).to_uppercase(), text());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    fn parse_audio_properties(&self, source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        // The factory picks the AAC or ALAC decoder from the sample entry
+        let format = DecoderType::for_source(source)
+            .probe_source(source)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?;
+        let duration = format.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+
+        // Calculate average bit rate from file size and duration
+        let bit_rate = if duration > 0.0 {
+            let file_size = source.open().ok().and_then(|stream| stream.byte_len()).unwrap_or(0) as f64;
+            ((file_size * 8.0) / (duration * 1000.0)) as u32
+        } else {
+            0
+        };
+
+        Ok((duration, format.sample_rate, format.channels as u8, bit_rate))
+    }
+}
+
+impl Default for Mp4Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode the `(number, total)` pair stored by `trkn` and `disk`
+fn number_pair(value: &[u8]) -> (Option<u32>, Option<u32>) {
+    let read = |offset: usize| {
+        value.get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+            .filter(|&n| n > 0)
+    };
+    (read(2), read(4))
+}
+
+impl MetadataParser for Mp4Parser {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
+        let mut reader = BufReader::new(source.open().map_err(MetadataError::IoError)?);
+        let mut metadata = self.parse_ilst(&mut reader).map_err(MetadataError::IoError)?;
+
+        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(source) {
+            metadata.duration = Some(duration);
+            metadata.sample_rate = Some(sample_rate);
+            metadata.channels = Some(channels);
+            metadata.bit_rate = Some(bit_rate);
+        }
+
+        Ok(metadata)
+    }
+
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Mp4(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_supports_format() {
+        let parser = Mp4Parser::new();
+        assert!(parser.supports_format(Path::new("test.m4a")));
+        assert!(parser.supports_format(Path::new("test/testaudio-short-alac.m4a")));
+        assert!(parser.supports_format(Path::new("test/testaudio-silence-aac.m4a")));
+        assert!(!parser.supports_format(Path::new("test.mp3")));
+    }
+
+    #[test]
+    fn test_parse_ilst_atoms() {
+        let parser = Mp4Parser::new();
+        for name in ["test/testaudio-short-alac.m4a", "test/testaudio-silence-aac.m4a"] {
+            let metadata = parser.parse(Path::new(name)).unwrap();
+            assert_eq!(metadata.title.as_deref(), Some("Test Audio Short"), "{}", name);
+            assert_eq!(metadata.artist.as_deref(), Some("PlayTUI"), "{}", name);
+            assert_eq!(metadata.track, Some(3), "{}", name);
+            assert_eq!(metadata.extra.get("TRACKTOTAL").map(String::as_str), Some("12"), "{}", name);
+            assert_eq!(metadata.extra.get("ALBUMARTIST").map(String::as_str), Some("PlayTUI Fixtures"), "{}", name);
+            assert_eq!(metadata.extra.get("HAS_COVER_ART").map(String::as_str), Some("true"), "{}", name);
+            assert_eq!(metadata.sample_rate, Some(48000), "{}", name);
+            assert_eq!(metadata.channels, Some(1), "{}", name);
+        }
+
+        let alac = parser.parse(Path::new("test/testaudio-short-alac.m4a")).unwrap();
+        assert_eq!(alac.duration, Some(1.0));
+    }
+
+    #[test]
+    fn test_number_pair() {
+        assert_eq!(number_pair(&[0, 0, 0, 3, 0, 12, 0, 0]), (Some(3), Some(12)));
+        assert_eq!(number_pair(&[0, 0, 0, 7, 0, 0]), (Some(7), None));
+        assert_eq!(number_pair(&[0, 0]), (None, None));
+    }
+
+    #[test]
+    fn test_untagged_file() {
+        let parser = Mp4Parser::new();
+        let mut reader = std::io::Cursor::new(b"\0\0\0\x10ftypM4A \0\0\0\0".to_vec());
+        let metadata = parser.parse_ilst(&mut reader).unwrap();
+        assert!(metadata.title.is_none());
+        assert!(metadata.extra.is_empty());
+    }
+}
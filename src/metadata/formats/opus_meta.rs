@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use crate::audio::formats::opus::OpusDecoder;
+use crate::audio::formats::{detect_source_format, AudioDecoder, FileFormat, OggCodec};
+use crate::media::MediaSource;
+use crate::metadata::{chapters, Metadata, MetadataError, MetadataParser};
+use super::vorbis::TagExtractor;
+
+pub struct OpusParser;
+
+impl OpusParser {
+    pub fn new() -> Self {
+        OpusParser
+    }
+
+    fn parse_audio_properties(&self, source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        // Only the identification header and granules are read, nothing is decoded
+        let format = OpusDecoder::new()
+            .probe_source(source)
+            .map_err(|e| MetadataError::ParseError(e.to_string()))?;
+        let duration = format.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+
+        // Opus is always VBR; average over the whole file
+        let bit_rate = if duration > 0.0 {
+            let file_size = source.open().ok().and_then(|stream| stream.byte_len()).unwrap_or(0) as f64;
+            ((file_size * 8.0) / (duration * 1000.0)) as u32
+        } else {
+            0
+        };
+
+        Ok((duration, format.sample_rate, format.channels as u8, bit_rate))
+    }
+}
+
+impl Default for OpusParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataParser for OpusParser {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
+        // OpusTags uses the Vorbis comment layout
+        let mut metadata = TagExtractor::extract_metadata(source)?;
+
+        if let Ok((duration, sample_rate, channels, bit_rate)) = self.parse_audio_properties(source) {
+            metadata.duration = Some(duration);
+            metadata.sample_rate = Some(sample_rate);
+            metadata.channels = Some(channels);
+            metadata.bit_rate = Some(bit_rate);
+        }
+        metadata.chapters = chapters::from_vorbis_comments(&metadata.extra, metadata.duration.map(Duration::from_secs_f64));
+
+        Ok(metadata)
+    }
+
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Ogg(OggCodec::Opus)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_supports_format() {
+        let parser = OpusParser::new();
+        assert!(parser.supports_format(Path::new("test.opus")));
+        assert!(parser.supports_format(Path::new("test/testaudio-short.opus")));
+        assert!(!parser.supports_format(Path::new("test.ogg")));
+        assert!(!parser.supports_format(Path::new("test/testaudio-short.ogg")));
+    }
+
+    #[test]
+    fn test_parse_opus_tags() {
+        let parser = OpusParser::new();
+        let metadata = parser.parse(Path::new("test/testaudio-short.opus")).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Test Audio Short"));
+        assert_eq!(metadata.artist.as_deref(), Some("PlayTUI"));
+        assert_eq!(metadata.track, Some(3));
+        assert_eq!(metadata.duration, Some(1.0));
+        assert_eq!(metadata.sample_rate, Some(48000));
+        assert_eq!(metadata.channels, Some(1));
+    }
+}
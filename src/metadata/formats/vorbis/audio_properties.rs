@@ -1,17 +1,17 @@
-use std::path::Path;
-use std::fs::File;
 use symphonia::core::probe::Hint;
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
+use crate::media::MediaSource;
 use crate::metadata::MetadataError;
 
 pub struct AudioPropertiesExtractor;
 
 impl AudioPropertiesExtractor {
-    pub fn parse_properties(path: &Path) -> Result<(f64, u32, u8, u32), MetadataError> {
-        let file = File::open(path).map_err(MetadataError::IoError)?;
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    pub fn parse_properties(source: &MediaSource) -> Result<(f64, u32, u8, u32), MetadataError> {
+        let stream = source.open().map_err(MetadataError::IoError)?;
+        let file_size = stream.byte_len().unwrap_or(0);
+        let mss = MediaSourceStream::new(Box::new(stream), Default::default());
 
         let hint = Hint::new();
         let format_opts = FormatOptions::default();
@@ -44,10 +44,7 @@ impl AudioPropertiesExtractor {
         
         // Calculate average bit rate from file size and duration
         let bit_rate = if duration > 0.0 {
-            let file_size = std::fs::metadata(path)
-                .map(|m| m.len())
-                .unwrap_or(0) as f64;
-            ((file_size * 8.0) / (duration * 1000.0)) as u32 // Convert to kbps
+            ((file_size as f64 * 8.0) / (duration * 1000.0)) as u32 // Convert to kbps
         } else {
             0
         };
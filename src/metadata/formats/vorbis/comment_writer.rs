@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::Path;
+use crate::metadata::MetadataError;
+
+/// Header packets every Vorbis stream opens with: identification, comment and setup
+const HEADER_PACKETS: usize = 3;
+const COMMENT_MAGIC: &[u8] = b"\x03vorbis";
+const MAX_SEGMENTS: usize = 255;
+
+const CONTINUED: u8 = 0x01;
+const FIRST_PAGE: u8 = 0x02;
+/// Granule position of a page on which no packet ends
+const NO_GRANULE: u64 = u64::MAX;
+
+/// One Ogg page; the checksum is recomputed whenever it is written
+struct Page {
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    segments: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl Page {
+    /// Parse the page at the start of `data`, returning it and its length in bytes
+    fn parse(data: &[u8]) -> Result<(Page, usize), MetadataError> {
+        let invalid = || MetadataError::ParseError("Truncated or invalid Ogg page".to_string());
+        if data.len() < 27 || &data[..4] != b"OggS" || data[4] != 0 {
+            return Err(invalid());
+        }
+        let header_len = 27 + data[26] as usize;
+        let segments = data.get(27..header_len).ok_or_else(invalid)?.to_vec();
+        let body_len: usize = segments.iter().map(|&lace| lace as usize).sum();
+        let body = data.get(header_len..header_len + body_len).ok_or_else(invalid)?.to_vec();
+
+        let page = Page {
+            header_type: data[5],
+            granule: u64::from_le_bytes(data[6..14].try_into().unwrap()),
+            serial: u32::from_le_bytes(data[14..18].try_into().unwrap()),
+            sequence: u32::from_le_bytes(data[18..22].try_into().unwrap()),
+            segments,
+            body,
+        };
+        Ok((page, header_len + body_len))
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        out.extend_from_slice(b"OggS");
+        out.push(0);
+        out.push(self.header_type);
+        out.extend_from_slice(&self.granule.to_le_bytes());
+        out.extend_from_slice(&self.serial.to_le_bytes());
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.push(self.segments.len() as u8);
+        out.extend_from_slice(&self.segments);
+        out.extend_from_slice(&self.body);
+
+        let crc = crc32(&out[start..]);
+        out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+    }
+}
+
+/// The CRC-32 Ogg pages carry: polynomial 0x04c11db7, unreflected, no final XOR
+fn crc32(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, &byte| {
+        let mut crc = crc ^ ((byte as u32) << 24);
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 { (crc << 1) ^ 0x04c1_1db7 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// Lay `packets` out over as many pages as they need, numbered from `sequence`
+fn paginate(packets: &[&[u8]], serial: u32, sequence: u32, header_type: u8) -> Vec<Page> {
+    let mut pages = Vec::new();
+    let mut page = Page { header_type, granule: NO_GRANULE, serial, sequence, segments: Vec::new(), body: Vec::new() };
+
+    for packet in packets {
+        let mut laces: Vec<u8> = vec![255; packet.len() / 255];
+        laces.push((packet.len() % 255) as u8);
+        let mut offset = 0;
+        for (index, &lace) in laces.iter().enumerate() {
+            if page.segments.len() == MAX_SEGMENTS {
+                let next = Page {
+                    header_type: if index > 0 { CONTINUED } else { 0 },
+                    granule: NO_GRANULE,
+                    serial,
+                    sequence: page.sequence + 1,
+                    segments: Vec::new(),
+                    body: Vec::new(),
+                };
+                pages.push(std::mem::replace(&mut page, next));
+            }
+            page.segments.push(lace);
+            page.body.extend_from_slice(&packet[offset..offset + lace as usize]);
+            offset += lace as usize;
+        }
+        // Header packets all sit at granule position zero
+        page.granule = 0;
+    }
+    pages.push(page);
+    pages
+}
+
+/// Replace the fields named in `tags` within a comment header packet, keeping the rest
+fn rewrite_comment(packet: &[u8], tags: &[(String, String)]) -> Result<Vec<u8>, MetadataError> {
+    let invalid = || MetadataError::ParseError("Invalid Vorbis comment header".to_string());
+    let mut pos = COMMENT_MAGIC.len();
+    let read_u32 = |pos: &mut usize| -> Result<usize, MetadataError> {
+        let bytes = packet.get(*pos..*pos + 4).ok_or_else(invalid)?;
+        *pos += 4;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+
+    let vendor_len = read_u32(&mut pos)?;
+    let vendor = packet.get(pos..pos + vendor_len).ok_or_else(invalid)?;
+    pos += vendor_len;
+    let count = read_u32(&mut pos)?;
+    let mut comments = Vec::with_capacity(count + tags.len());
+    for _ in 0..count {
+        let len = read_u32(&mut pos)?;
+        let comment = packet.get(pos..pos + len).ok_or_else(invalid)?;
+        pos += len;
+        let key = comment.split(|&b| b == b'=').next().unwrap_or_default();
+        if !tags.iter().any(|(name, _)| name.as_bytes().eq_ignore_ascii_case(key)) {
+            comments.push(comment.to_vec());
+        }
+    }
+    comments.extend(tags.iter().map(|(key, value)| format!("{}={}", key, value).into_bytes()));
+
+    let mut out = COMMENT_MAGIC.to_vec();
+    out.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    out.extend_from_slice(vendor);
+    out.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in &comments {
+        out.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        out.extend_from_slice(comment);
+    }
+    // Framing bit
+    out.push(1);
+    Ok(out)
+}
+
+/// Rewrite the comment header of the Ogg Vorbis file at `path` with `tags` merged in.
+/// Pages after the headers are renumbered if the comment now spans more or fewer pages.
+pub(super) fn write_comments(path: &Path, tags: &[(String, String)]) -> Result<(), MetadataError> {
+    let data = fs::read(path).map_err(MetadataError::IoError)?;
+
+    // Gather the header packets and the pages they occupy
+    let mut offset = 0;
+    let mut header_pages = 0;
+    let mut serial = None;
+    let mut packets: Vec<Vec<u8>> = Vec::new();
+    let mut partial = Vec::new();
+    while packets.len() < HEADER_PACKETS {
+        let (page, len) = Page::parse(&data[offset..])?;
+        offset += len;
+        header_pages += 1;
+        if *serial.get_or_insert(page.serial) != page.serial {
+            return Err(MetadataError::UnsupportedFormat);
+        }
+        let mut start = 0;
+        for &lace in &page.segments {
+            partial.extend_from_slice(&page.body[start..start + lace as usize]);
+            start += lace as usize;
+            if lace < 255 {
+                packets.push(std::mem::take(&mut partial));
+            }
+        }
+    }
+    let serial = serial.unwrap_or_default();
+    // Audio sharing the last header page would have to be repaginated too
+    if packets.len() > HEADER_PACKETS || !partial.is_empty() {
+        return Err(MetadataError::ParseError("Audio data shares a page with the Vorbis headers".to_string()));
+    }
+    if !packets[0].starts_with(b"\x01vorbis") || !packets[1].starts_with(COMMENT_MAGIC) {
+        return Err(MetadataError::UnsupportedFormat);
+    }
+
+    let comment = rewrite_comment(&packets[1], tags)?;
+    let mut pages = paginate(&[&packets[0]], serial, 0, FIRST_PAGE);
+    pages.extend(paginate(&[&comment, &packets[2]], serial, 1, 0));
+
+    let mut out = Vec::with_capacity(data.len() + comment.len());
+    for page in &pages {
+        page.write(&mut out);
+    }
+    if pages.len() == header_pages {
+        out.extend_from_slice(&data[offset..]);
+    } else {
+        let shift = pages.len() as i64 - header_pages as i64;
+        while offset < data.len() {
+            let (mut page, len) = Page::parse(&data[offset..])?;
+            if page.serial == serial {
+                page.sequence = (page.sequence as i64 + shift) as u32;
+            }
+            page.write(&mut out);
+            offset += len;
+        }
+    }
+
+    // Write alongside and swap in, so a failure never leaves a half-written file
+    let temp = path.with_extension("playtui-tmp");
+    fs::write(&temp, &out).map_err(MetadataError::IoError)?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        MetadataError::IoError(e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc_matches_encoder() {
+        let data = fs::read("test/testaudio-short.ogg").unwrap();
+        let (page, len) = Page::parse(&data).unwrap();
+        let mut out = Vec::new();
+        page.write(&mut out);
+        assert_eq!(out, data[..len]);
+    }
+
+    #[test]
+    fn test_paginate_long_packet() {
+        // 300 bytes need two laces: 255 and 45; 70000 bytes spill onto a second page
+        let pages = paginate(&[&[1; 300]], 7, 0, FIRST_PAGE);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].segments, vec![255, 45]);
+
+        let pages = paginate(&[&vec![1; 70000], &[2; 10]], 7, 1, 0);
+        assert_eq!(pages.len(), 2);
+        assert_eq!((pages[0].granule, pages[1].granule), (NO_GRANULE, 0));
+        assert_eq!((pages[1].header_type, pages[1].sequence), (CONTINUED, 2));
+        assert_eq!(pages.iter().map(|page| page.body.len()).sum::<usize>(), 70010);
+    }
+}
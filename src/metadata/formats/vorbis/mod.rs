@@ -1,8 +1,11 @@
 mod parser;
 mod tag_extractor;
 mod audio_properties;
+mod comment_writer;
 
 #[cfg(test)]
 mod tests;
 
 pub use parser::VorbisParser;
+// Opus carries the same comment header, so its parser reads tags the same way
+pub(crate) use tag_extractor::TagExtractor;
@@ -1,7 +1,9 @@
 use std::path::Path;
-use crate::metadata::{Metadata, MetadataError, MetadataParser};
-use crate::metadata::formats::matches_extension;
-use super::{tag_extractor::TagExtractor, audio_properties::AudioPropertiesExtractor};
+use std::time::Duration;
+use crate::metadata::{chapters, Metadata, MetadataError, MetadataParser};
+use crate::audio::formats::{detect_source_format, FileFormat, OggCodec};
+use crate::media::MediaSource;
+use super::{tag_extractor::TagExtractor, audio_properties::AudioPropertiesExtractor, comment_writer};
 
 pub struct VorbisParser;
 
@@ -12,22 +14,27 @@ impl VorbisParser {
 }
 
 impl MetadataParser for VorbisParser {
-    fn parse(&self, path: &Path) -> Result<Metadata, MetadataError> {
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
         // First get the metadata from tags
-        let mut metadata = TagExtractor::extract_metadata(path)?;
+        let mut metadata = TagExtractor::extract_metadata(source)?;
 
         // Then add audio properties
-        if let Ok((duration, sample_rate, channels, bit_rate)) = AudioPropertiesExtractor::parse_properties(path) {
+        if let Ok((duration, sample_rate, channels, bit_rate)) = AudioPropertiesExtractor::parse_properties(source) {
             metadata.duration = Some(duration);
             metadata.sample_rate = Some(sample_rate);
             metadata.channels = Some(channels);
             metadata.bit_rate = Some(bit_rate);
         }
+        metadata.chapters = chapters::from_vorbis_comments(&metadata.extra, metadata.duration.map(Duration::from_secs_f64));
 
         Ok(metadata)
     }
 
-    fn supports_format(&self, path: &Path) -> bool {
-        matches_extension(path, &["ogg"])
+    fn supports_source(&self, source: &MediaSource) -> bool {
+        matches!(detect_source_format(source), Some(FileFormat::Ogg(codec)) if codec != OggCodec::Opus)
+    }
+
+    fn write_tags(&self, path: &Path, tags: &[(String, String)]) -> Result<(), MetadataError> {
+        comment_writer::write_comments(path, tags)
     }
 }
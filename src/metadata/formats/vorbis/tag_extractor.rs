@@ -1,17 +1,16 @@
-use std::path::Path;
-use std::fs::File;
 use symphonia::core::probe::Hint;
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::{MetadataOptions, Tag};
+use crate::media::MediaSource;
 use crate::metadata::{Metadata, MetadataError};
 
 pub struct TagExtractor;
 
 impl TagExtractor {
-    pub fn extract_metadata(path: &Path) -> Result<Metadata, MetadataError> {
-        let file = File::open(path).map_err(MetadataError::IoError)?;
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    pub fn extract_metadata(source: &MediaSource) -> Result<Metadata, MetadataError> {
+        let stream = source.open().map_err(MetadataError::IoError)?;
+        let mss = MediaSourceStream::new(Box::new(stream), Default::default());
 
         let hint = Hint::new();
         let format_opts = FormatOptions::default();
@@ -25,3 +25,42 @@ fn test_parse_ogg() {
         assert!(metadata.duration.is_some());
     }
 }
+
+#[test]
+fn test_write_tags_keeps_audio_intact() {
+    use crate::audio::formats::{get_decoder, AudioDecoder};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tagged.ogg");
+    std::fs::copy("test/testaudio-short.ogg", &path).unwrap();
+    let parser = VorbisParser::new();
+    let original = parser.parse(&path).unwrap();
+
+    // Long enough to push the comment header onto more pages than before
+    let long = "x".repeat(70000);
+    let tags = vec![
+        ("replaygain_track_gain".to_string(), "-3.00 dB".to_string()),
+        ("COMMENT".to_string(), long.clone()),
+    ];
+    parser.write_tags(&path, &tags).unwrap();
+    parser.write_tags(&path, &[("REPLAYGAIN_TRACK_GAIN".to_string(), "+1.50 dB".to_string())]).unwrap();
+
+    let metadata = parser.parse(&path).unwrap();
+    assert_eq!(metadata.extra.get("REPLAYGAIN_TRACK_GAIN").map(String::as_str), Some("+1.50 dB"));
+    assert_eq!(metadata.extra.get("COMMENT"), Some(&long));
+    assert_eq!(metadata.title, original.title);
+
+    let decode = |path: &Path| {
+        let mut reader = get_decoder(path).decode(path).unwrap();
+        let mut samples = Vec::new();
+        let mut buffer = vec![0.0f32; 4096];
+        loop {
+            let read = reader.read(&mut buffer).unwrap();
+            if read == 0 {
+                break samples;
+            }
+            samples.extend_from_slice(&buffer[..read]);
+        }
+    };
+    assert_eq!(decode(&path), decode(Path::new("test/testaudio-short.ogg")));
+}
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::collections::HashMap;
+use crate::media::MediaSource;
 
 /// Represents the metadata of an audio file
 #[derive(Debug, Clone, Default)]
@@ -26,6 +27,24 @@ pub struct Metadata {
     pub bit_rate: Option<u32>,
     /// Additional format-specific metadata
     pub extra: HashMap<String, String>,
+    /// Chapters, in playing order
+    pub chapters: Vec<Chapter>,
+}
+
+impl Metadata {
+    /// Whether this track comes straight after `previous` on the same album, going by
+    /// the album name and, when both have one, the track number
+    pub fn continues_album(&self, previous: &Metadata) -> bool {
+        let same_album = match (self.album.as_deref(), previous.album.as_deref()) {
+            (Some(album), Some(previous)) => album.trim().eq_ignore_ascii_case(previous.trim()),
+            _ => false,
+        };
+        let consecutive = match (self.track, previous.track) {
+            (Some(track), Some(previous)) => track == previous + 1,
+            _ => true,
+        };
+        same_album && consecutive
+    }
 }
 
 /// Error types for metadata operations
@@ -41,13 +60,47 @@ pub enum MetadataError {
     MissingField(String),
 }
 
-/// Trait for metadata parsing
+impl std::fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::IoError(e) => write!(f, "I/O error: {}", e),
+            MetadataError::UnsupportedFormat => write!(f, "unsupported format"),
+            MetadataError::ParseError(message) => write!(f, "{}", message),
+            MetadataError::MissingField(field) => write!(f, "missing field {}", field),
+        }
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+/// Trait for metadata parsing, from any `MediaSource`
 pub trait MetadataParser {
+    /// Parse metadata from a source
+    fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError>;
+
+    /// Check if this parser supports the source's format
+    fn supports_source(&self, source: &MediaSource) -> bool;
+
     /// Parse metadata from a file path
-    fn parse(&self, path: &Path) -> Result<Metadata, MetadataError>;
-    
+    fn parse(&self, path: &Path) -> Result<Metadata, MetadataError> {
+        self.parse_source(&MediaSource::from_path(path))
+    }
+
     /// Check if this parser supports the given file format
-    fn supports_format(&self, path: &Path) -> bool;
+    fn supports_format(&self, path: &Path) -> bool {
+        self.supports_source(&MediaSource::from_path(path))
+    }
+
+    /// Write `tags` into the file, replacing any values already under those keys
+    fn write_tags(&self, _path: &Path, _tags: &[(String, String)]) -> Result<(), MetadataError> {
+        Err(MetadataError::UnsupportedFormat)
+    }
+
+    /// CUE sheet stored in the file, such as in a CUESHEET tag; read without decoding
+    /// anything, as the library browser asks this of every file it lists
+    fn cue_sheet(&self, _path: &Path) -> Option<CueSheet> {
+        None
+    }
 }
 
 /// Trait for metadata caching
@@ -68,7 +121,11 @@ pub trait MetadataCache {
 pub mod parser;
 pub mod cache;
 pub mod formats;
+pub mod cuesheet;
+pub mod chapters;
 
 // Re-export commonly used items
 pub use self::parser::MetadataManager;
 pub use self::cache::FileMetadataCache;
+pub use self::cuesheet::{CueSheet, CueTrack};
+pub use self::chapters::Chapter;
@@ -1,6 +1,9 @@
 use std::path::Path;
 use std::sync::Arc;
-use crate::metadata::{Metadata, MetadataError, MetadataParser};
+use crate::metadata::{CueSheet, Metadata, MetadataError, MetadataParser};
+use crate::metadata::cuesheet::{self, SidecarIndex};
+use crate::media::MediaSource;
+use crate::metadata::formats::{AiffParser, FlacMetadataParser, Id3Parser, Mp4Parser, OpusParser, VorbisParser};
 
 /// Manages metadata parsing across different file formats
 pub struct MetadataManager {
@@ -15,26 +18,74 @@ impl MetadataManager {
         }
     }
 
+    /// Create a MetadataManager with a parser for every supported format
+    pub fn with_format_parsers() -> Self {
+        let mut manager = Self::new();
+        manager.register_parser(Arc::new(Id3Parser::new()));
+        manager.register_parser(Arc::new(FlacMetadataParser::new()));
+        manager.register_parser(Arc::new(OpusParser::new()));
+        manager.register_parser(Arc::new(VorbisParser::new()));
+        manager.register_parser(Arc::new(Mp4Parser::new()));
+        manager.register_parser(Arc::new(AiffParser::new()));
+        manager
+    }
+
     /// Register a new metadata parser
     pub fn register_parser(&mut self, parser: Arc<dyn MetadataParser + Send + Sync>) {
         self.parsers.push(parser);
     }
 
-    /// Parse metadata from a file
+    /// Parse metadata from a file, or for a CUE track from its sheet over the album file's tags
     pub fn parse_metadata(&self, path: &Path) -> Result<Metadata, MetadataError> {
-        // Find a parser that supports this format
-        for parser in &self.parsers {
-            if parser.supports_format(path) {
-                return parser.parse(path);
-            }
+        if let Some((file, number)) = cuesheet::split_track_path(path) {
+            let album = self.parse_metadata(&file)?;
+            let sheet = self.cue_sheet(&file).ok_or(MetadataError::MissingField("CUESHEET".to_string()))?;
+            return sheet.track_metadata(number, &album).ok_or(MetadataError::MissingField(format!("track {}", number)));
+        }
+
+        self.parse_source(&MediaSource::from_path(path))
+    }
+
+    /// Parse metadata from a source through the parser for its format
+    pub fn parse_source(&self, source: &MediaSource) -> Result<Metadata, MetadataError> {
+        match self.parsers.iter().find(|parser| parser.supports_source(source)) {
+            Some(parser) => parser.parse_source(source),
+            None => Err(MetadataError::UnsupportedFormat),
+        }
+    }
+
+    /// Write tags into a file through the parser for its format
+    pub fn write_tags(&self, path: &Path, tags: &[(String, String)]) -> Result<(), MetadataError> {
+        match self.parsers.iter().find(|parser| parser.supports_format(path)) {
+            Some(parser) => parser.write_tags(path, tags),
+            None => Err(MetadataError::UnsupportedFormat),
         }
-        
-        Err(MetadataError::UnsupportedFormat)
     }
 
     /// Check if any registered parser supports the given format
     pub fn supports_format(&self, path: &Path) -> bool {
-        self.parsers.iter().any(|parser| parser.supports_format(path))
+        let file = cuesheet::split_track_path(path).map(|(file, _)| file);
+        let path = file.as_deref().unwrap_or(path);
+        self.supports_source(&MediaSource::from_path(path))
+    }
+
+    /// Check if any registered parser supports the source's format
+    pub fn supports_source(&self, source: &MediaSource) -> bool {
+        self.parsers.iter().any(|parser| parser.supports_source(source))
+    }
+
+    /// CUE sheet splitting `path` into tracks: a `.cue` file beside it, or failing that one
+    /// stored in the file itself
+    pub fn cue_sheet(&self, path: &Path) -> Option<CueSheet> {
+        self.cue_sheet_with(path, &SidecarIndex::read(cuesheet::directory_of(path)))
+    }
+
+    /// As `cue_sheet`, taking the `.cue` files of the file's directory from ones already read,
+    /// so listing a directory reads each of them once
+    pub fn cue_sheet_with(&self, path: &Path, sidecars: &SidecarIndex) -> Option<CueSheet> {
+        sidecars.find(path).cloned().or_else(|| {
+            self.parsers.iter().find(|parser| parser.supports_format(path))?.cue_sheet(path)
+        })
     }
 }
 
@@ -52,7 +103,7 @@ mod tests {
     struct MockParser;
 
     impl MetadataParser for MockParser {
-        fn parse(&self, _path: &Path) -> Result<Metadata, MetadataError> {
+        fn parse_source(&self, _source: &MediaSource) -> Result<Metadata, MetadataError> {
             Ok(Metadata {
                 title: Some("Test Track".to_string()),
                 artist: Some("Test Artist".to_string()),
@@ -65,14 +116,12 @@ mod tests {
                 channels: Some(2),
                 bit_rate: Some(320),
                 extra: HashMap::new(),
+                chapters: Vec::new(),
             })
         }
 
-        fn supports_format(&self, path: &Path) -> bool {
-            path.extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext == "mp3")
-                .unwrap_or(false)
+        fn supports_source(&self, source: &MediaSource) -> bool {
+            source.extension() == Some("mp3")
         }
     }
 
@@ -89,4 +138,28 @@ mod tests {
         assert_eq!(metadata.title.as_deref(), Some("Test Track"));
         assert_eq!(metadata.artist.as_deref(), Some("Test Artist"));
     }
+
+    #[test]
+    fn test_format_parsers_registered() {
+        let manager = MetadataManager::with_format_parsers();
+        for name in ["a.mp3", "a.flac", "a.ogg", "a.opus", "a.m4a", "a.aiff"] {
+            assert!(manager.supports_format(Path::new(name)), "{}", name);
+        }
+        let metadata = manager.parse_metadata(Path::new("test/testaudio-short.aiff")).unwrap();
+        assert_eq!(metadata.track, Some(3));
+    }
+
+    #[test]
+    fn test_continues_album() {
+        let track = |album: Option<&str>, track: Option<u32>| Metadata {
+            album: album.map(str::to_string),
+            track,
+            ..Default::default()
+        };
+        assert!(track(Some("Live"), Some(2)).continues_album(&track(Some("Live "), Some(1))));
+        assert!(track(Some("live"), None).continues_album(&track(Some("Live"), Some(7))));
+        assert!(!track(Some("Live"), Some(3)).continues_album(&track(Some("Live"), Some(1))));
+        assert!(!track(Some("Live"), Some(2)).continues_album(&track(Some("Studio"), Some(1))));
+        assert!(!track(None, Some(2)).continues_album(&track(None, Some(1))));
+    }
 }
@@ -2,6 +2,7 @@ use std::time::Duration;
 use std::path::PathBuf;
 
 use super::*;
+use super::cuesheet;
 use crate::{create_metadata_manager, create_metadata_cache};
 
 #[test]
@@ -92,3 +93,29 @@ fn test_metadata_fields() {
         }
     }
 }
+
+#[test]
+fn test_cue_track_metadata() {
+    let manager = create_metadata_manager();
+    let dir = tempfile::tempdir().unwrap();
+    let album = dir.path().join("album.flac");
+    std::fs::copy("test/testaudio-short.flac", &album).unwrap();
+    let track = PathBuf::from(cuesheet::track_path(&album, 2));
+    assert!(manager.cue_sheet(&album).is_none());
+    assert!(manager.supports_format(&track));
+    assert!(manager.parse_metadata(&track).is_err());
+
+    // A sheet embedded as a tag is found when no .cue file sits beside the album
+    let sheet = "PERFORMER \"Band\"\nTITLE \"Live\"\nFILE \"album.flac\" WAVE\n  TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 01 00:00:00\n  \
+                 TRACK 02 AUDIO\n    TITLE \"Encore\"\n    INDEX 00 00:00:20\n    INDEX 01 00:00:30\n";
+    manager.write_tags(&album, &[("CUESHEET".to_string(), sheet.to_string())]).unwrap();
+    let metadata = manager.parse_metadata(&track).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Encore"));
+    assert_eq!((metadata.artist.as_deref(), metadata.album.as_deref()), (Some("Band"), Some("Live")));
+    assert_eq!((metadata.track, metadata.sample_rate), (Some(2), Some(48000)));
+    assert!((metadata.duration.unwrap() - 0.6).abs() < 1e-3);
+
+    // A .cue file beside it takes precedence
+    std::fs::write(album.with_extension("cue"), sheet.replace("Encore", "Outro")).unwrap();
+    assert_eq!(manager.parse_metadata(&track).unwrap().title.as_deref(), Some("Outro"));
+}
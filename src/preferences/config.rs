@@ -1,5 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
+use crate::analysis::{Ballistics, SpectrumSettings};
+use crate::audio::dsp::{default_chain, ProcessorStage};
+use crate::audio::eq::{find_preset, EqPreset, FLAT};
+use crate::audio::fade::{Crossfade, FadeCurve};
+use crate::audio::record::{default_directory, RecordFormat, RecordSettings};
+use crate::audio::replaygain::{ReplayGain, ReplayGainMode};
+use crate::audio::speed::{PlaybackSpeed, SpeedMode};
+use crate::audio::volume::{VolumeCurve, DEFAULT_RANGE_DB};
+use crate::components::visualizer::{VisualizerMode, VisualizerPane};
 
 /// Configuration structure for user preferences
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,8 +18,111 @@ pub struct PreferencesConfig {
     pub theme: String,
     /// Volume level (0-100)
     pub volume: u8,
+    /// Attenuation at 1% volume relative to 100%, in dB
+    #[serde(default = "default_volume_range")]
+    pub volume_range_db: f32,
+    /// Attenuation kept at 100% volume, in dB
+    #[serde(default)]
+    pub volume_headroom_db: f32,
     /// Last accessed directory
     pub last_directory: PathBuf,
+    /// Audio output spec such as `auto`, `alsa:hw:0` or `wav:out.wav`
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// Overlap between consecutive tracks in milliseconds; 0 plays them gaplessly
+    #[serde(default)]
+    pub crossfade_ms: u32,
+    /// Shape of the crossfade
+    #[serde(default)]
+    pub crossfade_curve: FadeCurve,
+    /// Which ReplayGain tag levels playback
+    #[serde(default)]
+    pub replay_gain_mode: ReplayGainMode,
+    /// Added to the tagged ReplayGain, in dB
+    #[serde(default)]
+    pub replay_gain_preamp_db: f32,
+    /// Applied to tracks without ReplayGain tags, in dB
+    #[serde(default)]
+    pub replay_gain_fallback_db: f32,
+    /// Lower the gain where the tagged peak would otherwise clip
+    #[serde(default = "default_prevent_clipping")]
+    pub replay_gain_prevent_clipping: bool,
+    /// Whether the equalizer is switched on
+    #[serde(default)]
+    pub eq_enabled: bool,
+    /// Name of the selected built-in or user EQ preset
+    #[serde(default = "default_eq_preset")]
+    pub eq_preset: String,
+    /// EQ presets the user has saved
+    #[serde(default)]
+    pub eq_user_presets: Vec<EqPreset>,
+    /// Effects applied to everything played, in order
+    #[serde(default = "default_chain")]
+    pub processors: Vec<ProcessorStage>,
+    /// Playback rate relative to normal
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Whether other speeds keep the pitch or let it follow the rate
+    #[serde(default)]
+    pub speed_mode: SpeedMode,
+    /// Let fast-forward and rewind run on into the next or previous playlist entry
+    #[serde(default)]
+    pub cue_across_tracks: bool,
+    /// Folder recordings are saved in; empty for a `playtui` folder in the music directory
+    #[serde(default)]
+    pub record_directory: PathBuf,
+    #[serde(default)]
+    pub record_format: RecordFormat,
+    /// What the visualizer shows when started
+    #[serde(default)]
+    pub visualizer_mode: VisualizerMode,
+    /// Pane the visualizer shares, or `hidden`
+    #[serde(default)]
+    pub visualizer_pane: VisualizerPane,
+    /// Number of spectrum bars
+    #[serde(default = "default_visualizer_bands")]
+    pub visualizer_bands: usize,
+    /// How fast the bars fall, in dB per second
+    #[serde(default = "default_visualizer_falloff")]
+    pub visualizer_falloff_db: f32,
+    /// How long peak markers stay up, in milliseconds
+    #[serde(default = "default_visualizer_peak_hold")]
+    pub visualizer_peak_hold_ms: u32,
+    /// How the level meters move
+    #[serde(default)]
+    pub meter_ballistics: Ballistics,
+}
+
+fn default_output() -> String {
+    crate::audio::output::DEFAULT_OUTPUT.to_string()
+}
+
+fn default_volume_range() -> f32 {
+    DEFAULT_RANGE_DB
+}
+
+fn default_eq_preset() -> String {
+    FLAT.to_string()
+}
+
+fn default_prevent_clipping() -> bool {
+    true
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+fn default_visualizer_bands() -> usize {
+    SpectrumSettings::default().bands
+}
+
+fn default_visualizer_falloff() -> f32 {
+    SpectrumSettings::default().falloff_db
+}
+
+fn default_visualizer_peak_hold() -> u32 {
+    SpectrumSettings::default().peak_hold.as_millis() as u32
 }
 
 impl Default for PreferencesConfig {
@@ -17,7 +130,97 @@ impl Default for PreferencesConfig {
         Self {
             theme: "monokai".to_string(), // Default theme
             volume: 50,                    // Default volume
+            volume_range_db: default_volume_range(),
+            volume_headroom_db: 0.0,
             last_directory: PathBuf::new(),
+            output: default_output(),
+            crossfade_ms: 0,
+            crossfade_curve: FadeCurve::default(),
+            replay_gain_mode: ReplayGainMode::default(),
+            replay_gain_preamp_db: 0.0,
+            replay_gain_fallback_db: 0.0,
+            replay_gain_prevent_clipping: default_prevent_clipping(),
+            eq_enabled: false,
+            eq_preset: default_eq_preset(),
+            eq_user_presets: Vec::new(),
+            processors: default_chain(),
+            speed: default_speed(),
+            speed_mode: SpeedMode::default(),
+            cue_across_tracks: false,
+            record_directory: PathBuf::new(),
+            record_format: RecordFormat::default(),
+            visualizer_mode: VisualizerMode::default(),
+            visualizer_pane: VisualizerPane::default(),
+            visualizer_bands: default_visualizer_bands(),
+            visualizer_falloff_db: default_visualizer_falloff(),
+            visualizer_peak_hold_ms: default_visualizer_peak_hold(),
+            meter_ballistics: Ballistics::default(),
+        }
+    }
+}
+
+impl PreferencesConfig {
+    /// The crossfade to hand the playback engine, if one is set
+    pub fn crossfade(&self) -> Option<Crossfade> {
+        (self.crossfade_ms > 0).then(|| Crossfade {
+            duration: Duration::from_millis(self.crossfade_ms as u64),
+            curve: self.crossfade_curve,
+        })
+    }
+
+    /// How the volume scale maps onto decibels
+    pub fn volume_curve(&self) -> VolumeCurve {
+        VolumeCurve {
+            range_db: self.volume_range_db,
+            headroom_db: self.volume_headroom_db,
+        }
+    }
+
+    /// The selected EQ preset, flat if it no longer exists
+    pub fn eq_preset(&self) -> EqPreset {
+        find_preset(&self.eq_preset, &self.eq_user_presets).unwrap_or_else(EqPreset::flat)
+    }
+
+    /// The EQ to hand the playback engine, if switched on
+    pub fn equalizer(&self) -> Option<EqPreset> {
+        self.eq_enabled.then(|| self.eq_preset())
+    }
+
+    /// The playback speed to hand the playback engine
+    pub fn playback_speed(&self) -> PlaybackSpeed {
+        PlaybackSpeed {
+            rate: self.speed,
+            mode: self.speed_mode,
+        }
+    }
+
+    /// Where and how to record the output
+    pub fn record_settings(&self) -> RecordSettings {
+        RecordSettings {
+            directory: match self.record_directory.as_os_str().is_empty() {
+                true => default_directory(),
+                false => self.record_directory.clone(),
+            },
+            format: self.record_format,
+        }
+    }
+
+    /// How the visualizer lays out and moves its spectrum bars
+    pub fn spectrum_settings(&self) -> SpectrumSettings {
+        SpectrumSettings {
+            bands: self.visualizer_bands,
+            falloff_db: self.visualizer_falloff_db,
+            peak_hold: Duration::from_millis(self.visualizer_peak_hold_ms as u64),
+        }
+    }
+
+    /// The ReplayGain settings to hand the playback engine
+    pub fn replay_gain(&self) -> ReplayGain {
+        ReplayGain {
+            mode: self.replay_gain_mode,
+            preamp_db: self.replay_gain_preamp_db,
+            fallback_db: self.replay_gain_fallback_db,
+            prevent_clipping: self.replay_gain_prevent_clipping,
         }
     }
 }
@@ -41,6 +244,131 @@ mod tests {
         assert_eq!(deserialized.theme, "monokai");
         assert_eq!(deserialized.volume, 50);
         assert_eq!(deserialized.last_directory, PathBuf::new());
+        assert_eq!(deserialized.output, "auto");
+    }
+
+    #[test]
+    fn test_output_defaults_when_missing() {
+        // Files saved before the output setting existed still load
+        let deserialized: PreferencesConfig =
+            serde_json::from_str(r#"{"theme":"monokai","volume":50,"last_directory":""}"#).unwrap();
+        assert_eq!(deserialized.output, "auto");
+        assert_eq!(deserialized.crossfade_ms, 0);
+        assert_eq!(deserialized.crossfade_curve, FadeCurve::EqualPower);
+        assert!(deserialized.crossfade().is_none());
+        assert_eq!(deserialized.replay_gain(), ReplayGain::default());
+        assert_eq!(deserialized.volume_curve(), VolumeCurve::default());
+        assert!(deserialized.equalizer().is_none());
+        assert_eq!(deserialized.playback_speed(), PlaybackSpeed::default());
+        assert!(!deserialized.cue_across_tracks);
+        assert_eq!(deserialized.record_settings(), RecordSettings::default());
+        assert_eq!(deserialized.spectrum_settings(), SpectrumSettings::default());
+        assert_eq!(deserialized.visualizer_pane, VisualizerPane::TrackDetails);
+        assert_eq!(deserialized.meter_ballistics, Ballistics::Digital);
+    }
+
+    #[test]
+    fn test_crossfade_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","crossfade_ms":2500,"crossfade_curve":"logarithmic"}"#,
+        ).unwrap();
+        assert_eq!(deserialized.crossfade(), Some(Crossfade {
+            duration: Duration::from_millis(2500),
+            curve: FadeCurve::Logarithmic,
+        }));
+    }
+
+    #[test]
+    fn test_replay_gain_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","replay_gain_mode":"album","replay_gain_preamp_db":3.5,"replay_gain_prevent_clipping":false}"#,
+        ).unwrap();
+        assert_eq!(deserialized.replay_gain(), ReplayGain {
+            mode: ReplayGainMode::Album,
+            preamp_db: 3.5,
+            fallback_db: 0.0,
+            prevent_clipping: false,
+        });
+    }
+
+    #[test]
+    fn test_volume_curve_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","volume_range_db":60,"volume_headroom_db":3}"#,
+        ).unwrap();
+        assert_eq!(deserialized.volume_curve(), VolumeCurve { range_db: 60.0, headroom_db: 3.0 });
+    }
+
+    #[test]
+    fn test_eq_settings() {
+        let mut config: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","eq_enabled":true,"eq_preset":"Mine",
+                "eq_user_presets":[{"name":"Mine","bands":[{"frequency":100,"q":0.7,"gain_db":3}]}]}"#,
+        ).unwrap();
+        let preset = config.equalizer().unwrap();
+        assert_eq!(preset.name, "Mine");
+        assert_eq!(preset.preamp_db, 0.0);
+        assert_eq!(preset.bands[0].gain_db, 3.0);
+
+        config.eq_preset = "Rock".to_string();
+        assert_eq!(config.eq_preset().name, "Rock");
+        config.eq_preset = "Deleted".to_string();
+        assert_eq!(config.eq_preset(), EqPreset::flat());
+    }
+
+    #[test]
+    fn test_processor_chain_settings() {
+        let config: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":""}"#,
+        ).unwrap();
+        assert_eq!(config.processors, default_chain());
+
+        let config: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","processors":[{"name":"equalizer","bypassed":true}]}"#,
+        ).unwrap();
+        assert!(config.processors[0].bypassed);
+    }
+
+    #[test]
+    fn test_speed_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","speed":1.5,"speed_mode":"varispeed"}"#,
+        ).unwrap();
+        assert_eq!(deserialized.playback_speed(), PlaybackSpeed { rate: 1.5, mode: SpeedMode::Varispeed });
+    }
+
+    #[test]
+    fn test_record_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","record_directory":"/tmp/takes","record_format":"wav"}"#,
+        ).unwrap();
+        assert_eq!(deserialized.record_settings(), RecordSettings {
+            directory: PathBuf::from("/tmp/takes"),
+            format: RecordFormat::Wav,
+        });
+    }
+
+    #[test]
+    fn test_visualizer_settings() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","visualizer_mode":"phase","visualizer_pane":"volume-control",
+                "visualizer_bands":48,"visualizer_falloff_db":12,"visualizer_peak_hold_ms":250}"#,
+        ).unwrap();
+        assert_eq!(deserialized.visualizer_mode, VisualizerMode::Phase);
+        assert_eq!(deserialized.visualizer_pane.component(), Some("volume_control"));
+        assert_eq!(deserialized.spectrum_settings(), SpectrumSettings {
+            bands: 48,
+            falloff_db: 12.0,
+            peak_hold: Duration::from_millis(250),
+        });
+    }
+
+    #[test]
+    fn test_meter_ballistics() {
+        let deserialized: PreferencesConfig = serde_json::from_str(
+            r#"{"theme":"monokai","volume":50,"last_directory":"","meter_ballistics":"ppm"}"#,
+        ).unwrap();
+        assert_eq!(deserialized.meter_ballistics, Ballistics::Ppm);
     }
 
     #[test]
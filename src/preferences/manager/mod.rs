@@ -2,8 +2,13 @@ use std::path::PathBuf;
 use std::io;
 use log::{warn, info, debug};
 
+use crate::audio::dsp::ProcessorStage;
+use crate::audio::eq::EqPreset;
+use crate::audio::speed::PlaybackSpeed;
+use crate::audio::volume::VolumeCurve;
 use crate::preferences::config::PreferencesConfig;
 use crate::preferences::persistence;
+use crate::preferences::validation::validate_config;
 
 mod save_handler;
 #[cfg(test)]
@@ -20,14 +25,29 @@ pub struct PreferencesManager {
     dirty: bool,
     /// Handler for save operations and filesystem state
     save_handler: SaveHandler,
+    /// File preferences are kept in, when not the system configuration file
+    path: Option<PathBuf>,
 }
 
 impl PreferencesManager {
     /// Creates a new PreferencesManager instance
     pub fn new() -> io::Result<Self> {
-        let config = match persistence::load_preferences() {
-            Ok(config) => {
+        Ok(Self::with_loaded(persistence::load_preferences(), None))
+    }
+
+    /// Creates a PreferencesManager that keeps its preferences in `path` rather than
+    /// the system configuration file
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self::with_loaded(persistence::load_preferences_from(&path), Some(path))
+    }
+
+    fn with_loaded(loaded: io::Result<PreferencesConfig>, path: Option<PathBuf>) -> Self {
+        let config = match loaded {
+            Ok(mut config) => {
                 info!("Successfully loaded preferences");
+                // A hand-edited or outdated file can hold values the player rejects
+                validate_config(&mut config);
                 config
             },
             Err(e) => {
@@ -36,11 +56,12 @@ impl PreferencesManager {
             }
         };
         
-        Ok(Self {
+        Self {
             config,
             dirty: false,
             save_handler: SaveHandler::new(),
-        })
+            path,
+        }
     }
     
     /// Gets a reference to the current preferences configuration
@@ -62,6 +83,14 @@ impl PreferencesManager {
         self.dirty = true;
     }
     
+    /// Updates the volume curve's range and headroom and marks preferences as dirty
+    pub fn update_volume_curve(&mut self, curve: VolumeCurve) {
+        debug!("Updating volume curve to: {:?}", curve);
+        self.config.volume_range_db = curve.range_db;
+        self.config.volume_headroom_db = curve.headroom_db;
+        self.dirty = true;
+    }
+    
     /// Updates the last accessed directory and marks preferences as dirty
     pub fn update_last_directory(&mut self, path: PathBuf) {
         debug!("Updating last directory to: {:?}", path);
@@ -69,6 +98,47 @@ impl PreferencesManager {
         self.dirty = true;
     }
     
+    /// Updates the audio output spec and marks preferences as dirty
+    pub fn update_output(&mut self, output: String) {
+        debug!("Updating audio output to: {}", output);
+        self.config.output = output;
+        self.dirty = true;
+    }
+    
+    /// Updates whether the equalizer is on and which preset it uses, and marks preferences as dirty
+    pub fn update_equalizer(&mut self, enabled: bool, preset: String) {
+        debug!("Updating equalizer to: {} ({})", preset, if enabled { "on" } else { "off" });
+        self.config.eq_enabled = enabled;
+        self.config.eq_preset = preset;
+        self.dirty = true;
+    }
+    
+    /// Adds a user EQ preset, replacing any of the same name, and marks preferences as dirty
+    pub fn save_eq_preset(&mut self, preset: EqPreset) {
+        debug!("Saving EQ preset: {}", preset.name);
+        let presets = &mut self.config.eq_user_presets;
+        match presets.iter_mut().find(|saved| saved.name == preset.name) {
+            Some(saved) => *saved = preset,
+            None => presets.push(preset),
+        }
+        self.dirty = true;
+    }
+    
+    /// Updates the processor chain and marks preferences as dirty
+    pub fn update_processors(&mut self, processors: Vec<ProcessorStage>) {
+        debug!("Updating processor chain to: {:?}", processors);
+        self.config.processors = processors;
+        self.dirty = true;
+    }
+    
+    /// Updates the playback speed and whether the pitch follows it, and marks preferences as dirty
+    pub fn update_speed(&mut self, speed: PlaybackSpeed) {
+        debug!("Updating playback speed to: {:?}", speed);
+        self.config.speed = speed.rate;
+        self.config.speed_mode = speed.mode;
+        self.dirty = true;
+    }
+    
     /// Saves preferences if they have been modified since last save
     pub fn save_if_dirty(&mut self) -> io::Result<()> {
         if !self.dirty {
@@ -80,7 +150,7 @@ impl PreferencesManager {
             return Ok(());
         }
 
-        match self.save_handler.attempt_save(&self.config) {
+        match self.save_handler.attempt_save(&self.config, self.path.as_deref()) {
             Ok(()) => {
                 self.dirty = false;
                 Ok(())
@@ -96,7 +166,7 @@ impl PreferencesManager {
             return Ok(());
         }
 
-        match self.save_handler.attempt_save(&self.config) {
+        match self.save_handler.attempt_save(&self.config, self.path.as_deref()) {
             Ok(()) => {
                 self.dirty = false;
                 Ok(())
@@ -1,5 +1,6 @@
 use std::time::{Duration, Instant};
 use std::io;
+use std::path::Path;
 use log::{error, warn, debug};
 
 use crate::preferences::config::PreferencesConfig;
@@ -35,11 +36,16 @@ impl SaveHandler {
             .unwrap_or(true)
     }
 
-    /// Attempts to save preferences, handling filesystem errors
-    pub fn attempt_save(&mut self, config: &PreferencesConfig) -> io::Result<()> {
+    /// Attempts to save preferences to `path`, or the system configuration file without one,
+    /// handling filesystem errors
+    pub fn attempt_save(&mut self, config: &PreferencesConfig, path: Option<&Path>) -> io::Result<()> {
         self.last_save_attempt = Some(Instant::now());
 
-        match persistence::save_preferences(config) {
+        let saved = match path {
+            Some(path) => persistence::save_preferences_to(config, path),
+            None => persistence::save_preferences(config),
+        };
+        match saved {
             Ok(()) => {
                 self.fs_writable = true;
                 self.save_failures = 0;
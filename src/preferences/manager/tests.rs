@@ -5,6 +5,8 @@ use std::thread;
 use std::os::unix::fs::PermissionsExt;
 use crate::preferences::persistence::get_preferences_path;
 use serial_test::serial;
+use crate::audio::dsp::EQUALIZER;
+use crate::audio::speed::SpeedMode;
 
 fn setup_test_env() -> io::Result<()> {
     cleanup_preferences()?;
@@ -53,11 +55,23 @@ fn test_preferences_manager() {
     assert!(manager.dirty);
     assert_eq!(manager.config().volume, 75);
     
-    let test_path = PathBuf::from("/test/path");
+    // Loading drops a last directory that no longer exists, so remember one that does
+    let test_path = std::env::temp_dir();
     manager.update_last_directory(test_path.clone());
     assert!(manager.dirty);
     assert_eq!(manager.config().last_directory, test_path);
     
+    let mine = EqPreset::graphic("Mine", [2.0; 10]);
+    manager.save_eq_preset(mine.clone());
+    manager.update_equalizer(true, "Mine".to_string());
+    assert_eq!(manager.config().equalizer(), Some(mine.clone()));
+    
+    let bypassed = vec![ProcessorStage { bypassed: true, ..ProcessorStage::new(EQUALIZER) }];
+    manager.update_processors(bypassed.clone());
+    
+    let lecture = PlaybackSpeed { rate: 1.5, mode: SpeedMode::PreservePitch };
+    manager.update_speed(lecture);
+    
     // Test save operations
     assert!(manager.save().is_ok());
     assert!(!manager.dirty);
@@ -68,11 +82,41 @@ fn test_preferences_manager() {
     assert_eq!(new_manager.config().theme, "manager_test_theme");
     assert_eq!(new_manager.config().volume, 75);
     assert_eq!(new_manager.config().last_directory, test_path);
+    assert_eq!(new_manager.config().equalizer(), Some(mine));
+    assert_eq!(new_manager.config().processors, bypassed);
+    assert_eq!(new_manager.config().playback_speed(), lecture);
 
     // Clean up after test
     cleanup_preferences().unwrap();
 }
 
+#[test]
+#[serial]
+fn test_invalid_preferences_fall_back_on_load() {
+    setup_test_env().unwrap();
+    let path = get_preferences_path().unwrap();
+    fs::write(&path, r#"{
+        "theme": "manager_test_theme",
+        "volume": 50,
+        "last_directory": "",
+        "crossfade_ms": 600000,
+        "processors": [{"name": "reverb", "bypassed": false}],
+        "speed": 9.0,
+        "visualizer_bands": 0
+    }"#).unwrap();
+
+    let manager = PreferencesManager::new().unwrap();
+    let config = manager.config();
+    assert_eq!(config.volume, 50);
+    assert_eq!(config.crossfade_ms, crate::audio::fade::MAX_CROSSFADE.as_millis() as u32);
+    assert!(config.processors.is_empty());
+    assert_eq!(config.speed, crate::audio::speed::MAX_SPEED);
+    assert_eq!(config.visualizer_bands, crate::analysis::spectrum::MIN_BANDS);
+    assert!(!manager.dirty);
+
+    cleanup_preferences().unwrap();
+}
+
 #[test]
 #[serial]
 fn test_unwritable_location_handling() {
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::{self, Read, Write};
 use directories::ProjectDirs;
@@ -44,8 +44,11 @@ pub fn ensure_preferences_dir() -> io::Result<PathBuf> {
 
 /// Load preferences from the system configuration file
 pub fn load_preferences() -> io::Result<PreferencesConfig> {
-    let path = ensure_preferences_dir()?;
-    
+    load_preferences_from(&ensure_preferences_dir()?)
+}
+
+/// Load preferences from `path`, the defaults if there is no file there yet
+pub fn load_preferences_from(path: &Path) -> io::Result<PreferencesConfig> {
     // If file doesn't exist, return default config
     if !path.exists() {
         debug!("Preferences file not found, using defaults");
@@ -53,7 +56,7 @@ pub fn load_preferences() -> io::Result<PreferencesConfig> {
     }
     
     // Read the file content
-    let mut file = fs::File::open(&path).map_err(|e| {
+    let mut file = fs::File::open(path).map_err(|e| {
         error!("Failed to open preferences file at {:?}: {}", path, e);
         e
     })?;
@@ -75,8 +78,11 @@ pub fn load_preferences() -> io::Result<PreferencesConfig> {
 
 /// Save preferences to the system configuration file
 pub fn save_preferences(config: &PreferencesConfig) -> io::Result<()> {
-    let path = ensure_preferences_dir()?;
-    
+    save_preferences_to(config, &ensure_preferences_dir()?)
+}
+
+/// Save preferences to `path`, whose directory must exist
+pub fn save_preferences_to(config: &PreferencesConfig, path: &Path) -> io::Result<()> {
     // Serialize to JSON
     let contents = serde_json::to_string_pretty(config).map_err(|e| {
         error!("Failed to serialize preferences: {}", e);
@@ -87,7 +93,7 @@ pub fn save_preferences(config: &PreferencesConfig) -> io::Result<()> {
     })?;
     
     // Write to file
-    let mut file = fs::File::create(&path).map_err(|e| {
+    let mut file = fs::File::create(path).map_err(|e| {
         error!("Failed to create preferences file at {:?}: {}", path, e);
         e
     })?;
@@ -154,6 +160,7 @@ mod tests {
             theme: "save_load_test_theme".to_string(),
             volume: 75,
             last_directory: PathBuf::from("/test/path"),
+            ..Default::default()
         };
         
         // Save it
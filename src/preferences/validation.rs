@@ -1,21 +1,53 @@
 use std::path::Path;
 use log::{warn, debug};
 use super::config::PreferencesConfig;
+use crate::analysis::spectrum::{SpectrumSettings, MAX_BANDS, MIN_BANDS};
+use crate::audio::dsp::BUILTIN_PROCESSORS;
+use crate::audio::eq::find_preset;
+use crate::audio::fade::MAX_CROSSFADE;
+use crate::audio::replaygain::MAX_GAIN_DB;
+use crate::audio::speed::{MAX_SPEED, MIN_SPEED};
+use crate::audio::volume::{DEFAULT_RANGE_DB, MAX_HEADROOM_DB, MAX_RANGE_DB, MIN_RANGE_DB};
+use crate::audio::output::{OutputBackend, DEFAULT_OUTPUT};
 
 /// Validates and normalizes preferences configuration
 pub fn validate_config(config: &mut PreferencesConfig) {
     validate_volume(config);
     validate_theme(config);
     validate_directory(config);
+    validate_output(config);
+    validate_crossfade(config);
+    validate_replay_gain(config);
+    validate_equalizer(config);
+    validate_processors(config);
+    validate_speed(config);
+    validate_visualizer(config);
 }
 
-/// Validates and ensures volume is within bounds (0-100)
+/// Validates and ensures volume is within bounds (0-100) and its curve within the engine's limits
 fn validate_volume(config: &mut PreferencesConfig) {
     if config.volume > 100 {
         warn!("Volume {} exceeds maximum (100), clamping", config.volume);
         config.volume = 100;
     }
-    debug!("Volume validated: {}", config.volume);
+    if !config.volume_range_db.is_finite() {
+        warn!("Volume range is not a number, resetting");
+        config.volume_range_db = DEFAULT_RANGE_DB;
+    } else if !(MIN_RANGE_DB..=MAX_RANGE_DB).contains(&config.volume_range_db) {
+        warn!("Volume range {}dB outside {}-{}dB, clamping", config.volume_range_db, MIN_RANGE_DB, MAX_RANGE_DB);
+        config.volume_range_db = config.volume_range_db.clamp(MIN_RANGE_DB, MAX_RANGE_DB);
+    }
+    if !config.volume_headroom_db.is_finite() {
+        warn!("Volume headroom is not a number, resetting");
+        config.volume_headroom_db = 0.0;
+    } else if !(0.0..=MAX_HEADROOM_DB).contains(&config.volume_headroom_db) {
+        warn!("Volume headroom {}dB outside 0-{}dB, clamping", config.volume_headroom_db, MAX_HEADROOM_DB);
+        config.volume_headroom_db = config.volume_headroom_db.clamp(0.0, MAX_HEADROOM_DB);
+    }
+    debug!(
+        "Volume validated: {} over {}dB with {}dB headroom",
+        config.volume, config.volume_range_db, config.volume_headroom_db
+    );
 }
 
 /// Validates theme exists, falls back to default if invalid
@@ -42,6 +74,101 @@ fn validate_directory(config: &mut PreferencesConfig) {
     debug!("Directory validated: {:?}", config.last_directory);
 }
 
+/// Validates the audio output names a backend built into this binary
+fn validate_output(config: &mut PreferencesConfig) {
+    if let Err(e) = OutputBackend::from_spec(&config.output) {
+        warn!("{}, falling back to {}", e, DEFAULT_OUTPUT);
+        config.output = DEFAULT_OUTPUT.to_string();
+    }
+    debug!("Output validated: {}", config.output);
+}
+
+/// Validates the crossfade fits within what the engine can overlap
+fn validate_crossfade(config: &mut PreferencesConfig) {
+    let max_ms = MAX_CROSSFADE.as_millis() as u32;
+    if config.crossfade_ms > max_ms {
+        warn!("Crossfade {}ms exceeds maximum ({}ms), clamping", config.crossfade_ms, max_ms);
+        config.crossfade_ms = max_ms;
+    }
+    debug!("Crossfade validated: {}ms {:?}", config.crossfade_ms, config.crossfade_curve);
+}
+
+/// Validates the ReplayGain preamp and fallback are finite and within ±20dB
+fn validate_replay_gain(config: &mut PreferencesConfig) {
+    for (name, db) in [
+        ("preamp", &mut config.replay_gain_preamp_db),
+        ("fallback", &mut config.replay_gain_fallback_db),
+    ] {
+        if !db.is_finite() {
+            warn!("ReplayGain {} is not a number, resetting", name);
+            *db = 0.0;
+        } else if db.abs() > MAX_GAIN_DB {
+            warn!("ReplayGain {} {}dB exceeds ±{}dB, clamping", name, db, MAX_GAIN_DB);
+            *db = db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+        }
+    }
+    debug!(
+        "ReplayGain validated: {:?} preamp {}dB fallback {}dB",
+        config.replay_gain_mode, config.replay_gain_preamp_db, config.replay_gain_fallback_db
+    );
+}
+
+/// Drops user EQ presets the engine would reject, and forgets a selection that no longer exists
+fn validate_equalizer(config: &mut PreferencesConfig) {
+    config.eq_user_presets.retain(|preset| match preset.validate() {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Dropping EQ preset '{}': {}", preset.name, e);
+            false
+        }
+    });
+    if find_preset(&config.eq_preset, &config.eq_user_presets).is_none() {
+        warn!("EQ preset '{}' not found, falling back to flat", config.eq_preset);
+        config.eq_preset = crate::audio::eq::FLAT.to_string();
+    }
+    debug!("Equalizer validated: {} ({})", config.eq_preset, if config.eq_enabled { "on" } else { "off" });
+}
+
+/// Drops processor stages the player cannot build
+fn validate_processors(config: &mut PreferencesConfig) {
+    config.processors.retain(|stage| {
+        let known = BUILTIN_PROCESSORS.contains(&stage.name.as_str());
+        if !known {
+            warn!("Dropping unknown audio processor '{}'", stage.name);
+        }
+        known
+    });
+    debug!("Processor chain validated: {} stages", config.processors.len());
+}
+
+/// Validates the playback speed is within what the engine can play
+fn validate_speed(config: &mut PreferencesConfig) {
+    if !config.speed.is_finite() {
+        warn!("Playback speed is not a number, resetting");
+        config.speed = 1.0;
+    } else if !(MIN_SPEED..=MAX_SPEED).contains(&config.speed) {
+        warn!("Playback speed {}x outside {}-{}x, clamping", config.speed, MIN_SPEED, MAX_SPEED);
+        config.speed = config.speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+    debug!("Playback speed validated: {}x {:?}", config.speed, config.speed_mode);
+}
+
+/// Validates the spectrum has a usable number of bars that fall at a finite rate
+fn validate_visualizer(config: &mut PreferencesConfig) {
+    if !(MIN_BANDS..=MAX_BANDS).contains(&config.visualizer_bands) {
+        warn!("Visualizer bands {} outside {}-{}, clamping", config.visualizer_bands, MIN_BANDS, MAX_BANDS);
+        config.visualizer_bands = config.visualizer_bands.clamp(MIN_BANDS, MAX_BANDS);
+    }
+    if !config.visualizer_falloff_db.is_finite() || config.visualizer_falloff_db <= 0.0 {
+        warn!("Visualizer falloff {}dB/s is not a positive number, resetting", config.visualizer_falloff_db);
+        config.visualizer_falloff_db = SpectrumSettings::default().falloff_db;
+    }
+    debug!(
+        "Visualizer validated: {:?} in {:?}, {} bands",
+        config.visualizer_mode, config.visualizer_pane, config.visualizer_bands
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,6 +187,12 @@ mod tests {
         config.volume = 50;
         validate_volume(&mut config);
         assert_eq!(config.volume, 50);
+
+        config.volume_range_db = 200.0;
+        config.volume_headroom_db = f32::NAN;
+        validate_volume(&mut config);
+        assert_eq!(config.volume_range_db, MAX_RANGE_DB);
+        assert_eq!(config.volume_headroom_db, 0.0);
     }
 
     #[test]
@@ -111,11 +244,91 @@ mod tests {
             theme: "nonexistent_theme".to_string(),
             volume: 150,
             last_directory: PathBuf::from("/nonexistent/path"),
+            output: "jack".to_string(),
+            crossfade_ms: 60000,
+            replay_gain_preamp_db: 30.0,
+            replay_gain_fallback_db: f32::NAN,
+            ..Default::default()
         };
         validate_config(&mut config);
 
         assert_eq!(config.volume, 100);
         assert_eq!(config.theme, "monokai");
         assert!(config.last_directory.as_os_str().is_empty());
+        assert_eq!(config.output, "auto");
+        assert_eq!(config.crossfade_ms, 12000);
+        assert_eq!(config.replay_gain_preamp_db, 20.0);
+        assert_eq!(config.replay_gain_fallback_db, 0.0);
+    }
+
+    #[test]
+    fn test_equalizer_validation() {
+        let mut broken = crate::audio::eq::EqPreset::flat();
+        broken.name = "Broken".to_string();
+        broken.bands[0].q = 0.0;
+        let mut config = PreferencesConfig {
+            eq_preset: "Broken".to_string(),
+            eq_user_presets: vec![broken],
+            ..Default::default()
+        };
+        validate_equalizer(&mut config);
+        assert!(config.eq_user_presets.is_empty());
+        assert_eq!(config.eq_preset, "Flat");
+    }
+
+    #[test]
+    fn test_processor_validation() {
+        use crate::audio::dsp::{ProcessorStage, EQUALIZER};
+        let mut config = PreferencesConfig {
+            processors: vec![ProcessorStage::new("reverb"), ProcessorStage { bypassed: true, ..ProcessorStage::new(EQUALIZER) }],
+            ..Default::default()
+        };
+        validate_processors(&mut config);
+        assert_eq!(config.processors.len(), 1);
+        assert!(config.processors[0].bypassed);
+    }
+
+    #[test]
+    fn test_speed_validation() {
+        let mut config = PreferencesConfig {
+            speed: 8.0,
+            ..Default::default()
+        };
+        validate_speed(&mut config);
+        assert_eq!(config.speed, MAX_SPEED);
+
+        config.speed = f32::NAN;
+        validate_speed(&mut config);
+        assert_eq!(config.speed, 1.0);
+
+        config.speed = 0.75;
+        validate_speed(&mut config);
+        assert_eq!(config.speed, 0.75);
+    }
+
+    #[test]
+    fn test_visualizer_validation() {
+        let mut config = PreferencesConfig {
+            visualizer_bands: 1000,
+            visualizer_falloff_db: -5.0,
+            ..Default::default()
+        };
+        validate_visualizer(&mut config);
+        assert_eq!(config.visualizer_bands, MAX_BANDS);
+        assert_eq!(config.visualizer_falloff_db, SpectrumSettings::default().falloff_db);
+    }
+
+    #[test]
+    fn test_output_validation() {
+        let mut config = PreferencesConfig {
+            output: "wav:render.wav".to_string(),
+            ..Default::default()
+        };
+        validate_output(&mut config);
+        assert_eq!(config.output, "wav:render.wav");
+
+        config.output = "wav".to_string();
+        validate_output(&mut config);
+        assert_eq!(config.output, "auto");
     }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::{SystemTime, UNIX_EPOCH};
 use crate::events::{Action, PlayerAction, PlaylistAction, UIAction, MetadataAction, FocusDirection};
 
 pub trait StateManager {
@@ -66,6 +67,12 @@ impl StateManager for AppState {
             }
             Action::SetVolume(volume) => {
                 self.player.volume = volume;
+                self.player.muted = false;
+                None
+            }
+            Action::ToggleMute => Some(Action::SetMuted(!self.player.muted)),
+            Action::SetMuted(muted) => {
+                self.player.muted = muted;
                 None
             }
 
@@ -98,6 +105,10 @@ impl StateManager for AppState {
                             self.player.current_track.clone().unwrap(),
                         )))
                     }
+                    // The position is taken from the engine once it has seeked
+                    PlayerAction::Seek(_) | PlayerAction::NextChapter | PlayerAction::PreviousChapter => None,
+                    PlayerAction::SetSpeed(_) => None,
+                    PlayerAction::SetProcessorChain(_) | PlayerAction::BypassProcessor { .. } => None,
                     PlayerAction::Record => {
                         self.player.is_recording = !self.player.is_recording;
                         None
@@ -132,12 +143,11 @@ impl StateManager for AppState {
                         }
                     }
                     PlaylistAction::AddTrack(path) => {
-                        self.playlist.tracks.push(path);
+                        self.playlist.add_track(path);
                         None
                     }
                     PlaylistAction::RemoveTrack(index) => {
-                        if index < self.playlist.tracks.len() {
-                            self.playlist.tracks.remove(index);
+                        if self.playlist.remove_track(index).is_some() {
                             if let Some(selected) = self.playlist.selected_index {
                                 if selected >= index {
                                     self.playlist.selected_index = if selected > 0 {
@@ -151,8 +161,13 @@ impl StateManager for AppState {
                         None
                     }
                     PlaylistAction::Clear => {
-                        self.playlist.tracks.clear();
-                        self.playlist.selected_index = None;
+                        self.playlist.clear();
+                        None
+                    }
+                    PlaylistAction::ToggleShuffle => {
+                        // The clock is as good a seed as any for a play order
+                        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |time| time.subsec_nanos());
+                        self.playlist.set_shuffle(!self.playlist.shuffle, seed);
                         None
                     }
                 }
@@ -165,7 +180,8 @@ impl StateManager for AppState {
                             ("track_list", FocusDirection::Next) => "track_details",
                             ("track_details", FocusDirection::Next) => "current_track_info",
                             ("current_track_info", FocusDirection::Next) => "playback_status",
-                            ("playback_status", FocusDirection::Next) => "controls",
+                            ("playback_status", FocusDirection::Next) => "equalizer",
+                            ("equalizer", FocusDirection::Next) => "controls",
                             ("controls", FocusDirection::Next) => "volume_control",
                             ("volume_control", FocusDirection::Next) => "library_browser",
                             
@@ -174,7 +190,8 @@ impl StateManager for AppState {
                             ("track_details", FocusDirection::Previous) => "track_list",
                             ("current_track_info", FocusDirection::Previous) => "track_details",
                             ("playback_status", FocusDirection::Previous) => "current_track_info",
-                            ("controls", FocusDirection::Previous) => "playback_status",
+                            ("equalizer", FocusDirection::Previous) => "playback_status",
+                            ("controls", FocusDirection::Previous) => "equalizer",
                             ("volume_control", FocusDirection::Previous) => "controls",
                             
                             _ => "library_browser",
@@ -211,9 +228,12 @@ impl StateManager for AppState {
                         self.metadata.current_metadata = None;
                         None
                     }
+                    MetadataAction::ReplayGain(_)
+                    | MetadataAction::ScanLoudness(_)
+                    | MetadataAction::LoudnessScan(_) => None,
                 }
             }
-            Action::App(_) => None,
+            Action::Equalizer(_) | Action::App(_) => None,
         }
     }
 }
@@ -29,6 +29,8 @@ pub enum SeekState {
 pub struct PlayerState {
     pub playback_state: PlaybackState,
     pub volume: u8,
+    /// Output silenced, keeping `volume` to return to
+    pub muted: bool,
     pub position: Duration,
     pub current_track: Option<String>,
     pub is_recording: bool,
@@ -40,6 +42,7 @@ impl Default for PlayerState {
         Self {
             playback_state: PlaybackState::Stopped,
             volume: 100,
+            muted: false,
             position: Duration::from_secs(0),
             current_track: None,
             is_recording: false,
@@ -52,6 +55,10 @@ impl Default for PlayerState {
 pub struct PlaylistState {
     pub tracks: Vec<String>,
     pub selected_index: Option<usize>,
+    /// Whether `tracks` is in shuffled order, which also picks track over album ReplayGain
+    pub shuffle: bool,
+    /// The tracks in the order they were added, to go back to when shuffle is turned off
+    unshuffled: Vec<String>,
 }
 
 impl Default for PlaylistState {
@@ -59,10 +66,73 @@ impl Default for PlaylistState {
         Self {
             tracks: Vec::new(),
             selected_index: None,
+            shuffle: false,
+            unshuffled: Vec::new(),
         }
     }
 }
 
+impl PlaylistState {
+    /// Shuffling moves the selected track to the top and puts the rest after it in random
+    /// order; turning shuffle off puts the tracks back in the order they were added
+    pub fn set_shuffle(&mut self, shuffle: bool, seed: u32) {
+        if shuffle == self.shuffle {
+            return;
+        }
+        let selected = self.selected_index.and_then(|index| self.tracks.get(index)).cloned();
+        if shuffle {
+            self.unshuffled = self.tracks.clone();
+            if let Some(index) = self.selected_index.filter(|&index| index < self.tracks.len()) {
+                self.tracks[..=index].rotate_right(1);
+                self.selected_index = Some(0);
+            }
+            let start = self.selected_index.map_or(0, |_| 1);
+            shuffle_in_place(&mut self.tracks[start..], seed);
+        } else {
+            self.tracks = std::mem::take(&mut self.unshuffled);
+            self.selected_index = selected.and_then(|track| self.tracks.iter().position(|t| *t == track));
+        }
+        self.shuffle = shuffle;
+    }
+
+    /// Add a track at the end, in both the shuffled and the added order
+    pub fn add_track(&mut self, track: String) {
+        if self.shuffle {
+            self.unshuffled.push(track.clone());
+        }
+        self.tracks.push(track);
+    }
+
+    /// Remove the track at `index`, from the added order too
+    pub fn remove_track(&mut self, index: usize) -> Option<String> {
+        if index >= self.tracks.len() {
+            return None;
+        }
+        let track = self.tracks.remove(index);
+        if let Some(position) = self.unshuffled.iter().position(|t| *t == track) {
+            self.unshuffled.remove(position);
+        }
+        Some(track)
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.unshuffled.clear();
+        self.selected_index = None;
+    }
+}
+
+/// Fisher-Yates shuffle driven by xorshift32, which is plenty for a play order
+fn shuffle_in_place(tracks: &mut [String], seed: u32) {
+    let mut state = seed.max(1);
+    for last in (1..tracks.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        tracks.swap(last, state as usize % (last + 1));
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UIState {
     pub theme: String,
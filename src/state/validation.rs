@@ -97,8 +97,8 @@ impl AppStateValidator {
         // Validate focused component
         match state.ui.focused_component.as_str() {
             "library_browser" | "track_list" | "track_details" |
-            "current_track_info" | "playback_status" | "controls" |
-            "volume_control" => Ok(()),
+            "current_track_info" | "playback_status" | "equalizer" | "controls" |
+            "visualizer" | "meters" | "volume_control" => Ok(()),
             _ => Err("Invalid focused component"),
         }
     }
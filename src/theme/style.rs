@@ -58,6 +58,14 @@ pub fn get_style(theme: &Theme, style_name: &str) -> Style {
         "volume_indicator" => style_from_config(&theme.styles.volume_indicator),
         "tab_active" => style_from_config(&theme.styles.tab_active),
         "tab_inactive" => style_from_config(&theme.styles.tab_inactive),
+        "visualizer_low" => optional_style(&theme.styles.visualizer_low, &theme.styles.volume_indicator),
+        "visualizer_high" => optional_style(&theme.styles.visualizer_high, &theme.styles.progress_bar),
+        "visualizer_peak" => optional_style(&theme.styles.visualizer_peak, &theme.styles.text_bold),
         _ => Style::default(),
     }
 }
+
+/// A style added after the first themes were written, or the one it falls back to
+fn optional_style(config: &Option<StyleConfig>, fallback: &StyleConfig) -> Style {
+    style_from_config(config.as_ref().unwrap_or(fallback))
+}
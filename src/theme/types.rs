@@ -53,4 +53,12 @@ pub struct Styles {
     pub volume_indicator: StyleConfig,
     pub tab_active: StyleConfig,
     pub tab_inactive: StyleConfig,
+    /// Visualizer bars and traces, their loudest rows, and the bars' peak markers; themes
+    /// without them fall back to `volume_indicator`, `progress_bar` and `text_bold`
+    #[serde(default)]
+    pub visualizer_low: Option<StyleConfig>,
+    #[serde(default)]
+    pub visualizer_high: Option<StyleConfig>,
+    #[serde(default)]
+    pub visualizer_peak: Option<StyleConfig>,
 }
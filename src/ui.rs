@@ -4,6 +4,7 @@ use ratatui::{
 };
 use crate::app::App;
 use crate::components::Component;
+use crate::components::visualizer::VisualizerPane;
 
 pub fn render(frame: &mut Frame, app: &mut App) {
     // Clear the frame first
@@ -29,24 +30,43 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         ])
         .split(main_chunks[0]);
 
-    // Split Secondary Row (25%) into two columns
+    // Split Secondary Row (25%) into three columns
     let secondary_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(50),  // Current Track Info
-            Constraint::Percentage(50),  // Playback Status
+            Constraint::Percentage(35),  // Current Track Info
+            Constraint::Percentage(30),  // Playback Status
+            Constraint::Percentage(35),  // Equalizer
         ])
         .split(main_chunks[1]);
 
-    // Split Control Row (15%) into controls (80%) and volume (20%)
+    // Split Control Row (15%) into controls (60%), meters (20%) and volume (20%)
     let control_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(80),  // Controls
+            Constraint::Percentage(60),  // Controls
+            Constraint::Percentage(20),  // Meters
             Constraint::Percentage(20),  // Volume Control
         ])
         .split(main_chunks[2]);
 
+    // The visualizer takes half of the pane it is placed in
+    let (mut primary_chunks, mut secondary_chunks, mut control_chunks) =
+        (primary_chunks.to_vec(), secondary_chunks.to_vec(), control_chunks.to_vec());
+    let pane = app.visualizer.borrow().pane();
+    let host = match pane {
+        VisualizerPane::LibraryBrowser => Some(&mut primary_chunks[0]),
+        VisualizerPane::TrackList => Some(&mut primary_chunks[1]),
+        VisualizerPane::TrackDetails => Some(&mut primary_chunks[2]),
+        VisualizerPane::CurrentTrackInfo => Some(&mut secondary_chunks[0]),
+        VisualizerPane::PlaybackStatus => Some(&mut secondary_chunks[1]),
+        VisualizerPane::Equalizer => Some(&mut secondary_chunks[2]),
+        VisualizerPane::Controls => Some(&mut control_chunks[0]),
+        VisualizerPane::VolumeControl => Some(&mut control_chunks[2]),
+        VisualizerPane::Hidden => None,
+    };
+    let visualizer_area = host.map(share_pane);
+
     // Create a background block with the theme's background color
     let background = Block::default()
         .style(Style::default().bg(
@@ -96,6 +116,24 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         &app.theme
     );
 
+    app.update_component_area("equalizer", secondary_chunks[2]);
+    app.equalizer.borrow().render(
+        frame,
+        secondary_chunks[2],
+        app.state.ui.focused_component == "equalizer",
+        &app.theme
+    );
+
+    if let Some(area) = visualizer_area {
+        app.update_component_area("visualizer", area);
+        app.visualizer.borrow().render(
+            frame,
+            area,
+            app.state.ui.focused_component == "visualizer",
+            &app.theme
+        );
+    }
+
     // Store areas and render Control Row components
     app.update_component_area("controls", control_chunks[0]);
     app.controls.borrow().render(
@@ -105,11 +143,32 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         &app.theme
     );
 
-    app.update_component_area("volume_control", control_chunks[1]);
-    app.volume_control.borrow().render(
+    app.update_component_area("meters", control_chunks[1]);
+    app.meters.borrow().render(
         frame,
         control_chunks[1],
+        app.state.ui.focused_component == "meters",
+        &app.theme
+    );
+
+    app.update_component_area("volume_control", control_chunks[2]);
+    app.volume_control.borrow().render(
+        frame,
+        control_chunks[2],
         app.state.ui.focused_component == "volume_control",
         &app.theme
     );
 }
+
+/// Split `pane` in two, leaving the first half to its component and returning the second;
+/// wide, short panes are split side by side
+fn share_pane(pane: &mut Rect) -> Rect {
+    // Terminal cells are about twice as tall as they are wide
+    let direction = if pane.width > pane.height * 4 { Direction::Horizontal } else { Direction::Vertical };
+    let halves = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(*pane);
+    *pane = halves[0];
+    halves[1]
+}
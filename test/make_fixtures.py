@@ -0,0 +1,331 @@
+#!/usr/bin/env python3
+"""Regenerate the AIFF, MP4 and Opus fixtures from testaudio-short.wav, the
+synthetic sine sweep used by the conversion tests, and the album ZIP holding some
+of them.
+
+No encoders are needed: AIFF and ALAC store the PCM verbatim, the AAC file
+holds silent AAC-LC frames, and the Opus file holds zero-length frames.
+"""
+import math
+import struct
+import wave
+import zipfile
+from pathlib import Path
+
+HERE = Path(__file__).parent
+TITLE = "Test Audio Short"
+ARTIST = "PlayTUI"
+ALBUM_ARTIST = "PlayTUI Fixtures"
+
+
+def read_wav():
+    with wave.open(str(HERE / "testaudio-short.wav")) as wav:
+        assert wav.getnchannels() == 1 and wav.getsampwidth() == 2
+        rate = wav.getframerate()
+        frames = wav.readframes(wav.getnframes())
+    return rate, list(struct.unpack("<%dh" % (len(frames) // 2), frames))
+
+
+# --- AIFF / AIFC -------------------------------------------------------------
+
+def ieee_extended(value):
+    exponent = value.bit_length() - 1
+    mantissa = value << (63 - exponent)
+    return struct.pack(">HQ", 16383 + exponent, mantissa)
+
+
+def id3v24_tag():
+    def text_frame(frame_id, text):
+        body = b"\x03" + text.encode()
+        return frame_id + struct.pack(">I", len(body)) + b"\0\0" + body
+
+    frames = text_frame(b"TIT2", TITLE) + text_frame(b"TPE1", ARTIST) + text_frame(b"TRCK", "3/12")
+    size = len(frames)
+    syncsafe = bytes([(size >> 21) & 0x7F, (size >> 14) & 0x7F, (size >> 7) & 0x7F, size & 0x7F])
+    return b"ID3\x04\x00\x00" + syncsafe + frames
+
+
+def iff_chunk(chunk_id, body):
+    padding = b"\0" if len(body) % 2 else b""
+    return chunk_id + struct.pack(">I", len(body)) + body + padding
+
+
+def write_aiff(rate, samples, name, aifc):
+    comm = struct.pack(">hIh", 1, len(samples), 16) + ieee_extended(rate)
+    if aifc:
+        # Little-endian 'sowt' samples, as written by macOS
+        comm += b"sowt" + bytes([14]) + b"not compressed" + b"\0"
+        data = struct.pack("<%dh" % len(samples), *samples)
+    else:
+        data = struct.pack(">%dh" % len(samples), *samples)
+
+    chunks = b""
+    if aifc:
+        chunks += iff_chunk(b"FVER", struct.pack(">I", 0xA2805140))
+    chunks += iff_chunk(b"COMM", comm)
+    chunks += iff_chunk(b"SSND", struct.pack(">II", 0, 0) + data)
+    chunks += iff_chunk(b"ID3 ", id3v24_tag())
+
+    form = b"AIFC" if aifc else b"AIFF"
+    (HERE / name).write_bytes(b"FORM" + struct.pack(">I", len(chunks) + 4) + form + chunks)
+
+
+# --- MP4 ---------------------------------------------------------------------
+
+def box(kind, *children):
+    payload = b"".join(children)
+    return struct.pack(">I", 8 + len(payload)) + kind + payload
+
+
+def full_box(kind, version, flags, *children):
+    return box(kind, struct.pack(">I", (version << 24) | flags), *children)
+
+
+def audio_sample_entry(kind, rate, *children):
+    fields = b"\0" * 6 + struct.pack(">H", 1) + b"\0" * 8
+    fields += struct.pack(">HHHHI", 1, 16, 0, 0, rate << 16)
+    return box(kind, fields, *children)
+
+
+def ilst(freeform=()):
+    def item(kind, data_type, payload):
+        return box(kind, box(b"data", struct.pack(">II", data_type, 0), payload))
+
+    def freeform_item(name, value):
+        return box(b"----", full_box(b"mean", 0, 0, b"com.apple.iTunes"), full_box(b"name", 0, 0, name.encode()),
+                   box(b"data", struct.pack(">II", 1, 0), value.encode()))
+
+    # A 1x1 PNG is plenty to exercise cover art handling
+    png = bytes.fromhex(
+        "89504e470d0a1a0a0000000d49484452000000010000000108060000001f15c489"
+        "0000000d4944415478da63f8cfc0f01f0005000201aa3a6f8c0000000049454e44ae426082"
+    )
+    return box(
+        b"ilst",
+        item(b"\xa9nam", 1, TITLE.encode()),
+        item(b"\xa9ART", 1, ARTIST.encode()),
+        item(b"aART", 1, ALBUM_ARTIST.encode()),
+        item(b"trkn", 0, struct.pack(">HHHH", 0, 3, 12, 0)),
+        item(b"covr", 14, png),
+        *[freeform_item(name, value) for name, value in freeform],
+    )
+
+
+def write_mp4(name, rate, sample_entry, samples, durations, freeform=()):
+    total = sum(durations)
+    mdat_payload = b"".join(samples)
+
+    stts_entries = []
+    for duration in durations:
+        if stts_entries and stts_entries[-1][1] == duration:
+            stts_entries[-1][0] += 1
+        else:
+            stts_entries.append([1, duration])
+
+    def moov(chunk_offset):
+        stbl = box(
+            b"stbl",
+            full_box(b"stsd", 0, 0, struct.pack(">I", 1), sample_entry),
+            full_box(b"stts", 0, 0, struct.pack(">I", len(stts_entries)),
+                     *[struct.pack(">II", c, d) for c, d in stts_entries]),
+            full_box(b"stsc", 0, 0, struct.pack(">IIII", 1, 1, len(samples), 1)),
+            full_box(b"stsz", 0, 0, struct.pack(">II", 0, len(samples)),
+                     *[struct.pack(">I", len(s)) for s in samples]),
+            full_box(b"stco", 0, 0, struct.pack(">II", 1, chunk_offset)),
+        )
+        minf = box(
+            b"minf",
+            full_box(b"smhd", 0, 0, struct.pack(">HH", 0, 0)),
+            box(b"dinf", full_box(b"dref", 0, 0, struct.pack(">I", 1), full_box(b"url ", 0, 1))),
+            stbl,
+        )
+        mdia = box(
+            b"mdia",
+            full_box(b"mdhd", 0, 0, struct.pack(">IIIIHH", 0, 0, rate, total, 0x55C4, 0)),
+            full_box(b"hdlr", 0, 0, struct.pack(">I4s12s", 0, b"soun", b""), b"SoundHandler\0"),
+            minf,
+        )
+        matrix = struct.pack(">9I", 0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x40000000)
+        tkhd = full_box(b"tkhd", 0, 7, struct.pack(">IIIII", 0, 0, 1, 0, total * 1000 // rate),
+                        b"\0" * 8, struct.pack(">hhhH", 0, 0, 0x0100, 0), matrix, struct.pack(">II", 0, 0))
+        mvhd = full_box(b"mvhd", 0, 0, struct.pack(">IIII", 0, 0, 1000, total * 1000 // rate),
+                        struct.pack(">IH", 0x10000, 0x0100), b"\0" * 10, matrix, b"\0" * 24, struct.pack(">I", 2))
+        meta = full_box(b"meta", 0, 0,
+                        full_box(b"hdlr", 0, 0, struct.pack(">I4s12s", 0, b"mdir", b"appl"), b"\0"),
+                        ilst(freeform))
+        return box(b"moov", mvhd, box(b"trak", tkhd, mdia), box(b"udta", meta))
+
+    ftyp = box(b"ftyp", b"M4A ", struct.pack(">I", 0), b"M4A mp42isom\0\0\0\0")
+    header_len = len(ftyp) + len(moov(0)) + 8
+    (HERE / name).write_bytes(ftyp + moov(header_len) + box(b"mdat", mdat_payload))
+
+
+class BitWriter:
+    def __init__(self):
+        self.bits = []
+
+    def write(self, value, count):
+        self.bits.extend((value >> (count - 1 - i)) & 1 for i in range(count))
+
+    def bytes(self):
+        bits = self.bits + [0] * (-len(self.bits) % 8)
+        return bytes(int("".join(map(str, bits[i:i + 8])), 2) for i in range(0, len(bits), 8))
+
+
+def write_alac(rate, samples):
+    frame_len = 4096
+    frames, durations = [], []
+    for start in range(0, len(samples), frame_len):
+        chunk = samples[start:start + frame_len]
+        bits = BitWriter()
+        bits.write(0, 3)    # single channel element
+        bits.write(0, 4)    # element instance tag
+        bits.write(0, 12)   # unused
+        partial = len(chunk) != frame_len
+        bits.write((int(partial) << 3) | 1, 4)  # partial frame flag, no shift, verbatim
+        if partial:
+            bits.write(len(chunk), 32)
+        for sample in chunk:
+            bits.write(sample & 0xFFFF, 16)
+        bits.write(7, 3)    # end element
+        frames.append(bits.bytes())
+        durations.append(len(chunk))
+
+    config = struct.pack(">IBBBBBBHIII", frame_len, 0, 16, 40, 10, 14, 1, 255, 0, 0, rate)
+    entry = audio_sample_entry(b"alac", rate, full_box(b"alac", 0, 0, config))
+    write_mp4("testaudio-short-alac.m4a", rate, entry, frames, durations)
+
+
+def write_aac_silence(rate):
+    # Mono AAC-LC raw frame: SCE with max_sfb = 0 followed by END
+    bits = BitWriter()
+    bits.write(0, 3)       # single channel element
+    bits.write(0, 4)       # element instance tag
+    bits.write(100, 8)     # global gain
+    bits.write(0, 1 + 2 + 1)  # ics_info: reserved, ONLY_LONG_SEQUENCE, sine window
+    bits.write(0, 6)       # max_sfb
+    bits.write(0, 1)       # no prediction
+    bits.write(0, 3)       # no pulse, TNS or gain control data
+    bits.write(7, 3)       # end element
+    frame = bits.bytes()
+
+    # One second of audio behind iTunes' usual 2112 samples of priming, padded out to whole frames
+    delay, length = 2112, rate
+    frame_count = -(-(delay + length) // 1024)
+    padding = frame_count * 1024 - delay - length
+    smpb = " 00000000 %08X %08X %016X" % (delay, padding, length) + " 00000000" * 8
+    asc = bytes([0x11, 0x88])  # AAC-LC, 48kHz, mono
+    decoder_specific = bytes([0x05, len(asc)]) + asc
+    decoder_config = bytes([0x04, 13 + len(decoder_specific), 0x40, 0x15]) + b"\0\0\0" \
+        + struct.pack(">II", 64000, 64000) + decoder_specific
+    es = bytes([0x03, 3 + len(decoder_config) + 3]) + struct.pack(">HB", 1, 0) + decoder_config + bytes([0x06, 1, 2])
+    entry = audio_sample_entry(b"mp4a", rate, full_box(b"esds", 0, 0, es))
+    write_mp4("testaudio-silence-aac.m4a", rate, entry, [frame] * frame_count, [1024] * frame_count,
+              [("iTunSMPB", smpb)])
+
+
+# --- Ogg Opus ----------------------------------------------------------------
+
+def ogg_crc(data):
+    crc = 0
+    for byte in data:
+        crc ^= byte << 24
+        for _ in range(8):
+            crc = ((crc << 1) ^ 0x04C11DB7) if crc & 0x80000000 else crc << 1
+            crc &= 0xFFFFFFFF
+    return crc
+
+
+def ogg_page(packets, granule, sequence, flags):
+    lacing = b""
+    for packet in packets:
+        lacing += b"\xff" * (len(packet) // 255) + bytes([len(packet) % 255])
+    header = b"OggS" + struct.pack("<BBqIIIB", 0, flags, granule, 0x504C4159, sequence, 0, len(lacing))
+    page = header + lacing + b"".join(packets)
+    return page[:22] + struct.pack("<I", ogg_crc(page)) + page[26:]
+
+
+def write_opus():
+    pre_skip = 312
+    head = b"OpusHead" + struct.pack("<BBHIhB", 1, 1, pre_skip, 48000, 0, 0)
+    comments = [b"TITLE=" + TITLE.encode(), b"ARTIST=" + ARTIST.encode(), b"TRACKNUMBER=3"]
+    vendor = b"playtui fixtures"
+    tags = b"OpusTags" + struct.pack("<I", len(vendor)) + vendor + struct.pack("<I", len(comments))
+    tags += b"".join(struct.pack("<I", len(c)) + c for c in comments)
+
+    # 20ms CELT fullband packets holding a single zero-length frame. Granule positions
+    # count decoded samples, pre-skip included, and the last page trims the excess.
+    packet = bytes([0xF8])
+    total = 48000 + pre_skip
+    packet_count = -(-total // 960)
+    pages = [ogg_page([head], 0, 0, 0x02), ogg_page([tags], 0, 1, 0)]
+    for first in range(0, packet_count, 10):
+        count = min(10, packet_count - first)
+        last = first + count == packet_count
+        granule = total if last else (first + count) * 960
+        pages.append(ogg_page([packet] * count, granule, len(pages), 0x04 if last else 0))
+    (HERE / "testaudio-short.opus").write_bytes(b"".join(pages))
+
+
+# --- Sine sweep --------------------------------------------------------------
+
+SWEEP_RATE = 96000
+SWEEP_SECONDS = 0.25
+SWEEP_FADE = 0.005
+
+
+def sweep(t):
+    """Exponential 20Hz-20kHz sweep at half scale, faded in and out.
+
+    Mirrors `sweep_at` in src/audio/convert/tests.rs."""
+    f0, f1 = 20.0, 20000.0
+    k = math.log(f1 / f0) / SWEEP_SECONDS
+    value = 0.5 * math.sin(2 * math.pi * f0 * (math.exp(k * t) - 1) / k)
+    edge = min(t, SWEEP_SECONDS - t)
+    if edge < SWEEP_FADE:
+        value *= 0.5 - 0.5 * math.cos(math.pi * max(edge, 0.0) / SWEEP_FADE)
+    return value
+
+
+def write_sweep():
+    # 24-bit stereo at 96kHz; the right channel is the left inverted at half level
+    frames = int(SWEEP_RATE * SWEEP_SECONDS)
+    data = bytearray()
+    for n in range(frames):
+        left = sweep(n / SWEEP_RATE)
+        for value in (left, -0.5 * left):
+            data += struct.pack("<i", round(value * (1 << 23)))[:3]
+    with wave.open(str(HERE / "testaudio-sweep-96k24.wav"), "wb") as wav:
+        wav.setnchannels(2)
+        wav.setsampwidth(3)
+        wav.setframerate(SWEEP_RATE)
+        wav.writeframes(bytes(data))
+
+
+# --- ZIP album ---------------------------------------------------------------
+
+def write_zip():
+    # Deflated and stored members under a folder with no entry of its own, as many
+    # archivers leave it
+    members = [
+        ("Album/01 Short.flac", "testaudio-short.flac", zipfile.ZIP_DEFLATED),
+        ("Album/Bonus/02 Short.aiff", "testaudio-short.aiff", zipfile.ZIP_STORED),
+        ("notes.txt", None, zipfile.ZIP_DEFLATED),
+    ]
+    with zipfile.ZipFile(HERE / "testaudio-album.zip", "w") as archive:
+        for name, source, method in members:
+            info = zipfile.ZipInfo(name, date_time=(2024, 11, 29, 0, 0, 0))
+            info.compress_type = method
+            data = (HERE / source).read_bytes() if source else b"Fixture album for the archive tests\n"
+            archive.writestr(info, data)
+
+
+if __name__ == "__main__":
+    rate, samples = read_wav()
+    write_aiff(rate, samples, "testaudio-short.aiff", aifc=False)
+    write_aiff(rate, samples, "testaudio-short.aifc", aifc=True)
+    write_alac(rate, samples)
+    write_aac_silence(rate)
+    write_opus()
+    write_sweep()
+    write_zip()
@@ -43,10 +43,12 @@ pub fn generate_test_flac_data() -> Vec<u8> {
 }
 
 pub fn generate_test_mp3_data() -> Vec<u8> {
-    // Minimal MP3 frame
+    // Two silent MPEG1 Layer 3 frames, 128kbps at 48kHz (384 bytes each)
     let mut data = Vec::new();
-    // Add MP3 frame header and minimal data
-    data.extend(&[0xFF, 0xFB]); // MPEG1 Layer 3 frame sync
+    for _ in 0..2 {
+        data.extend(&[0xFF, 0xFB, 0x94, 0x44]); // Frame sync and header
+        data.extend([0u8; 380]); // Side info and main data
+    }
     data
 }
 
@@ -85,58 +87,75 @@ mod tests {
     use super::*;
     use std::fs::File;
     use std::io::Write;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
 
-    fn create_test_file(data: &[u8], extension: &str) -> std::path::PathBuf {
+    // The TempDir must outlive the path, otherwise the file is gone before it is probed
+    fn create_test_file(data: &[u8], extension: &str) -> (TempDir, std::path::PathBuf) {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join(format!("test.{}", extension));
         let mut file = File::create(&file_path).unwrap();
         file.write_all(data).unwrap();
-        file_path
+        (dir, file_path)
     }
 
     #[test]
     fn test_wav_validation() {
         let data = generate_test_wav_data();
-        let test_path = create_test_file(&data, "wav");
+        let (_dir, test_path) = create_test_file(&data, "wav");
         assert!(validate_wav_format(&test_path), "Should validate WAV format");
         
-        // Test invalid extension
-        let test_path = create_test_file(&data, "mp3");
-        assert!(!validate_wav_format(&test_path), "Should reject wrong extension");
+        // Contents win over a misleading extension
+        let (_dir, test_path) = create_test_file(&data, "mp3");
+        assert!(validate_wav_format(&test_path), "Should detect WAV by content");
+
+        // A file that cannot be read falls back to its extension
+        let missing = test_path.with_file_name("missing.mp3");
+        assert!(!validate_wav_format(&missing), "Should reject wrong extension");
     }
 
     #[test]
     fn test_flac_validation() {
         let data = generate_test_flac_data();
-        let test_path = create_test_file(&data, "flac");
+        let (_dir, test_path) = create_test_file(&data, "flac");
         assert!(validate_flac_format(&test_path), "Should validate FLAC format");
         
-        // Test invalid extension
-        let test_path = create_test_file(&data, "wav");
-        assert!(!validate_flac_format(&test_path), "Should reject wrong extension");
+        // Contents win over a misleading extension
+        let (_dir, test_path) = create_test_file(&data, "wav");
+        assert!(validate_flac_format(&test_path), "Should detect FLAC by content");
+
+        // A file that cannot be read falls back to its extension
+        let missing = test_path.with_file_name("missing.wav");
+        assert!(!validate_flac_format(&missing), "Should reject wrong extension");
     }
 
     #[test]
     fn test_mp3_validation() {
         let data = generate_test_mp3_data();
-        let test_path = create_test_file(&data, "mp3");
+        let (_dir, test_path) = create_test_file(&data, "mp3");
         assert!(validate_mp3_format(&test_path), "Should validate MP3 format");
         
-        // Test invalid extension
-        let test_path = create_test_file(&data, "wav");
-        assert!(!validate_mp3_format(&test_path), "Should reject wrong extension");
+        // Contents win over a misleading extension
+        let (_dir, test_path) = create_test_file(&data, "wav");
+        assert!(validate_mp3_format(&test_path), "Should detect MP3 by content");
+
+        // A file that cannot be read falls back to its extension
+        let missing = test_path.with_file_name("missing.wav");
+        assert!(!validate_mp3_format(&missing), "Should reject wrong extension");
     }
 
     #[test]
     fn test_ogg_validation() {
         let data = generate_test_ogg_data();
-        let test_path = create_test_file(&data, "ogg");
+        let (_dir, test_path) = create_test_file(&data, "ogg");
         assert!(validate_ogg_format(&test_path), "Should validate OGG format");
         
-        // Test invalid extension
-        let test_path = create_test_file(&data, "wav");
-        assert!(!validate_ogg_format(&test_path), "Should reject wrong extension");
+        // Contents win over a misleading extension
+        let (_dir, test_path) = create_test_file(&data, "wav");
+        assert!(validate_ogg_format(&test_path), "Should detect OGG by content");
+
+        // A file that cannot be read falls back to its extension
+        let missing = test_path.with_file_name("missing.wav");
+        assert!(!validate_ogg_format(&missing), "Should reject wrong extension");
     }
 
     #[test]
@@ -146,10 +165,10 @@ mod tests {
         let mp3_data = generate_test_mp3_data();
         let ogg_data = generate_test_ogg_data();
 
-        let wav_path = create_test_file(&wav_data, "wav");
-        let flac_path = create_test_file(&flac_data, "flac");
-        let mp3_path = create_test_file(&mp3_data, "mp3");
-        let ogg_path = create_test_file(&ogg_data, "ogg");
+        let (_wav_dir, wav_path) = create_test_file(&wav_data, "wav");
+        let (_flac_dir, flac_path) = create_test_file(&flac_data, "flac");
+        let (_mp3_dir, mp3_path) = create_test_file(&mp3_data, "mp3");
+        let (_ogg_dir, ogg_path) = create_test_file(&ogg_data, "ogg");
 
         // Test format probing
         let wav_decoder = WavDecoder::new();
@@ -201,6 +201,7 @@ fn test_metadata_propagation() {
         artist: Some("Test Artist".to_string()),
         album: Some("Test Album".to_string()),
         duration: Some(180),
+        chapters: Vec::new(),
     };
     
     let action = Action::Metadata(MetadataAction::Update(metadata));